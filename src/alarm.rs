@@ -0,0 +1,35 @@
+use crate::data::ThresholdAlarm;
+
+#[derive(Default)]
+pub struct AlarmState {
+    tripped: bool,
+    pub active: Option<String>
+}
+
+impl AlarmState {
+    pub fn on_sample(&mut self, value: f64, config: &ThresholdAlarm) -> Option<String> {
+        if !value.is_finite() {
+            return None;
+        }
+        if self.tripped {
+            self.tripped = config.low.is_some_and(|n| value < n + config.hysteresis)
+                || config.high.is_some_and(|n| value > n - config.hysteresis);
+            return None;
+        }
+        let limit = match (config.low, config.high) {
+            (Some(low), _) if value < low => format!("below {}", low),
+            (_, Some(high)) if value > high => format!("above {}", high),
+            _ => return None
+        };
+        self.tripped = true;
+        Some(limit)
+    }
+
+    pub fn acknowledge(&mut self) {
+        self.active = None;
+    }
+
+    pub fn reset(&mut self) {
+        self.tripped = false;
+    }
+}
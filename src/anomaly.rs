@@ -0,0 +1,89 @@
+use crate::data::Anomaly;
+use std::collections::VecDeque;
+
+const MIN_SAMPLES: usize = 10;
+const MIN_HISTORY: f64 = 0.5;
+
+#[derive(Default)]
+pub struct AnomalyDetector {
+    history: VecDeque<[f64; 2]>,
+    origin: f64,
+    sum: f64,
+    sum_sq: f64,
+    recent: VecDeque<f64>,
+    pub count: usize,
+    pub markers: Vec<[f64; 2]>
+}
+
+impl AnomalyDetector {
+    pub fn on_sample(&mut self, t: f64, value: f64, config: &Anomaly) -> bool {
+        if !value.is_finite() {
+            return false;
+        }
+        let window = f64::max(config.window, 0.001);
+        if self.history.back().is_some_and(|n| t - n[0] > window) {
+            self.clear_history();
+        }
+        while let Some(first) = self.history.front().copied().filter(|n| n[0] < t - window) {
+            self.history.pop_front();
+            let x = first[1] - self.origin;
+            self.sum -= x;
+            self.sum_sq -= x * x;
+        }
+
+        let anomalous = self.deviation(t, value, window).is_some_and(|n| n > config.sigma);
+        if !anomalous {
+            if self.history.is_empty() {
+                self.clear_history();
+                self.origin = value;
+            }
+            let x = value - self.origin;
+            self.history.push_back([t, value]);
+            self.sum += x;
+            self.sum_sq += x * x;
+            return false;
+        }
+
+        self.count += 1;
+        self.markers.push([t, value]);
+        self.recent.push_back(t);
+        while self.recent.front().is_some_and(|n| *n < t - window) {
+            self.recent.pop_front();
+        }
+        match config.burst {
+            Some(burst) if self.recent.len() >= burst.max(1) => {
+                self.recent.clear();
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn deviation(&self, t: f64, value: f64, window: f64) -> Option<f64> {
+        let n = self.history.len();
+        let oldest = self.history.front()?[0];
+        if n < MIN_SAMPLES || t - oldest < window * MIN_HISTORY {
+            return None;
+        }
+        let mean = self.sum / n as f64;
+        let variance = (self.sum_sq - self.sum * mean) / (n - 1) as f64;
+        let std = variance.max(0.0).sqrt();
+        match std > 0.0 {
+            true => Some((value - self.origin - mean).abs() / std),
+            false => None
+        }
+    }
+
+    fn clear_history(&mut self) {
+        self.history.clear();
+        self.sum = 0.0;
+        self.sum_sq = 0.0;
+    }
+
+    pub fn reset(&mut self) {
+        self.clear_history();
+        self.recent.clear();
+        self.count = 0;
+        self.markers.clear();
+    }
+}
@@ -1,37 +1,144 @@
-use crate::data::{ConnectionConfig, InputSlot, PlotConfig, PlotData, SerialMonitorData};
-use crate::serial_parser::SerialParser;
-use crate::serial_reader::{SerialConfig, SerialError, SerialReader, StartMode};
+use crate::alarm::AlarmState;
+use crate::anomaly::AnomalyDetector;
+use crate::audio::{self, AudioFeedback};
+use crate::blackbox::{BlackBox, Recording};
+use crate::clock::CaptureClock;
+use crate::data::{Anomaly, AudioConfig, Autosave, BlackBoxConfig, ConfigBackup, ConnectionConfig, ConsoleLine, DerivedSlot, DisconnectPolicy, EventChannel, FrameConfig, FrameMode, InputSlot, LineEnding, LogConfig, LossConfig, Marker, PlotConfig, PlotData, PlotKind, SendPreset, Sequence, SerialMonitorData, UiConfig};
+use crate::decimation::Decimator;
+use crate::derived::{DerivedChannel, SHOWN_SUFFIX};
+use crate::diagnostics::{Diagnostic, DiagnosticMode, DiagnosticState};
+use crate::events::EventCounter;
+use crate::export::{self, CsvStream};
+use crate::faults::{ConnectionSummary, FaultStats, LossSummary, RunSummary, TimingSummary};
+use crate::importers::{self, ImportSummary};
+use crate::logger::LogWriter;
+use crate::loss::LossTracker;
+use crate::memory;
+use crate::metrics::{ChannelSample, Metrics};
+use crate::plot_engine;
+use crate::profiles;
+use crate::raw_lines::RawLines;
+use crate::recording::{ReplaySpeed, SessionRecorder};
+use crate::sequence::{SequenceEvent, SequenceRun};
+use crate::serial_parser::{ParseError, ParseStats, RejectedLine, SerialParser, Separators};
+use crate::source::{NetworkAddress, Source};
+use crate::spectrum::Spectrum;
+use crate::stats::WindowStats;
+use crate::serial_reader::{ControlLine, Protocol, SerialConfig, SerialError, SerialReader, StartMode, StartProgress, TimingStats};
 use crate::ui::{Notification, NotificationType, SerialMonitorUI};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::VecDeque;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::io::Write;
 use std::iter::zip;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use egui::ecolor::rgb_from_hsv;
 
 const WIN_WIDTH: f32 = 1280.0;
 const WIN_HEIGHT: f32 = 720.0;
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(2);
+const INGEST_INTERVAL: Duration = Duration::from_millis(10);
+const REPAINT_INTERVAL: Duration = Duration::from_millis(50);
+const EXIT_STALL_LIMIT: Duration = Duration::from_secs(1);
 
 pub struct SerialMonitorApp {
     data: SerialMonitorData,
     ui: Option<SerialMonitorUI>,
+    repaint: Option<egui::Context>,
 
     reader: Option<SerialReader>,
+    source: Source,
     parser: SerialParser,
+    slots: Vec<usize>,
+    row_width: usize,
+    bytes_read: u64,
+    devices: Vec<Device>,
+    connected_at: Option<Instant>,
+    faults: FaultStats,
+    fault_port: Option<String>,
+    closed_faults: Vec<(String, FaultStats)>,
 
     values: Vec<Vec<[f64; 2]>>,
-    lines: VecDeque<String>,
+    latest: Vec<f64>,
+    stats: Vec<WindowStats>,
+    decimators: Vec<Decimator>,
+    events: Vec<EventCounter>,
+    derived: Vec<DerivedChannel>,
+    anomalies: Vec<AnomalyDetector>,
+    alarms: Vec<AlarmState>,
+    loss: LossTracker,
+    lines: VecDeque<ConsoleLine>,
+    raw_lines: Arc<Mutex<RawLines>>,
+    markers: Vec<Marker>,
+    sequence_run: Option<SequenceRun>,
+    logger: Option<LogWriter>,
+    log_files: Vec<PathBuf>,
+    diagnostic: Option<Diagnostic>,
+    output: Option<CsvStream>,
+    metrics: Arc<Metrics>,
+    black_box: Option<BlackBox>,
+    recorder: Option<SessionRecorder>,
+    replay_speed: ReplaySpeed,
+    audio: Option<AudioFeedback>,
 
-    paused: bool,
-    start_connected: bool,
+    clock: CaptureClock,
+    paused_at: Option<f64>,
+    view_reset: bool,
+    samples_cleared: bool,
+    locked: bool,
+    memory_notified: bool,
+    spectrum_usage: usize,
+    discarded_frames: usize,
+    bad_checksums: u64,
+    probe: Vec<String>,
+    frame_suggestion: Option<FrameConfig>,
+    start_source: Option<Source>,
+    start_sequence: Option<String>,
+    start_devices: bool,
     terminal_output: bool,
-    headless: bool
+    headless: bool,
+    summary: Option<(PathBuf, Instant)>,
+
+    autosave_hash: Option<u64>,
+    autosave_at: Instant,
+    restore: Option<(SerialMonitorData, SystemTime)>,
+    recovered: Vec<PathBuf>,
+    reconnect: Option<Reconnect>
+}
+
+pub struct Headless {
+    pub output: Option<PathBuf>,
+    pub duration: Option<Duration>,
+    pub poll_interval: Option<Duration>,
+    pub summary: Option<PathBuf>,
+    pub record: Option<PathBuf>
+}
+
+struct Device {
+    port: String,
+    reader: SerialReader,
+    parser: SerialParser,
+    slots: Vec<usize>,
+    row_width: usize,
+    bytes_read: u64,
+    faults: FaultStats
+}
+
+struct Reconnect {
+    port: String,
+    next: Instant
 }
 
 impl SerialMonitorApp {
-    pub const STORED_DURATION: f64 = 60.0;
-    pub const STORED_LINES: usize = 512;
+    pub const PROBE_LINES: usize = 20;
 
-    pub fn run(data: SerialMonitorData, connect: bool, terminal_output: bool, headless: bool) -> Result<(), String> {
+    pub fn run(mut data: SerialMonitorData, config_path: Option<PathBuf>, source: Option<Source>, sequence: Option<String>, terminal_output: bool, headless: Option<Headless>, metrics_port: Option<u16>) -> Result<(), String> {
+        PlotData::update_internal_ids(&mut data.plots);
         let icon = image::load_from_memory(include_bytes!("../res/icon.ico")).unwrap();
         let icon = egui::IconData {
             width: icon.width(),
@@ -43,54 +150,353 @@ impl SerialMonitorApp {
                 .with_inner_size(egui::Vec2::new(WIN_WIDTH, WIN_HEIGHT))
                 .with_min_inner_size(egui::Vec2::new(WIN_WIDTH, WIN_HEIGHT))
                 .with_icon(icon),
+            follow_system_theme: true,
             ..Default::default()
         };
-        let mut app = SerialMonitorApp {
-            data,
-            ui: None,
-            reader: None,
-            parser: SerialParser::new(),
-            values: Vec::new(),
-            lines: VecDeque::new(),
-            paused: false,
-            start_connected: connect,
-            terminal_output: terminal_output,
-            headless: headless
-        };
-
-        if headless {
-            loop {
-                app.update();
+        let mut app = SerialMonitorApp::new(data, source, sequence, terminal_output, headless.is_some());
+        app.recovered = BlackBox::recover();
+        app.autosave_hash = app.data.to_json().ok().map(|n| config_hash(&n));
+        if let Some(port) = metrics_port {
+            app.metrics.serve(port)
+                .map_err(|e| format!("Could not serve metrics on port {} ({})", port, e))?;
+        }
+        let mut warnings = std::mem::take(&mut app.data.load_warnings);
+        if let Some(options) = headless {
+            warnings.iter().for_each(|n| eprintln!("{}", n));
+            if !app.recovered.is_empty() {
+                let files: Vec<String> = app.recovered.iter().map(|n| n.display().to_string()).collect();
+                eprintln!("Black box of an unclean shutdown kept as {}", files.join(", "));
             }
+            return app.run_headless(options);
         }
+        app.restore = find_restore(&app.data, config_path.as_deref());
+        warnings.extend(app.missing_port_warning());
 
+        let app = Arc::new(Mutex::new(app));
+        Self::spawn_ingest(Arc::downgrade(&app));
         eframe::run_native(
             "SerialMonitor",
             native_opts,
             Box::new(move |ctx| {
-                let ui = SerialMonitorUI::new(ctx);
-                app.ui = Some(ui);
-                Box::new(app)
+                let mut ui = SerialMonitorUI::new(ctx);
+                show_load_warnings(&mut ui, &warnings, false);
+                let mut locked = lock_app(&app);
+                locked.ui = Some(ui);
+                locked.repaint = Some(ctx.egui_ctx.clone());
+                std::mem::drop(locked);
+                Box::new(SerialMonitorWindow { app })
             }),
         )
         .map_err(|e| e.to_string())
     }
 
+    fn new(data: SerialMonitorData, source: Option<Source>, sequence: Option<String>, terminal_output: bool, headless: bool) -> Self {
+        SerialMonitorApp {
+            data,
+            ui: None,
+            repaint: None,
+            reader: None,
+            source: Source::Serial,
+            parser: SerialParser::new(),
+            slots: Vec::new(),
+            row_width: 0,
+            bytes_read: 0,
+            devices: Vec::new(),
+            connected_at: None,
+            faults: FaultStats::default(),
+            fault_port: None,
+            closed_faults: Vec::new(),
+            values: Vec::new(),
+            latest: Vec::new(),
+            stats: Vec::new(),
+            decimators: Vec::new(),
+            events: Vec::new(),
+            derived: Vec::new(),
+            anomalies: Vec::new(),
+            alarms: Vec::new(),
+            loss: LossTracker::default(),
+            lines: VecDeque::new(),
+            raw_lines: Arc::new(Mutex::new(RawLines::default())),
+            markers: Vec::new(),
+            sequence_run: None,
+            logger: None,
+            log_files: Vec::new(),
+            diagnostic: None,
+            output: None,
+            metrics: Arc::new(Metrics::default()),
+            black_box: None,
+            recorder: None,
+            replay_speed: ReplaySpeed::Normal,
+            audio: None,
+            clock: CaptureClock::default(),
+            paused_at: None,
+            view_reset: false,
+            samples_cleared: false,
+            locked: false,
+            memory_notified: false,
+            spectrum_usage: 0,
+            discarded_frames: 0,
+            bad_checksums: 0,
+            probe: Vec::new(),
+            frame_suggestion: None,
+            start_source: source,
+            start_sequence: sequence,
+            start_devices: headless,
+            terminal_output,
+            headless,
+            summary: None,
+            autosave_hash: None,
+            autosave_at: Instant::now(),
+            restore: None,
+            recovered: Vec::new(),
+            reconnect: None
+        }
+    }
+
+    fn run_headless(&mut self, options: Headless) -> Result<(), String> {
+        if let Some(path) = &options.output {
+            let output = CsvStream::open(path)
+                .map_err(|e| format!("Could not open output {} ({})", path.display(), e))?;
+            self.output = Some(output);
+        }
+        if let Some(path) = &options.record {
+            let recorder = SessionRecorder::create(path)
+                .map_err(|e| format!("Could not open recording {} ({})", path.display(), e))?;
+            self.recorder = Some(recorder);
+        }
+        let interrupted = Arc::new(AtomicBool::new(false));
+        let handler = Arc::clone(&interrupted);
+        ctrlc::set_handler(move || handler.store(true, Ordering::Relaxed))
+            .map_err(|e| format!("Could not install Ctrl-C handler ({})", e))?;
+        let started = Instant::now();
+        self.summary = options.summary.clone().map(|n| (n, started));
+        loop {
+            self.update();
+            if let Some(output) = &mut self.output {
+                if let Err(e) = output.flush() {
+                    self.output = None;
+                    self.error(&format!("Could not write output ({})", e));
+                }
+            }
+            if interrupted.load(Ordering::Relaxed) || options.duration.is_some_and(|n| started.elapsed() >= n) {
+                self.shutdown(0);
+            }
+            thread::sleep(options.poll_interval.unwrap_or(INGEST_INTERVAL));
+        }
+    }
+
+    fn shutdown(&mut self, code: i32) -> ! {
+        if self.data.loss.is_some() {
+            eprintln!("{}", self.loss.summary());
+        }
+        let timing = self.timing_stats();
+        let loss = self.data.loss.is_some().then(|| LossSummary::from(&self.loss));
+        let locked = self.is_locked();
+        self.close();
+        if let Some((path, started)) = self.summary.take() {
+            if let Err(e) = self.write_summary(&path, started, locked, timing.as_ref(), loss) {
+                eprintln!("Could not write summary {} ({})", path.display(), e);
+            }
+        }
+        if let Some(mut output) = self.output.take() {
+            _ = output.finish();
+        }
+        _ = std::io::stdout().flush();
+        std::process::exit(code);
+    }
+
+    fn close(&mut self) {
+        if self.data.autosave.save_on_exit && !self.headless {
+            self.autosave();
+        }
+        let stalled = |reader: &SerialReader| reader.stalled_for().is_some_and(|n| n > EXIT_STALL_LIMIT);
+        if let Some(reader) = self.reader.as_mut().filter(|n| stalled(n)) {
+            reader.abandon();
+        }
+        for device in self.devices.iter_mut().filter(|n| stalled(&n.reader)) {
+            device.reader.abandon();
+        }
+        self.disconnect_current();
+    }
+
+    fn spawn_ingest(app: Weak<Mutex<SerialMonitorApp>>) {
+        thread::spawn(move || {
+            while let Some(shared) = app.upgrade() {
+                lock_app(&shared).update();
+                std::mem::drop(shared);
+                thread::sleep(INGEST_INTERVAL);
+            }
+        });
+    }
+
     pub fn update(&mut self) {
-        self.reset_port_if_missing();
+        let lines = self.metrics.lines.load(Ordering::Relaxed);
+        let port = self.data.conn_config.port.clone();
+        if self.reset_port_if_missing() && self.can_reconnect() {
+            self.connection_lost(port, "Device removed");
+        }
+        self.try_reconnect();
+        self.sync_events();
+        self.sync_derived();
+        self.sync_audio();
+        self.parser.sync_frame(&self.data.frame_config);
         self.read_input();
-        self.prep_input_slots(self.parser.columns());
+        self.read_devices();
+        self.sync_faults();
+        self.sync_metrics();
+        self.advance_events();
+        self.enforce_memory_budget();
+        self.prep_input_slots();
 
-        if self.start_connected {
-            self.start_connected = false;
-            if let Err(e) = self.connect_current() {
+        if let Some(source) = self.start_source.take() {
+            if let Err(e) = self.connect_source(source) {
                 self.error(&e.to_string());
             }
         }
+        if self.start_devices && self.is_connected() {
+            self.start_devices = false;
+            self.connect_devices();
+        }
+        if self.is_connected() {
+            if let Some(name) = self.start_sequence.take() {
+                match self.data.sequences.iter().position(|n| n.name == name) {
+                    Some(index) => self.start_sequence(index),
+                    None => self.notify_error(&format!("Unknown sequence ({})", name))
+                }
+            }
+        }
+        self.run_sequence();
+        self.autosave_tick();
+        if self.metrics.lines.load(Ordering::Relaxed) != lines {
+            self.request_repaint();
+        }
+    }
+
+    fn request_repaint(&self) {
+        if let Some(ctx) = &self.repaint {
+            ctx.request_repaint();
+        }
+    }
+
+    fn needs_repaint(&self) -> bool {
+        self.is_connected() || self.is_reconnecting() || self.sequence_run.is_some()
+    }
+
+    fn autosave_tick(&mut self) {
+        let interval = self.data.autosave.minutes * 60.0;
+        if self.headless || !self.data.autosave.enabled || self.autosave_at.elapsed().as_secs_f64() < interval {
+            return;
+        }
+        self.autosave_at = Instant::now();
+        self.autosave();
+    }
+
+    pub fn autosave(&mut self) {
+        let config = match self.data.to_json() {
+            Ok(config) => config,
+            Err(e) => return self.warning(&format!("Autosave failed ({})", e))
+        };
+        let hash = config_hash(&config);
+        if self.autosave_hash == Some(hash) {
+            return;
+        }
+        match SerialMonitorData::autosave(&config) {
+            Ok(_) => self.autosave_hash = Some(hash),
+            Err(e) => self.warning(&format!("Autosave failed ({})", e))
+        }
+    }
+
+    pub fn autosave_mut(&mut self) -> &mut Autosave {
+        &mut self.data.autosave
+    }
+
+    pub fn pending_restore(&self) -> Option<SystemTime> {
+        self.restore.as_ref().map(|n| n.1)
+    }
+
+    pub fn restore_autosave(&mut self, ui: &mut SerialMonitorUI) {
+        if let Some((config, _)) = self.restore.take() {
+            self.load_config(config, ui);
+        }
+    }
+
+    pub fn discard_autosave(&mut self) {
+        self.restore = None;
+    }
+
+    fn sync_events(&mut self) {
+        self.events.truncate(self.data.event_channels.len());
+        let mut invalid = None;
+        for (i, channel) in self.data.event_channels.iter().enumerate() {
+            match self.events.get_mut(i) {
+                Some(counter) => if counter.sync(channel) && !counter.is_valid() && !channel.pattern.is_empty() {
+                    invalid = Some(channel.pattern.clone());
+                },
+                None => self.events.push(EventCounter::new(channel))
+            }
+        }
+        if let Some(pattern) = invalid {
+            self.warning(&format!("Invalid event pattern ({})", pattern));
+        }
+    }
+
+    fn sync_derived(&mut self) {
+        self.derived.truncate(self.data.derived_slots.len());
+        for (i, slot) in self.data.derived_slots.iter().enumerate() {
+            match self.derived.get_mut(i) {
+                Some(channel) => channel.sync(slot),
+                None => self.derived.push(DerivedChannel::new(slot))
+            }
+        }
+    }
+
+    fn advance_events(&mut self) {
+        if let Some(reader) = &self.reader {
+            let t = reader.elapsed();
+            for (counter, channel) in zip(&mut self.events, &self.data.event_channels) {
+                counter.advance(t, channel.bucket);
+                memory::trim_history(&mut counter.values, t, self.data.plot_config.history_secs);
+            }
+        }
+    }
+
+    fn run_sequence(&mut self) {
+        while let Some(run) = &mut self.sequence_run {
+            match run.poll() {
+                SequenceEvent::Send(text) => {
+                    if let Err(e) = self.send(&text) {
+                        self.abort_sequence(&e.to_string());
+                    }
+                },
+                SequenceEvent::Waiting => break,
+                SequenceEvent::Finished => {
+                    let msg = format!("Sequence finished ({})", run.name());
+                    self.sequence_run = None;
+                    self.info(&msg);
+                },
+                SequenceEvent::TimedOut(pattern) => {
+                    self.abort_sequence(&format!("Timed out waiting for \"{}\"", pattern));
+                }
+            }
+        }
+    }
+
+    fn abort_sequence(&mut self, reason: &str) {
+        if let Some(run) = self.sequence_run.take() {
+            let (step, steps) = run.progress();
+            self.notify_error(&format!("Sequence aborted ({}, step {}/{}): {}", run.name(), step, steps, reason));
+        }
+    }
+
+    fn missing_port_warning(&mut self) -> Option<String> {
+        let port = self.data.conn_config.port.clone();
+        (port != ConnectionConfig::NO_PORT && self.reset_port_if_missing())
+            .then(|| format!("Port {} from the config is not available, no port is selected", port))
     }
 
     pub fn reset_port_if_missing(&mut self) -> bool {
-        if !self.available_devices().contains(&self.data.conn_config.port) {
+        let port = &self.data.conn_config.port;
+        if NetworkAddress::parse(port).is_none() && !self.available_devices().contains(port) {
             self.data.conn_config.port = String::from(ConnectionConfig::NO_PORT);
             return true;
         }
@@ -98,247 +504,2076 @@ impl SerialMonitorApp {
     }
 
     fn read_input(&mut self) {
-        let mut err: Option<String> = None;
+        let mut err: Option<SerialError> = None;
         if let Some(mut reader) = self.reader.take() {
             while let Some(line) = reader.get_line() {
-                match &line {
-                    Ok(line) => {
-                        if !self.paused {
-                            match self.parser.parse_values(&line.content) {
-                                Ok(values) => self.handle_input(line.t, &values),
-                                Err(e) => self.warning(&e.to_string())
+                match line {
+                    Ok(mut line) => {
+                        self.metrics.lines.fetch_add(1, Ordering::Relaxed);
+                        self.record_line(line.t, &line.content);
+                        if let Some(run) = &mut self.sequence_run {
+                            run.on_line(&line.content);
+                        }
+                        let host_t = line.t;
+                        let host = self.data.conn_config.timestamp_column.then_some(host_t);
+                        line.t = self.parser.device_time(host_t);
+                        if let Some(label) = self.mark_label(&line.content) {
+                            self.markers.push(Marker { t: line.t, label });
+                            self.log_line(line.t, &line.content, &[]);
+                            self.handle_input_line(line.t, host, &line.content);
+                        } else {
+                            let parsed = match line.values.take() {
+                                Some(values) => self.parser.accept_values(values),
+                                None => {
+                                    self.probe_line(&line.content);
+                                    self.parser.parse_values(&line.content)
+                                }
+                            };
+                            line.t = self.parser.device_time(host_t);
+                            match parsed {
+                                Ok(values) if !values.is_empty() => {
+                                    let raw = self.raw_text(None, &line.content);
+                                    self.ingest(None, line.t, &values, raw);
+                                    self.write_rows(line.t, &line.content);
+                                },
+                                Ok(_) => self.log_line(line.t, &line.content, &[]),
+                                Err(e) => {
+                                    self.metrics.parse_errors.fetch_add(1, Ordering::Relaxed);
+                                    if matches!(e, ParseError::ChecksumFailed) {
+                                        self.bad_checksums += 1;
+                                    }
+                                    if self.parser.should_warn(&e) {
+                                        self.parse_warning(&e, None);
+                                    }
+                                    self.log_line(line.t, &line.content, &[]);
+                                }
+                            }
+                            self.handle_input_line(line.t, host, &line.content);
+                            for (counter, channel) in zip(&mut self.events, &self.data.event_channels) {
+                                counter.on_line(line.t, &line.content, channel.bucket);
                             }
-                            self.handle_input_line(line.t, &line.content);
                         }
                     },
                     Err(e) => {
-                        err = Some(e.to_string());
+                        err = Some(e);
                         break;
                     }
                 }
             }
+            if err.is_none() {
+                err = reader.watchdog(self.data.conn_config.watchdog());
+            }
+            while let Some(e) = reader.control_error() {
+                self.warning(&e.to_string());
+            }
             self.reader = Some(reader);
         }
-        if let Some(e) = err {
-            self.error(&e)
+        if let Some(e @ SerialError::Unresponsive(_)) = &err {
+            self.fault_event(self.clock.now(), &format!("{}, closing the connection", e));
+        }
+        match err {
+            Some(SerialError::EndOfStream) => self.end_of_input(),
+            Some(e) if self.can_reconnect() => self.connection_lost(self.data.conn_config.port.clone(), &e.to_string()),
+            Some(e) => self.error(&e.to_string()),
+            None => {}
         }
     }
 
-    fn handle_input(&mut self, t: f64, values: &Vec<f64>) {
-        while self.values.len() < values.len() {
-            self.values.push(Vec::new());
+    fn read_devices(&mut self) {
+        let mut d = 0;
+        while d < self.devices.len() {
+            self.devices[d].parser.sync_frame(&self.data.frame_config);
+            let lines: Vec<_> = std::iter::from_fn(|| self.devices[d].reader.get_line()).collect();
+            let (offset, timestamps) = self.data.devices.iter()
+                .find(|n| n.port == self.devices[d].port)
+                .map_or((0.0, false), |n| (n.clock_offset_ms / 1000.0, n.timestamp_column));
+            let mut err = None;
+            for line in lines {
+                let mut line = match line {
+                    Ok(line) => line,
+                    Err(e) => {
+                        err = Some(e);
+                        break;
+                    }
+                };
+                self.metrics.lines.fetch_add(1, Ordering::Relaxed);
+                let host_t = line.t;
+                let host = timestamps.then_some(host_t + offset);
+                line.t = self.devices[d].parser.device_time(host_t) + offset;
+                if let Some(label) = self.mark_label(&line.content) {
+                    let content = format!("[{}] {}", self.devices[d].port, line.content);
+                    self.markers.push(Marker { t: line.t, label });
+                    self.log_line(line.t, &content, &[]);
+                    self.push_console_line(line.t, host, &content, false);
+                    continue;
+                }
+                let device = &mut self.devices[d];
+                let parsed = match line.values.take() {
+                    Some(values) => device.parser.accept_values(values),
+                    None => device.parser.parse_values(&line.content)
+                };
+                line.t = device.parser.device_time(host_t) + offset;
+                let content = format!("[{}] {}", device.port, line.content);
+                match parsed {
+                    Ok(values) if !values.is_empty() => {
+                        let raw = self.raw_text(Some(d), &content);
+                        self.ingest(Some(d), line.t, &values, raw);
+                        self.write_rows(line.t, &content);
+                    },
+                    Ok(_) => self.log_line(line.t, &content, &[]),
+                    Err(e) => {
+                        self.metrics.parse_errors.fetch_add(1, Ordering::Relaxed);
+                        if matches!(e, ParseError::ChecksumFailed) {
+                            self.bad_checksums += 1;
+                        }
+                        if self.devices[d].parser.should_warn(&e) {
+                            let port = self.devices[d].port.clone();
+                            self.parse_warning(&e, Some(&port));
+                        }
+                        self.log_line(line.t, &content, &[]);
+                    }
+                }
+                self.push_console_line(line.t, host, &content, false);
+            }
+            if err.is_none() {
+                let limit = self.data.devices.iter()
+                    .find(|n| n.port == self.devices[d].port)
+                    .and_then(|n| n.watchdog());
+                err = self.devices[d].reader.watchdog(limit);
+            }
+            match err {
+                Some(e) => {
+                    let device = self.devices.remove(d);
+                    if let SerialError::Unresponsive(_) = e {
+                        self.fault_event(self.clock.now(), &format!("[{}] {}, closing the connection", device.port, e));
+                    }
+                    self.notify_error(&format!("Device {} disconnected ({})", device.port, e));
+                    self.retire_device(device);
+                },
+                None => d += 1
+            }
         }
-        for (l, r) in zip(&mut self.values, values) {
-            l.push([t, *r]);
+    }
+
+    fn retire_device(&mut self, mut device: Device) {
+        if let Some(msg) = device.faults.update(device.reader.elapsed(), device.reader.line_faults()).or_else(|| device.faults.finish()) {
+            self.fault_event(device.reader.elapsed(), &format!("[{}] {}", device.port, msg));
         }
+        self.closed_faults.push((device.port, device.faults));
     }
 
-    fn handle_input_line(&mut self, t: f64, line: &String) {
-        let fmt_line = format!("[{:.2}] > {}", t, line);
-        if self.terminal_output {
-            println!("{}", &fmt_line);
-            _ = std::io::stdout().flush();
+    fn sync_faults(&mut self) {
+        let mut bursts = Vec::new();
+        if let Some(reader) = &self.reader {
+            let t = reader.elapsed();
+            if let Some(msg) = self.faults.update(t, reader.line_faults()) {
+                bursts.push((t, msg));
+            }
         }
-        self.lines.push_back(fmt_line);
-        if self.lines.len() > Self::STORED_LINES {
-            self.lines.pop_front();
+        for device in &mut self.devices {
+            let t = device.reader.elapsed();
+            if let Some(msg) = device.faults.update(t, device.reader.line_faults()) {
+                bursts.push((t, format!("[{}] {}", device.port, msg)));
+            }
+        }
+        for (t, msg) in bursts {
+            self.fault_event(t, &msg);
         }
     }
 
-    fn prep_input_slots(&mut self, slots: usize) {
-        for i in self.data.inp_slots.len()..slots {
-            let col = rgb_from_hsv((i as f32 * 0.15 % 1.0, 0.8, 0.8));
-            let slot = InputSlot {
-                index: i,
-                name: format!("Slot {}", (i + 1)),
-                color: col,
-                value: 0.0
-            };
-            self.data.inp_slots.push(slot);
+    fn fault_event(&mut self, t: f64, msg: &str) {
+        if self.headless {
+            eprintln!("{}", msg);
+        }
+        self.log_line(t, msg, &[]);
+        self.push_console_line(t, None, msg, false);
+        for (counter, channel) in zip(&mut self.events, &self.data.event_channels) {
+            counter.on_line(t, msg, channel.bucket);
+        }
+    }
+
+    pub fn fault_stats(&self) -> Vec<(&str, &FaultStats)> {
+        self.fault_port.iter()
+            .map(|n| (n.as_str(), &self.faults))
+            .chain(self.devices.iter().map(|n| (n.port.as_str(), &n.faults)))
+            .chain(self.closed_faults.iter().map(|n| (n.0.as_str(), &n.1)))
+            .collect()
+    }
+
+    fn write_summary(&self, path: &Path, started: Instant, locked: bool, timing: Option<&TimingStats>, loss: Option<LossSummary>) -> std::io::Result<()> {
+        let summary = RunSummary {
+            duration_secs: started.elapsed().as_secs_f64(),
+            lines: self.metrics.lines.load(Ordering::Relaxed),
+            parse_errors: self.metrics.parse_errors.load(Ordering::Relaxed),
+            bytes: self.metrics.bytes.load(Ordering::Relaxed),
+            connections: self.fault_stats().into_iter()
+                .map(|(port, faults)| ConnectionSummary {
+                    port,
+                    faults,
+                    downtime_secs: faults.downtime()
+                })
+                .collect(),
+            config_locked: locked,
+            log_files: &self.log_files,
+            timing: timing.map(TimingSummary::from),
+            loss
+        };
+        let file = fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, &summary)?;
+        Ok(())
+    }
+
+    fn write_rows(&mut self, t: f64, line: &str) {
+        let row = self.latest.clone();
+        self.log_line(t, line, &row);
+        self.write_output(t, &row);
+        self.write_black_box(t, &row);
+    }
+
+    pub fn devices_mut(&mut self) -> &mut Vec<ConnectionConfig> {
+        &mut self.data.devices
+    }
+
+    pub fn connected_devices(&self) -> Vec<String> {
+        self.devices.iter().map(|n| n.port.clone()).collect()
+    }
+
+    pub fn connect_device(&mut self, index: usize) -> Result<(), SerialError> {
+        let config = self.data.devices.get(index).cloned().ok_or(SerialError::PortNotOpen)?;
+        let in_use = self.source == Source::Serial && self.data.conn_config.port == config.port;
+        if in_use || self.devices.iter().any(|n| n.port == config.port) {
+            return Err(SerialError::AlreadyOpen);
+        }
+        let mut reader = SerialReader::new(SerialConfig::from(config.clone()), self.clock.clone());
+        reader.open()?;
+        let start_mode = StartMode::from(config.clone());
+        if !self.is_capturing() {
+            self.reset_capture();
+            self.clock.restart(match start_mode {
+                StartMode::Delay(delay) => delay,
+                _ => Duration::ZERO
+            });
+        }
+        reader.begin_read(start_mode, config.start_match)?;
+        if !config.connect_message.is_empty() {
+            reader.write(format!("{}{}", config.connect_message, config.connect_ending.as_str()).as_bytes())?;
         }
+        let mut parser = SerialParser::new();
+        parser.set_known_labels(self.known_labels(Some(&config.port)));
+        parser.set_separators(config.separators());
+        parser.set_checksum(config.checksum);
+        parser.set_timestamp(config.timestamp());
+        self.devices.push(Device {
+            port: config.port,
+            reader,
+            parser,
+            slots: Vec::new(),
+            row_width: 0,
+            bytes_read: 0,
+            faults: FaultStats::default()
+        });
+        Ok(())
+    }
 
-        for (i, slot) in self.data.inp_slots.iter_mut().enumerate() {
-            if i < self.values.len() {
-                slot.value = self.values[i].last().unwrap_or(&[0.0, 0.0])[1];
+    fn connect_devices(&mut self) {
+        for i in 0..self.data.devices.len() {
+            if let Err(e) = self.connect_device(i) {
+                self.error(&format!("Could not connect {} ({})", self.data.devices[i].port, e));
             }
         }
     }
 
-    fn warning(&mut self, msg: &str) {
-        if let Some(ui) = &mut self.ui {
-            ui.set_notification(Notification::new(msg, Duration::from_secs(5), NotificationType::Warning), true);
+    pub fn disconnect_device(&mut self, port: &str) {
+        self.devices.retain(|n| n.port != port);
+    }
+
+    fn sync_metrics(&mut self) {
+        let mut bytes = 0;
+        if let Some(reader) = &self.reader {
+            let read = reader.bytes_read();
+            bytes += read.saturating_sub(self.bytes_read);
+            self.bytes_read = read;
+        }
+        for device in &mut self.devices {
+            let read = device.reader.bytes_read();
+            bytes += read.saturating_sub(device.bytes_read);
+            device.bytes_read = read;
         }
+        self.metrics.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.metrics.connected.store(self.is_connected(), Ordering::Relaxed);
+        if !self.metrics.is_serving() {
+            return;
+        }
+        let channels = self.channels().into_iter()
+            .map(|(slot, values)| ChannelSample {
+                name: slot.name.clone(),
+                value: values.last().map_or(f64::NAN, |n| slot.apply(n[1])),
+                age: self.sample_age(values)
+            })
+            .collect();
+        self.metrics.set_channels(channels);
     }
 
-    fn error(&mut self, msg: &str) {
-        if let Some(ui) = &mut self.ui {
-            ui.set_notification(Notification::new(msg, Duration::from_secs(5), NotificationType::Error), false);
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    fn probe_line(&mut self, line: &str) {
+        if self.probe.len() >= Self::PROBE_LINES {
+            return;
         }
+        self.probe.push(line.to_owned());
+        if self.probe.len() < Self::PROBE_LINES {
+            return;
+        }
+        let lines: Vec<&str> = self.probe.iter().map(|n| n.as_str()).collect();
+        let Some(suggestion) = SerialParser::suggest_frame(&self.data.frame_config, self.data.conn_config.separators(), &lines) else {
+            return;
+        };
         if self.headless {
-            eprintln!("{}", msg);
-            _ = std::io::stdout().flush();
+            eprintln!("Parser mode '{}' extracts few or no values, '{}' would extract more", self.data.frame_config.mode, suggestion.mode);
         }
-        self.disconnect_current();
+        self.frame_suggestion = Some(suggestion);
     }
 
-    pub fn conn_config(&mut self) -> &mut ConnectionConfig {
-        &mut self.data.conn_config
+    pub fn frame_suggestion(&self) -> Option<&FrameConfig> {
+        self.frame_suggestion.as_ref()
     }
 
-    pub fn plot_config_mut(&mut self) -> &mut PlotConfig {
-        &mut self.data.plot_config
+    pub fn accept_frame_suggestion(&mut self) {
+        if let Some(config) = self.frame_suggestion.take() {
+            self.data.frame_config = config;
+        }
     }
 
-    pub fn plot_config(&self) -> &PlotConfig {
-        &self.data.plot_config
+    pub fn dismiss_frame_suggestion(&mut self) {
+        self.frame_suggestion = None;
     }
 
-    pub fn input_slots_mut(&mut self) -> &mut Vec<InputSlot> {
-        &mut self.data.inp_slots
+    fn end_of_input(&mut self) {
+        if self.headless {
+            self.shutdown(0);
+        }
+        let msg = format!("Input ended ({})", self.connected_port());
+        self.disconnect_current();
+        self.info(&msg);
     }
 
-    pub fn input_slots(&self) -> &Vec<InputSlot> {
-        &self.data.inp_slots
+    fn log_line(&mut self, t: f64, line: &str, values: &[f64]) {
+        let Some(logger) = &mut self.logger else {
+            return;
+        };
+        let names: Vec<&str> = self.data.inp_slots.iter().map(|n| n.name.as_str()).collect();
+        if let Err(e) = logger.write(t, line, values, &names) {
+            self.logger = None;
+            self.notify_error(&format!("Logging stopped ({})", e));
+        }
     }
 
-    pub fn input_columns(&self) -> usize {
-        self.parser.columns()
+    fn write_output(&mut self, t: f64, values: &[f64]) {
+        let Some(output) = &mut self.output else {
+            return;
+        };
+        let names: Vec<String> = self.data.inp_slots.iter().map(InputSlot::legend_name).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let (values, shifts): (Vec<f64>, Vec<f64>) = match self.data.export_raw {
+            true => (values.to_vec(), Vec::new()),
+            false => values.iter()
+                .enumerate()
+                .map(|(i, n)| match self.data.inp_slots.get(i) {
+                    Some(slot) => (slot.apply(*n), slot.time_shift()),
+                    None => (*n, 0.0)
+                })
+                .unzip()
+        };
+        if let Err(e) = output.write(t, &values, &shifts, &names) {
+            self.output = None;
+            self.error(&format!("Could not write output ({})", e));
+        }
     }
 
-    pub fn plots_mut(&mut self) -> &mut Vec<PlotData> {
-        &mut self.data.plots
+    fn write_black_box(&mut self, t: f64, values: &[f64]) {
+        let Some(black_box) = &mut self.black_box else {
+            return;
+        };
+        let names: Vec<&str> = self.data.inp_slots.iter().map(|n| n.name.as_str()).collect();
+        if let Err(e) = black_box.write(t, values, &names) {
+            self.black_box = None;
+            self.notify_error(&format!("Black box stopped ({})", e));
+        }
     }
 
-    pub fn plots(&self) -> &Vec<PlotData> {
-        &self.data.plots
+    fn close_black_box(&mut self) {
+        if let Some(black_box) = self.black_box.take() {
+            black_box.close();
+        }
     }
 
-    pub fn raw_values(&self) -> &Vec<Vec<[f64; 2]>> {
-        &self.values
+    pub fn disconnect_policy_mut(&mut self) -> &mut DisconnectPolicy {
+        &mut self.data.on_disconnect
     }
 
-    pub fn console_lines(&self) -> &VecDeque<String> {
-        &self.lines
+    pub fn black_box_mut(&mut self) -> &mut BlackBoxConfig {
+        &mut self.data.black_box
     }
 
-    pub fn available_devices(&self) -> Vec<String> {
-        match serialport::available_ports() {
-            Ok(ports) => ports.iter().map(|n| n.port_name.to_owned()).collect(),
-            Err(_) => Vec::new()
+    pub fn pending_recovery(&self) -> Option<&Path> {
+        self.recovered.first().and_then(|n| n.parent())
+    }
+
+    pub fn import_recovery(&mut self) {
+        let paths = std::mem::take(&mut self.recovered);
+        match BlackBox::load(&paths) {
+            Ok(recording) if !recording.rows.is_empty() => {
+                let rows = recording.rows.len();
+                self.disconnect_current();
+                self.load_session(recording);
+                self.info(&format!("Imported {} rows from the black box", rows));
+            },
+            Ok(_) => self.warning("The black box contains no rows"),
+            Err(e) => self.notify_error(&format!("Could not import the black box ({})", e))
         }
     }
 
-    pub fn can_connect(&self) -> bool {
-        self.data.conn_config.port != ConnectionConfig::NO_PORT
+    pub fn discard_recovery(&mut self) {
+        BlackBox::discard(&std::mem::take(&mut self.recovered));
     }
 
-    pub fn is_connected(&self) -> bool {
-        match &self.reader {
-            Some(reader) => reader.is_open(),
-            None => false
+    fn load_session(&mut self, recording: Recording) {
+        let columns = recording.rows.iter().map(|n| n.1.len()).max().unwrap_or_default();
+        for i in self.data.inp_slots.len()..columns {
+            let name = recording.names.get(i).cloned().unwrap_or_else(|| format!("Slot {}", i + 1));
+            self.push_input_slot(&name, None, i);
+        }
+        let window = self.data.plot_config.window;
+        self.values = vec![Vec::new(); columns];
+        self.latest = vec![0.0; columns];
+        self.stats = Vec::new();
+        self.stats.resize_with(columns, WindowStats::default);
+        for (t, values) in recording.rows {
+            for (i, value) in values.into_iter().enumerate() {
+                self.values[i].push([t, value]);
+                self.latest[i] = value;
+                self.stats[i].push(t, value, window);
+            }
         }
     }
 
-    pub fn connect_current(&mut self) -> Result<(), SerialError> {
-        let mut config = SerialConfig::from(self.data.conn_config.clone());
-        config.timeout = Duration::from_millis(50);
-        let mut reader = SerialReader::new(config);
-        reader.open(self.data.conn_config.dtr)?;
-        reader.begin_read(StartMode::from(self.data.conn_config.clone()))?;
-        self.reader = Some(reader);
-        self.paused = false;
-        Ok(())
+    fn record_line(&mut self, t: f64, line: &str) {
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+        if let Err(e) = recorder.write(t, line) {
+            self.recorder = None;
+            self.notify_error(&format!("Recording stopped ({})", e));
+        }
     }
 
-    pub fn disconnect_current(&mut self) {
-        if let Some(reader) = self.reader.take() {
-            std::mem::drop(reader);
-            self.parser.reset();
-            self.values.clear();
-            self.paused = false;
-        }
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
     }
 
-    pub fn has_input(&self) -> bool {
-        self.parser.columns() > 0
+    pub fn start_recording(&mut self, path: &Path) -> std::io::Result<()> {
+        self.recorder = Some(SessionRecorder::create(path)?);
+        Ok(())
     }
 
-    pub fn add_plot(&mut self) {
-        let off = self.has_console() as usize;
-        let index = self.data.plots.len() - off;
-        self.data.plots.insert(index, PlotData::new(&format!("Plot {}", self.data.plots.len() + 1 - off)));
+    pub fn stop_recording(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        match recorder.finish() {
+            Ok((path, lines)) => {
+                let msg = format!("Recorded {} lines ({})", lines, path.display());
+                if self.headless {
+                    eprintln!("{}", msg);
+                }
+                self.info(&msg);
+            },
+            Err(e) => self.notify_error(&format!("Could not finish recording ({})", e))
+        }
     }
 
-    pub fn remove_plot(&mut self, index: usize) {
-        self.data.plots.remove(index);
+    pub fn replay_speed_mut(&mut self) -> &mut ReplaySpeed {
+        &mut self.replay_speed
     }
 
-    pub fn reset_plot(&mut self, index: usize) {
-        if self.data.plots[index].console {
-            self.lines.clear();
+    pub fn open_recording(&mut self, path: PathBuf) -> Result<(), SerialError> {
+        if self.is_connected() || self.is_reconnecting() {
+            self.disconnect_current();
         }
+        self.connect_source(Source::Recording {
+            path,
+            speed: self.replay_speed
+        })
     }
 
-    pub fn add_console(&mut self) {
-        if !self.has_console() {
-            self.data.plots.push(PlotData::console())
+    fn close_log(&mut self) {
+        let Some(logger) = self.logger.take() else {
+            return;
+        };
+        match logger.close() {
+            Ok(files) if !files.is_empty() => {
+                let names: Vec<String> = files.iter().map(|n| n.display().to_string()).collect();
+                let msg = format!("Log files: {}", names.join(", "));
+                if self.headless {
+                    eprintln!("{}", msg);
+                }
+                self.info(&msg);
+                self.log_files.retain(|n| n.exists() && !files.contains(n));
+                self.log_files.extend(files);
+            },
+            Ok(_) => {},
+            Err(e) => self.notify_error(&e.to_string())
         }
     }
 
-    pub fn has_console(&self) -> bool {
-        self.data.plots.iter().any(|n| n.console)
+    pub fn log_status(&self) -> Option<(String, u64)> {
+        let (path, size) = self.logger.as_ref()?.current()?;
+        let name = path.file_name().map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+        Some((name, size))
     }
 
-    pub fn is_paused(&self) -> bool {
-        self.paused
+    pub fn log_config_mut(&mut self) -> &mut LogConfig {
+        &mut self.data.log_config
     }
 
-    pub fn set_paused(&mut self, pause: bool) {
-        self.paused = pause
+    pub fn audio_config_mut(&mut self) -> &mut AudioConfig {
+        &mut self.data.audio
     }
 
-    pub fn zoom_enabled(&self) -> bool {
-        self.is_paused()
+    pub fn ui_config(&self) -> &UiConfig {
+        &self.data.ui_config
     }
 
-    pub fn save_config_to_file(&self) -> std::io::Result<Option<String>> {
-        let file = rfd::FileDialog::new()
-            .add_filter("JSON", &["json"])
-            .save_file();
-        if let Some(path) = file {
-            SerialMonitorData::serialize(&path, &self.data)?;
-            return Ok(path.into_os_string().into_string().ok());
-        }
-        Ok(None)
+    pub fn ui_config_mut(&mut self) -> &mut UiConfig {
+        &mut self.data.ui_config
     }
 
-    pub fn load_config_from_file(&mut self, ui: &mut SerialMonitorUI) -> std::io::Result<bool> {
-        let file = rfd::FileDialog::new()
-            .add_filter("JSON", &["json"])
-            .pick_file();
-        if let Some(path) = file {
-            let config = SerialMonitorData::deserialize(&path)?;
-            self.load_config(config, ui);
-            return Ok(true);
+    fn sync_audio(&mut self) {
+        if !self.data.audio.enabled {
+            self.audio = None;
+            return;
+        }
+        if self.audio.is_some() {
+            return;
+        }
+        match AudioFeedback::start() {
+            Ok(audio) => self.audio = Some(audio),
+            Err(e) => {
+                self.data.audio.enabled = false;
+                self.notify_error(&format!("Could not start audio feedback ({})", e));
+            }
         }
-        Ok(false)
     }
 
-    pub fn load_config(&mut self, config: SerialMonitorData, ui: &mut SerialMonitorUI) {
-        ui.reset();
-        self.disconnect_current();
-        self.data = config;
-        PlotData::update_internal_ids(&self.data.plots);
+    pub fn export_raw_mut(&mut self) -> &mut bool {
+        &mut self.data.export_raw
+    }
+
+    pub fn config_backup_mut(&mut self) -> &mut ConfigBackup {
+        &mut self.data.backup
+    }
+
+    fn raw_text(&self, device: Option<usize>, content: &str) -> Option<String> {
+        if !self.data.plot_config.raw_lines {
+            return None;
+        }
+        if self.data.frame_config.mode == FrameMode::Line {
+            return Some(content.to_owned());
+        }
+        let (parser, prefix) = match device {
+            Some(d) => (&self.devices[d].parser, format!("[{}] ", self.devices[d].port)),
+            None => (&self.parser, String::new())
+        };
+        Some(format!("{}{}", prefix, parser.frame_source().join(" | ")))
+    }
+
+    fn ingest(&mut self, device: Option<usize>, t: f64, values: &[Option<f64>], raw: Option<String>) {
+        let (port, labels, previous, change) = match device {
+            Some(d) => {
+                let device = &mut self.devices[d];
+                (Some(device.port.clone()), parser_labels(&device.parser), device.row_width, device.parser.take_width_change())
+            },
+            None => (None, parser_labels(&self.parser), self.row_width, self.parser.take_width_change())
+        };
+        let slots = self.assign_slots(port.as_deref(), &labels);
+        if previous > 0 && values.len() > previous {
+            self.new_channels(&slots[previous..values.len().min(slots.len())]);
+        } else if let Some((from, to)) = change {
+            let msg = match port {
+                Some(port) => format!("Values per line changed from {} to {} ({})", from, to, port),
+                None => format!("Values per line changed from {} to {}", from, to)
+            };
+            if self.headless {
+                eprintln!("{}", msg);
+            }
+            self.info(&msg);
+        }
+        self.handle_input(t, &slots, values);
+        if let (Some(raw), Ok(mut raw_lines)) = (raw, self.raw_lines.lock()) {
+            raw_lines.push(t, raw, &slots, values);
+            raw_lines.trim(t, self.data.plot_config.raw_retention);
+        }
+        match device {
+            Some(d) => {
+                self.devices[d].row_width = values.len();
+                self.devices[d].slots = slots;
+            },
+            None => {
+                self.row_width = values.len();
+                self.slots = slots;
+            }
+        }
+    }
+
+    fn assign_slots(&mut self, device: Option<&str>, labels: &[Option<String>]) -> Vec<usize> {
+        let labels = &labels[..labels.len().min(self.data.frame_config.max_columns)];
+        let mut slots = Vec::with_capacity(labels.len());
+        for (column, label) in labels.iter().enumerate() {
+            let existing = self.data.inp_slots.iter().position(|n| n.device.as_deref() == device && n.index == column);
+            if let Some(i) = existing {
+                slots.push(i);
+                continue;
+            }
+            let label = match label {
+                Some(label) => label.clone(),
+                None => format!("Slot {}", column + 1)
+            };
+            let name = match device {
+                Some(port) => format!("{}/{}", port, label),
+                None => label
+            };
+            slots.push(self.push_input_slot(&name, device, column));
+        }
+        slots
+    }
+
+    fn known_labels(&self, device: Option<&str>) -> Vec<String> {
+        let prefix = device.map(|n| format!("{}/", n)).unwrap_or_default();
+        let mut labels = Vec::new();
+        for slot in self.data.inp_slots.iter().filter(|n| n.device.as_deref() == device && n.index < self.data.frame_config.max_columns) {
+            if labels.len() <= slot.index {
+                labels.resize(slot.index + 1, String::new());
+            }
+            labels[slot.index] = slot.name.strip_prefix(&prefix).unwrap_or(&slot.name).to_owned();
+        }
+        labels
+    }
+
+    fn handle_input(&mut self, t: f64, slots: &[usize], values: &[Option<f64>]) {
+        if let Some(audio) = &mut self.audio {
+            audio.frame(self.data.audio.every);
+        }
+        let width = slots.iter().take(values.len()).map(|n| n + 1).max().unwrap_or_default();
+        while self.values.len() < width {
+            self.values.push(Vec::new());
+        }
+        self.latest.resize(self.values.len(), 0.0);
+        self.stats.resize_with(self.values.len(), WindowStats::default);
+        self.anomalies.resize_with(self.values.len(), AnomalyDetector::default);
+        self.alarms.resize_with(self.values.len(), AlarmState::default);
+        self.decimators.resize_with(self.values.len(), Decimator::default);
+        let (window, history) = (self.data.plot_config.window, self.data.plot_config.history_secs);
+        for (&i, &r) in zip(slots, values) {
+            let Some(r) = r else {
+                continue;
+            };
+            self.latest[i] = r;
+            let decimator = &mut self.decimators[i];
+            let sample = match &self.data.plot_config.decimation {
+                Some(config) => decimator.push(t, r, config),
+                None => {
+                    decimator.reset();
+                    Some([t, r])
+                }
+            };
+            let l = &mut self.values[i];
+            if let Some([st, r]) = sample {
+                let filtered = match (self.data.inp_slots.get(i), l.last()) {
+                    (Some(slot), Some(last)) => slot.deadband.as_ref()
+                        .is_some_and(|n| !n.passes(slot.apply(last[1]), slot.apply(r), st - last[0])),
+                    _ => false
+                };
+                if !filtered {
+                    l.push([st, r]);
+                    self.stats[i].push(st, r, window);
+                }
+            }
+            memory::trim_history(l, t, history);
+        }
+
+        let mut alarm = None;
+        let mut trips = Vec::new();
+        for (&i, &value) in zip(slots, values) {
+            let (Some(slot), Some(value)) = (self.data.inp_slots.get(i), value) else {
+                continue;
+            };
+            let detector = &mut self.anomalies[i];
+            match &slot.anomaly {
+                Some(anomaly) if detector.on_sample(t, value, anomaly) => alarm = Some((i, anomaly.clone())),
+                Some(_) => {},
+                None => detector.reset()
+            }
+            memory::trim_history(&mut detector.markers, t, history);
+            let state = &mut self.alarms[i];
+            match &slot.alarm {
+                Some(config) => if let Some(limit) = state.on_sample(slot.apply(value), config) {
+                    trips.push((i, limit, slot.apply(value), config.beep));
+                },
+                None => {
+                    state.reset();
+                    state.acknowledge();
+                }
+            }
+        }
+        if let Some((slot, anomaly)) = alarm {
+            self.anomaly_alarm(slot, &anomaly);
+        }
+        for (slot, limit, value, beep) in trips {
+            self.threshold_alarm(slot, &limit, value, beep);
+        }
+        if let Some(config) = self.data.loss.as_ref().filter(|n| zip(slots, values).any(|(i, value)| *i == n.slot && value.is_some())) {
+            self.loss.sync(config);
+            if self.loss.on_sample(t, &self.latest, config) {
+                let msg = format!("Packet loss above {}% ({})", config.alarm_rate.unwrap_or_default(), self.loss.summary());
+                if self.headless {
+                    eprintln!("{}", msg);
+                }
+                self.warning(&msg);
+            }
+        }
+
+        let (slots, latest, stored) = (&self.data.inp_slots, &self.latest, &self.values);
+        let mut invalid = None;
+        for (channel, derived) in zip(&mut self.derived, &self.data.derived_slots) {
+            let shown_t = t + derived.slot.time_shift();
+            let lookup = |name: &str| {
+                let (name, shown) = match name.strip_suffix(SHOWN_SUFFIX) {
+                    Some(name) => (name, true),
+                    None => (name, false)
+                };
+                let index = slots.iter().position(|n| n.name == name)
+                    .or_else(|| name.strip_prefix("slot").and_then(|n| n.parse::<usize>().ok()).and_then(|n| n.checked_sub(1)))?;
+                match shown {
+                    true => {
+                        let slot = slots.get(index)?;
+                        let value = plot_engine::interpolate(stored.get(index)?, shown_t - slot.time_shift());
+                        Some(value.map_or(f64::NAN, |n| slot.apply(n)))
+                    },
+                    false => latest.get(index).copied()
+                }
+            };
+            if let Some(e) = channel.on_sample(t, &lookup) {
+                invalid = Some(format!("Invalid expression for {} ({})", derived.slot.name, e));
+            }
+            memory::trim_history(&mut channel.values, t, history);
+        }
+        if let Some(msg) = invalid {
+            self.warning(&msg);
+        }
+    }
+
+    fn new_channels(&mut self, slots: &[usize]) {
+        let hide = self.data.plot_config.hide_new_channels;
+        let mut names = Vec::new();
+        for &i in slots {
+            names.push(self.data.inp_slots[i].name.clone());
+            if !hide {
+                continue;
+            }
+            for plot in self.data.plots.iter_mut().filter(|n| n.is_graph() && n.shows(i)) {
+                if !plot.hidden.contains(&i) {
+                    plot.hidden.push(i);
+                }
+            }
+        }
+        let plural = match names.len() {
+            1 => "",
+            _ => "s"
+        };
+        let suffix = match hide {
+            true => " (hidden)",
+            false => ""
+        };
+        let msg = format!("{} new channel{} detected: {}{}", names.len(), plural, names.join(", "), suffix);
+        if self.headless {
+            eprintln!("{}", msg);
+        }
+        self.info(&msg);
+    }
+
+    fn anomaly_alarm(&mut self, slot: usize, anomaly: &Anomaly) {
+        let msg = format!("Anomaly burst on {} ({} within {}s)", self.data.inp_slots[slot].name, anomaly.burst.unwrap_or_default(), anomaly.window);
+        if self.headless {
+            eprintln!("{}", msg);
+        }
+        self.warning(&msg);
+        let Some(name) = &anomaly.sequence else {
+            return;
+        };
+        if self.sequence_run.is_some() {
+            return;
+        }
+        match self.data.sequences.iter().position(|n| &n.name == name) {
+            Some(index) => self.start_sequence(index),
+            None => self.notify_error(&format!("Unknown sequence ({})", name))
+        }
+    }
+
+    fn threshold_alarm(&mut self, slot: usize, limit: &str, value: f64, beep: bool) {
+        let msg = format!("Alarm: {} {} ({})", self.data.inp_slots[slot].name, limit, value);
+        self.alarms[slot].active = Some(msg.clone());
+        self.notify_error(&msg);
+        if beep {
+            match self.headless || self.terminal_output {
+                true => eprint!("\x07"),
+                false => audio::beep()
+            }
+        }
+    }
+
+    pub fn active_alarms(&self) -> Vec<(usize, &str)> {
+        self.alarms.iter()
+            .enumerate()
+            .filter_map(|(i, n)| Some((i, n.active.as_deref()?)))
+            .collect()
+    }
+
+    pub fn acknowledge_alarm(&mut self, slot: Option<usize>) {
+        match slot {
+            Some(slot) => if let Some(alarm) = self.alarms.get_mut(slot) {
+                alarm.acknowledge();
+            },
+            None => self.alarms.iter_mut().for_each(AlarmState::acknowledge)
+        }
+    }
+
+    pub fn loss_config_mut(&mut self) -> &mut Option<LossConfig> {
+        &mut self.data.loss
+    }
+
+    pub fn loss(&self) -> Option<(&LossConfig, &LossTracker)> {
+        self.data.loss.as_ref().map(|n| (n, &self.loss))
+    }
+
+    pub fn anomalies(&self, slot: usize) -> Option<&AnomalyDetector> {
+        self.anomalies.get(slot)
+    }
+
+    fn mark_label(&self, line: &str) -> Option<String> {
+        let prefix = &self.data.frame_config.mark_prefix;
+        match prefix.is_empty() {
+            true => None,
+            false => line.trim_start().strip_prefix(prefix.as_str()).map(|n| n.trim().to_owned())
+        }
+    }
+
+    pub fn add_marker(&mut self, label: &str) {
+        let Some(t) = self.reader.as_ref().filter(|_| self.is_connected()).map(|n| n.elapsed()) else {
+            return;
+        };
+        let label = match label.trim().is_empty() {
+            true => format!("Marker {}", self.markers.len() + 1),
+            false => label.trim().to_owned()
+        };
+        self.markers.push(Marker { t, label });
+    }
+
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    fn handle_input_line(&mut self, t: f64, host_t: Option<f64>, line: &str) {
+        self.push_console_line(t, host_t, line, false);
+    }
+
+    fn push_console_line(&mut self, t: f64, host_t: Option<f64>, line: &str, sent: bool) {
+        let console_line = ConsoleLine {
+            t,
+            host_t,
+            content: line.to_owned(),
+            sent
+        };
+        if self.terminal_output {
+            println!("{} {} {}", console_line.prefix(), if sent { "<" } else { ">" }, line);
+            _ = std::io::stdout().flush();
+        }
+        self.lines.push_back(console_line);
+        while self.lines.len() > self.data.plot_config.console_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    fn enforce_memory_budget(&mut self) {
+        let budget = (self.data.plot_config.memory_budget * memory::BYTES_PER_MB) as usize;
+        let budget = budget.saturating_sub(memory::estimate_lines(&self.lines));
+        let budget = budget.saturating_sub(self.raw_lines.lock().map_or(0, |n| n.estimate()));
+        let budget = budget.saturating_sub(self.events.iter().map(|n| memory::estimate_channel(&n.values)).sum());
+        let budget = budget.saturating_sub(self.derived.iter().map(|n| memory::estimate_channel(&n.values)).sum());
+        let budget = budget.saturating_sub(self.anomalies.iter().map(|n| memory::estimate_channel(&n.markers)).sum());
+        let budget = budget.saturating_sub(self.loss.gaps.len() * std::mem::size_of::<f64>());
+        let budget = budget.saturating_sub(self.spectrum_usage);
+        if let Some(cutoff) = memory::trim_values(&mut self.values, budget) {
+            let channels = self.events.iter_mut().map(|n| &mut n.values)
+                .chain(self.derived.iter_mut().map(|n| &mut n.values))
+                .chain(self.anomalies.iter_mut().map(|n| &mut n.markers));
+            for values in channels {
+                memory::drop_before(values, cutoff);
+            }
+            let start = self.loss.gaps.partition_point(|n| *n < cutoff);
+            self.loss.gaps.drain(..start);
+            if let Ok(mut raw_lines) = self.raw_lines.lock() {
+                raw_lines.drop_before(cutoff);
+            }
+            if !self.memory_notified {
+                self.memory_notified = true;
+                self.info(&format!("Memory budget reached, dropping samples older than {:.2}s", cutoff));
+            }
+        }
+    }
+
+    fn prep_input_slots(&mut self) {
+        let labels = parser_labels(&self.parser);
+        self.slots = self.assign_slots(None, &labels);
+        for d in 0..self.devices.len() {
+            let labels = parser_labels(&self.devices[d].parser);
+            let port = self.devices[d].port.clone();
+            self.devices[d].slots = self.assign_slots(Some(&port), &labels);
+        }
+
+        for (slot, latest) in zip(&mut self.data.inp_slots, &self.latest) {
+            slot.value = *latest;
+        }
+        for (channel, counter) in zip(&mut self.data.event_channels, &self.events) {
+            if let Some(last) = counter.values.last() {
+                channel.slot.value = last[1];
+            }
+        }
+        for (derived, channel) in zip(&mut self.data.derived_slots, &self.derived) {
+            if let Some(last) = channel.values.last() {
+                derived.slot.value = last[1];
+            }
+        }
+    }
+
+    fn info(&mut self, msg: &str) {
+        if let Some(ui) = &mut self.ui {
+            ui.set_notification(Notification::new(msg, Duration::from_secs(5), NotificationType::Info), false);
+        }
+        self.request_repaint();
+    }
+
+    fn warning(&mut self, msg: &str) {
+        if let Some(ui) = &mut self.ui {
+            ui.set_notification(Notification::new(msg, Duration::from_secs(5), NotificationType::Warning), true);
+        }
+        self.request_repaint();
+    }
+
+    fn parse_warning(&mut self, e: &ParseError, port: Option<&str>) {
+        let msg = match port {
+            Some(port) => format!("{} ({})", e, port),
+            None => e.to_string()
+        };
+        if self.headless && matches!(e, ParseError::TooManyColumns(..)) {
+            eprintln!("{}", msg);
+        }
+        self.warning(&msg);
+    }
+
+    fn notify_error(&mut self, msg: &str) {
+        if let Some(ui) = &mut self.ui {
+            ui.set_notification(Notification::new(msg, Duration::from_secs(5), NotificationType::Error), false);
+        }
+        self.request_repaint();
+        if self.headless {
+            eprintln!("{}", msg);
+            _ = std::io::stdout().flush();
+        }
+    }
+
+    fn error(&mut self, msg: &str) {
+        self.notify_error(msg);
+        if self.headless {
+            self.shutdown(1);
+        }
+        self.disconnect_current();
+    }
+
+    pub fn separators(&self) -> Separators {
+        self.data.conn_config.separators()
+    }
+
+    pub fn conn_config(&mut self) -> &mut ConnectionConfig {
+        &mut self.data.conn_config
+    }
+
+    pub fn sidepanel_collapsed_mut(&mut self) -> &mut bool {
+        &mut self.data.sidepanel_collapsed
+    }
+
+    pub fn plot_config_mut(&mut self) -> &mut PlotConfig {
+        &mut self.data.plot_config
+    }
+
+    pub fn plot_config(&self) -> &PlotConfig {
+        &self.data.plot_config
+    }
+
+    pub fn input_slots_mut(&mut self) -> &mut Vec<InputSlot> {
+        &mut self.data.inp_slots
+    }
+
+    pub fn input_slots(&self) -> &Vec<InputSlot> {
+        &self.data.inp_slots
+    }
+
+    fn push_input_slot(&mut self, name: &str, device: Option<&str>, column: usize) -> usize {
+        let i = self.data.inp_slots.len();
+        let col = rgb_from_hsv((i as f32 * 0.15 % 1.0, 0.8, 0.8));
+        let mut slot = InputSlot::new(column, name, col);
+        slot.device = device.map(String::from);
+        self.data.inp_slots.push(slot);
+        i
+    }
+
+    pub fn slot_mapped(&self, slot: usize) -> bool {
+        self.slots.contains(&slot) || self.devices.iter().any(|n| n.slots.contains(&slot))
+    }
+
+    pub fn set_slot_column(&mut self, slot: usize, column: usize) {
+        let Some(current) = self.data.inp_slots.get(slot) else {
+            return;
+        };
+        let (device, previous) = (current.device.clone(), current.index);
+        if let Some(other) = self.data.inp_slots.iter_mut().find(|n| n.device == device && n.index == column) {
+            other.index = previous;
+        }
+        self.data.inp_slots[slot].index = column;
+        self.prep_input_slots();
+    }
+
+    pub fn remove_input_slot(&mut self, slot: usize) {
+        if slot >= self.data.inp_slots.len() {
+            return;
+        }
+        self.data.remove_input_slot(slot);
+        remove_at(&mut self.values, slot);
+        remove_at(&mut self.latest, slot);
+        remove_at(&mut self.stats, slot);
+        remove_at(&mut self.anomalies, slot);
+        remove_at(&mut self.alarms, slot);
+        remove_at(&mut self.decimators, slot);
+        if let Ok(mut raw_lines) = self.raw_lines.lock() {
+            raw_lines.remove_slot(slot);
+        }
+        self.prep_input_slots();
+    }
+
+    pub fn input_columns(&self) -> usize {
+        self.devices.iter()
+            .flat_map(|n| &n.slots)
+            .chain(&self.slots)
+            .map(|n| n + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    pub fn plots_mut(&mut self) -> &mut Vec<PlotData> {
+        &mut self.data.plots
+    }
+
+    pub fn plots(&self) -> &Vec<PlotData> {
+        &self.data.plots
+    }
+
+    pub fn raw_values(&self) -> &Vec<Vec<[f64; 2]>> {
+        &self.values
+    }
+
+    pub fn channels(&self) -> Vec<(&InputSlot, &[[f64; 2]])> {
+        let mut channels: Vec<(&InputSlot, &[[f64; 2]])> = self.data.inp_slots.iter()
+            .enumerate()
+            .map(|(i, slot)| (slot, self.values.get(i).map_or(&[][..], Vec::as_slice)))
+            .collect();
+        channels.extend(self.data.event_channels.iter()
+            .enumerate()
+            .map(|(i, channel)| (&channel.slot, self.events.get(i).map_or(&[][..], |n| n.values.as_slice()))));
+        channels.extend(self.data.derived_slots.iter()
+            .enumerate()
+            .map(|(i, derived)| (&derived.slot, self.derived.get(i).map_or(&[][..], |n| n.values.as_slice()))));
+        channels
+    }
+
+    pub fn display_channels(&self) -> Vec<(&InputSlot, &[[f64; 2]])> {
+        let mut channels = self.channels();
+        if let Some(t) = self.paused_at {
+            for (slot, values) in &mut channels {
+                let shift = slot.time_shift();
+                *values = &values[..values.partition_point(|n| n[0] + shift <= t)];
+            }
+        }
+        channels
+    }
+
+    pub fn interpolate(&self, channel: usize, t: f64) -> Option<f64> {
+        let channels = self.display_channels();
+        let (slot, values) = channels.get(channel)?;
+        plot_engine::interpolate(values, t - slot.time_shift()).map(|n| slot.apply(n))
+    }
+
+    pub fn slot_stats(&self, index: usize) -> Option<&WindowStats> {
+        self.stats.get(index)
+    }
+
+    pub fn stored_rate(&self) -> f64 {
+        self.stats.iter().map(WindowStats::rate).fold(0.0, f64::max)
+    }
+
+    pub fn sample_age(&self, values: &[[f64; 2]]) -> Option<f64> {
+        let reader = self.reader.as_ref().filter(|n| n.is_open())?;
+        values.last().map(|n| f64::max(reader.elapsed() - n[0], 0.0))
+    }
+
+    pub fn is_stale(&self, slot: &InputSlot, values: &[[f64; 2]]) -> bool {
+        let threshold = slot.stale_after.unwrap_or(self.data.plot_config.stale_after);
+        !self.is_paused() && self.sample_age(values).is_some_and(|age| age > threshold)
+    }
+
+    pub fn event_channels_mut(&mut self) -> &mut Vec<EventChannel> {
+        &mut self.data.event_channels
+    }
+
+    pub fn frame_config_mut(&mut self) -> &mut FrameConfig {
+        &mut self.data.frame_config
+    }
+
+    pub fn discarded_frames(&self) -> usize {
+        self.discarded_frames
+    }
+
+    pub fn bad_checksums(&self) -> u64 {
+        self.bad_checksums
+    }
+
+    pub fn parse_stats(&self) -> ParseStats {
+        let mut stats = self.parser.stats();
+        for device in &self.devices {
+            stats.add(&device.parser.stats());
+        }
+        stats
+    }
+
+    pub fn rejected_lines(&self) -> Vec<(Option<&str>, &RejectedLine)> {
+        let mut lines: Vec<(Option<&str>, &RejectedLine)> = self.parser.rejected().iter().map(|n| (None, n)).collect();
+        for device in &self.devices {
+            lines.extend(device.parser.rejected().iter().map(|n| (Some(device.port.as_str()), n)));
+        }
+        lines
+    }
+
+    pub fn reset_parse_stats(&mut self) {
+        self.parser.reset_stats();
+        self.devices.iter_mut().for_each(|n| n.parser.reset_stats());
+        self.bad_checksums = 0;
+    }
+
+    pub fn derived_slots_mut(&mut self) -> &mut Vec<DerivedSlot> {
+        &mut self.data.derived_slots
+    }
+
+    pub fn derived_error(&self, index: usize) -> Option<&str> {
+        self.derived.get(index).and_then(|n| n.parse_error())
+    }
+
+    pub fn add_derived_slot(&mut self) {
+        let i = self.data.derived_slots.len();
+        let col = rgb_from_hsv(((i as f32 * 0.15 + 0.25) % 1.0, 0.7, 0.9));
+        self.data.derived_slots.push(DerivedSlot::new(&format!("Derived {}", i + 1), col));
+    }
+
+    pub fn add_difference_channel(&mut self, a: usize, b: usize) -> Option<String> {
+        let (slot_a, slot_b) = (self.data.inp_slots.get(a)?, self.data.inp_slots.get(b)?);
+        let name = plot_engine::difference_name(slot_a, slot_b);
+        let shift = f64::min(slot_a.time_shift_ms, slot_b.time_shift_ms);
+        self.add_derived_slot();
+        let derived = self.data.derived_slots.last_mut()?;
+        derived.slot.name = name.clone();
+        derived.slot.time_shift_ms = shift;
+        derived.expression = format!("slot{}{shown} - slot{}{shown}", a + 1, b + 1, shown = SHOWN_SUFFIX);
+        Some(name)
+    }
+
+    pub fn add_event_channel(&mut self) {
+        let i = self.data.event_channels.len();
+        let col = rgb_from_hsv(((i as f32 * 0.15 + 0.5) % 1.0, 0.6, 0.9));
+        self.data.event_channels.push(EventChannel::new(&format!("Events {}", i + 1), col));
+    }
+
+    pub fn console_lines(&self) -> &VecDeque<ConsoleLine> {
+        &self.lines
+    }
+
+    pub fn timing_stats(&self) -> Option<TimingStats> {
+        self.reader.as_ref().map(|n| n.timing())
+    }
+
+    pub fn start_progress(&self) -> Option<StartProgress> {
+        self.reader.as_ref()
+            .filter(|n| n.is_open())
+            .map(|n| n.start_progress())
+            .filter(|n| !n.started)
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        memory::estimate_values(&self.values)
+            + self.events.iter().map(|n| memory::estimate_channel(&n.values)).sum::<usize>()
+            + self.derived.iter().map(|n| memory::estimate_channel(&n.values)).sum::<usize>()
+            + self.anomalies.iter().map(|n| memory::estimate_channel(&n.markers)).sum::<usize>()
+            + self.loss.gaps.len() * std::mem::size_of::<f64>()
+            + memory::estimate_lines(&self.lines)
+            + self.spectrum_usage
+    }
+
+    pub fn track_spectra<'a>(&mut self, spectra: impl IntoIterator<Item = &'a mut Spectrum>) {
+        let budget = (self.data.plot_config.memory_budget * memory::BYTES_PER_MB) as usize;
+        let budget = budget.saturating_sub(self.memory_usage() - self.spectrum_usage);
+        self.spectrum_usage = memory::trim_spectra(spectra, budget);
+    }
+
+    pub fn available_devices(&self) -> Vec<String> {
+        match serialport::available_ports() {
+            Ok(ports) => ports.iter().map(|n| n.port_name.to_owned()).collect(),
+            Err(_) => Vec::new()
+        }
+    }
+
+    pub fn can_connect(&self) -> bool {
+        self.data.conn_config.port != ConnectionConfig::NO_PORT
+    }
+
+    pub fn is_connected(&self) -> bool {
+        match &self.reader {
+            Some(reader) => reader.is_open(),
+            None => false
+        }
+    }
+
+    fn can_reconnect(&self) -> bool {
+        self.data.conn_config.auto_reconnect && self.source == Source::Serial && self.is_connected()
+    }
+
+    fn connection_lost(&mut self, port: String, reason: &str) {
+        match self.data.conn_config.continue_time {
+            true => self.suspend_current(true),
+            false => self.disconnect_current()
+        }
+        self.faults.connection_lost();
+        let now = Instant::now();
+        self.reconnect = Some(Reconnect {
+            port,
+            next: now + RECONNECT_INTERVAL
+        });
+        let msg = format!("Connection lost ({}), reconnecting\u{2026}", reason);
+        if self.headless {
+            eprintln!("{}", msg);
+        }
+        self.warning(&msg);
+    }
+
+    fn try_reconnect(&mut self) {
+        let Some(reconnect) = &mut self.reconnect else {
+            return;
+        };
+        if Instant::now() < reconnect.next {
+            return;
+        }
+        reconnect.next = Instant::now() + RECONNECT_INTERVAL;
+        let port = reconnect.port.clone();
+        if NetworkAddress::parse(&port).is_none() && !self.available_devices().contains(&port) {
+            self.warning(&format!("Reconnecting to {}\u{2026}", port));
+            return;
+        }
+        self.data.conn_config.port = port.clone();
+        match self.connect_with_clock(Source::Serial, !self.data.conn_config.continue_time) {
+            Ok(_) => {
+                self.faults.reconnected();
+                let msg = format!("Reconnected ({})", port);
+                if self.headless {
+                    eprintln!("{}", msg);
+                }
+                self.info(&msg);
+            },
+            Err(e) => self.warning(&format!("Reconnecting to {}\u{2026} ({})", port, e))
+        }
+    }
+
+    pub fn is_reconnecting(&self) -> bool {
+        self.reconnect.is_some()
+    }
+
+    pub fn connect_current(&mut self) -> Result<(), SerialError> {
+        self.connect_source(Source::Serial)
+    }
+
+    pub fn connect_source(&mut self, source: Source) -> Result<(), SerialError> {
+        let fresh = !self.is_capturing();
+        if fresh {
+            self.reset_capture();
+        }
+        self.connect_with_clock(source, fresh)
+    }
+
+    fn is_capturing(&self) -> bool {
+        self.reader.is_some() || self.reconnect.is_some() || !self.devices.is_empty()
+    }
+
+    fn reset_capture(&mut self) {
+        self.values.clear();
+        self.decimators.clear();
+        self.latest.clear();
+        self.stats.clear();
+        self.faults = FaultStats::default();
+        self.closed_faults.clear();
+    }
+
+    fn connect_with_clock(&mut self, source: Source, restart_clock: bool) -> Result<(), SerialError> {
+        self.diagnostic = None;
+        let send_on_connect = source == Source::Serial && !self.data.conn_config.connect_message.is_empty();
+        let mut config = SerialConfig::from(self.data.conn_config.clone());
+        if source != Source::Serial {
+            config.protocol = Protocol::Ascii;
+        }
+        let mut reader = SerialReader::new(config, self.clock.clone());
+        reader.open_source(&source)?;
+        let start_mode = match source {
+            Source::Serial => StartMode::from(self.data.conn_config.clone()),
+            _ => StartMode::Immediate
+        };
+        if restart_clock {
+            self.clock.restart(match start_mode {
+                StartMode::Delay(delay) => delay,
+                _ => Duration::ZERO
+            });
+        }
+        reader.begin_read(start_mode, self.data.conn_config.start_match)?;
+        self.reader = Some(reader);
+        self.faults.reader_started();
+        self.fault_port = Some(match &source {
+            Source::Serial => self.data.conn_config.port.clone(),
+            _ => source.to_string()
+        });
+        self.bytes_read = 0;
+        self.source = source;
+        self.parser.set_known_labels(self.known_labels(None));
+        self.parser.set_separators(self.data.conn_config.separators());
+        self.parser.set_checksum(self.data.conn_config.checksum);
+        self.parser.reset_stats();
+        self.bad_checksums = 0;
+        self.parser.set_timestamp(self.data.conn_config.timestamp());
+        self.reconnect = None;
+        if self.data.log_config.enabled && self.logger.is_none() {
+            match LogWriter::new(self.data.log_config.clone()) {
+                Ok(logger) => self.logger = Some(logger),
+                Err(e) => self.notify_error(&e.to_string())
+            }
+        }
+        if self.data.black_box.enabled && self.black_box.is_none() {
+            match BlackBox::open(self.data.black_box.clone()) {
+                Ok(black_box) => self.black_box = Some(black_box),
+                Err(e) => self.notify_error(&format!("Could not open the black box ({})", e))
+            }
+        }
+        self.connected_at = Some(Instant::now());
+        self.discarded_frames = 0;
+        self.probe.clear();
+        self.frame_suggestion = None;
+        self.memory_notified = false;
+        self.paused_at = None;
+        if send_on_connect {
+            let message = self.data.conn_config.connect_message.clone();
+            if let Err(e) = self.send_with_ending(&message, self.data.conn_config.connect_ending) {
+                self.notify_error(&format!("Could not send the connect message ({})", e));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn start_diagnostic(&mut self, mode: DiagnosticMode) -> Result<(), SerialError> {
+        if self.is_connected() {
+            return Err(SerialError::AlreadyOpen);
+        }
+        self.diagnostic = None;
+        let config = SerialConfig::from(self.data.conn_config.clone());
+        self.diagnostic = Some(Diagnostic::start(mode, config)?);
+        Ok(())
+    }
+
+    pub fn stop_diagnostic(&mut self) {
+        self.diagnostic = None;
+    }
+
+    pub fn diagnostic(&self) -> Option<(DiagnosticMode, DiagnosticState)> {
+        self.diagnostic.as_ref().map(|n| (n.mode(), n.state()))
+    }
+
+    pub fn send(&mut self, text: &str) -> Result<(), SerialError> {
+        self.send_with_ending(text, self.data.conn_config.line_ending)
+    }
+
+    pub fn send_preset(&mut self, index: usize) -> Result<(), SerialError> {
+        let Some(preset) = self.data.presets.get(index).cloned() else {
+            return Ok(());
+        };
+        self.send_with_ending(&preset.payload, preset.line_ending)
+    }
+
+    pub fn control_lines(&self) -> Option<(bool, bool)> {
+        self.reader.as_ref()?.control_lines()
+    }
+
+    pub fn set_dtr(&mut self, on: bool) -> Result<(), SerialError> {
+        self.reader.as_mut().ok_or(SerialError::PortNotOpen)?.set_dtr(on)
+    }
+
+    pub fn set_rts(&mut self, on: bool) -> Result<(), SerialError> {
+        self.reader.as_mut().ok_or(SerialError::PortNotOpen)?.set_rts(on)
+    }
+
+    pub fn send_break(&mut self) -> Result<(), SerialError> {
+        self.reader.as_mut().ok_or(SerialError::PortNotOpen)?.send_break(ControlLine::BREAK)
+    }
+
+    pub fn reset_target(&mut self) -> Result<(), SerialError> {
+        self.reader.as_mut().ok_or(SerialError::PortNotOpen)?.reset_esp32()
+    }
+
+    fn send_with_ending(&mut self, text: &str, line_ending: LineEnding) -> Result<(), SerialError> {
+        let reader = self.reader.as_ref().ok_or(SerialError::PortNotOpen)?;
+        reader.write(format!("{}{}", text, line_ending.as_str()).as_bytes())?;
+        let host_t = reader.elapsed();
+        let host = self.data.conn_config.timestamp_column.then_some(host_t);
+        self.push_console_line(self.parser.peek_device_time(host_t), host, text, true);
+        Ok(())
+    }
+
+    pub fn presets(&self) -> &[SendPreset] {
+        &self.data.presets
+    }
+
+    pub fn presets_mut(&mut self) -> &mut Vec<SendPreset> {
+        &mut self.data.presets
+    }
+
+    pub fn sequences(&self) -> &[Sequence] {
+        &self.data.sequences
+    }
+
+    pub fn sequences_mut(&mut self) -> &mut Vec<Sequence> {
+        &mut self.data.sequences
+    }
+
+    pub fn start_sequence(&mut self, index: usize) {
+        if let Some(sequence) = self.data.sequences.get(index) {
+            self.sequence_run = Some(SequenceRun::new(sequence.clone()));
+        }
+    }
+
+    pub fn stop_sequence(&mut self) {
+        self.abort_sequence("stopped");
+    }
+
+    pub fn sequence_progress(&self) -> Option<(&str, usize, usize)> {
+        self.sequence_run.as_ref().map(|run| {
+            let (step, steps) = run.progress();
+            (run.name(), step, steps)
+        })
+    }
+
+    pub fn disconnect_current(&mut self) {
+        let suspended = self.reconnect.take().is_some();
+        self.abort_sequence("disconnected");
+        for device in std::mem::take(&mut self.devices) {
+            self.retire_device(device);
+        }
+        self.close_log();
+        self.close_black_box();
+        self.stop_recording();
+        if self.reader.is_some() || suspended {
+            let policy = self.data.on_disconnect.clone();
+            self.suspend_current(policy.reset_parser);
+            self.slots.clear();
+            self.row_width = 0;
+            if policy.clear_samples {
+                self.clear_samples();
+            }
+            if policy.clear_console {
+                self.lines.clear();
+            }
+            if policy.reset_stats {
+                self.stats.iter_mut().for_each(|n| n.reset());
+                self.loss.reset();
+            }
+            self.view_reset |= policy.reset_plot_ranges;
+            self.paused_at = None;
+        }
+    }
+
+    pub fn clear_data(&mut self) {
+        self.clear_samples();
+        self.stats.iter_mut().for_each(|n| n.reset());
+        self.loss.reset();
+        self.lines.clear();
+        self.parser.reset();
+        self.devices.iter_mut().for_each(|n| n.parser.reset());
+        self.discarded_frames = 0;
+        self.view_reset = true;
+    }
+
+    fn clear_samples(&mut self) {
+        self.values.clear();
+        self.decimators.clear();
+        self.latest.clear();
+        self.events.iter_mut().for_each(|n| n.reset());
+        self.derived.iter_mut().for_each(|n| n.reset());
+        self.anomalies.clear();
+        self.alarms.clear();
+        self.markers.clear();
+        if let Ok(mut raw_lines) = self.raw_lines.lock() {
+            raw_lines.clear();
+        }
+        self.samples_cleared = true;
+    }
+
+    pub fn restart_capture(&mut self) {
+        self.clear_data();
+        self.clock.restart(Duration::ZERO);
+    }
+
+    pub fn capture_time(&self) -> Option<f64> {
+        match self.reader.as_ref().filter(|n| n.is_open()) {
+            Some(reader) => Some(reader.elapsed()),
+            None => (!self.devices.is_empty()).then(|| self.clock.now())
+        }
+    }
+
+    pub fn take_view_reset(&mut self) -> bool {
+        std::mem::take(&mut self.view_reset)
+    }
+
+    pub fn take_samples_cleared(&mut self) -> bool {
+        std::mem::take(&mut self.samples_cleared)
+    }
+
+    fn suspend_current(&mut self, reset_parser: bool) {
+        self.abort_sequence("connection lost");
+        if let Some(reader) = self.reader.take() {
+            if let Some(msg) = self.faults.update(reader.elapsed(), reader.line_faults()).or_else(|| self.faults.finish()) {
+                self.fault_event(reader.elapsed(), &msg);
+            }
+            std::mem::drop(reader);
+            self.connected_at = None;
+            if self.parser.has_pending_frame() {
+                self.discarded_frames += 1;
+                self.metrics.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+            match reset_parser {
+                true => self.parser.reset(),
+                false => self.parser.discard_frame()
+            }
+        }
+    }
+
+    pub fn connection_time(&self) -> Option<Duration> {
+        match self.is_connected() {
+            true => self.connected_at.map(|n| n.elapsed()),
+            false => None
+        }
+    }
+
+    pub fn connected_port(&self) -> String {
+        match &self.source {
+            Source::Serial => self.data.conn_config.port.clone(),
+            source => source.to_string()
+        }
+    }
+
+    pub fn has_input(&self) -> bool {
+        self.input_columns() > 0
+    }
+
+    pub fn add_plot(&mut self) {
+        let off = self.has_console() as usize;
+        let index = self.plot_insert_index();
+        self.data.plots.insert(index, PlotData::new(&format!("Plot {}", self.data.plots.len() + 1 - off)));
+    }
+
+    pub fn add_watch(&mut self) {
+        let index = self.plot_insert_index();
+        let count = self.data.plots.iter().filter(|n| n.kind == PlotKind::Watch).count();
+        self.data.plots.insert(index, PlotData::watch(&format!("Watch {}", count + 1)));
+    }
+
+    pub fn add_spectrum(&mut self) {
+        let index = self.plot_insert_index();
+        let count = self.data.plots.iter().filter(|n| n.kind == PlotKind::Spectrum).count();
+        self.data.plots.insert(index, PlotData::spectrum(&format!("Spectrum {}", count + 1)));
+    }
+
+    pub fn add_table(&mut self) {
+        let index = self.plot_insert_index();
+        let count = self.data.plots.iter().filter(|n| n.kind == PlotKind::Table).count();
+        self.data.plots.insert(index, PlotData::table(&format!("Table {}", count + 1)));
+    }
+
+    pub fn remove_plot(&mut self, index: usize) {
+        self.data.plots.remove(index);
+    }
+
+    fn plot_insert_index(&self) -> usize {
+        self.data.plots.iter()
+            .position(|n| n.kind == PlotKind::Console)
+            .unwrap_or(self.data.plots.len())
+    }
+
+    pub fn move_plot(&mut self, from: usize, to: usize) {
+        if from < self.data.plots.len() && to < self.data.plots.len() {
+            self.data.plots.swap(from, to);
+        }
+    }
+
+    pub fn reset_plot(&mut self, index: usize) {
+        self.stats.iter_mut().for_each(|n| n.reset());
+        match self.data.plots[index].kind {
+            PlotKind::Console => self.lines.clear(),
+            _ => self.markers.clear()
+        }
+    }
+
+    pub fn add_console(&mut self) {
+        if !self.has_console() {
+            self.data.plots.push(PlotData::console())
+        }
+    }
+
+    pub fn raw_lines(&self) -> Option<Arc<Mutex<RawLines>>> {
+        self.data.plot_config.raw_lines.then(|| Arc::clone(&self.raw_lines))
+    }
+
+    pub fn export_lines_mut(&mut self) -> &mut bool {
+        &mut self.data.export_lines
+    }
+
+    pub fn snapshot_dir_mut(&mut self) -> &mut String {
+        &mut self.data.snapshot_dir
+    }
+
+    pub fn has_console(&self) -> bool {
+        self.data.plots.iter().any(|n| n.kind == PlotKind::Console)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn paused_at(&self) -> Option<f64> {
+        self.paused_at
+    }
+
+    pub fn set_paused(&mut self, pause: bool) {
+        if let Some(reader) = &self.reader {
+            reader.set_paused(pause);
+        }
+        self.paused_at = match pause {
+            true => Some(self.parser.peek_device_time(self.reader.as_ref().map_or_else(|| self.clock.now(), |n| n.elapsed()))),
+            false => None
+        };
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked || (self.data.log_config.lock_config && self.logger.is_some())
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+    }
+
+    pub fn zoom_enabled(&self) -> bool {
+        self.is_paused()
+    }
+
+    pub fn save_config(&mut self, path: &Path) -> std::io::Result<()> {
+        SerialMonitorData::serialize(path, &self.data)?;
+        self.autosave_hash = self.data.to_json().ok().map(|n| config_hash(&n));
+        Ok(())
+    }
+
+    pub fn export_csv(&self, path: &Path) -> std::io::Result<()> {
+        let names: Vec<String> = self.data.inp_slots.iter().map(InputSlot::legend_name).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let raw_lines = self.raw_lines.lock().map_err(|_| std::io::Error::other("Raw lines unavailable"))?;
+        let lines = || (self.data.export_lines && self.data.plot_config.raw_lines).then(|| raw_lines.by_time());
+        match self.data.export_raw {
+            true => export::write_csv(path, &names, &self.values, lines())?,
+            false => {
+                let values: Vec<Vec<[f64; 2]>> = self.values.iter()
+                    .enumerate()
+                    .map(|(i, values)| match self.data.inp_slots.get(i) {
+                        Some(slot) => plot_engine::display_values(values, slot).into_owned(),
+                        None => values.clone()
+                    })
+                    .collect();
+                export::write_csv(path, &names, &values, lines())?;
+            }
+        }
+        if !self.markers.is_empty() {
+            export::write_markers(&export::markers_path(path), &self.markers)?;
+        }
+        Ok(())
+    }
+
+    pub fn load_config_file(&mut self, path: &PathBuf, ui: &mut SerialMonitorUI) -> std::io::Result<()> {
+        let config = SerialMonitorData::deserialize(path)?;
+        self.load_config(config, ui);
+        Ok(())
+    }
+
+    pub fn import_settings(&mut self, path: &Path, ui: &mut SerialMonitorUI) -> std::io::Result<ImportSummary> {
+        let (config, summary) = importers::import(path)?;
+        self.load_config(config, ui);
+        Ok(summary)
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.data.profile.as_deref()
+    }
+
+    pub fn load_profile(&mut self, name: &str, ui: &mut SerialMonitorUI) -> std::io::Result<()> {
+        let config = profiles::load(name)?;
+        self.load_config(config, ui);
+        Ok(())
+    }
+
+    pub fn save_profile(&mut self, name: &str) -> std::io::Result<()> {
+        profiles::store(name, &self.data)?;
+        self.data.profile = Some(name.to_owned());
+        self.autosave_hash = self.data.to_json().ok().map(|n| config_hash(&n));
+        Ok(())
+    }
+
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> std::io::Result<()> {
+        profiles::rename(name, new_name)?;
+        if self.profile() == Some(name) {
+            self.data.profile = Some(new_name.to_owned());
+        }
+        Ok(())
+    }
+
+    pub fn delete_profile(&mut self, name: &str) -> std::io::Result<()> {
+        profiles::delete(name)?;
+        if self.profile() == Some(name) {
+            self.data.profile = None;
+        }
+        Ok(())
+    }
+
+    pub fn import_profile(&mut self, path: &PathBuf) -> std::io::Result<String> {
+        let config = SerialMonitorData::deserialize(path)?;
+        let stem = path.file_stem().map_or(String::from("Imported"), |n| n.to_string_lossy().into_owned());
+        let name = profiles::unique_name(&stem)?;
+        profiles::store(&name, &config)?;
+        Ok(name)
+    }
+
+    pub fn load_config(&mut self, config: SerialMonitorData, ui: &mut SerialMonitorUI) {
+        ui.reset();
+        self.disconnect_current();
+        self.data = config;
+        PlotData::update_internal_ids(&mut self.data.plots);
+        self.autosave_hash = self.data.to_json().ok().map(|n| config_hash(&n));
+        self.restore = None;
+        let mut warnings = std::mem::take(&mut self.data.load_warnings);
+        warnings.extend(self.missing_port_warning());
+        show_load_warnings(ui, &warnings, true);
+    }
+}
+
+fn show_load_warnings(ui: &mut SerialMonitorUI, warnings: &[String], minor: bool) {
+    if !warnings.is_empty() {
+        ui.set_notification(Notification::new(&warnings.join("\n"), Duration::from_secs(10), NotificationType::Warning), minor);
+    }
+}
+
+fn remove_at<T>(values: &mut Vec<T>, i: usize) {
+    if i < values.len() {
+        values.remove(i);
+    }
+}
+
+fn parser_labels(parser: &SerialParser) -> Vec<Option<String>> {
+    (0..parser.columns()).map(|i| parser.label(i).map(String::from)).collect()
+}
+
+fn config_hash(config: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    config.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn find_restore(data: &SerialMonitorData, config_path: Option<&Path>) -> Option<(SerialMonitorData, SystemTime)> {
+    let path = SerialMonitorData::autosave_path()?;
+    let modified = fs::metadata(&path).and_then(|n| n.modified()).ok()?;
+    let loaded = config_path.and_then(|n| fs::metadata(n).and_then(|n| n.modified()).ok());
+    if loaded.is_some_and(|n| n >= modified) {
+        return None;
+    }
+    let autosave = SerialMonitorData::deserialize(&path).ok()?;
+    match autosave.to_json().ok()? != data.to_json().ok()? {
+        true => Some((autosave, modified)),
+        false => None
+    }
+}
+
+fn lock_app(app: &Mutex<SerialMonitorApp>) -> MutexGuard<'_, SerialMonitorApp> {
+    app.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+struct SerialMonitorWindow {
+    app: Arc<Mutex<SerialMonitorApp>>
+}
+
+impl eframe::App for SerialMonitorWindow {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        lock_app(&self.app).close();
     }
-}
 
-impl eframe::App for SerialMonitorApp {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        self.update();
-        if let Some(mut ui) = self.ui.take() {
-            ui.update(ctx, frame, self);
-            self.ui = Some(ui);
+        let dialog = {
+            let mut app = lock_app(&self.app);
+            let mut dialog = None;
+            if let Some(mut ui) = app.ui.take() {
+                ui.update(ctx, frame, &mut app);
+                dialog = ui.take_dialog();
+                app.ui = Some(ui);
+            }
+            if app.needs_repaint() {
+                ctx.request_repaint_after(REPAINT_INTERVAL);
+            }
+            dialog
+        };
+        let Some(dialog) = dialog else {
+            return;
+        };
+        let path = dialog.pick();
+        let mut app = lock_app(&self.app);
+        if let Some(mut ui) = app.ui.take() {
+            ui.finish_dialog(dialog, path, &mut app);
+            app.ui = Some(ui);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::net::TcpStream;
+
+    fn samples(app: &Mutex<SerialMonitorApp>) -> usize {
+        lock_app(app).raw_values().iter().map(Vec::len).sum()
+    }
+
+    fn wait_for_samples(app: &Mutex<SerialMonitorApp>, more_than: usize) -> usize {
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_secs(5) {
+            let count = samples(app);
+            if count > more_than {
+                return count;
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+        samples(app)
+    }
+
+    fn feed(app: &mut SerialMonitorApp, t: f64, line: &str) {
+        let values = app.parser.parse_values(line).unwrap();
+        app.ingest(None, t, &values, None);
+    }
+
+    #[test]
+    fn pause_truncates_shifted_channels() {
+        let mut app = SerialMonitorApp::new(SerialMonitorData::default(), None, None, false, false);
+        app.set_paused(true);
+        assert!(app.is_paused());
+        app.set_paused(false);
+
+        for t in 0..4 {
+            feed(&mut app, t as f64, "1,2");
+        }
+        app.data.inp_slots[1].time_shift_ms = 1500.0;
+        app.paused_at = Some(2.0);
+        let lens: Vec<usize> = app.display_channels().iter().map(|n| n.1.len()).collect();
+        assert_eq!(lens, [3, 1]);
+    }
+
+    #[test]
+    fn new_plots_go_before_moved_console() {
+        let data = SerialMonitorData {
+            plots: vec![PlotData::new("Plot 1"), PlotData::new("Plot 2")],
+            ..Default::default()
+        };
+        let mut app = SerialMonitorApp::new(data, None, None, false, false);
+        app.add_console();
+        app.move_plot(2, 1);
+        app.add_plot();
+        app.add_watch();
+        let kinds: Vec<PlotKind> = app.plots().iter().map(|n| n.kind).collect();
+        assert_eq!(kinds, [PlotKind::Time, PlotKind::Time, PlotKind::Watch, PlotKind::Console, PlotKind::Time]);
+        assert_eq!(app.plots()[1].name, "Plot 3");
+    }
+
+    #[test]
+    fn growing_columns_keep_bounds() {
+        let data = SerialMonitorData {
+            plots: vec![PlotData::new("Plot 1")],
+            ..Default::default()
+        };
+        let mut app = SerialMonitorApp::new(data, None, None, false, false);
+        let mut stored = HashMap::new();
+        let mut bounds = Vec::new();
+        for i in 0..20 {
+            let t = i as f64 * 0.1;
+            let line = match i {
+                0..10 => format!("{},{},{}", i % 10, 10 - i % 10, 5),
+                10 => format!("{},{},{},1000000,-1000000", i % 10, 10 - i % 10, 5),
+                _ => format!("{},{},{},3,4", i % 10, 10 - i % 10, 5)
+            };
+            feed(&mut app, t, &line);
+            let plot = &app.data.plots[0];
+            let ranges = app.values.iter()
+                .enumerate()
+                .filter(|(i, _)| !plot.hidden.contains(i))
+                .map(|(i, values)| {
+                    let (min, max) = plot_engine::value_range(values);
+                    (i, [min, max])
+                })
+                .collect();
+            bounds.push(plot_engine::track_max_range(&mut stored, ranges));
         }
-        ctx.request_repaint();
+
+        let names: Vec<&str> = app.data.inp_slots.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["Slot 1", "Slot 2", "Slot 3", "Slot 4", "Slot 5"]);
+        assert_eq!(app.data.plots[0].hidden, [3, 4]);
+        assert_eq!(app.values[3].first(), Some(&[1.0, 1000000.0]));
+        assert!(bounds.iter().all(|n| n.is_some_and(|(min, max)| min >= 0.0 && max <= 10.0)), "{:?}", bounds);
+        assert_eq!(bounds.last(), Some(&Some((0.0, 10.0))));
+    }
+
+    #[test]
+    fn event_channels_keep_their_index() {
+        let mut plot = PlotData::new("Plot 1");
+        plot.slots = Some(vec![2]);
+        let data = SerialMonitorData {
+            inp_slots: vec![InputSlot::new(0, "a", [1.0; 3]), InputSlot::new(1, "b", [1.0; 3])],
+            event_channels: vec![EventChannel::new("ev", [1.0; 3])],
+            plots: vec![plot],
+            ..Default::default()
+        };
+        let mut app = SerialMonitorApp::new(data, None, None, false, false);
+        app.sync_events();
+        let shown = |app: &SerialMonitorApp| app.channels().iter()
+            .enumerate()
+            .filter(|(i, _)| app.plots()[0].shows(*i))
+            .map(|(_, n)| n.0.name.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(shown(&app), ["ev"]);
+
+        feed(&mut app, 0.0, "1");
+        assert_eq!(app.channels().len(), 3);
+        assert_eq!(shown(&app), ["ev"]);
+        feed(&mut app, 1.0, "1,2");
+        app.clear_samples();
+        assert_eq!(shown(&app), ["ev"]);
+        assert!(app.channels().iter().all(|n| n.1.is_empty()));
+    }
+
+    #[test]
+    fn promoted_difference_matches_compare() {
+        let mut app = SerialMonitorApp::new(SerialMonitorData::default(), None, None, false, false);
+        feed(&mut app, 0.0, "5,2");
+        app.data.inp_slots[1].scale = 2.0;
+        app.data.inp_slots[1].offset = -3.0;
+        app.data.inp_slots[1].time_shift_ms = 500.0;
+        assert_eq!(app.add_difference_channel(0, 1).as_deref(), Some("Slot 1 \u{2212} Slot 2"));
+        assert_eq!(app.add_difference_channel(0, 2), None);
+        assert_eq!(app.data.derived_slots.len(), 1);
+        assert_eq!(app.data.derived_slots[0].expression, "slot1.shown - slot2.shown");
+        assert_eq!(app.data.derived_slots[0].slot.time_shift_ms, 0.0);
+
+        app.sync_derived();
+        feed(&mut app, 1.0, "10,4");
+        feed(&mut app, 2.0, "12,6");
+        assert_eq!(app.derived_error(0), None);
+        assert_eq!(app.derived[0].values.last(), Some(&[2.0, 12.0 - (5.0 * 2.0 - 3.0)]));
+        assert_eq!(app.interpolate(2, 2.0), Some(12.0 - (5.0 * 2.0 - 3.0)));
+        assert_eq!(app.interpolate(0, 2.0).zip(app.interpolate(1, 2.0)).map(|(a, b)| a - b), Some(5.0));
+    }
+
+    #[test]
+    fn ingest_continues_without_frames() {
+        let app = SerialMonitorApp::new(SerialMonitorData::default(), Some(Source::Demo), None, false, false);
+        let app = Arc::new(Mutex::new(app));
+        SerialMonitorApp::spawn_ingest(Arc::downgrade(&app));
+
+        let first = wait_for_samples(&app, 0);
+        assert!(first > 0, "no samples ingested without a frame");
+        assert!(wait_for_samples(&app, first) > first, "ingestion stopped");
+    }
+
+    #[test]
+    fn metrics_scrape_demo_source() {
+        let app = SerialMonitorApp::new(SerialMonitorData::default(), Some(Source::Demo), None, false, false);
+        let port = app.metrics.serve(0).unwrap();
+        let app = Arc::new(Mutex::new(app));
+        SerialMonitorApp::spawn_ingest(Arc::downgrade(&app));
+        wait_for_samples(&app, 0);
+        thread::sleep(Duration::from_millis(200));
+
+        let _stalled = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let started = Instant::now();
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1), "scrape waited for a stalled client");
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        let value = |name: &str| response.lines()
+            .find_map(|n| n.strip_prefix(name)?.strip_prefix(' ')?.parse::<f64>().ok())
+            .unwrap_or_else(|| panic!("{} missing from {}", name, response));
+        assert!(value("serialmonitor_lines_received_total") > 0.0);
+        assert!(value("serialmonitor_bytes_read_total") > 0.0);
+        assert_eq!(value("serialmonitor_parse_errors_total"), 0.0);
+        assert_eq!(value("serialmonitor_connected"), 1.0);
+        assert!(value("serialmonitor_uptime_seconds") > 0.0);
+        assert!(response.contains("serialmonitor_channel_value{channel="));
+    }
+
+    #[test]
+    fn ingest_survives_poisoned_lock() {
+        let app = SerialMonitorApp::new(SerialMonitorData::default(), Some(Source::Demo), None, false, false);
+        let app = Arc::new(Mutex::new(app));
+        SerialMonitorApp::spawn_ingest(Arc::downgrade(&app));
+        let first = wait_for_samples(&app, 0);
+
+        let frame = Arc::clone(&app);
+        _ = thread::spawn(move || {
+            let _app = frame.lock();
+            panic!("frame panicked");
+        }).join();
+        assert!(app.is_poisoned());
+        assert!(wait_for_samples(&app, first) > first, "ingestion stopped after a poisoned lock");
     }
 }
@@ -0,0 +1,188 @@
+use rodio::source::SineWave;
+use rodio::{OutputStream, Source};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+const SAMPLE_RATE: u32 = 44100;
+const VOLUME: f32 = 0.3;
+const CLICK_SAMPLES: u32 = SAMPLE_RATE / 500;
+const CLICK_GAP: u32 = SAMPLE_RATE / 100;
+const MAX_QUEUED: usize = 8;
+const RATE_WINDOW: u32 = SAMPLE_RATE / 4;
+const TONE_RATE: f64 = 50.0;
+const TONE_MIN_HZ: f64 = 220.0;
+const TONE_MAX_HZ: f64 = 1760.0;
+const TONE_RAMP: f32 = 1.0 / (SAMPLE_RATE / 100) as f32;
+const BEEP_HZ: f32 = 1000.0;
+const BEEP_DURATION: Duration = Duration::from_millis(250);
+
+pub struct AudioFeedback {
+    pending: Arc<AtomicUsize>,
+    frames: usize,
+    stop: Option<Sender<()>>,
+    worker_thread: Option<JoinHandle<()>>
+}
+
+impl AudioFeedback {
+    pub fn start() -> Result<Self, String> {
+        let pending = Arc::new(AtomicUsize::new(0));
+        let ticks = Ticks::new(Arc::clone(&pending));
+        let (stop, stopped) = mpsc::channel::<()>();
+        let (ready, started) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let stream = OutputStream::try_default()
+                .map_err(|e| e.to_string())
+                .and_then(|(stream, handle)| handle.play_raw(ticks).map(|_| stream).map_err(|e| e.to_string()));
+            match stream {
+                Ok(_stream) => {
+                    _ = ready.send(Ok(()));
+                    _ = stopped.recv();
+                },
+                Err(e) => _ = ready.send(Err(e))
+            }
+        });
+        match started.recv() {
+            Ok(Ok(())) => Ok(Self {
+                pending,
+                frames: 0,
+                stop: Some(stop),
+                worker_thread: Some(handle)
+            }),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(String::from("Audio thread stopped unexpectedly"))
+        }
+    }
+
+    pub fn frame(&mut self, every: usize) {
+        self.frames += 1;
+        if self.frames >= every.max(1) {
+            self.frames = 0;
+            self.pending.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+pub fn beep() {
+    thread::spawn(|| {
+        let Ok((_stream, handle)) = OutputStream::try_default() else {
+            eprint!("\x07");
+            return;
+        };
+        let tone = SineWave::new(BEEP_HZ).take_duration(BEEP_DURATION).amplify(VOLUME);
+        if handle.play_raw(tone).is_ok() {
+            thread::sleep(BEEP_DURATION * 2);
+        }
+    });
+}
+
+impl Drop for AudioFeedback {
+    fn drop(&mut self) {
+        self.stop.take();
+        if let Some(handle) = self.worker_thread.take() {
+            _ = handle.join();
+        }
+    }
+}
+
+struct Ticks {
+    pending: Arc<AtomicUsize>,
+    queued: usize,
+    click: u32,
+    since_click: u32,
+    noise: u32,
+    window: u32,
+    window_ticks: usize,
+    rate: f64,
+    phase: f64,
+    level: f32
+}
+
+impl Ticks {
+    fn new(pending: Arc<AtomicUsize>) -> Self {
+        Self {
+            pending,
+            queued: 0,
+            click: 0,
+            since_click: CLICK_GAP,
+            noise: 0x9e37_79b9,
+            window: 0,
+            window_ticks: 0,
+            rate: 0.0,
+            phase: 0.0,
+            level: 0.0
+        }
+    }
+
+    fn noise(&mut self) -> f32 {
+        self.noise ^= self.noise << 13;
+        self.noise ^= self.noise >> 17;
+        self.noise ^= self.noise << 5;
+        self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+
+    fn tone_hz(&self) -> f64 {
+        (TONE_MIN_HZ * (self.rate / TONE_RATE).sqrt()).clamp(TONE_MIN_HZ, TONE_MAX_HZ)
+    }
+}
+
+impl Iterator for Ticks {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let received = self.pending.swap(0, Ordering::Relaxed);
+        self.window_ticks += received;
+        self.window += 1;
+        if self.window >= RATE_WINDOW {
+            let rate = self.window_ticks as f64 * (SAMPLE_RATE as f64 / RATE_WINDOW as f64);
+            self.rate = (self.rate + rate) / 2.0;
+            self.window = 0;
+            self.window_ticks = 0;
+        }
+
+        let tone = self.rate > TONE_RATE;
+        self.queued = match tone {
+            true => 0,
+            false => usize::min(self.queued + received, MAX_QUEUED)
+        };
+        self.since_click = self.since_click.saturating_add(1);
+        if self.click == 0 && self.queued > 0 && self.since_click >= CLICK_GAP {
+            self.queued -= 1;
+            self.click = CLICK_SAMPLES;
+            self.since_click = 0;
+        }
+        let mut sample = 0.0;
+        if self.click > 0 {
+            sample += self.noise() * self.click as f32 / CLICK_SAMPLES as f32;
+            self.click -= 1;
+        }
+
+        let target = if tone { 0.5 } else { 0.0 };
+        self.level += (target - self.level).clamp(-TONE_RAMP, TONE_RAMP);
+        if self.level > 0.0 {
+            self.phase = (self.phase + self.tone_hz() / SAMPLE_RATE as f64).fract();
+            sample += self.level * (self.phase * std::f64::consts::TAU).sin() as f32;
+        }
+        Some(sample * VOLUME)
+    }
+}
+
+impl Source for Ticks {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
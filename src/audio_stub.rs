@@ -0,0 +1,13 @@
+pub struct AudioFeedback;
+
+impl AudioFeedback {
+    pub fn start() -> Result<Self, String> {
+        Err(String::from("built without the audio feature"))
+    }
+
+    pub fn frame(&mut self, _every: usize) {}
+}
+
+pub fn beep() {
+    eprint!("\x07");
+}
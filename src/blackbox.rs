@@ -0,0 +1,176 @@
+use crate::data::BlackBoxConfig;
+use crate::memory::BYTES_PER_MB;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const SEGMENTS: usize = 2;
+const EXTENSION: &str = "csv";
+const RECOVERED: &str = "recovered";
+
+pub struct BlackBox {
+    config: BlackBoxConfig,
+    writer: BufWriter<File>,
+    segment: usize,
+    started: Option<f64>,
+    size: u64,
+    synced: Instant
+}
+
+pub struct Recording {
+    pub names: Vec<String>,
+    pub rows: Vec<(f64, Vec<f64>)>
+}
+
+impl BlackBox {
+    pub fn open(config: BlackBoxConfig) -> std::io::Result<Self> {
+        let dir = directory()?;
+        fs::create_dir_all(&dir)?;
+        for segment in 1..SEGMENTS {
+            _ = fs::remove_file(segment_path(&dir, segment, false));
+        }
+        Ok(Self {
+            config,
+            writer: BufWriter::new(File::create(segment_path(&dir, 0, false))?),
+            segment: 0,
+            started: None,
+            size: 0,
+            synced: Instant::now()
+        })
+    }
+
+    pub fn write(&mut self, t: f64, values: &[f64], names: &[&str]) -> std::io::Result<()> {
+        let max_size = self.config.max_mb * BYTES_PER_MB / SEGMENTS as f64;
+        let full = self.started.is_some_and(|n| t - n >= self.config.seconds) || self.size as f64 >= max_size;
+        if full {
+            self.rotate()?;
+        }
+        let mut text = String::new();
+        if self.started.is_none() {
+            self.started = Some(t);
+            text.push('t');
+            for i in 0..values.len() {
+                match names.get(i) {
+                    Some(name) => text.push_str(&format!(",{}", name)),
+                    None => text.push_str(&format!(",Slot {}", i + 1))
+                }
+            }
+            text.push('\n');
+        }
+        text.push_str(&t.to_string());
+        values.iter().for_each(|n| text.push_str(&format!(",{}", n)));
+        text.push('\n');
+        self.writer.write_all(text.as_bytes())?;
+        self.size += text.len() as u64;
+        if self.synced.elapsed() >= Duration::from_secs_f64(self.config.sync_seconds) {
+            self.sync()?;
+        }
+        Ok(())
+    }
+
+    pub fn close(self) {
+        std::mem::drop(self);
+        if let Ok(dir) = directory() {
+            for segment in 0..SEGMENTS {
+                _ = fs::remove_file(segment_path(&dir, segment, false));
+            }
+        }
+    }
+
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.synced = Instant::now();
+        self.writer.flush()?;
+        self.writer.get_ref().sync_data()
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.sync()?;
+        self.segment = (self.segment + 1) % SEGMENTS;
+        self.writer = BufWriter::new(File::create(segment_path(&directory()?, self.segment, false))?);
+        self.started = None;
+        self.size = 0;
+        Ok(())
+    }
+
+    pub fn recover() -> Vec<PathBuf> {
+        let Ok(dir) = directory() else {
+            return Vec::new();
+        };
+        let mut recovered = Vec::new();
+        for segment in 0..SEGMENTS {
+            let (path, target) = (segment_path(&dir, segment, false), segment_path(&dir, segment, true));
+            let leftover = fs::metadata(&path).is_ok_and(|n| n.len() > 0);
+            if leftover && fs::rename(&path, &target).is_ok() {
+                recovered.push(target);
+            } else {
+                _ = fs::remove_file(&path);
+            }
+        }
+        if !recovered.is_empty() {
+            for segment in 0..SEGMENTS {
+                let target = segment_path(&dir, segment, true);
+                if !recovered.contains(&target) {
+                    _ = fs::remove_file(target);
+                }
+            }
+        }
+        recovered
+    }
+
+    pub fn load(paths: &[PathBuf]) -> std::io::Result<Recording> {
+        let mut recording = Recording {
+            names: Vec::new(),
+            rows: Vec::new()
+        };
+        for path in paths {
+            let mut lines = BufReader::new(File::open(path)?).lines();
+            let Some(header) = lines.next().transpose()? else {
+                continue;
+            };
+            let names: Vec<String> = header.split(',').skip(1).map(String::from).collect();
+            if names.len() > recording.names.len() {
+                recording.names = names;
+            }
+            for line in lines {
+                let Ok(line) = line else {
+                    break;
+                };
+                let mut fields = line.split(',').map(|n| n.trim().parse::<f64>());
+                let Some(Ok(t)) = fields.next() else {
+                    continue;
+                };
+                if let Ok(values) = fields.collect::<Result<Vec<f64>, _>>() {
+                    recording.rows.push((t, values));
+                }
+            }
+        }
+        recording.rows.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(recording)
+    }
+
+    pub fn discard(paths: &[PathBuf]) {
+        for path in paths {
+            _ = fs::remove_file(path);
+        }
+    }
+}
+
+impl Drop for BlackBox {
+    fn drop(&mut self) {
+        _ = self.sync();
+    }
+}
+
+fn directory() -> std::io::Result<PathBuf> {
+    dirs::config_dir()
+        .map(|n| n.join("SerialMonitor"))
+        .ok_or_else(|| std::io::Error::other("No config directory available"))
+}
+
+fn segment_path(dir: &Path, segment: usize, recovered: bool) -> PathBuf {
+    match recovered {
+        true => dir.join(format!("blackbox_{}.{}.{}", segment, RECOVERED, EXTENSION)),
+        false => dir.join(format!("blackbox_{}.{}", segment, EXTENSION))
+    }
+}
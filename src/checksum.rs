@@ -0,0 +1,60 @@
+use crate::data::Checksum;
+
+pub fn strip(checksum: Checksum, line: &str) -> Option<&str> {
+    let digits = match checksum {
+        Checksum::None | Checksum::LastColumnSum => return Some(line),
+        Checksum::NmeaXor | Checksum::Crc8 => 2,
+        Checksum::Crc16 => 4
+    };
+    let (payload, hex) = line.trim().rsplit_once('*')?;
+    let payload = match checksum {
+        Checksum::NmeaXor => payload.strip_prefix('$').unwrap_or(payload),
+        _ => payload
+    };
+    if hex.len() != digits {
+        return None;
+    }
+    let expected = u16::from_str_radix(hex, 16).ok()?;
+    let found = match checksum {
+        Checksum::NmeaXor => xor(payload.as_bytes()) as u16,
+        Checksum::Crc8 => crc8(payload.as_bytes()) as u16,
+        _ => crc16(payload.as_bytes())
+    };
+    (found == expected).then_some(payload)
+}
+
+pub fn sum_matches(values: &[f64], sum: f64, text: &str) -> bool {
+    let decimals = text.rsplit_once('.').map_or(0, |n| n.1.chars().take_while(char::is_ascii_digit).count());
+    let tolerance = 0.5 * 10f64.powi(-(decimals as i32)) + f64::EPSILON * sum.abs();
+    (values.iter().sum::<f64>() - sum).abs() <= tolerance
+}
+
+fn xor(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |acc, n| acc ^ n)
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |mut crc, n| {
+        crc ^= n;
+        for _ in 0..8 {
+            crc = match crc & 0x80 != 0 {
+                true => (crc << 1) ^ 0x07,
+                false => crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0xFFFF, |mut crc, n| {
+        crc ^= (*n as u16) << 8;
+        for _ in 0..8 {
+            crc = match crc & 0x8000 != 0 {
+                true => (crc << 1) ^ 0x1021,
+                false => crc << 1
+            };
+        }
+        crc
+    })
+}
@@ -0,0 +1,35 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+pub struct CaptureClock {
+    origin: Arc<Mutex<Instant>>
+}
+
+impl Default for CaptureClock {
+    fn default() -> Self {
+        Self {
+            origin: Arc::new(Mutex::new(Instant::now()))
+        }
+    }
+}
+
+impl CaptureClock {
+    pub fn restart(&self, delay: Duration) {
+        if let Ok(mut origin) = self.origin.lock() {
+            *origin = Instant::now() + delay;
+        }
+    }
+
+    pub fn now(&self) -> f64 {
+        self.at(Instant::now())
+    }
+
+    pub fn at(&self, instant: Instant) -> f64 {
+        let origin = self.origin.lock().map_or(instant, |n| *n);
+        match instant >= origin {
+            true => (instant - origin).as_secs_f64(),
+            false => -(origin - instant).as_secs_f64()
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt::Display;
+
+pub const COMMANDS: &[Command] = &[
+    Command::Connect,
+    Command::Pause,
+    Command::AddPlot,
+    Command::AddWatch,
+    Command::AddSpectrum,
+    Command::AddTable,
+    Command::AddConsole,
+    Command::AddMarker,
+    Command::ClearData,
+    Command::RestartCapture,
+    Command::Record,
+    Command::OpenRecording,
+    Command::ExportCsv,
+    Command::Snapshot,
+    Command::SaveConfig,
+    Command::LoadConfig,
+    Command::ImportSettings,
+    Command::Settings,
+    Command::Lock,
+    Command::Sequences,
+    Command::Presets,
+    Command::Slots,
+    Command::Timing,
+    Command::Diagnostics,
+    Command::Help,
+    Command::ResetPlot,
+    Command::MaximizePlot,
+    Command::DetachPlot,
+    Command::MovePlotUp,
+    Command::MovePlotDown,
+    Command::RemovePlot
+];
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Command {
+    Connect,
+    Pause,
+    AddPlot,
+    AddWatch,
+    AddSpectrum,
+    AddTable,
+    AddConsole,
+    AddMarker,
+    ClearData,
+    RestartCapture,
+    Record,
+    OpenRecording,
+    ExportCsv,
+    Snapshot,
+    SaveConfig,
+    LoadConfig,
+    ImportSettings,
+    Settings,
+    Lock,
+    Sequences,
+    Presets,
+    Slots,
+    Timing,
+    Diagnostics,
+    Help,
+    ResetPlot,
+    MaximizePlot,
+    DetachPlot,
+    MovePlotUp,
+    MovePlotDown,
+    RemovePlot
+}
+
+impl Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Connect => write!(f, "Connect / Disconnect"),
+            Self::Pause => write!(f, "Pause / Continue"),
+            Self::AddPlot => write!(f, "Add plot"),
+            Self::AddWatch => write!(f, "Add watch"),
+            Self::AddSpectrum => write!(f, "Add spectrum"),
+            Self::AddTable => write!(f, "Add data table"),
+            Self::AddConsole => write!(f, "Add console"),
+            Self::AddMarker => write!(f, "Add marker"),
+            Self::ClearData => write!(f, "Clear data"),
+            Self::RestartCapture => write!(f, "Restart capture clock"),
+            Self::Record => write!(f, "Record session / Stop recording"),
+            Self::OpenRecording => write!(f, "Open recording"),
+            Self::ExportCsv => write!(f, "Export CSV"),
+            Self::Snapshot => write!(f, "Snapshot plots and data"),
+            Self::SaveConfig => write!(f, "Save config"),
+            Self::LoadConfig => write!(f, "Load config"),
+            Self::ImportSettings => write!(f, "Import settings"),
+            Self::Settings => write!(f, "Show / Hide settings"),
+            Self::Lock => write!(f, "Lock / Unlock configuration"),
+            Self::Sequences => write!(f, "Sequences"),
+            Self::Presets => write!(f, "Send presets"),
+            Self::Slots => write!(f, "Manage input slots"),
+            Self::Timing => write!(f, "Timing diagnostics"),
+            Self::Diagnostics => write!(f, "Diagnostics"),
+            Self::Help => write!(f, "Help"),
+            Self::ResetPlot => write!(f, "Plot: Reset / Clear"),
+            Self::MaximizePlot => write!(f, "Plot: Maximize / Restore"),
+            Self::DetachPlot => write!(f, "Plot: Pop out / Dock"),
+            Self::MovePlotUp => write!(f, "Plot: Move up"),
+            Self::MovePlotDown => write!(f, "Plot: Move down"),
+            Self::RemovePlot => write!(f, "Plot: Delete")
+        }
+    }
+}
+
+impl Command {
+    pub fn is_plot_action(&self) -> bool {
+        matches!(self, Self::ResetPlot | Self::MaximizePlot | Self::DetachPlot | Self::MovePlotUp | Self::MovePlotDown | Self::RemovePlot)
+    }
+}
+
+pub fn search(query: &str) -> Vec<Command> {
+    let mut matches: Vec<(usize, Command)> = COMMANDS.iter()
+        .filter_map(|n| fuzzy_score(query, &n.to_string()).map(|score| (score, *n)))
+        .collect();
+    matches.sort_by_key(|n| std::cmp::Reverse(n.0));
+    matches.into_iter().map(|n| n.1).collect()
+}
+
+fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last: Option<usize> = None;
+    for c in query.to_lowercase().chars().filter(|n| !n.is_whitespace()) {
+        let found = pos + text[pos..].iter().position(|n| *n == c)?;
+        score += 1;
+        if last.is_some_and(|n| n + 1 == found) {
+            score += 2;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        last = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
@@ -1,8 +1,149 @@
-use crate::serial_reader::{FlowCtrl, Parity, SerialConfig, StartMode};
-use std::{fmt::Display, fs::File, io::Write, path::PathBuf, sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+use crate::serial_reader::{FlowCtrl, Parity, Protocol, SerialConfig, StartMatch, StartMode};
+use crate::derived::{self, DerivedChannel};
+use crate::serial_parser::Separators;
+use crate::format;
+use std::{collections::HashSet, fmt::Display, fs::{self, File}, io::Write, ops::RangeInclusive, path::{Path, PathBuf}, sync::atomic::{AtomicUsize, Ordering}, time::Duration};
 use serde::{Serialize, Deserialize};
+use serde_json::error::Category;
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum LineEnding {
+    None,
+    #[default]
+    Lf,
+    CrLf
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Lf => write!(f, "\\n"),
+            Self::CrLf => write!(f, "\\r\\n")
+        }
+    }
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n"
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum LineTerminator {
+    #[default]
+    Lf,
+    CrLf,
+    Cr,
+    Custom
+}
+
+impl Display for LineTerminator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lf => write!(f, "\\n"),
+            Self::CrLf => write!(f, "\\r\\n"),
+            Self::Cr => write!(f, "\\r"),
+            Self::Custom => write!(f, "Custom")
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Delimiter {
+    #[default]
+    Comma,
+    Semicolon,
+    Tab,
+    Space,
+    Custom
+}
+
+impl Display for Delimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Comma => write!(f, "Comma"),
+            Self::Semicolon => write!(f, "Semicolon"),
+            Self::Tab => write!(f, "Tab"),
+            Self::Space => write!(f, "Whitespace"),
+            Self::Custom => write!(f, "Custom")
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum DecimalSeparator {
+    #[default]
+    Point,
+    Comma
+}
+
+impl Display for DecimalSeparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Point => write!(f, "Point (1.5)"),
+            Self::Comma => write!(f, "Comma (1,5)")
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Checksum {
+    #[default]
+    None,
+    NmeaXor,
+    Crc8,
+    Crc16,
+    LastColumnSum
+}
+
+impl Display for Checksum {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::NmeaXor => write!(f, "NMEA XOR (*HH)"),
+            Self::Crc8 => write!(f, "CRC-8 (*HH)"),
+            Self::Crc16 => write!(f, "CRC-16 (*HHHH)"),
+            Self::LastColumnSum => write!(f, "Sum column")
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum TimestampUnit {
+    #[default]
+    Milliseconds,
+    Microseconds,
+    Seconds
+}
+
+impl Display for TimestampUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Milliseconds => write!(f, "ms"),
+            Self::Microseconds => write!(f, "µs"),
+            Self::Seconds => write!(f, "s")
+        }
+    }
+}
+
+impl TimestampUnit {
+    pub fn seconds(self, ticks: f64) -> f64 {
+        match self {
+            Self::Milliseconds => ticks / 1000.0,
+            Self::Microseconds => ticks / 1000000.0,
+            Self::Seconds => ticks
+        }
+    }
+}
 
 #[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ConnectionConfig {
     pub port: String,
     pub baud_rate: u32,
@@ -11,9 +152,49 @@ pub struct ConnectionConfig {
     pub stop_bits: u8,
     pub flow_ctrl: FlowCtrl,
     pub dtr: bool,
+    #[serde(default = "ConnectionConfig::default_rts")]
+    pub rts: bool,
     pub start_mode: StartMode,
     pub start_delay: u32,
-    pub start_msg: String
+    pub start_msg: String,
+    #[serde(default)]
+    pub start_match: StartMatch,
+    #[serde(default)]
+    pub connect_message: String,
+    #[serde(default)]
+    pub connect_ending: LineEnding,
+    #[serde(default)]
+    pub line_ending: LineEnding,
+    #[serde(default)]
+    pub terminator: LineTerminator,
+    #[serde(default)]
+    pub custom_terminator: String,
+    #[serde(default = "ConnectionConfig::default_read_timeout_ms")]
+    pub read_timeout_ms: u32,
+    #[serde(default)]
+    pub protocol: Protocol,
+    #[serde(default)]
+    pub auto_reconnect: bool,
+    #[serde(default)]
+    pub continue_time: bool,
+    #[serde(default)]
+    pub delimiter: Delimiter,
+    #[serde(default)]
+    pub custom_delimiter: String,
+    #[serde(default)]
+    pub decimal: DecimalSeparator,
+    #[serde(default)]
+    pub checksum: Checksum,
+    #[serde(default = "ConnectionConfig::default_max_bad_lines")]
+    pub max_bad_lines: u32,
+    #[serde(default = "ConnectionConfig::default_watchdog_secs")]
+    pub watchdog_secs: f64,
+    #[serde(default)]
+    pub clock_offset_ms: f64,
+    #[serde(default)]
+    pub timestamp_column: bool,
+    #[serde(default)]
+    pub timestamp_unit: TimestampUnit
 }
 
 impl Default for ConnectionConfig {
@@ -26,15 +207,36 @@ impl Default for ConnectionConfig {
             stop_bits: 1,
             flow_ctrl: FlowCtrl::None,
             dtr: true,
+            rts: true,
             start_mode: StartMode::Delay(Duration::ZERO),
             start_delay: 1000,
-            start_msg: String::from("Start")
+            start_msg: String::from("Start"),
+            start_match: StartMatch::EndsWith,
+            connect_message: String::new(),
+            connect_ending: LineEnding::Lf,
+            line_ending: LineEnding::Lf,
+            terminator: LineTerminator::Lf,
+            custom_terminator: String::new(),
+            read_timeout_ms: ConnectionConfig::default_read_timeout_ms(),
+            protocol: Protocol::Ascii,
+            auto_reconnect: false,
+            continue_time: false,
+            delimiter: Delimiter::Comma,
+            custom_delimiter: String::new(),
+            decimal: DecimalSeparator::Point,
+            checksum: Checksum::None,
+            max_bad_lines: ConnectionConfig::default_max_bad_lines(),
+            watchdog_secs: ConnectionConfig::default_watchdog_secs(),
+            clock_offset_ms: 0.0,
+            timestamp_column: false,
+            timestamp_unit: TimestampUnit::Milliseconds
         }
     }
 }
 
 impl From<ConnectionConfig> for SerialConfig {
     fn from(value: ConnectionConfig) -> Self {
+        let terminator = value.terminator();
         SerialConfig {
             port: value.port,
             baud_rate: value.baud_rate,
@@ -42,7 +244,12 @@ impl From<ConnectionConfig> for SerialConfig {
             parity: value.parity,
             stop_bits: value.stop_bits,
             flow_ctrl: value.flow_ctrl,
-            timeout: Duration::ZERO
+            dtr: value.dtr,
+            rts: value.rts,
+            timeout: Duration::from_millis(value.read_timeout_ms as u64),
+            terminator,
+            protocol: value.protocol,
+            max_bad_lines: value.max_bad_lines
         }
     }
 }
@@ -59,6 +266,54 @@ impl From<ConnectionConfig> for StartMode {
 
 impl ConnectionConfig {
     pub const NO_PORT: &'static str = "-";
+
+    fn default_max_bad_lines() -> u32 {
+        20
+    }
+
+    fn default_watchdog_secs() -> f64 {
+        5.0
+    }
+
+    fn default_rts() -> bool {
+        true
+    }
+
+    fn default_read_timeout_ms() -> u32 {
+        50
+    }
+
+    pub fn terminator(&self) -> Vec<u8> {
+        match self.terminator {
+            LineTerminator::Lf | LineTerminator::CrLf => b"\n".to_vec(),
+            LineTerminator::Cr => b"\r".to_vec(),
+            LineTerminator::Custom => match self.custom_terminator.chars().next() {
+                Some(c) => c.to_string().into_bytes(),
+                None => b"\n".to_vec()
+            }
+        }
+    }
+
+    pub fn watchdog(&self) -> Option<Duration> {
+        (self.watchdog_secs > 0.0).then(|| Duration::from_secs_f64(self.watchdog_secs))
+    }
+
+    pub fn separators(&self) -> Separators {
+        Separators {
+            delimiter: match self.delimiter {
+                Delimiter::Comma => Some(','),
+                Delimiter::Semicolon => Some(';'),
+                Delimiter::Tab => Some('\t'),
+                Delimiter::Space => None,
+                Delimiter::Custom => self.custom_delimiter.chars().next()
+            },
+            decimal_comma: self.decimal == DecimalSeparator::Comma
+        }
+    }
+
+    pub fn timestamp(&self) -> Option<TimestampUnit> {
+        self.timestamp_column.then_some(self.timestamp_unit)
+    }
 }
 
 #[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
@@ -86,13 +341,255 @@ impl Display for PlotScaleMode {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum ValueFormat {
+    Decimals,
+    Significant,
+    Engineering
+}
+
+impl Display for ValueFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum TimeFormat {
+    Auto,
+    Seconds,
+    Milliseconds,
+    MinSec
+}
+
+impl Display for TimeFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MinSec => write!(f, "Min:Sec"),
+            _ => write!(f, "{:?}", self)
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlotAxis {
+    pub value_format: ValueFormat,
+    pub digits: usize,
+    pub unit: String,
+    pub time_format: TimeFormat
+}
+
+impl Default for PlotAxis {
+    fn default() -> Self {
+        Self {
+            value_format: ValueFormat::Decimals,
+            digits: 2,
+            unit: String::new(),
+            time_format: TimeFormat::Auto
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ShadingColor {
+    pub value: f64,
+    pub color: [f32; 3]
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Shading {
+    pub channel: usize,
+    pub colors: Vec<ShadingColor>
+}
+
+impl Shading {
+    pub fn new(channel: usize) -> Self {
+        Self {
+            channel,
+            colors: Vec::new()
+        }
+    }
+
+    pub fn color(&self, value: f64) -> Option<[f32; 3]> {
+        self.colors.iter()
+            .find(|n| (n.value - value).abs() < 0.5)
+            .map(|n| n.color)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct RefLine {
+    pub value: f64,
+    #[serde(default)]
+    pub label: String,
+    pub color: [f32; 3],
+    #[serde(default)]
+    pub band: Option<f64>
+}
+
+impl RefLine {
+    pub fn range(&self) -> (f64, f64) {
+        let band = self.band.unwrap_or_default().abs();
+        (self.value - band, self.value + band)
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Smoothing {
+    pub window: usize,
+    #[serde(default)]
+    pub show_raw: bool,
+    #[serde(default)]
+    pub envelope: bool
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Self {
+            window: 10,
+            show_raw: false,
+            envelope: false
+        }
+    }
+}
+
+impl Smoothing {
+    pub const MAX_WINDOW: usize = 10000;
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct DetachedWindow {
+    pub size: [f32; 2]
+}
+
+impl Default for DetachedWindow {
+    fn default() -> Self {
+        Self {
+            size: [800.0, 450.0]
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum LogFormat {
+    Raw,
+    Csv
+}
+
+impl Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Raw => write!(f, "Raw"),
+            Self::Csv => write!(f, "CSV")
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum LogRotation {
+    Never,
+    Duration,
+    Size
+}
+
+impl Display for LogRotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    pub enabled: bool,
+    pub format: LogFormat,
+    pub timestamps: bool,
+    pub lock_config: bool,
+    pub directory: String,
+    pub pattern: String,
+    pub rotation: LogRotation,
+    pub rotate_minutes: f64,
+    pub rotate_mb: f64,
+    pub keep_files: usize
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            format: LogFormat::Raw,
+            timestamps: true,
+            lock_config: false,
+            directory: String::new(),
+            pattern: String::from("serial_{timestamp}"),
+            rotation: LogRotation::Never,
+            rotate_minutes: 60.0,
+            rotate_mb: 100.0,
+            keep_files: 0
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FrameMode {
+    Line,
+    Marker,
+    LineCount
+}
+
+impl Display for FrameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Line => write!(f, "Single line"),
+            Self::Marker => write!(f, "End marker"),
+            Self::LineCount => write!(f, "Line count")
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FrameConfig {
+    pub mode: FrameMode,
+    pub marker: String,
+    pub lines: usize,
+    pub mark_prefix: String,
+    pub strict_columns: bool,
+    pub max_columns: usize
+}
+
+impl Default for FrameConfig {
+    fn default() -> Self {
+        Self {
+            mode: FrameMode::Line,
+            marker: String::from("END"),
+            lines: 3,
+            mark_prefix: String::from("#MARK:"),
+            strict_columns: false,
+            max_columns: 64
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct PlotConfig {
     pub mode: PlotMode,
     pub window: f64,
     pub scale_mode: PlotScaleMode,
     pub y_min: f64,
-    pub y_max: f64
+    pub y_max: f64,
+    pub memory_budget: f64,
+    pub stale_after: f64,
+    pub sync_channel: Option<usize>,
+    pub sync_threshold: f64,
+    pub hide_new_channels: bool,
+    pub history_secs: f64,
+    pub console_lines: usize,
+    pub raw_lines: bool,
+    pub raw_retention: f64,
+    pub decimation: Option<Decimation>
 }
 
 impl Default for PlotConfig {
@@ -102,80 +599,1403 @@ impl Default for PlotConfig {
             window: 5.0,
             scale_mode: PlotScaleMode::Auto,
             y_min: 0.0,
-            y_max: 1.0
+            y_max: 1.0,
+            memory_budget: 256.0,
+            stale_after: 5.0,
+            sync_channel: None,
+            sync_threshold: 0.0,
+            hide_new_channels: true,
+            history_secs: 60.0,
+            console_lines: 512,
+            raw_lines: false,
+            raw_retention: 60.0,
+            decimation: None
         }
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
-pub struct InputSlot {
-    pub index: usize,
-    pub name: String,
-    pub color: [f32; 3],
-    #[serde(skip)]
-    pub value: f64
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum DecimationMode {
+    Every,
+    Average
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct PlotData {
-    pub id: usize,
-    pub name: String,
-    pub hidden: Vec<usize>,
-    pub height: f32,
-    pub console: bool
+impl Display for DecimationMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Every => write!(f, "Every Nth"),
+            Self::Average => write!(f, "Average")
+        }
+    }
 }
 
-static PLOT_ID: AtomicUsize = AtomicUsize::new(1);
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Decimation {
+    pub mode: DecimationMode,
+    pub every: usize,
+    pub bucket_ms: f64
+}
 
-impl PlotData {
-    pub fn new(name: &str) -> Self {
+impl Default for Decimation {
+    fn default() -> Self {
         Self {
-            id: PLOT_ID.fetch_add(1, Ordering::SeqCst),
-            name: name.to_owned(),
-            hidden: Vec::new(),
-            height: 256.0,
-            console: false
+            mode: DecimationMode::Every,
+            every: 10,
+            bucket_ms: 5.0
         }
     }
+}
 
-    pub fn console() -> Self {
+impl Decimation {
+    pub const MIN_BUCKET_MS: f64 = 0.01;
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct LossConfig {
+    pub slot: usize,
+    #[serde(default)]
+    pub modulus: u64,
+    #[serde(default)]
+    pub alarm_rate: Option<f64>,
+    #[serde(default)]
+    pub hide: bool
+}
+
+impl LossConfig {
+    pub fn new(slot: usize) -> Self {
         Self {
-            id: PLOT_ID.fetch_add(1, Ordering::SeqCst),
-            name: String::from("Console"),
-            hidden: Vec::new(),
-            height: 192.0,
-            console: true
+            slot,
+            modulus: 0,
+            alarm_rate: None,
+            hide: false
         }
     }
+}
 
-    pub fn update_internal_ids(plots: &Vec<PlotData>) {
-        if let Some(max) = plots.iter().max_by_key(|n| n.id) {
-            PLOT_ID.store(max.id + 1, Ordering::SeqCst);
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Calibration {
+    pub raw: [f64; 2],
+    pub reference: [f64; 2]
+}
+
+impl Calibration {
+    const MIN_SPAN: f64 = 1e-9;
+
+    pub fn fit(&self) -> Option<(f64, f64)> {
+        let span = self.raw[1] - self.raw[0];
+        let magnitude = f64::max(f64::max(self.raw[0].abs(), self.raw[1].abs()), 1.0);
+        if span.abs() < Self::MIN_SPAN * magnitude {
+            return None;
         }
+        let scale = (self.reference[1] - self.reference[0]) / span;
+        Some((scale, self.reference[0] - scale * self.raw[0]))
     }
 }
 
-#[derive(Default, Serialize, Deserialize)]
-pub struct SerialMonitorData {
-    pub conn_config: ConnectionConfig,
-    pub plot_config: PlotConfig,
-    pub inp_slots: Vec<InputSlot>,
-    pub plots: Vec<PlotData>
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Deadband {
+    pub threshold: f64,
+    pub percent: bool,
+    pub keep_alive: f64
 }
 
-impl SerialMonitorData {
-    pub fn serialize(path: &PathBuf, data: &SerialMonitorData) -> std::io::Result<()> {
-        let config = serde_json::to_string_pretty(data)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-        let mut file = File::create(&path)?;
-        file.write_all(config.as_bytes())?;
-        Ok(())
+impl Default for Deadband {
+    fn default() -> Self {
+        Self {
+            threshold: 0.01,
+            percent: false,
+            keep_alive: 1.0
+        }
     }
+}
 
-    pub fn deserialize(path: &PathBuf) -> Result<SerialMonitorData, std::io::Error> {
-        let file = File::open(path)?;
-        let config: SerialMonitorData = serde_json::from_reader(&file)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-        Ok(config)
+impl Deadband {
+    pub fn passes(&self, last: f64, value: f64, elapsed: f64) -> bool {
+        let threshold = match self.percent {
+            true => last.abs() * self.threshold / 100.0,
+            false => self.threshold
+        };
+        (value - last).abs() > threshold || elapsed >= self.keep_alive
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub sigma: f64,
+    pub window: f64,
+    #[serde(default)]
+    pub burst: Option<usize>,
+    #[serde(default)]
+    pub sequence: Option<String>
+}
+
+impl Default for Anomaly {
+    fn default() -> Self {
+        Self {
+            sigma: 3.0,
+            window: 10.0,
+            burst: None,
+            sequence: None
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ColorMap {
+    pub min: f64,
+    pub max: f64,
+    pub low: [f32; 3],
+    #[serde(default)]
+    pub mid: Option<[f32; 3]>,
+    pub high: [f32; 3]
+}
+
+impl ColorMap {
+    pub fn color_at(&self, value: f64) -> [f32; 3] {
+        let t = ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0) as f32;
+        let lerp = |from: [f32; 3], to: [f32; 3], t: f32| [0, 1, 2].map(|c| from[c] + (to[c] - from[c]) * t);
+        match self.mid {
+            Some(mid) if t < 0.5 => lerp(self.low, mid, t * 2.0),
+            Some(mid) => lerp(mid, self.high, t * 2.0 - 1.0),
+            None => lerp(self.low, self.high, t)
+        }
+    }
+}
+
+impl Default for ColorMap {
+    fn default() -> Self {
+        Self {
+            min: 0.0,
+            max: 100.0,
+            low: [0.0, 0.2, 1.0],
+            mid: None,
+            high: [1.0, 0.05, 0.0]
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ThresholdAlarm {
+    pub low: Option<f64>,
+    pub high: Option<f64>,
+    #[serde(default)]
+    pub hysteresis: f64,
+    #[serde(default)]
+    pub beep: bool
+}
+
+impl Default for ThresholdAlarm {
+    fn default() -> Self {
+        Self {
+            low: Some(0.0),
+            high: None,
+            hysteresis: 0.0,
+            beep: true
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InputSlot {
+    pub index: usize,
+    pub name: String,
+    pub color: [f32; 3],
+    #[serde(default)]
+    pub time_shift_ms: f64,
+    #[serde(default)]
+    pub stale_after: Option<f64>,
+    #[serde(default = "InputSlot::default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+    #[serde(default)]
+    pub calibration: Option<Calibration>,
+    #[serde(default)]
+    pub deadband: Option<Deadband>,
+    #[serde(default)]
+    pub anomaly: Option<Anomaly>,
+    #[serde(default)]
+    pub alarm: Option<ThresholdAlarm>,
+    #[serde(default)]
+    pub color_map: Option<ColorMap>,
+    #[serde(default)]
+    pub print_color: Option<[f32; 3]>,
+    #[serde(default)]
+    pub device: Option<String>,
+    #[serde(default)]
+    pub unit: String,
+    #[serde(default = "InputSlot::default_precision")]
+    pub precision: u8,
+    #[serde(skip)]
+    pub value: f64
+}
+
+impl InputSlot {
+    pub fn new(index: usize, name: &str, color: [f32; 3]) -> Self {
+        Self {
+            index,
+            name: name.to_owned(),
+            color,
+            time_shift_ms: 0.0,
+            stale_after: None,
+            scale: Self::default_scale(),
+            offset: 0.0,
+            calibration: None,
+            deadband: None,
+            anomaly: None,
+            alarm: None,
+            color_map: None,
+            print_color: None,
+            device: None,
+            unit: String::new(),
+            precision: Self::default_precision(),
+            value: 0.0
+        }
+    }
+
+    pub const MAX_PRECISION: u8 = 12;
+    pub const UNMAPPED: usize = usize::MAX;
+
+    fn default_scale() -> f64 {
+        1.0
+    }
+
+    pub fn default_precision() -> u8 {
+        2
+    }
+
+    pub fn legend_name(&self) -> String {
+        match self.unit.is_empty() {
+            true => self.name.clone(),
+            false => format!("{} [{}]", self.name, self.unit)
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        match self.unit.is_empty() {
+            true => format!("{:.*}", self.precision as usize, value),
+            false => format!("{:.*} {}", self.precision as usize, value, self.unit)
+        }
+    }
+
+    pub fn time_shift(&self) -> f64 {
+        self.time_shift_ms / 1000.0
+    }
+
+    pub fn apply(&self, value: f64) -> f64 {
+        value * self.scale + self.offset
+    }
+
+    pub fn is_scaled(&self) -> bool {
+        self.scale != 1.0 || self.offset != 0.0
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EventChannel {
+    pub pattern: String,
+    pub regex: bool,
+    pub bucket: f64,
+    pub slot: InputSlot
+}
+
+impl EventChannel {
+    pub fn new(name: &str, color: [f32; 3]) -> Self {
+        Self {
+            pattern: String::new(),
+            regex: false,
+            bucket: 1.0,
+            slot: InputSlot::new(InputSlot::UNMAPPED, name, color)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DerivedSlot {
+    pub expression: String,
+    pub slot: InputSlot
+}
+
+impl DerivedSlot {
+    pub fn new(name: &str, color: [f32; 3]) -> Self {
+        Self {
+            expression: String::new(),
+            slot: InputSlot::new(InputSlot::UNMAPPED, name, color)
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum PlotKind {
+    #[default]
+    Time,
+    Console,
+    Watch,
+    Spectrum,
+    Xy,
+    Table
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct XyConfig {
+    pub x_slot: usize,
+    pub y_slot: usize,
+    #[serde(default)]
+    pub points: bool
+}
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableConfig {
+    pub rows: usize,
+    pub precision: usize
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        Self {
+            rows: 1000,
+            precision: 6
+        }
+    }
+}
+
+impl TableConfig {
+    pub const MAX_ROWS: usize = 100000;
+    pub const MAX_PRECISION: usize = 15;
+}
+
+impl Default for XyConfig {
+    fn default() -> Self {
+        Self {
+            x_slot: 0,
+            y_slot: 1,
+            points: true
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PlotData {
+    #[serde(default)]
+    pub id: usize,
+    pub name: String,
+    #[serde(default)]
+    pub hidden: Vec<usize>,
+    pub height: f32,
+    #[serde(default)]
+    pub kind: PlotKind,
+    #[serde(default, rename = "console", skip_serializing)]
+    legacy_console: bool,
+    #[serde(default)]
+    pub axis: PlotAxis,
+    #[serde(default, rename = "watch", skip_serializing)]
+    legacy_watch: bool,
+    #[serde(default = "PlotData::default_fft_size")]
+    pub fft_size: usize,
+    #[serde(default)]
+    pub watched: Vec<usize>,
+    #[serde(default)]
+    pub watch_range: bool,
+    #[serde(default)]
+    pub slots: Option<Vec<usize>>,
+    #[serde(default)]
+    pub shading: Option<Shading>,
+    #[serde(default)]
+    pub stacked: bool,
+    #[serde(default = "PlotData::default_stack_spacing")]
+    pub stack_spacing: f64,
+    #[serde(default)]
+    pub smoothing: Option<Smoothing>,
+    #[serde(default)]
+    pub xy: XyConfig,
+    #[serde(default)]
+    pub detached: Option<DetachedWindow>,
+    #[serde(default)]
+    pub table: TableConfig,
+    #[serde(default)]
+    pub override_config: Option<PlotConfig>,
+    #[serde(default)]
+    pub references: Vec<RefLine>,
+    #[serde(default)]
+    pub fit_references: bool
+}
+
+static PLOT_ID: AtomicUsize = AtomicUsize::new(1);
+
+impl PlotData {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: PLOT_ID.fetch_add(1, Ordering::SeqCst),
+            name: name.to_owned(),
+            hidden: Vec::new(),
+            height: 256.0,
+            kind: PlotKind::Time,
+            legacy_console: false,
+            axis: PlotAxis::default(),
+            legacy_watch: false,
+            fft_size: Self::default_fft_size(),
+            watched: Vec::new(),
+            watch_range: false,
+            slots: None,
+            shading: None,
+            stacked: false,
+            stack_spacing: Self::default_stack_spacing(),
+            smoothing: None,
+            xy: XyConfig::default(),
+            detached: None,
+            table: TableConfig::default(),
+            override_config: None,
+            references: Vec::new(),
+            fit_references: false
+        }
+    }
+
+    pub fn watch(name: &str) -> Self {
+        Self {
+            height: 160.0,
+            kind: PlotKind::Watch,
+            watch_range: true,
+            ..Self::new(name)
+        }
+    }
+
+    pub fn spectrum(name: &str) -> Self {
+        Self {
+            kind: PlotKind::Spectrum,
+            ..Self::new(name)
+        }
+    }
+
+    pub fn table(name: &str) -> Self {
+        Self {
+            height: 320.0,
+            kind: PlotKind::Table,
+            ..Self::new(name)
+        }
+    }
+
+    pub fn console() -> Self {
+        Self {
+            id: PLOT_ID.fetch_add(1, Ordering::SeqCst),
+            name: String::from("Console"),
+            hidden: Vec::new(),
+            height: 192.0,
+            kind: PlotKind::Console,
+            legacy_console: false,
+            axis: PlotAxis::default(),
+            legacy_watch: false,
+            fft_size: Self::default_fft_size(),
+            watched: Vec::new(),
+            watch_range: false,
+            slots: None,
+            shading: None,
+            stacked: false,
+            stack_spacing: Self::default_stack_spacing(),
+            smoothing: None,
+            xy: XyConfig::default(),
+            detached: None,
+            table: TableConfig::default(),
+            override_config: None,
+            references: Vec::new(),
+            fit_references: false
+        }
+    }
+
+    pub const FFT_SIZES: &'static [usize] = &[128, 256, 512, 1024, 2048, 4096];
+
+    fn default_fft_size() -> usize {
+        1024
+    }
+
+    fn default_stack_spacing() -> f64 {
+        10.0
+    }
+
+    pub fn effective_config(&self, global: &PlotConfig) -> PlotConfig {
+        match &self.override_config {
+            Some(config) => PlotConfig {
+                mode: config.mode,
+                window: config.window.min(global.history_secs),
+                scale_mode: config.scale_mode,
+                y_min: config.y_min,
+                y_max: config.y_max,
+                ..global.clone()
+            },
+            None => global.clone()
+        }
+    }
+
+    pub fn is_graph(&self) -> bool {
+        matches!(self.kind, PlotKind::Time | PlotKind::Spectrum)
+    }
+
+    fn migrate(&mut self) {
+        if self.kind == PlotKind::Time {
+            self.kind = match (self.legacy_console, self.legacy_watch) {
+                (true, _) => PlotKind::Console,
+                (_, true) => PlotKind::Watch,
+                _ => PlotKind::Time
+            };
+        }
+    }
+
+    pub fn shows(&self, channel: usize) -> bool {
+        self.slots.as_ref().is_none_or(|slots| slots.contains(&channel))
+    }
+
+    pub fn update_internal_ids(plots: &mut [PlotData]) {
+        if let Some(max) = plots.iter().max_by_key(|n| n.id) {
+            PLOT_ID.fetch_max(max.id + 1, Ordering::SeqCst);
+        }
+
+        let mut used = HashSet::new();
+        for plot in plots.iter_mut() {
+            if !used.insert(plot.id) {
+                plot.id = PLOT_ID.fetch_add(1, Ordering::SeqCst);
+                used.insert(plot.id);
+            }
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum SequenceStep {
+    Send(String),
+    Wait(u32),
+    WaitFor {
+        pattern: String,
+        timeout: u32
+    }
+}
+
+impl SequenceStep {
+    pub const KINDS: &'static [&'static str] = &["Send", "Wait", "Wait for"];
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Send(_) => Self::KINDS[0],
+            Self::Wait(_) => Self::KINDS[1],
+            Self::WaitFor { .. } => Self::KINDS[2]
+        }
+    }
+
+    pub fn from_kind(kind: &str) -> Self {
+        match kind {
+            "Wait" => Self::Wait(100),
+            "Wait for" => Self::WaitFor {
+                pattern: String::new(),
+                timeout: 1000
+            },
+            _ => Self::Send(String::new())
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SendPreset {
+    pub label: String,
+    pub payload: String,
+    #[serde(default)]
+    pub line_ending: LineEnding,
+    #[serde(default)]
+    pub confirm: bool
+}
+
+impl SendPreset {
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_owned(),
+            payload: String::new(),
+            line_ending: LineEnding::default(),
+            confirm: false
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Sequence {
+    pub name: String,
+    pub steps: Vec<SequenceStep>,
+    #[serde(default)]
+    pub button: bool
+}
+
+impl Sequence {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            steps: Vec::new(),
+            button: false
+        }
+    }
+}
+
+pub struct Marker {
+    pub t: f64,
+    pub label: String
+}
+
+pub struct ConsoleLine {
+    pub t: f64,
+    pub host_t: Option<f64>,
+    pub content: String,
+    pub sent: bool
+}
+
+impl ConsoleLine {
+    pub fn prefix(&self) -> String {
+        match self.host_t {
+            Some(host_t) => format!("[{:.2} | dev {:.3}]", host_t, self.t),
+            None => format!("[{:.2}]", self.t)
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConfigBackup {
+    pub enabled: bool,
+    pub keep: usize
+}
+
+impl Default for ConfigBackup {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            keep: 3
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Autosave {
+    pub enabled: bool,
+    pub minutes: f64,
+    pub save_on_exit: bool
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            minutes: 5.0,
+            save_on_exit: true
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlackBoxConfig {
+    pub enabled: bool,
+    pub seconds: f64,
+    pub max_mb: f64,
+    pub sync_seconds: f64
+}
+
+impl Default for BlackBoxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            seconds: 30.0,
+            max_mb: 16.0,
+            sync_seconds: 1.0
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisconnectPolicy {
+    pub clear_samples: bool,
+    pub clear_console: bool,
+    pub reset_parser: bool,
+    pub reset_plot_ranges: bool,
+    pub reset_stats: bool
+}
+
+impl Default for DisconnectPolicy {
+    fn default() -> Self {
+        Self {
+            clear_samples: true,
+            clear_console: false,
+            reset_parser: true,
+            reset_plot_ranges: false,
+            reset_stats: true
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioConfig {
+    pub enabled: bool,
+    pub every: usize
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            every: 1
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Theme {
+    Dark,
+    Light,
+    System
+}
+
+impl Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dark => write!(f, "Dark"),
+            Self::Light => write!(f, "Light"),
+            Self::System => write!(f, "System")
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiConfig {
+    pub theme: Theme,
+    pub line_width: f32,
+    pub plot_background: Option<[f32; 3]>
+}
+
+impl UiConfig {
+    pub const LINE_WIDTHS: RangeInclusive<f32> = 0.5..=6.0;
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Dark,
+            line_width: 1.5,
+            plot_background: None
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct SerialMonitorData {
+    #[serde(default)]
+    pub conn_config: ConnectionConfig,
+    #[serde(default)]
+    pub devices: Vec<ConnectionConfig>,
+    #[serde(default)]
+    pub plot_config: PlotConfig,
+    #[serde(default)]
+    pub inp_slots: Vec<InputSlot>,
+    #[serde(default)]
+    pub plots: Vec<PlotData>,
+    #[serde(default)]
+    pub sequences: Vec<Sequence>,
+    #[serde(default)]
+    pub presets: Vec<SendPreset>,
+    #[serde(default)]
+    pub event_channels: Vec<EventChannel>,
+    #[serde(default)]
+    pub derived_slots: Vec<DerivedSlot>,
+    #[serde(default)]
+    pub loss: Option<LossConfig>,
+    #[serde(default)]
+    pub frame_config: FrameConfig,
+    #[serde(default)]
+    pub log_config: LogConfig,
+    #[serde(default)]
+    pub black_box: BlackBoxConfig,
+    #[serde(default)]
+    pub on_disconnect: DisconnectPolicy,
+    #[serde(default)]
+    pub backup: ConfigBackup,
+    #[serde(default)]
+    pub autosave: Autosave,
+    #[serde(default)]
+    pub audio: AudioConfig,
+    #[serde(default)]
+    pub sidepanel_collapsed: bool,
+    #[serde(default)]
+    pub export_raw: bool,
+    #[serde(default)]
+    pub export_lines: bool,
+    #[serde(default)]
+    pub snapshot_dir: String,
+    #[serde(default)]
+    pub ui_config: UiConfig,
+    #[serde(skip)]
+    pub profile: Option<String>,
+    #[serde(skip)]
+    pub load_warnings: Vec<String>
+}
+
+fn parse_error(e: serde_json::Error) -> std::io::Error {
+    let location = format!(" at line {} column {}", e.line(), e.column());
+    let text = e.to_string();
+    let msg = text.strip_suffix(&location).unwrap_or(&text);
+    match e.classify() {
+        Category::Syntax | Category::Eof => std::io::Error::other(format!("Invalid JSON at line {}, column {}: {}", e.line(), e.column(), msg)),
+        Category::Data => std::io::Error::other(format!("Invalid value at line {}, column {}: {}", e.line(), e.column(), msg)),
+        Category::Io => std::io::Error::other(msg.to_owned())
+    }
+}
+
+fn positive(value: f64) -> bool {
+    value > 0.0
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut sibling = path.as_os_str().to_owned();
+    sibling.push(suffix);
+    PathBuf::from(sibling)
+}
+
+fn step_error(step: &str, e: impl Display) -> std::io::Error {
+    std::io::Error::other(format!("{} ({})", step, e))
+}
+
+impl SerialMonitorData {
+    pub const AUTOSAVE_FILE: &'static str = "autosave.json";
+
+    pub fn serialize(path: &Path, data: &SerialMonitorData) -> std::io::Result<()> {
+        let config = data.to_json()?;
+        Self::write(path, &config, data.backup.enabled.then_some(data.backup.keep))
+    }
+
+    pub fn to_json(&self) -> std::io::Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| step_error("Could not serialize config", e))
+    }
+
+    pub fn autosave_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|n| n.join("SerialMonitor").join(Self::AUTOSAVE_FILE))
+    }
+
+    pub fn autosave(config: &str) -> std::io::Result<PathBuf> {
+        let path = Self::autosave_path()
+            .ok_or_else(|| std::io::Error::other("No config directory available"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| step_error(&format!("Could not create {}", dir.display()), e))?;
+        }
+        Self::write(&path, config, None)?;
+        Ok(path)
+    }
+
+    pub fn write(path: &Path, config: &str, backup: Option<usize>) -> std::io::Result<()> {
+        let tmp = sibling_path(path, ".tmp");
+        let written = File::create(&tmp)
+            .map_err(|e| step_error(&format!("Could not create {}", tmp.display()), e))
+            .and_then(|mut file| {
+                file.write_all(config.as_bytes())
+                    .and_then(|_| file.sync_all())
+                    .map_err(|e| step_error(&format!("Could not write {}", tmp.display()), e))
+            });
+        if let Err(e) = written {
+            _ = fs::remove_file(&tmp);
+            return Err(e);
+        }
+
+        if let Some(keep) = backup.filter(|_| path.exists()) {
+            if let Err(e) = Self::backup(path, keep) {
+                _ = fs::remove_file(&tmp);
+                return Err(e);
+            }
+        }
+        fs::rename(&tmp, path).map_err(|e| {
+            _ = fs::remove_file(&tmp);
+            step_error(&format!("Could not replace {}", path.display()), e)
+        })
+    }
+
+    fn backup(path: &Path, keep: usize) -> std::io::Result<()> {
+        let timestamp = format::file_timestamp_utc();
+        let backup = (0..)
+            .map(|n| sibling_path(path, &format!(".{}.{:03}.bak", timestamp, n)))
+            .find(|n| !n.exists())
+            .unwrap_or_default();
+        fs::copy(path, &backup)
+            .map_err(|e| step_error(&format!("Could not back up to {}", backup.display()), e))?;
+
+        let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+            return Ok(());
+        };
+        let dir = match dir.as_os_str().is_empty() {
+            true => Path::new("."),
+            false => dir
+        };
+        let prefix = format!("{}.", name.to_string_lossy());
+        let mut backups: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|e| step_error(&format!("Could not list backups in {}", dir.display()), e))?
+            .filter_map(|n| n.ok().map(|n| n.path()))
+            .filter(|n| n.file_name().is_some_and(|n| {
+                let n = n.to_string_lossy();
+                n.starts_with(&prefix) && n.ends_with(".bak")
+            }))
+            .collect();
+        backups.sort();
+        let remove = backups.len().saturating_sub(keep);
+        for old in &backups[..remove] {
+            _ = fs::remove_file(old);
+        }
+        Ok(())
+    }
+
+    pub fn deserialize(path: &PathBuf) -> Result<SerialMonitorData, std::io::Error> {
+        let file = File::open(path)?;
+        let mut config: SerialMonitorData = serde_json::from_reader(&file)
+            .map_err(parse_error)?;
+        config.plots.iter_mut().for_each(PlotData::migrate);
+        config.sanitize();
+        Ok(config)
+    }
+
+    pub fn from_value(value: serde_json::Value) -> Result<SerialMonitorData, std::io::Error> {
+        let mut config: SerialMonitorData = serde_json::from_value(value)
+            .map_err(parse_error)?;
+        config.plots.iter_mut().for_each(PlotData::migrate);
+        config.sanitize();
+        Ok(config)
+    }
+
+    fn sanitize(&mut self) {
+        self.migrate_slot_columns();
+        self.event_channels.iter_mut().for_each(|n| n.slot.index = InputSlot::UNMAPPED);
+        self.derived_slots.iter_mut().for_each(|n| n.slot.index = InputSlot::UNMAPPED);
+        let channels = self.inp_slots.len() + self.event_channels.len() + self.derived_slots.len();
+        for plot in &mut self.plots {
+            let before = plot.hidden.len() + plot.watched.len() + plot.slots.as_ref().map_or(0, Vec::len);
+            plot.hidden.retain(|n| *n < channels);
+            plot.watched.retain(|n| *n < channels);
+            if let Some(slots) = &mut plot.slots {
+                slots.retain(|n| *n < channels);
+            }
+            let after = plot.hidden.len() + plot.watched.len() + plot.slots.as_ref().map_or(0, Vec::len);
+            if plot.slots.as_ref().is_some_and(Vec::is_empty) {
+                plot.slots = None;
+            }
+            if after < before {
+                self.load_warnings.push(format!("Plot \"{}\" referenced {} missing slot(s), they were dropped", plot.name, before - after));
+            }
+        }
+    }
+
+    fn migrate_slot_columns(&mut self) {
+        let mut devices: Vec<Option<String>> = Vec::new();
+        for slot in &self.inp_slots {
+            if !devices.contains(&slot.device) {
+                devices.push(slot.device.clone());
+            }
+        }
+        for device in devices {
+            let group: Vec<usize> = (0..self.inp_slots.len())
+                .filter(|i| self.inp_slots[*i].device == device)
+                .collect();
+            if group.iter().all(|i| self.inp_slots[*i].index == *i) {
+                for (column, i) in group.into_iter().enumerate() {
+                    self.inp_slots[i].index = column;
+                }
+            }
+        }
+    }
+
+    pub fn remove_input_slot(&mut self, slot: usize) -> InputSlot {
+        let shift = |n: usize| match n > slot {
+            true => n - 1,
+            false => n
+        };
+        let remap = |indexes: &mut Vec<usize>| {
+            indexes.retain(|n| *n != slot);
+            indexes.iter_mut().for_each(|n| *n = shift(*n));
+        };
+        for plot in &mut self.plots {
+            remap(&mut plot.hidden);
+            remap(&mut plot.watched);
+            if let Some(slots) = &mut plot.slots {
+                remap(slots);
+            }
+            if plot.slots.as_ref().is_some_and(Vec::is_empty) {
+                plot.slots = None;
+            }
+            match &mut plot.shading {
+                Some(shading) if shading.channel == slot => plot.shading = None,
+                Some(shading) => shading.channel = shift(shading.channel),
+                None => {}
+            }
+            let xy = XyConfig::default();
+            plot.xy.x_slot = match plot.xy.x_slot == slot {
+                true => xy.x_slot,
+                false => shift(plot.xy.x_slot)
+            };
+            plot.xy.y_slot = match plot.xy.y_slot == slot {
+                true => xy.y_slot,
+                false => shift(plot.xy.y_slot)
+            };
+        }
+        for derived in &mut self.derived_slots {
+            derived.expression = derived::remove_slot_references(&derived.expression, slot);
+        }
+        self.plot_config.sync_channel = self.plot_config.sync_channel.filter(|n| *n != slot).map(shift);
+        match &mut self.loss {
+            Some(loss) if loss.slot == slot => self.loss = None,
+            Some(loss) => loss.slot = shift(loss.slot),
+            None => {}
+        }
+        self.inp_slots.remove(slot)
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let conn = &self.conn_config;
+        if conn.baud_rate == 0 {
+            errors.push(String::from("conn_config.baud_rate must be greater than 0"));
+        }
+        if !(5..=8).contains(&conn.data_bits) {
+            errors.push(format!("conn_config.data_bits must be 5-8 (found {})", conn.data_bits));
+        }
+        if !(1..=2).contains(&conn.stop_bits) {
+            errors.push(format!("conn_config.stop_bits must be 1 or 2 (found {})", conn.stop_bits));
+        }
+        if matches!(conn.protocol, Protocol::BinaryFrames { fields: 0, .. }) {
+            errors.push(String::from("conn_config.protocol must have at least one field per frame"));
+        }
+        if conn.delimiter == Delimiter::Custom && conn.custom_delimiter.chars().count() != 1 {
+            errors.push(String::from("conn_config.custom_delimiter must be a single character"));
+        }
+        if conn.terminator == LineTerminator::Custom && conn.custom_terminator.chars().count() != 1 {
+            errors.push(String::from("conn_config.custom_terminator must be a single character"));
+        }
+        if conn.read_timeout_ms == 0 {
+            errors.push(String::from("conn_config.read_timeout_ms must be greater than 0"));
+        }
+        if conn.decimal == DecimalSeparator::Comma && conn.separators().delimiter == Some(',') {
+            errors.push(String::from("conn_config.decimal can not be a comma while values are separated by commas"));
+        }
+        if conn.watchdog_secs.is_nan() || conn.watchdog_secs < 0.0 {
+            errors.push(format!("conn_config.watchdog_secs must be 0 (off) or greater (found {})", conn.watchdog_secs));
+        }
+        if conn.start_match == StartMatch::Regex {
+            if let Err(e) = regex::Regex::new(conn.start_msg.trim()) {
+                errors.push(format!("conn_config.start_msg is not a valid pattern: {}", e));
+            }
+        }
+
+        let mut ports = HashSet::new();
+        for (i, device) in self.devices.iter().enumerate() {
+            if device.baud_rate == 0 {
+                errors.push(format!("devices[{}].baud_rate must be greater than 0", i));
+            }
+            if device.port != ConnectionConfig::NO_PORT && (device.port == conn.port || !ports.insert(device.port.as_str())) {
+                errors.push(format!("devices[{}] uses the port {} more than once", i, device.port));
+            }
+        }
+
+        let plot = &self.plot_config;
+        if !positive(plot.window) {
+            errors.push(format!("plot_config.window must be greater than 0 (found {})", plot.window));
+        }
+        if plot.scale_mode == PlotScaleMode::Manual && !positive(plot.y_max - plot.y_min) {
+            errors.push(format!("plot_config.y_min must be below y_max (found {} and {})", plot.y_min, plot.y_max));
+        }
+        if !positive(plot.history_secs) || plot.window > plot.history_secs {
+            errors.push(format!("plot_config.history_secs must be greater than 0 and at least the window (found {})", plot.history_secs));
+        }
+        if plot.console_lines == 0 {
+            errors.push(String::from("plot_config.console_lines must be greater than 0"));
+        }
+        if plot.raw_lines && !positive(plot.raw_retention) {
+            errors.push(format!("plot_config.raw_retention must be greater than 0 (found {})", plot.raw_retention));
+        }
+        match &plot.decimation {
+            Some(decimation) if decimation.mode == DecimationMode::Every && decimation.every == 0 => {
+                errors.push(String::from("plot_config.decimation.every must be greater than 0"));
+            },
+            Some(decimation) if decimation.mode == DecimationMode::Average && (decimation.bucket_ms.is_nan() || decimation.bucket_ms < Decimation::MIN_BUCKET_MS) => {
+                errors.push(format!("plot_config.decimation.bucket_ms must be at least {} (found {})", Decimation::MIN_BUCKET_MS, decimation.bucket_ms));
+            },
+            _ => {}
+        }
+        if !positive(plot.memory_budget) {
+            errors.push(format!("plot_config.memory_budget must be greater than 0 (found {})", plot.memory_budget));
+        }
+        if !UiConfig::LINE_WIDTHS.contains(&self.ui_config.line_width) {
+            errors.push(format!("ui_config.line_width must be {}-{} (found {})", UiConfig::LINE_WIDTHS.start(), UiConfig::LINE_WIDTHS.end(), self.ui_config.line_width));
+        }
+
+        for (i, plot) in self.plots.iter().enumerate() {
+            if plot.kind == PlotKind::Spectrum && !PlotData::FFT_SIZES.contains(&plot.fft_size) {
+                errors.push(format!("plots[{}] ({}) fft_size must be one of {:?} (found {})", i, plot.name, PlotData::FFT_SIZES, plot.fft_size));
+            }
+            if plot.stacked && !positive(plot.stack_spacing) {
+                errors.push(format!("plots[{}] ({}) stack_spacing must be greater than 0 (found {})", i, plot.name, plot.stack_spacing));
+            }
+            if plot.kind == PlotKind::Table && !(1..=TableConfig::MAX_ROWS).contains(&plot.table.rows) {
+                errors.push(format!("plots[{}] ({}) table.rows must be 1-{} (found {})", i, plot.name, TableConfig::MAX_ROWS, plot.table.rows));
+            }
+            if let Some(smoothing) = plot.smoothing.as_ref().filter(|n| !(1..=Smoothing::MAX_WINDOW).contains(&n.window)) {
+                errors.push(format!("plots[{}] ({}) smoothing.window must be 1-{} (found {})", i, plot.name, Smoothing::MAX_WINDOW, smoothing.window));
+            }
+            for (k, reference) in plot.references.iter().enumerate() {
+                if !reference.value.is_finite() || reference.band.is_some_and(|n| !positive(n)) {
+                    errors.push(format!("plots[{}] ({}) references[{}] needs a finite value and a band greater than 0", i, plot.name, k));
+                }
+            }
+            if let Some(config) = &plot.override_config {
+                if !positive(config.window) || config.window > self.plot_config.history_secs {
+                    errors.push(format!("plots[{}] ({}) override_config.window must be greater than 0 and at most the history (found {})", i, plot.name, config.window));
+                }
+                if config.scale_mode == PlotScaleMode::Manual && !positive(config.y_max - config.y_min) {
+                    errors.push(format!("plots[{}] ({}) override_config.y_min must be below y_max (found {} and {})", i, plot.name, config.y_min, config.y_max));
+                }
+            }
+        }
+
+        for (i, slot) in self.inp_slots.iter().enumerate() {
+            if let Some(other) = self.inp_slots[..i].iter().find(|n| n.device == slot.device && n.index == slot.index) {
+                errors.push(format!("inp_slots[{}] ({}) uses column {} like {}", i, slot.name, slot.index + 1, other.name));
+            }
+            if !slot.scale.is_finite() || !slot.offset.is_finite() || slot.scale == 0.0 {
+                errors.push(format!("inp_slots[{}] ({}) has an invalid scale/offset", i, slot.name));
+            }
+            if slot.precision > InputSlot::MAX_PRECISION {
+                errors.push(format!("inp_slots[{}] ({}) precision must be 0-{} (found {})", i, slot.name, InputSlot::MAX_PRECISION, slot.precision));
+            }
+            if slot.calibration.as_ref().is_some_and(|n| n.fit().is_none()) {
+                errors.push(format!("inp_slots[{}] ({}) has calibration points with identical raw values", i, slot.name));
+            }
+            if let Some(anomaly) = &slot.anomaly {
+                if !positive(anomaly.sigma) || !positive(anomaly.window) {
+                    errors.push(format!("inp_slots[{}] ({}) anomaly sigma and window must be greater than 0", i, slot.name));
+                }
+                if anomaly.sequence.as_ref().is_some_and(|n| !self.sequences.iter().any(|s| &s.name == n)) {
+                    errors.push(format!("inp_slots[{}] ({}) anomaly alarm uses an unknown sequence", i, slot.name));
+                }
+            }
+            if let Some(alarm) = &slot.alarm {
+                if alarm.low.is_none() && alarm.high.is_none() {
+                    errors.push(format!("inp_slots[{}] ({}) alarm needs a low or high limit", i, slot.name));
+                }
+                if let Some((low, high)) = alarm.low.zip(alarm.high).filter(|(low, high)| low >= high) {
+                    errors.push(format!("inp_slots[{}] ({}) alarm low limit must be below the high limit ({} >= {})", i, slot.name, low, high));
+                }
+                if alarm.hysteresis.is_nan() || alarm.hysteresis < 0.0 {
+                    errors.push(format!("inp_slots[{}] ({}) alarm hysteresis must not be negative (found {})", i, slot.name, alarm.hysteresis));
+                }
+            }
+            if let Some(map) = slot.color_map.as_ref().filter(|n| !n.min.is_finite() || !n.max.is_finite() || n.min >= n.max) {
+                errors.push(format!("inp_slots[{}] ({}) color map min must be below max ({} >= {})", i, slot.name, map.min, map.max));
+            }
+        }
+
+        if self.loss.as_ref().and_then(|n| n.alarm_rate).is_some_and(|n| !positive(n)) {
+            errors.push(String::from("loss.alarm_rate must be greater than 0"));
+        }
+
+        let mut names = HashSet::new();
+        for (i, sequence) in self.sequences.iter().enumerate() {
+            if sequence.name.trim().is_empty() {
+                errors.push(format!("sequences[{}] has no name", i));
+            } else if !names.insert(sequence.name.as_str()) {
+                errors.push(format!("sequences[{}] uses the name \"{}\" more than once", i, sequence.name));
+            }
+        }
+        for (i, channel) in self.event_channels.iter().enumerate() {
+            if channel.regex {
+                if let Err(e) = regex::Regex::new(&channel.pattern) {
+                    errors.push(format!("event_channels[{}] ({}) has an invalid pattern: {}", i, channel.slot.name, e));
+                }
+            }
+            if !positive(channel.bucket) {
+                errors.push(format!("event_channels[{}] ({}) bucket must be greater than 0", i, channel.slot.name));
+            }
+        }
+        for (i, derived) in self.derived_slots.iter().enumerate() {
+            if let Some(e) = DerivedChannel::new(derived).parse_error() {
+                errors.push(format!("derived_slots[{}] ({}) has an invalid expression: {}", i, derived.slot.name, e));
+            }
+        }
+
+        match self.frame_config.mode {
+            FrameMode::Marker if self.frame_config.marker.trim().is_empty() => errors.push(String::from("frame_config.marker must not be empty")),
+            FrameMode::LineCount if self.frame_config.lines == 0 => errors.push(String::from("frame_config.lines must be greater than 0")),
+            _ => {}
+        }
+        if self.frame_config.max_columns == 0 {
+            errors.push(String::from("frame_config.max_columns must be greater than 0"));
+        }
+        let log = &self.log_config;
+        match log.rotation {
+            LogRotation::Duration if !positive(log.rotate_minutes) => errors.push(String::from("log_config.rotate_minutes must be greater than 0")),
+            LogRotation::Size if !positive(log.rotate_mb) => errors.push(String::from("log_config.rotate_mb must be greater than 0")),
+            _ => {}
+        }
+        if log.pattern.trim().is_empty() {
+            errors.push(String::from("log_config.pattern must not be empty"));
+        }
+        let black_box = &self.black_box;
+        if !positive(black_box.seconds) || !positive(black_box.max_mb) || !positive(black_box.sync_seconds) {
+            errors.push(String::from("black_box.seconds, max_mb and sync_seconds must be greater than 0"));
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(ids: &[usize]) -> SerialMonitorData {
+        let plots: Vec<serde_json::Value> = ids.iter()
+            .map(|id| serde_json::json!({ "id": id, "name": format!("Plot {}", id), "height": 200.0 }))
+            .collect();
+        let mut config = SerialMonitorData::from_value(serde_json::json!({ "plots": plots })).unwrap();
+        PlotData::update_internal_ids(&mut config.plots);
+        config
+    }
+
+    fn ids(config: &SerialMonitorData) -> Vec<usize> {
+        config.plots.iter().map(|n| n.id).collect()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("serial_monitor_{}_{}", name, std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn backups(dir: &Path) -> Vec<String> {
+        let mut backups: Vec<String> = fs::read_dir(dir).unwrap()
+            .filter_map(|n| n.ok())
+            .map(|n| n.file_name().to_string_lossy().into_owned())
+            .filter(|n| n.ends_with(".bak"))
+            .collect();
+        backups.sort();
+        backups
+    }
+
+    #[test]
+    fn save_on_exit_defaults_on() {
+        let config = SerialMonitorData::from_value(serde_json::json!({ "autosave": { "enabled": false, "minutes": 5.0 } })).unwrap();
+        assert!(!config.autosave.enabled);
+        assert!(config.autosave.save_on_exit);
+    }
+
+    #[test]
+    fn failed_write_keeps_original() {
+        let dir = temp_dir("failed_write");
+        let path = dir.join("config.json");
+        fs::write(&path, "original").unwrap();
+        fs::create_dir(dir.join("config.json.tmp")).unwrap();
+
+        let e = SerialMonitorData::serialize(&path, &SerialMonitorData::default()).unwrap_err();
+        assert!(e.to_string().starts_with("Could not create"), "{}", e);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert!(backups(&dir).is_empty());
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_rename_keeps_original() {
+        let dir = temp_dir("failed_rename");
+        let path = dir.join("config.json");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("keep"), "original").unwrap();
+
+        let e = SerialMonitorData::write(&path, "new", None).unwrap_err();
+        assert!(e.to_string().starts_with("Could not replace"), "{}", e);
+        assert_eq!(fs::read_to_string(path.join("keep")).unwrap(), "original");
+        assert!(!dir.join("config.json.tmp").exists());
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn missing_directory() {
+        let dir = temp_dir("missing_directory");
+        let path = dir.join("missing").join("config.json");
+        let e = SerialMonitorData::serialize(&path, &SerialMonitorData::default()).unwrap_err();
+        assert!(e.to_string().starts_with("Could not create"), "{}", e);
+        assert!(!path.exists());
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn backups_within_one_second() {
+        let dir = temp_dir("backups");
+        let path = dir.join("config.json");
+        for version in 0..5 {
+            SerialMonitorData::write(&path, &format!("v{}", version), Some(3)).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "v4");
+        let contents: Vec<String> = backups(&dir).iter()
+            .map(|n| fs::read_to_string(dir.join(n)).unwrap())
+            .collect();
+        assert_eq!(contents, ["v1", "v2", "v3"]);
+        _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extra_channels_have_no_column() {
+        let slot = |name: &str| serde_json::json!({ "index": 0, "name": name, "color": [1.0, 0.0, 0.0] });
+        let config = SerialMonitorData::from_value(serde_json::json!({
+            "inp_slots": [slot("a")],
+            "event_channels": [{ "pattern": "x", "regex": false, "bucket": 1.0, "slot": slot("e") }],
+            "derived_slots": [{ "expression": "slot1", "slot": slot("d") }]
+        })).unwrap();
+        assert_eq!(config.inp_slots[0].index, 0);
+        assert_eq!(config.event_channels[0].slot.index, InputSlot::UNMAPPED);
+        assert_eq!(config.derived_slots[0].slot.index, InputSlot::UNMAPPED);
+        assert_eq!(EventChannel::new("e", [1.0; 3]).slot.index, InputSlot::UNMAPPED);
+        assert_eq!(DerivedSlot::new("d", [1.0; 3]).slot.index, InputSlot::UNMAPPED);
+    }
+
+    #[test]
+    fn remove_slot_remaps_references() {
+        let mut data = SerialMonitorData {
+            inp_slots: (0..4).map(|i| InputSlot::new(i, &format!("s{}", i), [1.0; 3])).collect(),
+            ..Default::default()
+        };
+        let mut xy = PlotData::new("xy");
+        xy.xy = XyConfig { x_slot: 1, y_slot: 3, points: false };
+        let mut shifted = PlotData::new("shifted");
+        shifted.xy = XyConfig { x_slot: 3, y_slot: 2, points: false };
+        data.plots = vec![xy, shifted];
+        data.derived_slots = vec![DerivedSlot {
+            expression: String::from("slot4 - slot1 + slot2"),
+            ..DerivedSlot::new("d", [1.0; 3])
+        }];
+
+        let removed = data.remove_input_slot(1);
+        assert_eq!(removed.name, "s1");
+        assert_eq!((data.plots[0].xy.x_slot, data.plots[0].xy.y_slot), (XyConfig::default().x_slot, 2));
+        assert_eq!((data.plots[1].xy.x_slot, data.plots[1].xy.y_slot), (2, 1));
+        assert_eq!(data.derived_slots[0].expression, "slot3 - slot1 + deleted_slot2");
+    }
+
+    #[test]
+    fn plot_ids_do_not_bleed_between_configs() {
+        let mut a = config(&[500, 501]);
+        assert_eq!(ids(&a), [500, 501]);
+        a.plots.push(PlotData::new("Added to A"));
+        a.plots.push(PlotData::new("Added to A"));
+        let seen = ids(&a);
+        assert!(seen[2] > 501 && seen[3] > seen[2]);
+
+        let mut b = config(&[3, 4, 4]);
+        let loaded = ids(&b);
+        assert_eq!(loaded[..2], [3, 4]);
+        assert!(!seen.contains(&loaded[2]));
+        b.plots.push(PlotData::new("Added to B"));
+        b.plots.push(PlotData::new("Added to B"));
+        let added = &ids(&b)[3..];
+        assert!(added.iter().all(|n| !seen.contains(n) && !loaded.contains(n)), "{:?} reuses {:?} or {:?}", added, seen, loaded);
+        assert_ne!(added[0], added[1]);
     }
 }
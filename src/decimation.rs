@@ -0,0 +1,48 @@
+use crate::data::{Decimation, DecimationMode};
+
+#[derive(Default)]
+pub struct Decimator {
+    config: Option<Decimation>,
+    count: usize,
+    bucket: Option<i64>,
+    sum: f64,
+    samples: usize
+}
+
+impl Decimator {
+    pub fn push(&mut self, t: f64, value: f64, config: &Decimation) -> Option<[f64; 2]> {
+        if self.config.as_ref() != Some(config) {
+            self.reset();
+            self.config = Some(config.clone());
+        }
+        match config.mode {
+            DecimationMode::Every => {
+                let keep = self.count.is_multiple_of(config.every.max(1));
+                self.count += 1;
+                keep.then_some([t, value])
+            },
+            DecimationMode::Average => {
+                let width = config.bucket_ms.max(Decimation::MIN_BUCKET_MS) / 1000.0;
+                let bucket = (t / width).floor() as i64;
+                let mut done = None;
+                if self.bucket != Some(bucket) {
+                    if let Some(last) = self.bucket.filter(|_| self.samples > 0) {
+                        done = Some([(last as f64 + 0.5) * width, self.sum / self.samples as f64]);
+                    }
+                    self.bucket = Some(bucket);
+                    self.sum = 0.0;
+                    self.samples = 0;
+                }
+                if value.is_finite() {
+                    self.sum += value;
+                    self.samples += 1;
+                }
+                done
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
@@ -0,0 +1,326 @@
+use crate::data::DerivedSlot;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+pub const SHOWN_SUFFIX: &str = ".shown";
+
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div
+}
+
+#[derive(Clone, Copy)]
+enum Function {
+    Abs,
+    Sqrt,
+    Min,
+    Max
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "abs" => Some(Self::Abs),
+            "sqrt" => Some(Self::Sqrt),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            _ => None
+        }
+    }
+
+    fn arity(&self) -> usize {
+        match self {
+            Self::Abs | Self::Sqrt => 1,
+            Self::Min | Self::Max => 2
+        }
+    }
+}
+
+enum Expression {
+    Number(f64),
+    Slot(String),
+    Neg(Box<Expression>),
+    Binary(Op, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>)
+}
+
+impl Expression {
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            text,
+            chars: text.char_indices().peekable()
+        };
+        let expr = parser.expr()?;
+        match parser.peek() {
+            Some(c) => Err(format!("Unexpected '{}'", c)),
+            None => Ok(expr)
+        }
+    }
+
+    fn eval(&self, lookup: &impl Fn(&str) -> Option<f64>) -> Result<f64, String> {
+        Ok(match self {
+            Self::Number(n) => *n,
+            Self::Slot(name) => lookup(name).ok_or_else(|| format!("Unknown slot '{}'", name))?,
+            Self::Neg(expr) => -expr.eval(lookup)?,
+            Self::Binary(op, a, b) => {
+                let (a, b) = (a.eval(lookup)?, b.eval(lookup)?);
+                match op {
+                    Op::Add => a + b,
+                    Op::Sub => a - b,
+                    Op::Mul => a * b,
+                    Op::Div => a / b
+                }
+            },
+            Self::Call(function, args) => {
+                let args = args.iter().map(|n| n.eval(lookup)).collect::<Result<Vec<f64>, String>>()?;
+                match function {
+                    Function::Abs => args[0].abs(),
+                    Function::Sqrt => args[0].sqrt(),
+                    Function::Min => f64::min(args[0], args[1]),
+                    Function::Max => f64::max(args[0], args[1])
+                }
+            }
+        })
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    chars: Peekable<CharIndices<'a>>
+}
+
+impl Parser<'_> {
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|n| n.1.is_whitespace()).is_some() {}
+        self.chars.peek().map(|n| n.1)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        match self.peek() == Some(c) {
+            true => {
+                self.chars.next();
+                true
+            },
+            false => false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expression, String> {
+        let mut expr = self.term()?;
+        loop {
+            let op = match self.peek() {
+                Some('+') => Op::Add,
+                Some('-') => Op::Sub,
+                _ => return Ok(expr)
+            };
+            self.chars.next();
+            expr = Expression::Binary(op, Box::new(expr), Box::new(self.term()?));
+        }
+    }
+
+    fn term(&mut self) -> Result<Expression, String> {
+        let mut expr = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some('*') => Op::Mul,
+                Some('/') => Op::Div,
+                _ => return Ok(expr)
+            };
+            self.chars.next();
+            expr = Expression::Binary(op, Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expression, String> {
+        if self.eat('-') {
+            return Ok(Expression::Neg(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expression, String> {
+        match self.peek() {
+            Some('(') => {
+                self.chars.next();
+                let expr = self.expr()?;
+                match self.eat(')') {
+                    true => Ok(expr),
+                    false => Err(String::from("Missing ')'"))
+                }
+            },
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) if c.is_alphabetic() || c == '_' => self.identifier(),
+            Some(c) => Err(format!("Unexpected '{}'", c)),
+            None => Err(String::from("Unexpected end of expression"))
+        }
+    }
+
+    fn number(&mut self) -> Result<Expression, String> {
+        let start = self.chars.peek().map_or(self.text.len(), |n| n.0);
+        let mut end = start;
+        let mut last = ' ';
+        while let Some((i, c)) = self.chars.next_if(|(_, c)| {
+            c.is_ascii_digit() || *c == '.' || *c == 'e' || *c == 'E' || ((*c == '-' || *c == '+') && (last == 'e' || last == 'E'))
+        }) {
+            end = i + c.len_utf8();
+            last = c;
+        }
+        let text = &self.text[start..end];
+        text.parse::<f64>()
+            .map(Expression::Number)
+            .map_err(|_| format!("Invalid number '{}'", text))
+    }
+
+    fn identifier(&mut self) -> Result<Expression, String> {
+        let start = self.chars.peek().map_or(self.text.len(), |n| n.0);
+        let mut end = start;
+        while let Some((i, c)) = self.chars.next_if(|(_, c)| c.is_alphanumeric() || *c == '_' || *c == '.') {
+            end = i + c.len_utf8();
+        }
+        let name = &self.text[start..end];
+        if !self.eat('(') {
+            return Ok(Expression::Slot(name.to_owned()));
+        }
+        let function = Function::from_name(name).ok_or_else(|| format!("Unknown function '{}'", name))?;
+        let mut args = Vec::new();
+        if !self.eat(')') {
+            loop {
+                args.push(self.expr()?);
+                if self.eat(')') {
+                    break;
+                }
+                if !self.eat(',') {
+                    return Err(String::from("Missing ')'"));
+                }
+            }
+        }
+        match args.len() == function.arity() {
+            true => Ok(Expression::Call(function, args)),
+            false => Err(format!("{}() takes {} argument(s)", name, function.arity()))
+        }
+    }
+}
+
+pub struct DerivedChannel {
+    source: String,
+    expression: Result<Expression, String>,
+    error: Option<String>,
+    pub values: Vec<[f64; 2]>
+}
+
+impl DerivedChannel {
+    pub fn new(slot: &DerivedSlot) -> Self {
+        Self {
+            source: slot.expression.clone(),
+            expression: Expression::parse(&slot.expression),
+            error: None,
+            values: Vec::new()
+        }
+    }
+
+    pub fn sync(&mut self, slot: &DerivedSlot) {
+        if self.source != slot.expression {
+            self.source = slot.expression.clone();
+            self.expression = Expression::parse(&slot.expression);
+            self.error = None;
+        }
+    }
+
+    pub fn parse_error(&self) -> Option<&str> {
+        self.expression.as_ref().err().map(|n| n.as_str())
+    }
+
+    pub fn on_sample(&mut self, t: f64, lookup: &impl Fn(&str) -> Option<f64>) -> Option<String> {
+        let value = match &self.expression {
+            Ok(expression) => expression.eval(lookup),
+            Err(e) => Err(e.clone())
+        };
+        self.values.push([t, *value.as_ref().unwrap_or(&f64::NAN)]);
+        match (value, self.error.is_some()) {
+            (Err(e), false) => {
+                self.error = Some(e.clone());
+                Some(e)
+            },
+            _ => None
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.error = None;
+        self.values.clear();
+    }
+}
+
+pub fn remove_slot_references(expression: &str, slot: usize) -> String {
+    let is_name = |c: char| c.is_alphanumeric() || c == '_' || c == '.';
+    let mut result = String::with_capacity(expression.len());
+    let mut chars = expression.char_indices().peekable();
+    let mut previous = ' ';
+    while let Some((start, c)) = chars.next() {
+        if !(c.is_alphabetic() || c == '_') || is_name(previous) {
+            result.push(c);
+            previous = c;
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some((i, c)) = chars.next_if(|n| is_name(n.1)) {
+            end = i + c.len_utf8();
+        }
+        let name = &expression[start..end];
+        let (name, suffix) = match name.strip_suffix(SHOWN_SUFFIX) {
+            Some(name) => (name, SHOWN_SUFFIX),
+            None => (name, "")
+        };
+        let number = name.strip_prefix("slot")
+            .filter(|n| n.chars().all(|n| n.is_ascii_digit()))
+            .and_then(|n| n.parse::<usize>().ok())
+            .filter(|n| *n > 0);
+        match number {
+            Some(n) if n - 1 == slot => result.push_str(&format!("deleted_slot{}", n)),
+            Some(n) if n - 1 > slot => result.push_str(&format!("slot{}", n - 1)),
+            _ => result.push_str(name)
+        }
+        result.push_str(suffix);
+        previous = 'a';
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_references() {
+        let cases = [
+            ("slot1 + slot2 * slot3", 1, "slot1 + deleted_slot2 * slot2"),
+            ("max(slot3,slot10)/2", 0, "max(slot2,slot9)/2"),
+            ("slot2 - slot1", 5, "slot2 - slot1"),
+            ("myslot3 + slot3x + slot0 + 2e3 + slot.3", 0, "myslot3 + slot3x + slot0 + 2e3 + slot.3"),
+            ("temp - 273.15", 0, "temp - 273.15"),
+            ("Übergang_slot2+slot2", 0, "Übergang_slot2+slot1"),
+            ("slot3.shown - slot2.shown", 1, "slot2.shown - deleted_slot2.shown")
+        ];
+        for (expression, slot, expected) in cases {
+            assert_eq!(remove_slot_references(expression, slot), expected);
+        }
+    }
+
+    #[test]
+    fn deleted_reference_is_reported() {
+        let slot = DerivedSlot {
+            expression: remove_slot_references("slot1 + slot2", 0),
+            ..DerivedSlot::new("d", [1.0; 3])
+        };
+        let mut channel = DerivedChannel::new(&slot);
+        let lookup = |name: &str| (name == "slot1").then_some(2.0);
+        assert_eq!(channel.on_sample(0.0, &lookup).as_deref(), Some("Unknown slot 'deleted_slot1'"));
+        assert!(channel.values[0][1].is_nan());
+    }
+}
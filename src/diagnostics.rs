@@ -0,0 +1,197 @@
+use crate::serial_reader::{self, SerialConfig, SerialError};
+use serialport::SerialPort;
+use std::fmt::Display;
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const LISTEN_DURATION: Duration = Duration::from_secs(5);
+const LOOPBACK_CHUNK: usize = 64;
+const LOOPBACK_MIN_BYTES: usize = 256;
+const LOOPBACK_MAX_BYTES: usize = 16384;
+const LOOPBACK_MIN_TIMEOUT: Duration = Duration::from_secs(2);
+const READ_TIMEOUT: Duration = Duration::from_millis(20);
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DiagnosticMode {
+    Loopback,
+    Listen
+}
+
+impl Display for DiagnosticMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Loopback => write!(f, "Loopback"),
+            Self::Listen => write!(f, "Listen")
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DiagnosticReport {
+    pub mode: DiagnosticMode,
+    pub sent: usize,
+    pub received: usize,
+    pub errors: usize,
+    pub elapsed: f64
+}
+
+impl DiagnosticReport {
+    pub fn error_rate(&self) -> f64 {
+        match self.sent {
+            0 => 0.0,
+            sent => self.errors as f64 / sent as f64
+        }
+    }
+
+    pub fn throughput(&self) -> f64 {
+        match self.elapsed > 0.0 {
+            true => self.received as f64 / self.elapsed,
+            false => 0.0
+        }
+    }
+
+    pub fn conclusion(&self) -> &'static str {
+        match self.mode {
+            DiagnosticMode::Loopback if self.received == 0 => "Nothing came back. Check that TX and RX are bridged, otherwise the adapter or cable is faulty.",
+            DiagnosticMode::Loopback if self.errors == 0 => "Adapter OK. Data sent on TX was read back without errors.",
+            DiagnosticMode::Loopback if self.error_rate() < 0.01 => "Adapter mostly OK, but a few bytes were lost or corrupted. Check the cable and try a lower baud rate.",
+            DiagnosticMode::Loopback => "Most bytes were lost or corrupted. The port settings don't match the hardware or the wiring is faulty.",
+            DiagnosticMode::Listen if self.received == 0 => "Port opens but no bytes arrived. If the loopback test passes, the adapter is OK and the device is not sending.",
+            DiagnosticMode::Listen => "Device is sending. If no lines show up, check the baud rate and line endings."
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DiagnosticState {
+    pub progress: f32,
+    pub result: Option<Result<DiagnosticReport, String>>
+}
+
+pub struct Diagnostic {
+    mode: DiagnosticMode,
+    state: Arc<Mutex<DiagnosticState>>,
+    stop: Arc<AtomicBool>,
+    worker_thread: Option<JoinHandle<()>>
+}
+
+impl Diagnostic {
+    pub fn start(mode: DiagnosticMode, mut config: SerialConfig) -> Result<Self, SerialError> {
+        config.timeout = READ_TIMEOUT;
+        let port = serial_reader::open_port(&config)?;
+        let state = Arc::new(Mutex::new(DiagnosticState::default()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let worker_state = Arc::clone(&state);
+        let worker_stop = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let result = match mode {
+                DiagnosticMode::Loopback => loopback(port, config.baud_rate, &worker_state, &worker_stop),
+                DiagnosticMode::Listen => listen(port, &worker_state, &worker_stop)
+            };
+            if let Ok(mut state) = worker_state.lock() {
+                state.progress = 1.0;
+                state.result = Some(result);
+            }
+        });
+        Ok(Self {
+            mode,
+            state,
+            stop,
+            worker_thread: Some(handle)
+        })
+    }
+
+    pub fn mode(&self) -> DiagnosticMode {
+        self.mode
+    }
+
+    pub fn state(&self) -> DiagnosticState {
+        match self.state.lock() {
+            Ok(state) => state.clone(),
+            Err(_) => DiagnosticState::default()
+        }
+    }
+}
+
+impl Drop for Diagnostic {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.worker_thread.take() {
+            _ = handle.join();
+        }
+    }
+}
+
+fn loopback(mut port: Box<dyn SerialPort>, baud_rate: u32, state: &Mutex<DiagnosticState>, stop: &AtomicBool) -> Result<DiagnosticReport, String> {
+    let len = (baud_rate as usize / 10).clamp(LOOPBACK_MIN_BYTES, LOOPBACK_MAX_BYTES);
+    let pattern = pattern(len);
+    let timeout = Duration::max(LOOPBACK_MIN_TIMEOUT, Duration::from_secs_f64(len as f64 * 30.0 / baud_rate as f64));
+    _ = port.clear(serialport::ClearBuffer::All);
+
+    let start = Instant::now();
+    let mut received = Vec::with_capacity(len);
+    let mut written = 0;
+    let mut buffer = [0u8; LOOPBACK_CHUNK * 4];
+    while received.len() < len && start.elapsed() < timeout && !stop.load(Ordering::Relaxed) {
+        if written < len && written - received.len().min(written) < LOOPBACK_CHUNK * 4 {
+            let end = usize::min(written + LOOPBACK_CHUNK, len);
+            port.write_all(&pattern[written..end]).map_err(|e| e.to_string())?;
+            written = end;
+        }
+        match port.read(&mut buffer) {
+            Ok(read) => received.extend_from_slice(&buffer[..read]),
+            Err(e) if e.kind() == ErrorKind::TimedOut => {},
+            Err(e) => return Err(e.to_string())
+        }
+        if let Ok(mut state) = state.lock() {
+            state.progress = received.len() as f32 / len as f32;
+        }
+    }
+
+    received.truncate(len);
+    let mismatched = pattern.iter().zip(&received).filter(|(a, b)| a != b).count();
+    Ok(DiagnosticReport {
+        mode: DiagnosticMode::Loopback,
+        sent: written,
+        received: received.len(),
+        errors: mismatched + (len - received.len()),
+        elapsed: start.elapsed().as_secs_f64()
+    })
+}
+
+fn listen(mut port: Box<dyn SerialPort>, state: &Mutex<DiagnosticState>, stop: &AtomicBool) -> Result<DiagnosticReport, String> {
+    let start = Instant::now();
+    let mut received = 0;
+    let mut buffer = [0u8; 1024];
+    while start.elapsed() < LISTEN_DURATION && !stop.load(Ordering::Relaxed) {
+        match port.read(&mut buffer) {
+            Ok(read) => received += read,
+            Err(e) if e.kind() == ErrorKind::TimedOut => {},
+            Err(e) => return Err(e.to_string())
+        }
+        if let Ok(mut state) = state.lock() {
+            state.progress = start.elapsed().as_secs_f32() / LISTEN_DURATION.as_secs_f32();
+        }
+    }
+    Ok(DiagnosticReport {
+        mode: DiagnosticMode::Listen,
+        sent: 0,
+        received,
+        errors: 0,
+        elapsed: start.elapsed().as_secs_f64()
+    })
+}
+
+fn pattern(len: usize) -> Vec<u8> {
+    let seed = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64;
+    let mut x = seed | 1;
+    (0..len).map(|_| {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        (x >> 32) as u8
+    }).collect()
+}
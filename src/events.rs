@@ -0,0 +1,98 @@
+use crate::data::EventChannel;
+use regex::Regex;
+
+const MAX_EMPTY_BUCKETS: f64 = 1000.0;
+
+enum Matcher {
+    Substring(String),
+    Regex(Regex),
+    Invalid
+}
+
+pub struct EventCounter {
+    pattern: String,
+    regex: bool,
+    matcher: Matcher,
+    bucket_start: Option<f64>,
+    count: u64,
+    pub values: Vec<[f64; 2]>
+}
+
+impl EventCounter {
+    pub fn new(channel: &EventChannel) -> Self {
+        let mut counter = Self {
+            pattern: String::new(),
+            regex: false,
+            matcher: Matcher::Invalid,
+            bucket_start: None,
+            count: 0,
+            values: Vec::new()
+        };
+        counter.compile(channel);
+        counter
+    }
+
+    pub fn sync(&mut self, channel: &EventChannel) -> bool {
+        if self.pattern != channel.pattern || self.regex != channel.regex {
+            self.compile(channel);
+            return true;
+        }
+        false
+    }
+
+    pub fn is_valid(&self) -> bool {
+        !matches!(self.matcher, Matcher::Invalid)
+    }
+
+    fn compile(&mut self, channel: &EventChannel) {
+        self.pattern = channel.pattern.clone();
+        self.regex = channel.regex;
+        self.matcher = match (channel.regex, channel.pattern.is_empty()) {
+            (_, true) => Matcher::Invalid,
+            (true, false) => match Regex::new(&channel.pattern) {
+                Ok(regex) => Matcher::Regex(regex),
+                Err(_) => Matcher::Invalid
+            },
+            (false, false) => Matcher::Substring(channel.pattern.clone())
+        };
+    }
+
+    pub fn on_line(&mut self, t: f64, line: &str, bucket: f64) {
+        self.advance(t, bucket);
+        let matched = match &self.matcher {
+            Matcher::Substring(pattern) => line.contains(pattern.as_str()),
+            Matcher::Regex(regex) => regex.is_match(line),
+            Matcher::Invalid => false
+        };
+        if matched {
+            self.count += 1;
+        }
+    }
+
+    pub fn advance(&mut self, t: f64, bucket: f64) {
+        let bucket = f64::max(bucket, 0.001);
+        let start = *self.bucket_start.get_or_insert((t / bucket).floor() * bucket);
+        if t < start + bucket {
+            return;
+        }
+
+        self.values.push([start + bucket, self.count as f64 / bucket]);
+        self.count = 0;
+        let mut start = start + bucket;
+        if (t - start) / bucket > MAX_EMPTY_BUCKETS {
+            self.values.push([start + bucket, 0.0]);
+            start = (t / bucket).floor() * bucket;
+        }
+        while t >= start + bucket {
+            self.values.push([start + bucket, 0.0]);
+            start += bucket;
+        }
+        self.bucket_start = Some(start);
+    }
+
+    pub fn reset(&mut self) {
+        self.bucket_start = None;
+        self.count = 0;
+        self.values.clear();
+    }
+}
@@ -0,0 +1,186 @@
+use crate::data::Marker;
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub const STDOUT_PATH: &str = "-";
+
+pub struct CsvStream {
+    writer: BufWriter<Box<dyn Write + Send>>,
+    header: bool,
+    columns: usize,
+    pending: Vec<(f64, usize, f64)>
+}
+
+impl CsvStream {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let writer: Box<dyn Write + Send> = match path.as_os_str() == STDOUT_PATH {
+            true => Box::new(io::stdout()),
+            false => Box::new(File::create(path)?)
+        };
+        Ok(Self {
+            writer: BufWriter::new(writer),
+            header: false,
+            columns: 0,
+            pending: Vec::new()
+        })
+    }
+
+    pub fn write(&mut self, t: f64, values: &[f64], shifts: &[f64], names: &[&str]) -> io::Result<()> {
+        if !self.header {
+            self.header = true;
+            writeln!(self.writer, "{}", header(names, values.len()))?;
+        }
+        self.columns = self.columns.max(values.len());
+        if self.pending.is_empty() && shifts.iter().all(|n| *n == 0.0) {
+            let row: Vec<Option<f64>> = values.iter().copied().map(Some).collect();
+            return self.write_row(t, &row);
+        }
+        let shift = |i: usize| shifts.get(i).copied().unwrap_or_default();
+        self.pending.extend(values.iter().enumerate().map(|(i, n)| (t + shift(i), i, *n)));
+        let settled = t + (0..values.len()).map(shift).fold(f64::INFINITY, f64::min);
+        self.write_pending(settled)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.write_pending(f64::INFINITY)?;
+        self.writer.flush()
+    }
+
+    fn write_pending(&mut self, before: f64) -> io::Result<()> {
+        self.pending.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let count = self.pending.partition_point(|n| n.0 < before);
+        let settled: Vec<(f64, usize, f64)> = self.pending.drain(..count).collect();
+        for group in settled.chunk_by(|a, b| a.0 == b.0) {
+            let mut row = vec![None; self.columns];
+            for (_, i, value) in group {
+                row[*i] = Some(*value);
+            }
+            self.write_row(group[0].0, &row)?;
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, t: f64, values: &[Option<f64>]) -> io::Result<()> {
+        let mut row = t.to_string();
+        for value in values {
+            row.push(',');
+            if let Some(value) = value {
+                row.push_str(&value.to_string());
+            }
+        }
+        writeln!(self.writer, "{}", row)
+    }
+}
+
+pub fn write_csv(path: &Path, names: &[&str], values: &[Vec<[f64; 2]>], mut lines: Option<HashMap<u64, VecDeque<&str>>>) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let mut columns = header(names, values.len());
+    if lines.is_some() {
+        columns.push_str(",raw");
+    }
+    writeln!(writer, "{}", columns)?;
+
+    let mut cursors = vec![0; values.len()];
+    loop {
+        let next = values.iter()
+            .zip(&cursors)
+            .filter_map(|(n, i)| n.get(*i).map(|n| n[0]))
+            .reduce(f64::min);
+        let Some(t) = next else {
+            break;
+        };
+        let mut row = t.to_string();
+        for (channel, i) in values.iter().zip(&mut cursors) {
+            row.push(',');
+            if let Some(sample) = channel.get(*i).filter(|n| n[0] == t) {
+                row.push_str(&sample[1].to_string());
+                *i += 1;
+            }
+        }
+        if let Some(lines) = &mut lines {
+            row.push(',');
+            if let Some(line) = lines.get_mut(&t.to_bits()).and_then(|n| n.pop_front()) {
+                row.push_str(&format!("\"{}\"", line.replace('"', "\"\"")));
+            }
+        }
+        writeln!(writer, "{}", row)?;
+    }
+    writer.flush()
+}
+
+pub fn markers_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}_markers.csv", stem))
+}
+
+pub fn write_markers(path: &Path, markers: &[Marker]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    writeln!(writer, "t,label")?;
+    for marker in markers {
+        writeln!(writer, "{},{}", marker.t, escape(&marker.label))?;
+    }
+    writer.flush()
+}
+
+fn header(names: &[&str], columns: usize) -> String {
+    let mut header = String::from("t");
+    for i in 0..columns {
+        match names.get(i) {
+            Some(name) => header.push_str(&format!(",{}", escape(name))),
+            None => header.push_str(&format!(",Slot {}", i + 1))
+        }
+    }
+    header
+}
+
+fn escape(field: &str) -> String {
+    match field.contains([',', '"', '\n']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn stream(name: &str, rows: &[(f64, [f64; 2])], shifts: &[f64]) -> Vec<String> {
+        let path = std::env::temp_dir().join(format!("serial_monitor_{}_{}.csv", name, std::process::id()));
+        let mut stream = CsvStream::open(&path).unwrap();
+        for (t, values) in rows {
+            stream.write(*t, values, shifts, &["a", "b"]).unwrap();
+        }
+        stream.finish().unwrap();
+        drop(stream);
+        let text = fs::read_to_string(&path).unwrap();
+        _ = fs::remove_file(&path);
+        text.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn stream_without_shift() {
+        let rows = stream("unshifted", &[(0.0, [1.0, 10.0]), (1.0, [2.0, 20.0])], &[0.0, 0.0]);
+        assert_eq!(rows, ["t,a,b", "0,1,10", "1,2,20"]);
+    }
+
+    #[test]
+    fn stream_with_shift() {
+        let input = [(0.0, [1.0, 10.0]), (1.0, [2.0, 20.0]), (2.0, [3.0, 30.0])];
+        assert_eq!(stream("delayed", &input, &[0.0, 0.5]), [
+            "t,a,b", "0,1,", "0.5,,10", "1,2,", "1.5,,20", "2,3,", "2.5,,30"
+        ]);
+        assert_eq!(stream("advanced", &input, &[0.0, -0.5]), [
+            "t,a,b", "-0.5,,10", "0,1,", "0.5,,20", "1,2,", "1.5,,30", "2,3,"
+        ]);
+        assert_eq!(stream("same", &input, &[0.5, 0.5]), [
+            "t,a,b", "0.5,1,10", "1.5,2,20", "2.5,3,30"
+        ]);
+    }
+}
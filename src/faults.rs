@@ -0,0 +1,178 @@
+use crate::loss::LossTracker;
+use crate::serial_reader::{LineFaults, TimingStats};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Instant;
+
+const BURST_GAP: f64 = 1.0;
+
+#[derive(Default, Serialize)]
+pub struct FaultStats {
+    pub invalid_lines: u64,
+    pub truncated_lines: u64,
+    pub resyncs: u64,
+    pub reconnects: u64,
+    #[serde(skip)]
+    downtime: f64,
+    #[serde(skip)]
+    seen: LineFaults,
+    #[serde(skip)]
+    burst: Option<Burst>,
+    #[serde(skip)]
+    lost_at: Option<Instant>
+}
+
+struct Burst {
+    start: f64,
+    last: f64,
+    faults: LineFaults
+}
+
+impl FaultStats {
+    pub fn reader_started(&mut self) {
+        self.seen = LineFaults::default();
+    }
+
+    pub fn update(&mut self, t: f64, faults: LineFaults) -> Option<String> {
+        let new = LineFaults {
+            invalid: faults.invalid.saturating_sub(self.seen.invalid),
+            truncated: faults.truncated.saturating_sub(self.seen.truncated),
+            resyncs: faults.resyncs.saturating_sub(self.seen.resyncs)
+        };
+        self.seen = faults;
+        self.invalid_lines += new.invalid;
+        self.truncated_lines += new.truncated;
+        self.resyncs += new.resyncs;
+
+        if new.invalid + new.truncated + new.resyncs > 0 {
+            let burst = self.burst.get_or_insert(Burst {
+                start: t,
+                last: t,
+                faults: LineFaults::default()
+            });
+            burst.last = t;
+            burst.faults.invalid += new.invalid;
+            burst.faults.truncated += new.truncated;
+            burst.faults.resyncs += new.resyncs;
+            return None;
+        }
+        match self.burst.as_ref().is_some_and(|n| t - n.last >= BURST_GAP) {
+            true => self.finish(),
+            false => None
+        }
+    }
+
+    pub fn finish(&mut self) -> Option<String> {
+        let burst = self.burst.take()?;
+        let counts = [
+            (burst.faults.invalid, "invalid"),
+            (burst.faults.truncated, "truncated"),
+            (burst.faults.resyncs, "resynced")
+        ];
+        let text: Vec<String> = counts.iter()
+            .filter(|n| n.0 > 0)
+            .map(|(count, name)| format!("{} {}", count, name))
+            .collect();
+        Some(format!("Line faults from {:.2}s to {:.2}s: {}", burst.start, burst.last, text.join(", ")))
+    }
+
+    pub fn connection_lost(&mut self) {
+        self.lost_at.get_or_insert_with(Instant::now);
+    }
+
+    pub fn reconnected(&mut self) {
+        if let Some(lost_at) = self.lost_at.take() {
+            self.reconnects += 1;
+            self.downtime += lost_at.elapsed().as_secs_f64();
+        }
+    }
+
+    pub fn downtime(&self) -> f64 {
+        self.downtime + self.lost_at.map_or(0.0, |n| n.elapsed().as_secs_f64())
+    }
+
+    pub fn total(&self) -> u64 {
+        self.invalid_lines + self.truncated_lines + self.resyncs + self.reconnects
+    }
+
+    pub fn summary(&self) -> String {
+        format!("{} invalid, {} truncated, {} resyncs, {} reconnects ({:.1}s down)",
+            self.invalid_lines,
+            self.truncated_lines,
+            self.resyncs,
+            self.reconnects,
+            self.downtime())
+    }
+}
+
+#[derive(Serialize)]
+pub struct ConnectionSummary<'a> {
+    pub port: &'a str,
+    #[serde(flatten)]
+    pub faults: &'a FaultStats,
+    pub downtime_secs: f64
+}
+
+#[derive(Serialize)]
+pub struct RunSummary<'a> {
+    pub duration_secs: f64,
+    pub lines: u64,
+    pub parse_errors: u64,
+    pub bytes: u64,
+    pub connections: Vec<ConnectionSummary<'a>>,
+    pub config_locked: bool,
+    pub log_files: &'a [PathBuf],
+    pub timing: Option<TimingSummary>,
+    pub loss: Option<LossSummary>
+}
+
+#[derive(Serialize)]
+pub struct TimingSummary {
+    pub lines: u64,
+    pub mean_interval_ms: f64,
+    pub worst_gap_ms: f64,
+    pub loop_latency_ms: f64,
+    pub max_loop_latency_ms: f64,
+    pub intervals: Vec<IntervalCount>
+}
+
+#[derive(Serialize)]
+pub struct IntervalCount {
+    pub range: String,
+    pub lines: u64
+}
+
+#[derive(Serialize)]
+pub struct LossSummary {
+    pub received: u64,
+    pub lost: u64,
+    pub rate: f64
+}
+
+impl From<&LossTracker> for LossSummary {
+    fn from(loss: &LossTracker) -> Self {
+        Self {
+            received: loss.received,
+            lost: loss.lost,
+            rate: loss.rate()
+        }
+    }
+}
+
+impl From<&TimingStats> for TimingSummary {
+    fn from(timing: &TimingStats) -> Self {
+        Self {
+            lines: timing.lines,
+            mean_interval_ms: timing.mean_interval() * 1000.0,
+            worst_gap_ms: timing.worst_gap * 1000.0,
+            loop_latency_ms: timing.last_latency.as_secs_f64() * 1000.0,
+            max_loop_latency_ms: timing.max_latency.as_secs_f64() * 1000.0,
+            intervals: timing.histogram.iter().enumerate()
+                .map(|(i, lines)| IntervalCount {
+                    range: TimingStats::bucket_label(i),
+                    lines: *lines
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::data::{TimeFormat, ValueFormat};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SI_PREFIXES: &[&str] = &["y", "z", "a", "f", "p", "n", "\u{b5}", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+const SI_OFFSET: i32 = 8;
+
+pub fn format_value(value: f64, format: ValueFormat, digits: usize, unit: &str) -> String {
+    let (text, prefix) = match format {
+        ValueFormat::Decimals => (format!("{:.*}", digits, value), ""),
+        ValueFormat::Significant => (significant(value, digits), ""),
+        ValueFormat::Engineering => engineering(value, digits)
+    };
+    match (prefix, unit) {
+        ("", "") => text,
+        _ => format!("{} {}{}", text, prefix, unit)
+    }
+}
+
+pub fn format_time(t: f64, format: TimeFormat, window: f64, precise: bool) -> String {
+    match resolve_time_format(format, window) {
+        TimeFormat::Milliseconds => format!("{:.*}ms", if precise { 3 } else { 0 }, t * 1000.0),
+        TimeFormat::MinSec => {
+            let sign = if t < 0.0 { "-" } else { "" };
+            let decimals = if precise { 3 } else { 0 };
+            let width = if precise { 6 } else { 2 };
+            let minutes = (t.abs() / 60.0).floor();
+            format!("{}{}:{:0width$.decimals$}", sign, minutes, t.abs() - minutes * 60.0)
+        },
+        _ => format!("{:.*}s", if precise { 5 } else { 2 }, t)
+    }
+}
+
+pub fn file_timestamp_utc() -> String {
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as i64;
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}{:02}{:02}_{:02}{:02}{:02}", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
+
+fn resolve_time_format(format: TimeFormat, window: f64) -> TimeFormat {
+    match format {
+        TimeFormat::Auto if window < 2.0 => TimeFormat::Milliseconds,
+        TimeFormat::Auto if window >= 120.0 => TimeFormat::MinSec,
+        TimeFormat::Auto => TimeFormat::Seconds,
+        format => format
+    }
+}
+
+fn significant(value: f64, digits: usize) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return format!("{}", value);
+    }
+    let exp = value.abs().log10().floor() as i32;
+    let decimals = (digits.max(1) as i32 - 1 - exp).max(0) as usize;
+    format!("{:.*}", decimals, value)
+}
+
+fn engineering(value: f64, digits: usize) -> (String, &'static str) {
+    if value == 0.0 || !value.is_finite() {
+        return (format!("{}", value), "");
+    }
+    let exp = ((value.abs().log10() / 3.0).floor() as i32).clamp(-SI_OFFSET, SI_OFFSET);
+    let mantissa = value / 1000f64.powi(exp);
+    (significant(mantissa, digits), SI_PREFIXES[(exp + SI_OFFSET) as usize])
+}
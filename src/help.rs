@@ -0,0 +1,36 @@
+use std::fs;
+use std::path::PathBuf;
+
+const SHOWN_FILE: &str = "help_shown";
+
+pub const SECTIONS: &[(&str, &str)] = &[
+    ("Input format", "Every line is one entry of values separated by commas (the delimiter and decimal separator can be changed in the connection panel). Values can be labeled as name:value or name=value, labels become the slot names. Lines that can not be parsed are still printed to the console."),
+    ("Start modes", "Immediate reads from the first byte. Delay discards everything received in the given time after connecting, useful for devices that reset and print boot messages. Message waits for a line matching the start message (ends with, contains or exact) before reading values."),
+    ("Frame modes", "Line reads one entry per line. Marker collects values over several lines until a marker line is received. Line count collects a fixed number of lines per entry."),
+    ("Plot modes", "Continous scrolls the plot with the newest values on the right. Cyclic draws over the plot from left to right like an oscilloscope, optionally synced to a channel."),
+    ("Scale modes", "Auto fits the y axis to the visible values. AutoMax only grows the y axis, so it stays steady for periodic signals. Manual uses the fixed y range from the plot settings."),
+    ("Cursors", "While paused, click a plot to place cursor A and Shift + click for cursor B. Drag a cursor to move it. The box in the corner shows both times, the time difference and the values of the visible channels at the cursors.")
+];
+
+pub const EXAMPLES: &[(&str, &str)] = &[
+    ("Values", "Serial.print(a);\nSerial.print(',');\nSerial.println(b);"),
+    ("Labeled values", "Serial.print(\"temp:\");\nSerial.print(temp);\nSerial.print(\",hum:\");\nSerial.println(hum);"),
+    ("Start message", "void setup() {\n    Serial.begin(115200);\n    Serial.println(\"Start\");\n}")
+];
+
+pub fn was_shown() -> bool {
+    shown_path().is_some_and(|n| n.exists())
+}
+
+pub fn mark_shown() {
+    if let Some(path) = shown_path() {
+        if let Some(dir) = path.parent() {
+            _ = fs::create_dir_all(dir);
+        }
+        _ = fs::write(path, "");
+    }
+}
+
+fn shown_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|n| n.join("SerialMonitor").join(SHOWN_FILE))
+}
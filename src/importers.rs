@@ -0,0 +1,291 @@
+use crate::data::{Delimiter, InputSlot, PlotData, PlotScaleMode, SerialMonitorData, UiConfig};
+use crate::serial_reader::{FlowCtrl, Parity};
+use std::cell::Cell;
+use std::fs;
+use std::path::Path;
+
+pub struct ImportSummary {
+    pub format: &'static str,
+    pub file: String,
+    pub mapped: Vec<String>,
+    pub unmapped: Vec<String>
+}
+
+trait Importer {
+    fn name(&self) -> &'static str;
+    fn detect(&self, path: &Path, content: &str) -> bool;
+    fn import(&self, content: &str, data: &mut SerialMonitorData, summary: &mut ImportSummary);
+}
+
+const IMPORTERS: &[&dyn Importer] = &[&SerialPlot];
+
+pub fn import(path: &Path) -> std::io::Result<(SerialMonitorData, ImportSummary)> {
+    let content = fs::read_to_string(path)?;
+    let importer = IMPORTERS.iter()
+        .find(|n| n.detect(path, &content))
+        .ok_or_else(|| std::io::Error::other("Unknown file format"))?;
+    let mut data = SerialMonitorData {
+        plots: vec![PlotData::new("Plot 1")],
+        ..Default::default()
+    };
+    let mut summary = ImportSummary {
+        format: importer.name(),
+        file: path.file_name().map_or(String::new(), |n| n.to_string_lossy().into_owned()),
+        mapped: Vec::new(),
+        unmapped: Vec::new()
+    };
+    importer.import(&content, &mut data, &mut summary);
+    PlotData::update_internal_ids(&mut data.plots);
+    Ok((data, summary))
+}
+
+struct SerialPlot;
+
+impl Importer for SerialPlot {
+    fn name(&self) -> &'static str {
+        "SerialPlot"
+    }
+
+    fn detect(&self, path: &Path, content: &str) -> bool {
+        let ini = Ini::parse(content);
+        let extension = path.extension().and_then(|n| n.to_str()).unwrap_or_default().to_lowercase();
+        ini.has_section("Channels") && (["ini", "plt"].contains(&extension.as_str()) || ini.has_section("Plot"))
+    }
+
+    fn import(&self, content: &str, data: &mut SerialMonitorData, summary: &mut ImportSummary) {
+        let ini = Ini::parse(content);
+
+        let conn = &mut data.conn_config;
+        if let Some(port) = ini.get("Port", "selectedPort").filter(|n| !n.is_empty()) {
+            conn.port = port.to_owned();
+            summary.mapped.push(format!("Port {}", port));
+        }
+        if let Some(baud_rate) = ini.get("Port", "baudRate").and_then(|n| n.parse().ok()) {
+            conn.baud_rate = baud_rate;
+            summary.mapped.push(format!("Baud rate {}", baud_rate));
+        }
+        if let Some(data_bits) = ini.get("Port", "dataBits").and_then(|n| n.parse().ok()).filter(|n| (5..=8).contains(n)) {
+            conn.data_bits = data_bits;
+            summary.mapped.push(format!("{} data bits", data_bits));
+        }
+        let parity = ini.get("Port", "parity").and_then(|n| match n.to_lowercase().as_str() {
+            "none" => Some(Parity::None),
+            "odd" => Some(Parity::Odd),
+            "even" => Some(Parity::Even),
+            _ => None
+        });
+        if let Some(parity) = parity {
+            conn.parity = parity;
+            summary.mapped.push(String::from("Parity"));
+        }
+        if let Some(stop_bits) = ini.get("Port", "stopBits").and_then(|n| n.parse().ok()).filter(|n| (1..=2).contains(n)) {
+            conn.stop_bits = stop_bits;
+            summary.mapped.push(format!("{} stop bit(s)", stop_bits));
+        }
+        let flow_ctrl = ini.get("Port", "flowControl").and_then(|n| match n.to_lowercase().as_str() {
+            "none" => Some(FlowCtrl::None),
+            "software" => Some(FlowCtrl::Software),
+            "hardware" => Some(FlowCtrl::Hardware),
+            _ => None
+        });
+        if let Some(flow_ctrl) = flow_ctrl {
+            conn.flow_ctrl = flow_ctrl;
+            summary.mapped.push(String::from("Flow control"));
+        }
+        let delimiter = ini.get("ASCII", "delimiter").map(|n| match n {
+            "," => (Delimiter::Comma, String::new()),
+            ";" => (Delimiter::Semicolon, String::new()),
+            "\\t" => (Delimiter::Tab, String::new()),
+            "" => (Delimiter::Space, String::new()),
+            n => (Delimiter::Custom, n.to_owned())
+        });
+        if let Some((delimiter, custom)) = delimiter {
+            conn.delimiter = delimiter;
+            conn.custom_delimiter = custom;
+            summary.mapped.push(format!("Delimiter {}", delimiter));
+        }
+
+        let channels = ini.get("Channels", "channel\\size").and_then(|n| n.parse::<usize>().ok()).unwrap_or_default();
+        for i in 0..channels {
+            let key = |name: &str| format!("channel\\{}\\{}", i + 1, name);
+            let name = ini.get("Channels", &key("name"))
+                .filter(|n| !n.is_empty())
+                .map_or(format!("Slot {}", i + 1), String::from);
+            let color = ini.get("Channels", &key("color"))
+                .and_then(hex_color)
+                .unwrap_or([0.8, 0.8, 0.8]);
+            let mut slot = InputSlot::new(i, &name, color);
+            let gain = ini.get("Channels", &key("gain")).and_then(|n| n.parse::<f64>().ok());
+            if ini.get("Channels", &key("gainEn")) == Some("true") {
+                slot.scale = gain.filter(|n| *n != 0.0).unwrap_or(1.0);
+            }
+            let offset = ini.get("Channels", &key("offset")).and_then(|n| n.parse::<f64>().ok());
+            if ini.get("Channels", &key("offsetEn")) == Some("true") {
+                slot.offset = offset.unwrap_or_default();
+            }
+            if ini.get("Channels", &key("visible")) == Some("false") {
+                data.plots.iter_mut().for_each(|n| n.hidden.push(i));
+            }
+            data.inp_slots.push(slot);
+        }
+        if channels > 0 {
+            summary.mapped.push(format!("{} channel(s) with names, colors, gain and offset", channels));
+        }
+
+        let plot = &mut data.plot_config;
+        let range = (ini.get("Plot", "yMin").and_then(|n| n.parse::<f64>().ok()), ini.get("Plot", "yMax").and_then(|n| n.parse::<f64>().ok()));
+        if let (Some(min), Some(max)) = range {
+            if ini.get("Plot", "autoScale") == Some("false") && min < max {
+                plot.scale_mode = PlotScaleMode::Manual;
+                plot.y_min = min;
+                plot.y_max = max;
+                summary.mapped.push(format!("Y range {} to {}", min, max));
+            }
+        }
+        if let Some(width) = ini.get("Plot", "lineThickness").and_then(|n| n.parse::<f32>().ok()) {
+            data.ui_config.line_width = width.clamp(*UiConfig::LINE_WIDTHS.start(), *UiConfig::LINE_WIDTHS.end());
+            summary.mapped.push(format!("Line width {}", data.ui_config.line_width));
+        }
+        if ini.get("Plot", "darkBackground") == Some("false") {
+            data.ui_config.plot_background = Some([1.0, 1.0, 1.0]);
+            summary.mapped.push(String::from("Light plot background"));
+        }
+        if let Some(samples) = ini.get("Plot", "numOfSamples") {
+            summary.unmapped.push(format!("Plot/numOfSamples = {} (the window is set in seconds, not samples)", samples));
+        }
+
+        summary.unmapped.extend(ini.unused());
+    }
+}
+
+struct Ini {
+    entries: Vec<IniEntry>
+}
+
+struct IniEntry {
+    section: String,
+    key: String,
+    value: String,
+    used: Cell<bool>
+}
+
+impl Ini {
+    fn parse(content: &str) -> Self {
+        let mut section = String::new();
+        let mut entries = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+                section = name.trim().to_owned();
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.trim();
+            entries.push(IniEntry {
+                section: section.clone(),
+                key: key.trim().to_owned(),
+                value: value.strip_prefix('"').and_then(|n| n.strip_suffix('"')).unwrap_or(value).to_owned(),
+                used: Cell::new(false)
+            });
+        }
+        Self { entries }
+    }
+
+    fn has_section(&self, section: &str) -> bool {
+        self.entries.iter().any(|n| n.section == section)
+    }
+
+    fn get(&self, section: &str, key: &str) -> Option<&str> {
+        let entry = self.entries.iter().find(|n| n.section == section && n.key == key)?;
+        entry.used.set(true);
+        Some(entry.value.as_str())
+    }
+
+    fn unused(&self) -> Vec<String> {
+        self.entries.iter()
+            .filter(|n| !n.used.get())
+            .map(|n| format!("{}/{} = {}", n.section, n.key, n.value))
+            .collect()
+    }
+}
+
+fn hex_color(text: &str) -> Option<[f32; 3]> {
+    let hex = text.strip_prefix('#').filter(|n| n.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(|n| n as f32 / 255.0);
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::PlotMode;
+    use std::path::PathBuf;
+
+    fn fixture(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+    }
+
+    #[test]
+    fn serialplot_fixture() {
+        let (data, summary) = import(&fixture("serialplot.ini")).unwrap();
+        assert_eq!(summary.format, "SerialPlot");
+        assert_eq!(summary.file, "serialplot.ini");
+
+        let conn = &data.conn_config;
+        assert_eq!(conn.port, "/dev/ttyUSB0");
+        assert_eq!(conn.baud_rate, 115200);
+        assert_eq!(conn.data_bits, 7);
+        assert_eq!(conn.parity, Parity::Even);
+        assert_eq!(conn.stop_bits, 2);
+        assert_eq!(conn.flow_ctrl, FlowCtrl::Hardware);
+        assert_eq!(conn.delimiter, Delimiter::Semicolon);
+
+        let names: Vec<&str> = data.inp_slots.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(names, ["temperature", "Slot 2", "pressure"]);
+        let indexes: Vec<usize> = data.inp_slots.iter().map(|n| n.index).collect();
+        assert_eq!(indexes, [0, 1, 2]);
+        assert_eq!(data.inp_slots[0].color, [1.0, 128.0 / 255.0, 0.0]);
+        assert_eq!(data.inp_slots[1].color, [0.0, 0.0, 1.0]);
+        assert_eq!(data.inp_slots[2].color, [0.8, 0.8, 0.8]);
+        assert_eq!((data.inp_slots[0].scale, data.inp_slots[0].offset), (0.5, 0.0));
+        assert_eq!((data.inp_slots[2].scale, data.inp_slots[2].offset), (1.0, -10.0));
+        assert_eq!(data.plots.len(), 1);
+        assert_eq!(data.plots[0].hidden, [1]);
+
+        let plot = &data.plot_config;
+        assert_eq!(plot.scale_mode, PlotScaleMode::Manual);
+        assert_eq!((plot.y_min, plot.y_max), (-5.0, 40.0));
+        assert_eq!(plot.mode, PlotMode::Continous);
+        assert_eq!(data.ui_config.line_width, 2.0);
+        assert_eq!(data.ui_config.plot_background, Some([1.0, 1.0, 1.0]));
+        assert!(data.validate().is_empty(), "{:?}", data.validate());
+
+        assert_eq!(summary.unmapped, [
+            "Plot/numOfSamples = 1000 (the window is set in seconds, not samples)",
+            "DataFormat/format = ascii",
+            "ASCII/numOfChannels = 3",
+            "Plot/symbols = show when zoomed"
+        ]);
+        assert!(summary.mapped.contains(&String::from("3 channel(s) with names, colors, gain and offset")));
+        assert!(summary.mapped.contains(&String::from("Y range -5 to 40")));
+    }
+
+    #[test]
+    fn serialplot_detection() {
+        let path = Path::new("project.txt");
+        assert!(SerialPlot.detect(Path::new("project.ini"), "[Channels]\nchannel\\size=0"));
+        assert!(!SerialPlot.detect(path, "[Channels]\nchannel\\size=0"));
+        assert!(SerialPlot.detect(path, "[Channels]\nchannel\\size=0\n[Plot]\nyMin=0"));
+        assert!(!SerialPlot.detect(Path::new("config.json"), "{\"conn_config\": {}}"));
+    }
+
+    #[test]
+    fn unknown_format() {
+        let e = import(&fixture("valid.json")).err().expect("json config imported as a foreign format");
+        assert_eq!(e.to_string(), "Unknown file format");
+    }
+}
@@ -0,0 +1,235 @@
+use crate::data::{LogConfig, LogFormat, LogRotation};
+use crate::format;
+use crate::memory::BYTES_PER_MB;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const PART_EXTENSION: &str = "part";
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub enum LogError {
+    Open(PathBuf, String),
+    Write(PathBuf, String),
+    Rename(PathBuf, String),
+}
+
+impl Display for LogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open(path, e) => write!(f, "Could not open log file {}: {}", path.display(), e),
+            Self::Write(path, e) => write!(f, "Could not write log file {}: {}", path.display(), e),
+            Self::Rename(path, e) => write!(f, "Could not finish log file {}: {}", path.display(), e)
+        }
+    }
+}
+
+struct LogFile {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    size: u64,
+    opened: Instant,
+    flushed: Instant,
+    header: bool
+}
+
+pub struct LogWriter {
+    config: LogConfig,
+    file: Option<LogFile>,
+    index: usize,
+    files: Vec<PathBuf>
+}
+
+impl LogWriter {
+    pub fn new(config: LogConfig) -> Result<Self, LogError> {
+        let mut writer = Self {
+            config,
+            file: None,
+            index: 0,
+            files: Vec::new()
+        };
+        writer.open()?;
+        Ok(writer)
+    }
+
+    pub fn write(&mut self, t: f64, line: &str, values: &[f64], names: &[&str]) -> Result<(), LogError> {
+        let result = self.append(t, line, values, names);
+        if result.is_err() {
+            _ = self.finish();
+        }
+        result
+    }
+
+    fn append(&mut self, t: f64, line: &str, values: &[f64], names: &[&str]) -> Result<(), LogError> {
+        if self.should_rotate() {
+            self.finish()?;
+            self.prune();
+            self.open()?;
+        }
+        let (format, timestamps) = (self.config.format, self.config.timestamps);
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+        let mut text = String::new();
+        match format {
+            LogFormat::Raw => {
+                if timestamps {
+                    text.push_str(&format!("[{:.3}] ", t));
+                }
+                text.push_str(line);
+            },
+            LogFormat::Csv => {
+                if values.is_empty() {
+                    return Ok(());
+                }
+                if !file.header {
+                    file.header = true;
+                    text.push('t');
+                    for i in 0..values.len() {
+                        match names.get(i) {
+                            Some(name) => text.push_str(&format!(",{}", name)),
+                            None => text.push_str(&format!(",Slot {}", i + 1))
+                        }
+                    }
+                    text.push('\n');
+                }
+                text.push_str(&t.to_string());
+                values.iter().for_each(|n| text.push_str(&format!(",{}", n)));
+            }
+        }
+        text.push('\n');
+        file.writer.write_all(text.as_bytes())
+            .map_err(|e| LogError::Write(file.path.clone(), e.to_string()))?;
+        file.size += text.len() as u64;
+        if file.flushed.elapsed() >= FLUSH_INTERVAL {
+            file.flushed = Instant::now();
+            file.writer.flush()
+                .map_err(|e| LogError::Write(file.path.clone(), e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    pub fn close(mut self) -> Result<Vec<PathBuf>, LogError> {
+        self.finish()?;
+        self.prune();
+        Ok(std::mem::take(&mut self.files))
+    }
+
+    pub fn current(&self) -> Option<(&Path, u64)> {
+        self.file.as_ref().map(|n| (n.path.as_path(), n.size))
+    }
+
+    fn should_rotate(&self) -> bool {
+        let Some(file) = &self.file else {
+            return false;
+        };
+        match self.config.rotation {
+            LogRotation::Never => false,
+            LogRotation::Duration => file.opened.elapsed().as_secs_f64() >= self.config.rotate_minutes * 60.0,
+            LogRotation::Size => file.size as f64 >= self.config.rotate_mb * BYTES_PER_MB
+        }
+    }
+
+    fn open(&mut self) -> Result<(), LogError> {
+        let path = self.next_path();
+        let part = part_path(&path);
+        let file = File::create(&part)
+            .map_err(|e| LogError::Open(part.clone(), e.to_string()))?;
+        self.index += 1;
+        self.file = Some(LogFile {
+            writer: BufWriter::new(file),
+            path,
+            size: 0,
+            opened: Instant::now(),
+            flushed: Instant::now(),
+            header: false
+        });
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<(), LogError> {
+        let Some(LogFile { mut writer, path, .. }) = self.file.take() else {
+            return Ok(());
+        };
+        let part = part_path(&path);
+        let flushed = writer.flush()
+            .map_err(|e| LogError::Write(part.clone(), e.to_string()));
+        drop(writer);
+        fs::rename(&part, &path)
+            .map_err(|e| LogError::Rename(path.clone(), e.to_string()))?;
+        self.files.push(path);
+        flushed
+    }
+
+    fn prune(&mut self) {
+        if self.config.keep_files == 0 {
+            return;
+        }
+        while self.files.len() > self.config.keep_files {
+            let path = self.files.remove(0);
+            _ = fs::remove_file(path);
+        }
+    }
+
+    fn next_path(&self) -> PathBuf {
+        let extension = match self.config.format {
+            LogFormat::Raw => "log",
+            LogFormat::Csv => "csv"
+        };
+        let name = self.config.pattern
+            .replace("{timestamp}", &format::file_timestamp_utc())
+            .replace("{index}", &self.index.to_string());
+        let dir = PathBuf::from(&self.config.directory);
+        let mut path = dir.join(format!("{}.{}", name, extension));
+        let mut n = 1;
+        while path.exists() || part_path(&path).exists() {
+            path = dir.join(format!("{}_{}.{}", name, n, extension));
+            n += 1;
+        }
+        path
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        _ = self.finish();
+    }
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut part = path.as_os_str().to_owned();
+    part.push(format!(".{}", PART_EXTENSION));
+    PathBuf::from(part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn write_error_finishes_part_file() {
+        let dir = std::env::temp_dir().join(format!("serial_monitor_log_{}", std::process::id()));
+        _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config = LogConfig {
+            directory: dir.to_string_lossy().into_owned(),
+            pattern: String::from("log"),
+            ..LogConfig::default()
+        };
+        let mut writer = LogWriter::new(config).unwrap();
+        writer.write(0.0, "first", &[], &[]).unwrap();
+        writer.file.as_mut().unwrap().writer = BufWriter::new(File::options().write(true).open("/dev/full").unwrap());
+
+        let line = "x".repeat(64 * 1024);
+        assert!(matches!(writer.write(1.0, &line, &[], &[]), Err(LogError::Write(..))));
+        assert!(writer.current().is_none());
+        assert!(dir.join("log.log").is_file());
+        assert!(!dir.join("log.log.part").exists());
+        assert_eq!(writer.close().unwrap(), [dir.join("log.log")]);
+        _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,78 @@
+use crate::data::LossConfig;
+
+const MIN_ALARM_PACKETS: u64 = 100;
+
+#[derive(Default)]
+pub struct LossTracker {
+    slot: usize,
+    modulus: u64,
+    last: Option<u64>,
+    alarmed: bool,
+    pub received: u64,
+    pub lost: u64,
+    pub gaps: Vec<f64>
+}
+
+impl LossTracker {
+    pub fn sync(&mut self, config: &LossConfig) {
+        if self.slot != config.slot || self.modulus != config.modulus {
+            self.reset();
+            self.slot = config.slot;
+            self.modulus = config.modulus;
+        }
+    }
+
+    pub fn on_sample(&mut self, t: f64, values: &[f64], config: &LossConfig) -> bool {
+        let Some(value) = values.get(config.slot).filter(|n| n.is_finite() && **n >= 0.0) else {
+            return false;
+        };
+        let mut value = value.round() as u64;
+        if config.modulus > 0 {
+            value %= config.modulus;
+        }
+        let missed = match (self.last.replace(value), config.modulus) {
+            (None, _) => 0,
+            (Some(last), 0) if value > last => value - last - 1,
+            (Some(_), 0) => 0,
+            (Some(last), modulus) => {
+                let missed = (value + modulus - (last + 1) % modulus) % modulus;
+                match missed > modulus / 2 {
+                    true => 0,
+                    false => missed
+                }
+            }
+        };
+        self.received += 1;
+        self.lost += missed;
+        if missed > 0 {
+            self.gaps.push(t);
+        }
+
+        let Some(threshold) = config.alarm_rate else {
+            return false;
+        };
+        let above = self.received + self.lost >= MIN_ALARM_PACKETS && self.rate() * 100.0 > threshold;
+        let alarm = above && !self.alarmed;
+        self.alarmed = above;
+        alarm
+    }
+
+    pub fn rate(&self) -> f64 {
+        match self.received + self.lost {
+            0 => 0.0,
+            total => self.lost as f64 / total as f64
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        format!("Packet loss: {} of {} ({:.2}%)", self.lost, self.received + self.lost, self.rate() * 100.0)
+    }
+
+    pub fn reset(&mut self) {
+        self.last = None;
+        self.alarmed = false;
+        self.received = 0;
+        self.lost = 0;
+        self.gaps.clear();
+    }
+}
@@ -1,53 +1,253 @@
+mod alarm;
+mod anomaly;
 mod app;
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(not(feature = "audio"))]
+#[path = "audio_stub.rs"]
+mod audio;
+mod blackbox;
+mod checksum;
+mod clock;
+mod command;
 mod data;
+mod decimation;
+mod derived;
+mod diagnostics;
+mod events;
+mod export;
+mod faults;
+mod format;
+mod help;
+mod importers;
+mod logger;
+mod loss;
+mod memory;
+mod metrics;
+mod plot_engine;
+mod profiles;
+mod raw_lines;
+mod recording;
+mod sequence;
 mod serial_reader;
 mod serial_parser;
+mod snapshot;
+mod source;
+mod spectrum;
+mod stats;
 mod ui;
 
-use app::SerialMonitorApp;
+use app::{Headless, SerialMonitorApp};
 use data::{PlotData, SerialMonitorData};
+use recording::ReplaySpeed;
+use source::Source;
 use std::path::PathBuf;
+use std::time::Duration;
 use clap::Parser;
 
 #[derive(Parser, Debug)]
 struct Args {
-    #[arg(long, help = "Path to a json file containing a saved configuration")]
+    #[arg(long, help = "Path to a json file containing a saved configuration, or profile:NAME to load a saved profile")]
     config: Option<String>,
 
     #[arg(short, long, action, help = "Connect to the port from the configuration file")]
     connect: bool,
 
+    #[arg(long, action, help = "Read lines from stdin instead of a serial port", conflicts_with_all = &["connect", "replay", "demo"])]
+    stdin: bool,
+
+    #[arg(long, value_name = "FILE", help = "Replay lines from a raw log file instead of a serial port", conflicts_with_all = &["connect", "demo"])]
+    replay: Option<PathBuf>,
+
+    #[arg(long = "loop", action, help = "Restart the replay when the file ends", requires = "replay")]
+    looping: bool,
+
+    #[arg(long, value_name = "SPEED", help = "Playback speed of a recorded session (.jsonl): 0.5, 1, 10 or instant", requires = "replay")]
+    speed: Option<String>,
+
+    #[arg(long, action, help = "Generate demo data instead of reading a serial port", conflicts_with = "connect")]
+    demo: bool,
+
+    #[arg(long, help = "Name of a sequence from the configuration to run after connecting", requires = "connect")]
+    run_sequence: Option<String>,
+
     #[arg(short, long, action, help = "Enable output to the console/terminal")]
     terminal: bool,
 
-    #[arg(long, action, help = "Prevent GUI creation", requires_all = &["config", "connect"])]
-    headless: bool
+    #[arg(long, action, help = "Prevent GUI creation, requires a data source")]
+    headless: bool,
+
+    #[arg(long, value_name = "FILE", help = "Write parsed values as csv to a file, or to stdout if FILE is -", requires = "headless")]
+    output: Option<PathBuf>,
+
+    #[arg(long, value_name = "SECONDS", help = "Stop after the given number of seconds", requires = "headless")]
+    duration: Option<f64>,
+
+    #[arg(long, value_name = "MS", help = "Time between reads of the received data (10ms by default)", requires = "headless")]
+    poll_interval: Option<u64>,
+
+    #[arg(long, action, help = "Play a tick per received frame (a tone at high rates)", requires = "headless")]
+    audio: bool,
+
+    #[arg(long, value_name = "FILE", help = "Write a run summary with per-connection fault counters as json on exit", requires = "headless")]
+    summary: Option<PathBuf>,
+
+    #[arg(long, value_name = "FILE", help = "Record all received lines with their timestamps to a session file (.jsonl)", requires = "headless")]
+    record: Option<PathBuf>,
+
+    #[arg(long, value_name = "PORT", help = "Serve Prometheus metrics over http on the given port")]
+    metrics_port: Option<u16>,
+
+    #[arg(long, value_name = "FILE", help = "Check a configuration file and exit with 0 if it is valid", conflicts_with_all = &["config", "connect", "stdin", "replay", "demo", "headless"])]
+    validate: Option<PathBuf>,
+
+    #[arg(long, action, help = "Print --validate results as json", requires = "validate")]
+    json: bool
+}
+
+impl Args {
+    fn source(&self) -> Result<Option<Source>, String> {
+        if self.connect && self.config.is_none() {
+            return Err(String::from("--connect requires a port from --config"));
+        }
+        let source = if self.connect {
+            Some(Source::Serial)
+        } else if self.stdin {
+            Some(Source::Stdin)
+        } else if let Some(path) = self.replay.as_ref().filter(|n| n.extension().is_some_and(|n| n == recording::EXTENSION)) {
+            let speed = match &self.speed {
+                Some(speed) => ReplaySpeed::parse(speed).ok_or_else(|| format!("Unknown replay speed '{}' (0.5, 1, 10 or instant)", speed))?,
+                None => ReplaySpeed::Normal
+            };
+            Some(Source::Recording {
+                path: path.clone(),
+                speed
+            })
+        } else if let Some(path) = &self.replay {
+            if self.speed.is_some() {
+                return Err(String::from("--speed requires a recorded session (.jsonl)"));
+            }
+            Some(Source::Replay {
+                path: path.clone(),
+                looping: self.looping
+            })
+        } else if self.demo {
+            Some(Source::Demo)
+        } else {
+            None
+        };
+        if self.headless && source.is_none() {
+            return Err(String::from("--headless requires a data source: --config with --connect, --stdin, --replay <FILE> or --demo"));
+        }
+        Ok(source)
+    }
+
+    fn headless(&self) -> Result<Option<Headless>, String> {
+        if !self.headless {
+            return Ok(None);
+        }
+        if self.terminal && self.output.as_ref().is_some_and(|n| n.as_os_str() == export::STDOUT_PATH) {
+            return Err(String::from("--output - can not be combined with --terminal"));
+        }
+        let duration = match self.duration {
+            Some(n) if n.is_finite() && n > 0.0 => Some(Duration::from_secs_f64(n)),
+            Some(_) => return Err(String::from("--duration has to be a positive number of seconds")),
+            None => None
+        };
+        if self.poll_interval == Some(0) {
+            return Err(String::from("--poll-interval has to be at least 1ms"));
+        }
+        Ok(Some(Headless {
+            output: self.output.clone(),
+            duration,
+            poll_interval: self.poll_interval.map(Duration::from_millis),
+            summary: self.summary.clone(),
+            record: self.record.clone()
+        }))
+    }
 }
 
 fn main() {
     let args = Args::parse();
-    let mut data = SerialMonitorData::default();
-    data.plots = vec![PlotData::new("Plot 1")];
+    if let Some(path) = &args.validate {
+        std::process::exit(validate(path, args.json));
+    }
+    let (source, headless) = match args.source().and_then(|n| Ok((n, args.headless()?))) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut data = SerialMonitorData {
+        plots: vec![PlotData::new("Plot 1")],
+        ..Default::default()
+    };
+    let mut config_path = args.config.as_ref().map(PathBuf::from);
     if let Some(path) = &args.config {
-        data = match SerialMonitorData::deserialize(&PathBuf::from(&path)) {
+        let loaded = match path.strip_prefix(profiles::PREFIX) {
+            Some(name) => {
+                config_path = profiles::path();
+                profiles::load(name).map_err(|e| format!("Could not load profile: {} ({})", name, e))
+            },
+            None => SerialMonitorData::deserialize(&PathBuf::from(&path)).map_err(|e| format!("Could not load config from file: {} ({})", path, e))
+        };
+        data = match loaded {
             Ok(config) => config,
-            Err(e) => {
-                eprintln!("Could not load config from file: {} ({})", path, e.to_string());
+            Err(e) if args.headless => {
+                eprintln!("{}", e);
                 std::process::exit(1);
+            },
+            Err(e) => {
+                eprintln!("{}", e);
+                config_path = None;
+                SerialMonitorData {
+                    load_warnings: vec![format!("{}, starting with the default configuration", e)],
+                    ..data
+                }
             }
         };
     }
+    if args.headless {
+        data.audio.enabled = args.audio;
+    }
     if !args.terminal {
         #[cfg(target_os = "windows")]
         hide_console();
     }
 
-    if let Err(e) = SerialMonitorApp::run(data, args.config.is_some() && args.connect, args.terminal, args.headless) {
+    if let Err(e) = SerialMonitorApp::run(data, config_path, source, args.run_sequence, args.terminal, headless, args.metrics_port) {
         eprintln!("{:?}", e);
         std::process::exit(1);
     }
 }
 
+fn validate(path: &PathBuf, json: bool) -> i32 {
+    let errors = match SerialMonitorData::deserialize(path) {
+        Ok(config) => config.load_warnings.iter().cloned().chain(config.validate()).collect(),
+        Err(e) => vec![format!("Could not parse config: {}", e)]
+    };
+    if json {
+        let result = serde_json::json!({
+            "file": path.display().to_string(),
+            "valid": errors.is_empty(),
+            "errors": errors
+        });
+        println!("{}", result);
+    } else if errors.is_empty() {
+        println!("{}: OK", path.display());
+    } else {
+        eprintln!("{}: {} error(s)", path.display(), errors.len());
+        for error in &errors {
+            eprintln!("  {}", error);
+        }
+    }
+    match errors.is_empty() {
+        true => 0,
+        false => 1
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn hide_console() {
     use windows::Win32::System::Console::FreeConsole;
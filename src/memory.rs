@@ -0,0 +1,189 @@
+use crate::data::ConsoleLine;
+use crate::spectrum::Spectrum;
+use std::collections::VecDeque;
+use std::mem::{size_of, size_of_val};
+
+pub const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+const TRIM_TARGET: f64 = 0.9;
+const HISTORY_SLACK: f64 = 0.1;
+
+pub fn estimate_values(values: &[Vec<[f64; 2]>]) -> usize {
+    values.iter()
+        .map(estimate_channel)
+        .sum()
+}
+
+pub fn estimate_channel(values: &Vec<[f64; 2]>) -> usize {
+    size_of::<Vec<[f64; 2]>>() + values.capacity() * size_of::<[f64; 2]>()
+}
+
+pub fn estimate_lines(lines: &VecDeque<ConsoleLine>) -> usize {
+    lines.capacity() * size_of::<ConsoleLine>() + lines.iter().map(|n| n.content.capacity()).sum::<usize>()
+}
+
+pub fn estimate_spectra<'a>(spectra: impl IntoIterator<Item = &'a Spectrum>) -> usize {
+    spectra.into_iter()
+        .map(Spectrum::estimate)
+        .sum()
+}
+
+pub fn trim_spectra<'a>(spectra: impl IntoIterator<Item = &'a mut Spectrum>, budget: usize) -> usize {
+    let mut spectra: Vec<&mut Spectrum> = spectra.into_iter().collect();
+    let used = estimate_spectra(spectra.iter().map(|n| &**n));
+    if used <= budget {
+        return used;
+    }
+    for spectrum in spectra.iter_mut() {
+        spectrum.release();
+    }
+    estimate_spectra(spectra.iter().map(|n| &**n))
+}
+
+pub fn trim_history(values: &mut Vec<[f64; 2]>, t: f64, history: f64) {
+    if values.first().is_some_and(|n| n[0] < t - history * (1.0 + HISTORY_SLACK)) {
+        let start = values.partition_point(|n| n[0] < t - history);
+        values.drain(..start);
+    }
+}
+
+pub fn drop_before(values: &mut Vec<[f64; 2]>, cutoff: f64) {
+    let start = values.partition_point(|n| n[0] < cutoff);
+    values.drain(..start);
+    values.shrink_to(values.len() * 2);
+}
+
+pub fn trim_values(values: &mut [Vec<[f64; 2]>], budget: usize) -> Option<f64> {
+    let used = estimate_values(values);
+    if used <= budget {
+        return None;
+    }
+
+    let overhead = size_of_val(values);
+    let target = ((budget as f64 * TRIM_TARGET) as usize).saturating_sub(overhead);
+    let keep = target / size_of::<[f64; 2]>() / 2;
+
+    let mut times: Vec<f64> = values.iter()
+        .flat_map(|n| n.iter().map(|n| n[0]))
+        .collect();
+    let cutoff = match keep < times.len() {
+        true if keep == 0 => times.iter().copied().fold(f64::MIN, f64::max),
+        true => {
+            let index = times.len() - keep;
+            *times.select_nth_unstable_by(index, f64::total_cmp).1
+        },
+        false => f64::MIN
+    };
+
+    for channel in values.iter_mut() {
+        let start = channel.partition_point(|n| n[0] < cutoff).min(channel.len().saturating_sub(1));
+        channel.drain(..start);
+        channel.shrink_to(channel.len() * 2);
+    }
+    (cutoff > f64::MIN).then_some(cutoff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: usize = size_of::<[f64; 2]>();
+    const CHANNEL: usize = size_of::<Vec<[f64; 2]>>();
+
+    #[test]
+    fn estimate_counts_capacity() {
+        let mut values = Vec::with_capacity(100);
+        values.push([0.0, 1.0]);
+        assert_eq!(estimate_channel(&values), CHANNEL + 100 * SAMPLE);
+        assert_eq!(estimate_values(&[values, Vec::new()]), 2 * CHANNEL + 100 * SAMPLE);
+
+        let mut lines = VecDeque::with_capacity(8);
+        lines.push_back(ConsoleLine { t: 0.0, host_t: None, content: String::with_capacity(64), sent: false });
+        assert_eq!(estimate_lines(&lines), lines.capacity() * size_of::<ConsoleLine>() + 64);
+    }
+
+    #[test]
+    fn estimate_counts_spectra() {
+        let values: Vec<[f64; 2]> = (0..64).map(|n| [n as f64 / 100.0, (n as f64).sin()]).collect();
+        let mut spectra = vec![Spectrum::default(), Spectrum::default()];
+        let empty = estimate_spectra(&spectra);
+        assert_eq!(empty, 2 * size_of::<Spectrum>());
+        assert!(spectra[0].compute(&values, 64));
+        assert!(estimate_spectra(&spectra) >= empty + 3 * 64 * size_of::<f64>() + 33 * SAMPLE);
+    }
+
+    #[test]
+    fn trim_spectra_releases_buffers() {
+        let values: Vec<[f64; 2]> = (0..256).map(|n| [n as f64 / 100.0, (n as f64).sin()]).collect();
+        let mut spectra = vec![Spectrum::default(), Spectrum::default()];
+        for spectrum in spectra.iter_mut() {
+            assert!(spectrum.compute(&values, 256));
+        }
+        let used = estimate_spectra(&spectra);
+        assert_eq!(trim_spectra(&mut spectra, used), used);
+        let trimmed = trim_spectra(&mut spectra, used / 2);
+        assert!(trimmed < used / 2);
+        assert_eq!(trimmed, estimate_spectra(&spectra));
+        assert!(spectra.iter().all(|n| n.points.len() == 129));
+        assert!(spectra[0].compute(&values, 256));
+    }
+
+    #[test]
+    fn trim_within_budget() {
+        let mut values = vec![vec![[0.0, 1.0], [1.0, 2.0]]];
+        assert_eq!(trim_values(&mut values, 1024 * 1024), None);
+        assert_eq!(values[0].len(), 2);
+    }
+
+    #[test]
+    fn trim_keeps_newest_sample() {
+        let mut values = vec![
+            (0..100).map(|n| [n as f64, 1.0]).collect(),
+            (0..50).map(|n| [n as f64, 2.0]).collect(),
+            Vec::new()
+        ];
+        assert_eq!(trim_values(&mut values, 0), Some(99.0));
+        assert_eq!(values[0], [[99.0, 1.0]]);
+        assert_eq!(values[1], [[49.0, 2.0]]);
+        assert!(values[2].is_empty());
+    }
+
+    #[test]
+    fn trim_releases_capacity() {
+        let mut values = vec![Vec::with_capacity(100_000), Vec::with_capacity(10)];
+        values[1].push([1.0, 1.0]);
+        assert_eq!(trim_values(&mut values, 64 * 1024), None);
+        assert_eq!(values[1], [[1.0, 1.0]]);
+        assert!(estimate_values(&values) <= 64 * 1024);
+    }
+
+    #[test]
+    fn trim_high_rate_input() {
+        let budget = 256 * 1024;
+        let mut values: Vec<Vec<[f64; 2]>> = vec![Vec::new(); 4];
+        let mut trims = 0;
+        let mut last_cutoff = f64::MIN;
+        let pushes = 200_000;
+        for i in 0..pushes {
+            let t = i as f64 / 10_000.0;
+            for (c, channel) in values.iter_mut().enumerate() {
+                if i % (c + 1) == 0 {
+                    channel.push([t, c as f64]);
+                }
+            }
+            if let Some(cutoff) = trim_values(&mut values, budget) {
+                trims += 1;
+                assert!(cutoff.is_finite() && cutoff >= last_cutoff);
+                last_cutoff = cutoff;
+                assert!(estimate_values(&values) <= budget);
+            }
+            assert!(estimate_values(&values) <= 2 * budget);
+        }
+        assert!(trims > 0 && trims < pushes / 1000, "{} trims", trims);
+        for (c, channel) in values.iter().enumerate() {
+            assert!(channel.windows(2).all(|n| n[0][0] < n[1][0]));
+            assert!(channel.first().is_some_and(|n| n[0] >= last_cutoff));
+            assert!(channel.last().is_some_and(|n| n[0] > (pushes - 5) as f64 / 10_000.0 && n[1] == c as f64));
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const PREFIX: &str = "serialmonitor";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct ChannelSample {
+    pub name: String,
+    pub value: f64,
+    pub age: Option<f64>
+}
+
+pub struct Metrics {
+    started: Instant,
+    serving: AtomicBool,
+    pub lines: AtomicU64,
+    pub parse_errors: AtomicU64,
+    pub dropped: AtomicU64,
+    pub bytes: AtomicU64,
+    pub connected: AtomicBool,
+    channels: Mutex<(Instant, Vec<ChannelSample>)>
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            serving: AtomicBool::new(false),
+            lines: AtomicU64::new(0),
+            parse_errors: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            connected: AtomicBool::new(false),
+            channels: Mutex::new((Instant::now(), Vec::new()))
+        }
+    }
+}
+
+impl Metrics {
+    pub fn serve(self: &Arc<Self>, port: u16) -> std::io::Result<u16> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        let port = listener.local_addr()?.port();
+        self.serving.store(true, Ordering::Relaxed);
+        let metrics = Arc::clone(self);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let metrics = Arc::clone(&metrics);
+                thread::spawn(move || {
+                    _ = metrics.respond(stream);
+                });
+            }
+        });
+        Ok(port)
+    }
+
+    pub fn is_serving(&self) -> bool {
+        self.serving.load(Ordering::Relaxed)
+    }
+
+    pub fn set_channels(&self, channels: Vec<ChannelSample>) {
+        if let Ok(mut locked) = self.channels.lock() {
+            *locked = (Instant::now(), channels);
+        }
+    }
+
+    fn respond(&self, mut stream: TcpStream) -> std::io::Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        let mut request = String::new();
+        BufReader::new(&stream).read_line(&mut request)?;
+        let path = request.split_whitespace().nth(1).unwrap_or_default();
+        let (status, body) = match path {
+            "/" | "/metrics" => ("200 OK", self.render()),
+            _ => ("404 Not Found", String::from("Not found\n"))
+        };
+        write!(stream, "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, body.len(), body)
+    }
+
+    fn render(&self) -> String {
+        let mut text = String::new();
+        let counters = [
+            ("lines_received_total", "Lines received from the input", &self.lines),
+            ("parse_errors_total", "Lines that could not be parsed", &self.parse_errors),
+            ("dropped_lines_total", "Incomplete frames that were discarded", &self.dropped),
+            ("bytes_read_total", "Bytes read from the input", &self.bytes)
+        ];
+        for (name, help, counter) in counters {
+            metric(&mut text, name, help, "counter", &[(String::new(), counter.load(Ordering::Relaxed) as f64)]);
+        }
+        let connected = self.connected.load(Ordering::Relaxed) as u8 as f64;
+        metric(&mut text, "connected", "Whether an input is connected", "gauge", &[(String::new(), connected)]);
+        metric(&mut text, "uptime_seconds", "Seconds since the monitor started", "gauge", &[(String::new(), self.started.elapsed().as_secs_f64())]);
+
+        if let Ok(channels) = self.channels.lock() {
+            let since = channels.0.elapsed().as_secs_f64();
+            let values: Vec<(String, f64)> = channels.1.iter()
+                .map(|n| (channel_label(&n.name), n.value))
+                .collect();
+            let ages: Vec<(String, f64)> = channels.1.iter()
+                .filter_map(|n| n.age.map(|age| (channel_label(&n.name), age + since)))
+                .collect();
+            metric(&mut text, "channel_value", "Last value of each channel", "gauge", &values);
+            metric(&mut text, "channel_age_seconds", "Seconds since each channel was updated", "gauge", &ages);
+        }
+        text
+    }
+}
+
+fn metric(text: &mut String, name: &str, help: &str, ty: &str, samples: &[(String, f64)]) {
+    text.push_str(&format!("# HELP {}_{} {}\n# TYPE {}_{} {}\n", PREFIX, name, help, PREFIX, name, ty));
+    for (labels, value) in samples {
+        let value = match value.is_infinite() {
+            true if *value > 0.0 => String::from("+Inf"),
+            true => String::from("-Inf"),
+            false => value.to_string()
+        };
+        text.push_str(&format!("{}_{}{} {}\n", PREFIX, name, labels, value));
+    }
+}
+
+fn channel_label(name: &str) -> String {
+    let escaped = name.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("{{channel=\"{}\"}}", escaped)
+}
@@ -0,0 +1,397 @@
+use crate::data::{ColorMap, InputSlot, PlotConfig, PlotMode};
+use std::borrow::Cow;
+use std::collections::{HashMap, VecDeque};
+
+const MAX_COLOR_SEGMENTS: usize = 256;
+
+pub struct Smoothed {
+    pub mean: Vec<[f64; 2]>,
+    pub low: Vec<[f64; 2]>,
+    pub high: Vec<[f64; 2]>
+}
+
+pub fn latest_time(channels: &[(&InputSlot, &[[f64; 2]])]) -> f64 {
+    channels.iter()
+        .filter_map(|n| n.1.last().map(|last| last[0] + n.0.time_shift()))
+        .fold(f64::MIN, f64::max)
+}
+
+pub fn has_range(channels: &[(&InputSlot, &[[f64; 2]])]) -> bool {
+    channels.iter().any(|n| n.1.first().zip(n.1.last()).is_some_and(|(first, last)| first[0] < last[0]))
+}
+
+pub fn display_values<'a>(values: &'a [[f64; 2]], slot: &InputSlot) -> Cow<'a, [[f64; 2]]> {
+    let shift = slot.time_shift();
+    match shift == 0.0 && !slot.is_scaled() {
+        true => Cow::Borrowed(values),
+        false => Cow::Owned(values.iter()
+            .map(|n| [n[0] + shift, slot.apply(n[1])])
+            .collect())
+    }
+}
+
+pub fn since(values: &[[f64; 2]], t: f64) -> &[[f64; 2]] {
+    &values[values.partition_point(|n| n[0] < t)..]
+}
+
+pub fn since_with_history(values: &[[f64; 2]], t: f64, history: usize) -> &[[f64; 2]] {
+    &values[values.partition_point(|n| n[0] < t).saturating_sub(history)..]
+}
+
+pub fn recent_times(channels: &[&[[f64; 2]]], rows: usize) -> Vec<f64> {
+    let mut times: Vec<f64> = channels.iter()
+        .flat_map(|values| values[values.len().saturating_sub(rows)..].iter().map(|n| n[0]))
+        .collect();
+    times.sort_unstable_by(|a, b| b.total_cmp(a));
+    times.dedup();
+    times.truncate(rows);
+    times
+}
+
+pub fn value_at(values: &[[f64; 2]], t: f64) -> Option<f64> {
+    values.get(values.partition_point(|n| n[0] < t))
+        .filter(|n| n[0] == t)
+        .map(|n| n[1])
+}
+
+pub fn interpolate(values: &[[f64; 2]], t: f64) -> Option<f64> {
+    let i = values.partition_point(|n| n[0] < t);
+    match (i.checked_sub(1).and_then(|n| values.get(n)), values.get(i)) {
+        (_, Some(r)) if r[0] == t => Some(r[1]),
+        (Some(l), Some(r)) => Some(l[1] + (r[1] - l[1]) * (t - l[0]) / (r[0] - l[0])),
+        _ => None
+    }
+}
+
+pub fn difference_name(a: &InputSlot, b: &InputSlot) -> String {
+    format!("{} \u{2212} {}", a.name, b.name)
+}
+
+pub fn pair_nearest(xs: &[[f64; 2]], ys: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    if ys.is_empty() {
+        return Vec::new();
+    }
+    let mut k = 0;
+    xs.iter()
+        .map(|x| {
+            while k + 1 < ys.len() && (ys[k + 1][0] - x[0]).abs() <= (ys[k][0] - x[0]).abs() {
+                k += 1;
+            }
+            [x[1], ys[k][1]]
+        })
+        .collect()
+}
+
+pub fn window_values(values: &[[f64; 2]], t_now: f64, split: f64, config: &PlotConfig) -> Vec<[f64; 2]> {
+    match config.mode {
+        PlotMode::Continous => since(values, t_now - config.window).to_vec(),
+        PlotMode::Cyclic => {
+            let start = values.partition_point(|n| n[0] < t_now - config.window);
+            let split_idx = values.partition_point(|n| n[0] <= split);
+            let mut v: Vec<[f64; 2]> = Vec::with_capacity(values.len() - start);
+            v.extend_from_slice(&values[split_idx..]);
+            v.extend(values[start..split_idx.max(start)].iter()
+                .map(|n| [n[0] + config.window, n[1]]));
+            v
+        }
+    }
+}
+
+pub fn decimate(values: Vec<[f64; 2]>, bucket_width: f64) -> Vec<[f64; 2]> {
+    if values.len() <= 4 || bucket_width.is_nan() || bucket_width <= 0.0 {
+        return values;
+    }
+    let mut v: Vec<[f64; 2]> = Vec::new();
+    for bucket in values.chunk_by(|a, b| (a[0] / bucket_width).floor() == (b[0] / bucket_width).floor()) {
+        let min = bucket.iter().min_by(|a, b| a[1].total_cmp(&b[1])).unwrap_or(&bucket[0]);
+        let max = bucket.iter().max_by(|a, b| a[1].total_cmp(&b[1])).unwrap_or(&bucket[0]);
+        let (a, b) = match min[0] <= max[0] {
+            true => (min, max),
+            false => (max, min)
+        };
+        for n in [&bucket[0], a, b, &bucket[bucket.len() - 1]] {
+            if v.last() != Some(n) {
+                v.push(*n);
+            }
+        }
+    }
+    v
+}
+
+pub fn smooth(values: &[[f64; 2]], window: usize) -> Smoothed {
+    let window = window.max(1);
+    let mut smoothed = Smoothed {
+        mean: Vec::with_capacity(values.len()),
+        low: Vec::with_capacity(values.len()),
+        high: Vec::with_capacity(values.len())
+    };
+    let mut sum = 0.0;
+    let mut lows: VecDeque<usize> = VecDeque::new();
+    let mut highs: VecDeque<usize> = VecDeque::new();
+    for (i, n) in values.iter().enumerate() {
+        sum += n[1];
+        if i >= window {
+            sum -= values[i - window][1];
+        }
+        while lows.back().is_some_and(|k| values[*k][1] >= n[1]) {
+            lows.pop_back();
+        }
+        while highs.back().is_some_and(|k| values[*k][1] <= n[1]) {
+            highs.pop_back();
+        }
+        lows.push_back(i);
+        highs.push_back(i);
+        while lows.front().is_some_and(|k| k + window <= i) {
+            lows.pop_front();
+        }
+        while highs.front().is_some_and(|k| k + window <= i) {
+            highs.pop_front();
+        }
+        smoothed.mean.push([n[0], sum / usize::min(i + 1, window) as f64]);
+        smoothed.low.push([n[0], values[lows[0]][1]]);
+        smoothed.high.push([n[0], values[highs[0]][1]]);
+    }
+    smoothed
+}
+
+pub fn envelope(low: &[[f64; 2]], high: &[[f64; 2]], bucket_width: f64) -> Vec<[f64; 3]> {
+    let points: Vec<[f64; 3]> = low.iter().zip(high).map(|(l, h)| [l[0], l[1], h[1]]).collect();
+    if points.len() <= 4 || bucket_width.is_nan() || bucket_width <= 0.0 {
+        return points;
+    }
+    let mut v: Vec<[f64; 3]> = Vec::new();
+    for bucket in points.chunk_by(|a, b| (a[0] / bucket_width).floor() == (b[0] / bucket_width).floor()) {
+        let low = bucket.iter().map(|n| n[1]).fold(f64::MAX, f64::min);
+        let high = bucket.iter().map(|n| n[2]).fold(f64::MIN, f64::max);
+        v.push([bucket[0][0], low, high]);
+        if bucket.len() > 1 {
+            v.push([bucket[bucket.len() - 1][0], low, high]);
+        }
+    }
+    v
+}
+
+pub fn display_x(t: f64, t_now: f64, split: f64, config: &PlotConfig) -> Option<f64> {
+    match config.mode {
+        PlotMode::Continous => Some(t),
+        PlotMode::Cyclic => {
+            match t > split {
+                true => Some(t),
+                false if t >= t_now - config.window => Some(t + config.window),
+                false => None
+            }
+        }
+    }
+}
+
+pub fn source_t(x: f64, t_now: f64, config: &PlotConfig) -> f64 {
+    match config.mode {
+        PlotMode::Cyclic if x > t_now => x - config.window,
+        _ => x
+    }
+}
+
+pub fn free_split(t_now: f64, window: f64) -> f64 {
+    t_now - t_now % window
+}
+
+pub fn rising_edge(values: &[[f64; 2]], t_now: f64, window: f64, threshold: f64) -> Option<f64> {
+    values.windows(2)
+        .rev()
+        .take_while(|n| t_now - n[1][0] < window)
+        .find(|n| n[0][1] < threshold && n[1][1] >= threshold && n[1][0] <= t_now)
+        .map(|n| n[1][0])
+}
+
+pub fn value_range(values: &[[f64; 2]]) -> (f64, f64) {
+    values.iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), n| {
+            (f64::min(min, n[1]), f64::max(max, n[1]))
+        })
+}
+
+pub fn padded_range(min: f64, max: f64) -> (f64, f64) {
+    let margin = match max > min {
+        true => (max - min) * 0.05,
+        false => 0.5
+    };
+    (min - margin, max + margin)
+}
+
+pub fn manual_range(config: &PlotConfig, offset: f64) -> (f64, f64) {
+    let (min, max) = match config.y_min <= config.y_max {
+        true => (config.y_min, config.y_max),
+        false => (config.y_max, config.y_min)
+    };
+    (min, max + offset)
+}
+
+pub fn track_max_range(stored: &mut HashMap<usize, [f64; 2]>, ranges: HashMap<usize, [f64; 2]>) -> Option<(f64, f64)> {
+    stored.retain(|channel, _| ranges.contains_key(channel));
+    for (channel, range) in ranges {
+        let entry = stored.entry(channel).or_insert(range);
+        entry[0] = f64::min(entry[0], range[0]);
+        entry[1] = f64::max(entry[1], range[1]);
+    }
+    let (min, max) = stored.values()
+        .fold((f64::MAX, f64::MIN), |(min, max), n| (f64::min(min, n[0]), f64::max(max, n[1])));
+    Some((min, max)).filter(|n| n.0 <= n.1)
+}
+
+pub fn stack_offsets(visible: &[usize], spacing: f64) -> HashMap<usize, f64> {
+    visible.iter()
+        .enumerate()
+        .map(|(k, i)| (*i, (visible.len() - 1 - k) as f64 * spacing))
+        .collect()
+}
+
+pub fn color_segments(values: &[[f64; 2]], map: &ColorMap, offset: f64) -> Vec<(Vec<[f64; 2]>, [f32; 3])> {
+    let size = values.len().div_ceil(MAX_COLOR_SEGMENTS).max(1);
+    values.chunks(size)
+        .enumerate()
+        .map(|(k, chunk)| {
+            let mean = chunk.iter().map(|n| n[1]).sum::<f64>() / chunk.len() as f64;
+            let end = usize::min((k + 1) * size + 1, values.len());
+            (values[k * size..end].to_vec(), map.color_at(mean - offset))
+        })
+        .collect()
+}
+
+pub fn hidden_names(hidden: &[usize], slots: &[InputSlot]) -> Vec<String> {
+    hidden.iter()
+        .map(|n| match slots.get(*n) {
+            Some(slot) => slot.legend_name(),
+            None => String::new()
+        })
+        .collect()
+}
+
+pub fn hidden_slots(slots: &[InputSlot], is_hidden: impl Fn(&str) -> bool) -> Vec<usize> {
+    slots.iter()
+        .enumerate()
+        .filter(|(_, slot)| is_hidden(&slot.legend_name()))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::PlotScaleMode;
+
+    fn cyclic(window: f64) -> PlotConfig {
+        PlotConfig { mode: PlotMode::Cyclic, window, ..PlotConfig::default() }
+    }
+
+    fn samples(times: &[f64]) -> Vec<[f64; 2]> {
+        times.iter().map(|t| [*t, *t * 10.0]).collect()
+    }
+
+    #[test]
+    fn cyclic_window_matches_display_x() {
+        let config = cyclic(5.0);
+        let values = samples(&[6.0, 7.0, 8.0, 9.5, 10.0, 11.0, 12.0]);
+        let cases = [
+            (12.0, free_split(12.0, 5.0)),
+            (10.0, free_split(10.0, 5.0)),
+            (12.0, 12.0),
+            (12.0, 7.0)
+        ];
+        for (t_now, split) in cases {
+            let mut window = window_values(&values, t_now, split, &config);
+            window.sort_by(|a, b| a[1].total_cmp(&b[1]));
+            let expected: Vec<[f64; 2]> = values.iter()
+                .filter_map(|n| display_x(n[0], t_now, split, &config).map(|x| [x, n[1]]))
+                .collect();
+            assert_eq!(window, expected, "t_now {t_now}, split {split}");
+        }
+    }
+
+    #[test]
+    fn cyclic_wrap_edges() {
+        let config = cyclic(5.0);
+        assert_eq!(display_x(10.0, 12.0, 10.0, &config), Some(15.0));
+        assert_eq!(display_x(10.5, 12.0, 10.0, &config), Some(10.5));
+        assert_eq!(display_x(7.0, 12.0, 10.0, &config), Some(12.0));
+        assert_eq!(display_x(6.9, 12.0, 10.0, &config), None);
+        assert_eq!(source_t(12.5, 12.0, &config), 7.5);
+        assert_eq!(source_t(11.5, 12.0, &config), 11.5);
+    }
+
+    #[test]
+    fn continuous_window() {
+        let config = PlotConfig::default();
+        let values = samples(&[1.0, 5.0, 7.0, 9.0]);
+        assert_eq!(window_values(&values, 10.0, 0.0, &config), samples(&[5.0, 7.0, 9.0]));
+        assert_eq!(display_x(1.0, 10.0, 0.0, &config), Some(1.0));
+    }
+
+    #[test]
+    fn empty_channels() {
+        let slot = InputSlot::new(0, "a", [1.0; 3]);
+        assert_eq!(latest_time(&[(&slot, &[])]), f64::MIN);
+        assert!(window_values(&[], 10.0, 10.0, &cyclic(5.0)).is_empty());
+        assert!(window_values(&[], 10.0, 0.0, &PlotConfig::default()).is_empty());
+        assert!(decimate(Vec::new(), 0.1).is_empty());
+        let (min, max) = value_range(&[]);
+        assert!(min > max);
+        assert_eq!(track_max_range(&mut HashMap::new(), HashMap::new()), None);
+        assert!(color_segments(&[], &ColorMap::default(), 0.0).is_empty());
+    }
+
+    #[test]
+    fn nan_samples() {
+        let values = vec![[0.0, 1.0], [1.0, f64::NAN], [2.0, -3.0], [3.0, 2.0]];
+        assert_eq!(value_range(&values), (-3.0, 2.0));
+        let (min, max) = value_range(&[[0.0, f64::NAN]]);
+        assert!(min > max);
+
+        let mut values: Vec<[f64; 2]> = (0..100).map(|n| [n as f64 * 0.01, n as f64]).collect();
+        values[50][1] = f64::NAN;
+        let decimated = decimate(values.clone(), 0.25);
+        assert!(decimated.len() < values.len());
+        assert!(decimated.iter().any(|n| n[1] == 0.0) && decimated.iter().any(|n| n[1] == 99.0));
+        assert_eq!(decimate(values.clone(), f64::NAN).len(), values.len());
+        assert_eq!(rising_edge(&[[0.0, f64::NAN], [1.0, 5.0]], 1.0, 5.0, 1.0), None);
+    }
+
+    #[test]
+    fn manual_scale_clamping() {
+        let cases = [
+            (0.0, 1.0, 0.0, (0.0, 1.0)),
+            (-2.0, 3.0, 4.0, (-2.0, 7.0)),
+            (5.0, -5.0, 0.0, (-5.0, 5.0)),
+            (1.0, 1.0, 0.0, (1.0, 1.0))
+        ];
+        for (y_min, y_max, offset, expected) in cases {
+            let config = PlotConfig { scale_mode: PlotScaleMode::Manual, y_min, y_max, ..PlotConfig::default() };
+            assert_eq!(manual_range(&config, offset), expected);
+        }
+        assert_eq!(padded_range(0.0, 10.0), (-0.5, 10.5));
+        assert_eq!(padded_range(2.0, 2.0), (1.5, 2.5));
+    }
+
+    #[test]
+    fn auto_max_tracking() {
+        let mut stored = HashMap::new();
+        let frames = [
+            (vec![(0, [0.0, 1.0])], Some((0.0, 1.0))),
+            (vec![(0, [0.5, 0.8])], Some((0.0, 1.0))),
+            (vec![(0, [-1.0, 0.5]), (1, [2.0, 4.0])], Some((-1.0, 4.0))),
+            (vec![(1, [2.0, 3.0])], Some((2.0, 4.0))),
+            (vec![], None)
+        ];
+        for (ranges, expected) in frames {
+            assert_eq!(track_max_range(&mut stored, ranges.into_iter().collect()), expected);
+        }
+    }
+
+    #[test]
+    fn range_needs_two_times() {
+        let slot = InputSlot::new(0, "Slot 1", [1.0, 0.0, 0.0]);
+        let (empty, single, repeated, two) = (samples(&[]), samples(&[3.0]), samples(&[3.0, 3.0]), samples(&[3.0, 3.5]));
+        assert!(!has_range(&[]));
+        assert!(!has_range(&[(&slot, &empty), (&slot, &single), (&slot, &repeated)]));
+        assert!(has_range(&[(&slot, &single), (&slot, &two)]));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::data::SerialMonitorData;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+pub const PREFIX: &str = "profile:";
+const PROFILES_FILE: &str = "profiles.json";
+
+#[derive(Serialize, Deserialize)]
+struct Profile {
+    name: String,
+    config: serde_json::Value
+}
+
+pub fn path() -> Option<PathBuf> {
+    dirs::config_dir().map(|n| n.join("SerialMonitor").join(PROFILES_FILE))
+}
+
+pub fn names() -> std::io::Result<Vec<String>> {
+    Ok(read()?.into_iter().map(|n| n.name).collect())
+}
+
+pub fn load(name: &str) -> std::io::Result<SerialMonitorData> {
+    let profile = read()?.into_iter()
+        .find(|n| n.name == name)
+        .ok_or_else(|| std::io::Error::other(format!("No profile named {}", name)))?;
+    let mut data = SerialMonitorData::from_value(profile.config)?;
+    data.profile = Some(profile.name);
+    Ok(data)
+}
+
+pub fn store(name: &str, data: &SerialMonitorData) -> std::io::Result<()> {
+    let config = serde_json::to_value(data).map_err(std::io::Error::other)?;
+    let mut profiles = read()?;
+    match profiles.iter_mut().find(|n| n.name == name) {
+        Some(profile) => profile.config = config,
+        None => profiles.push(Profile {
+            name: name.to_owned(),
+            config
+        })
+    }
+    write(&profiles)
+}
+
+pub fn rename(name: &str, new_name: &str) -> std::io::Result<()> {
+    let mut profiles = read()?;
+    if profiles.iter().any(|n| n.name == new_name) {
+        return Err(std::io::Error::other(format!("A profile named {} already exists", new_name)));
+    }
+    let profile = profiles.iter_mut()
+        .find(|n| n.name == name)
+        .ok_or_else(|| std::io::Error::other(format!("No profile named {}", name)))?;
+    profile.name = new_name.to_owned();
+    write(&profiles)
+}
+
+pub fn delete(name: &str) -> std::io::Result<()> {
+    let mut profiles = read()?;
+    profiles.retain(|n| n.name != name);
+    write(&profiles)
+}
+
+pub fn unique_name(name: &str) -> std::io::Result<String> {
+    let names = names()?;
+    let mut unique = name.to_owned();
+    let mut i = 2;
+    while names.contains(&unique) {
+        unique = format!("{} ({})", name, i);
+        i += 1;
+    }
+    Ok(unique)
+}
+
+fn read() -> std::io::Result<Vec<Profile>> {
+    let path = path().ok_or_else(|| std::io::Error::other("No config directory available"))?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_reader(File::open(&path)?)
+        .map_err(|e| std::io::Error::other(format!("Could not read {} ({})", path.display(), e)))
+}
+
+fn write(profiles: &[Profile]) -> std::io::Result<()> {
+    let path = path().ok_or_else(|| std::io::Error::other("No config directory available"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let json = serde_json::to_string_pretty(profiles).map_err(std::io::Error::other)?;
+    SerialMonitorData::write(&path, &json, None)
+}
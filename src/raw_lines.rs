@@ -0,0 +1,72 @@
+use std::collections::{HashMap, VecDeque};
+use std::mem::size_of;
+
+pub struct RawRecord {
+    pub t: f64,
+    pub line: String,
+    values: Vec<(usize, f64)>
+}
+
+#[derive(Default)]
+pub struct RawLines {
+    records: VecDeque<RawRecord>
+}
+
+impl RawLines {
+    pub fn push(&mut self, t: f64, line: String, slots: &[usize], values: &[Option<f64>]) {
+        let values = slots.iter()
+            .zip(values)
+            .filter_map(|(slot, value)| Some((*slot, (*value)?)))
+            .collect();
+        self.records.push_back(RawRecord { t, line, values });
+    }
+
+    pub fn trim(&mut self, t: f64, retention: f64) {
+        while self.records.front().is_some_and(|n| n.t < t - retention) {
+            self.records.pop_front();
+        }
+    }
+
+    pub fn drop_before(&mut self, cutoff: f64) {
+        self.records.retain(|n| n.t >= cutoff);
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    pub fn remove_slot(&mut self, slot: usize) {
+        for record in &mut self.records {
+            record.values.retain(|n| n.0 != slot);
+            record.values.iter_mut().filter(|n| n.0 > slot).for_each(|n| n.0 -= 1);
+        }
+    }
+
+    pub fn estimate(&self) -> usize {
+        self.records.capacity() * size_of::<RawRecord>() + self.records.iter()
+            .map(|n| n.line.capacity() + n.values.capacity() * size_of::<(usize, f64)>())
+            .sum::<usize>()
+    }
+
+    pub fn find(&self, slot: usize, t: f64, value: impl Fn(f64) -> f64, y: f64) -> Option<&RawRecord> {
+        self.records.iter()
+            .filter_map(|record| record.values.iter()
+                .find(|n| n.0 == slot)
+                .map(|n| (record, (record.t - t).abs(), (value(n.1) - y).abs())))
+            .min_by(|l, r| l.1.total_cmp(&r.1).then(l.2.total_cmp(&r.2)))
+            .map(|n| n.0)
+    }
+
+    pub fn nearest(&self, t: f64) -> Option<&RawRecord> {
+        self.records.iter()
+            .min_by(|l, r| (l.t - t).abs().total_cmp(&(r.t - t).abs()))
+    }
+
+    pub fn by_time(&self) -> HashMap<u64, VecDeque<&str>> {
+        let mut lines: HashMap<u64, VecDeque<&str>> = HashMap::new();
+        for record in &self.records {
+            lines.entry(record.t.to_bits()).or_default().push_back(&record.line);
+        }
+        lines
+    }
+}
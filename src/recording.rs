@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+pub const EXTENSION: &str = "jsonl";
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize, Deserialize)]
+pub struct RecordedLine {
+    pub t: f64,
+    pub line: String
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ReplaySpeed {
+    Half,
+    Normal,
+    Ten,
+    Instant
+}
+
+impl Display for ReplaySpeed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Half => write!(f, "0.5x"),
+            Self::Normal => write!(f, "1x"),
+            Self::Ten => write!(f, "10x"),
+            Self::Instant => write!(f, "Instant")
+        }
+    }
+}
+
+impl ReplaySpeed {
+    pub fn factor(&self) -> Option<f64> {
+        match self {
+            Self::Half => Some(0.5),
+            Self::Normal => Some(1.0),
+            Self::Ten => Some(10.0),
+            Self::Instant => None
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().trim_end_matches('x') {
+            "0.5" => Some(Self::Half),
+            "1" => Some(Self::Normal),
+            "10" => Some(Self::Ten),
+            "instant" | "Instant" => Some(Self::Instant),
+            _ => None
+        }
+    }
+}
+
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    lines: u64,
+    flushed: Instant
+}
+
+impl SessionRecorder {
+    pub fn create(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            path: path.to_owned(),
+            lines: 0,
+            flushed: Instant::now()
+        })
+    }
+
+    pub fn write(&mut self, t: f64, line: &str) -> std::io::Result<()> {
+        let record = RecordedLine {
+            t,
+            line: line.to_owned()
+        };
+        serde_json::to_writer(&mut self.writer, &record)?;
+        self.writer.write_all(b"\n")?;
+        self.lines += 1;
+        if self.flushed.elapsed() >= FLUSH_INTERVAL {
+            self.flushed = Instant::now();
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<(PathBuf, u64)> {
+        self.writer.flush()?;
+        Ok((self.path, self.lines))
+    }
+}
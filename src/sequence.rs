@@ -0,0 +1,75 @@
+use crate::data::{Sequence, SequenceStep};
+use std::time::{Duration, Instant};
+
+pub enum SequenceEvent {
+    Send(String),
+    Waiting,
+    Finished,
+    TimedOut(String)
+}
+
+pub struct SequenceRun {
+    sequence: Sequence,
+    step: usize,
+    step_start: Instant,
+    matched: bool
+}
+
+impl SequenceRun {
+    pub fn new(sequence: Sequence) -> Self {
+        Self {
+            sequence,
+            step: 0,
+            step_start: Instant::now(),
+            matched: false
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.sequence.name
+    }
+
+    pub fn progress(&self) -> (usize, usize) {
+        (usize::min(self.step + 1, self.sequence.steps.len()), self.sequence.steps.len())
+    }
+
+    pub fn on_line(&mut self, line: &str) {
+        if let Some(SequenceStep::WaitFor { pattern, .. }) = self.sequence.steps.get(self.step) {
+            self.matched |= line.contains(pattern.as_str());
+        }
+    }
+
+    pub fn poll(&mut self) -> SequenceEvent {
+        loop {
+            let elapsed = self.step_start.elapsed();
+            match self.sequence.steps.get(self.step) {
+                None => return SequenceEvent::Finished,
+                Some(SequenceStep::Send(text)) => {
+                    let text = text.clone();
+                    self.next_step();
+                    return SequenceEvent::Send(text);
+                },
+                Some(SequenceStep::Wait(ms)) => {
+                    if elapsed < Duration::from_millis(*ms as u64) {
+                        return SequenceEvent::Waiting;
+                    }
+                },
+                Some(SequenceStep::WaitFor { pattern, timeout }) => {
+                    if !self.matched {
+                        return match elapsed < Duration::from_millis(*timeout as u64) {
+                            true => SequenceEvent::Waiting,
+                            false => SequenceEvent::TimedOut(pattern.clone())
+                        };
+                    }
+                }
+            }
+            self.next_step();
+        }
+    }
+
+    fn next_step(&mut self) {
+        self.step += 1;
+        self.step_start = Instant::now();
+        self.matched = false;
+    }
+}
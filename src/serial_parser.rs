@@ -1,51 +1,679 @@
+use crate::checksum;
+use crate::data::{Checksum, FrameConfig, FrameMode, TimestampUnit};
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fmt::Display;
+use std::sync::OnceLock;
+
+const LABELED_VALUE: &str = r"^\s*([A-Za-z_][\w.]*)\s*[:=]?\s*([-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?)\s*$";
+const LABELED_PAIR: &str = r"^\s*([A-Za-z_][\w.]*)\s*[:=]\s*([-+]?(?:\d+\.?\d*|\.\d+)(?:[eE][-+]?\d+)?)\s*$";
+const COUNTER_RANGE: f64 = 4294967296.0;
+const MAX_REJECTED_LINES: usize = 50;
 
 #[derive(Debug)]
 pub enum ParseError {
-    ColumnMismatch(usize, usize)
+    ColumnMismatch(usize, usize),
+    TooManyColumns(usize, usize),
+    UnknownLabel(String),
+    MissingLabel(String),
+    ChecksumFailed
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Self::ColumnMismatch(expected, found) => write!(f, "Column mismatch (expected {}, found {})", expected, found),
+            Self::TooManyColumns(found, max) => write!(f, "Lines with {} values are ignored, the channel limit is {}", found, max),
+            Self::UnknownLabel(label) => write!(f, "Unknown label '{}'", label),
+            Self::MissingLabel(label) => write!(f, "Missing label '{}'", label),
+            Self::ChecksumFailed => write!(f, "Checksum failed")
+        }
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct ParseStats {
+    pub lines: u64,
+    pub parsed: u64,
+    pub dropped_tokens: u64,
+    pub mismatches: u64
+}
+
+impl ParseStats {
+    pub fn add(&mut self, other: &ParseStats) {
+        self.lines += other.lines;
+        self.parsed += other.parsed;
+        self.dropped_tokens += other.dropped_tokens;
+        self.mismatches += other.mismatches;
+    }
+}
+
+pub struct RejectedLine {
+    pub line: String,
+    pub reason: String
+}
+
+pub enum ParsePreview {
+    Values(Vec<(String, f64)>),
+    Pending(String, f64),
+    NoMatch,
+    Error(ParseError)
+}
+
+enum FrameLine {
+    Pending(usize, f64),
+    Complete(Vec<f64>),
+    Unrelated
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Separators {
+    pub delimiter: Option<char>,
+    pub decimal_comma: bool
+}
+
+impl Default for Separators {
+    fn default() -> Self {
+        Self {
+            delimiter: Some(','),
+            decimal_comma: false
+        }
+    }
+}
+
+impl Separators {
+    fn fields<'a>(&self, line: &'a str) -> Vec<&'a str> {
+        match self.delimiter {
+            Some(delimiter) => line.split(delimiter).collect(),
+            None => line.split_whitespace().collect()
+        }
+    }
+
+    fn normalize<'a>(&self, field: &'a str) -> Cow<'a, str> {
+        match self.decimal_comma {
+            true => Cow::Owned(field.replace(',', ".")),
+            false => Cow::Borrowed(field)
+        }
     }
 }
 
 #[derive(Default)]
 pub struct SerialParser {
-    columns: usize
+    columns: usize,
+    width: usize,
+    width_change: Option<(usize, usize)>,
+    separators: Separators,
+    checksum: Checksum,
+    layout: Vec<Option<String>>,
+    known: Vec<String>,
+    frame: Frame,
+    clock: DeviceClock,
+    limit_warned: bool,
+    stats: ParseStats,
+    rejected: VecDeque<RejectedLine>,
+    dropped: Vec<String>
+}
+
+#[derive(Default)]
+struct DeviceClock {
+    unit: Option<TimestampUnit>,
+    base: f64,
+    last: Option<f64>,
+    pending: Option<f64>,
+    offset: Option<f64>
+}
+
+#[derive(Default)]
+struct Frame {
+    config: FrameConfig,
+    marker: Option<Regex>,
+    labels: Vec<String>,
+    pending: Vec<Option<f64>>,
+    lines: usize,
+    last: Vec<f64>,
+    source: Vec<String>,
+    completed: Vec<String>
 }
 
 impl SerialParser {
     pub fn new() -> Self {
         Self {
-            columns: 0
+            columns: 0,
+            width: 0,
+            width_change: None,
+            separators: Separators::default(),
+            checksum: Checksum::None,
+            layout: Vec::new(),
+            known: Vec::new(),
+            frame: Frame::default(),
+            clock: DeviceClock::default(),
+            limit_warned: false,
+            stats: ParseStats::default(),
+            rejected: VecDeque::new(),
+            dropped: Vec::new()
         }
     }
 
     pub fn reset(&mut self) {
         self.columns = 0;
+        self.width = 0;
+        self.width_change = None;
+        self.layout.clear();
+        self.frame.labels.clear();
+        self.frame.last.clear();
+        self.frame.completed.clear();
+        self.frame.clear();
+        self.clock = DeviceClock {
+            unit: self.clock.unit,
+            ..Default::default()
+        };
+        self.limit_warned = false;
+    }
+
+    pub fn discard_frame(&mut self) {
+        self.frame.clear();
     }
 
     pub fn columns(&self) -> usize {
         self.columns
     }
 
-    pub fn parse_values(&mut self, line: &str) -> Result<Vec<f64>, ParseError> {
-        let mut columns = 0;
-        let mut res: Vec<f64> = Vec::new();
-        for col in line.split(',') {
+    pub fn label(&self, column: usize) -> Option<&str> {
+        match self.frame.config.mode {
+            FrameMode::Line => self.layout.get(column).and_then(|n| n.as_deref()),
+            _ => self.frame.labels.get(column).map(|n| n.as_str())
+        }
+    }
+
+    pub fn accept_values(&mut self, values: Vec<f64>) -> Result<Vec<Option<f64>>, ParseError> {
+        self.stats.lines += 1;
+        let max = self.max_columns();
+        if values.len() > max {
+            return Err(ParseError::TooManyColumns(values.len(), max));
+        }
+        self.stats.parsed += 1;
+        self.columns = values.len();
+        Ok(values.into_iter().map(Some).collect())
+    }
+
+    pub fn stats(&self) -> ParseStats {
+        self.stats
+    }
+
+    pub fn rejected(&self) -> &VecDeque<RejectedLine> {
+        &self.rejected
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = ParseStats::default();
+        self.rejected.clear();
+    }
+
+    pub fn should_warn(&mut self, e: &ParseError) -> bool {
+        match e {
+            ParseError::TooManyColumns(..) => !std::mem::replace(&mut self.limit_warned, true),
+            ParseError::ChecksumFailed => false,
+            _ => true
+        }
+    }
+
+    fn max_columns(&self) -> usize {
+        self.frame.config.max_columns.max(1)
+    }
+
+    pub fn take_width_change(&mut self) -> Option<(usize, usize)> {
+        self.width_change.take()
+    }
+
+    pub fn set_separators(&mut self, separators: Separators) {
+        self.separators = separators;
+    }
+
+    pub fn set_checksum(&mut self, checksum: Checksum) {
+        self.checksum = checksum;
+    }
+
+    pub fn set_timestamp(&mut self, unit: Option<TimestampUnit>) {
+        if self.clock.unit != unit {
+            self.clock = DeviceClock {
+                unit,
+                ..Default::default()
+            };
+        }
+    }
+
+    pub fn device_time(&mut self, host_t: f64) -> f64 {
+        if let Some(t) = self.clock.pending.take() {
+            self.clock.offset = Some(t - host_t);
+        }
+        self.peek_device_time(host_t)
+    }
+
+    pub fn peek_device_time(&self, host_t: f64) -> f64 {
+        host_t + self.clock.offset.unwrap_or_default()
+    }
+
+    pub fn set_known_labels(&mut self, labels: Vec<String>) {
+        self.known = labels;
+    }
+
+    pub fn sync_frame(&mut self, config: &FrameConfig) {
+        if self.frame.config == *config {
+            return;
+        }
+        if self.frame.config.mode != config.mode {
+            self.reset();
+        }
+        self.frame.config = config.clone();
+        self.frame.marker = Regex::new(&format!("^(?:{})$", config.marker.trim())).ok();
+        self.frame.clear();
+    }
+
+    pub fn frame_source(&self) -> &[String] {
+        &self.frame.completed
+    }
+
+    pub fn has_pending_frame(&self) -> bool {
+        self.frame.lines > 0
+    }
+
+    pub fn dry_run(config: &FrameConfig, separators: Separators, lines: &[&str]) -> Vec<ParsePreview> {
+        let mut parser = Self::new();
+        parser.set_separators(separators);
+        parser.sync_frame(config);
+        lines.iter().map(|line| parser.preview(line)).collect()
+    }
+
+    pub fn suggest_frame(config: &FrameConfig, separators: Separators, lines: &[&str]) -> Option<FrameConfig> {
+        let current = Self::extracted_channels(config, separators, lines);
+        if current >= 2 {
+            return None;
+        }
+        let mut labels: Vec<&str> = Vec::new();
+        let normalized: Vec<Cow<str>> = lines.iter().map(|n| separators.normalize(n)).collect();
+        for captures in normalized.iter().filter_map(|n| labeled_value().captures(n)) {
+            let label = captures.get(1).map_or("", |n| n.as_str());
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        let candidates = [
+            FrameConfig { mode: FrameMode::Line, ..config.clone() },
+            FrameConfig { mode: FrameMode::Marker, ..config.clone() },
+            FrameConfig { mode: FrameMode::LineCount, lines: labels.len(), ..config.clone() }
+        ];
+        candidates.into_iter()
+            .filter(|n| n.mode != config.mode)
+            .map(|n| (Self::extracted_channels(&n, separators, lines), n))
+            .filter(|n| n.0 >= 2 && n.0 > current)
+            .fold(None, |best: Option<(usize, FrameConfig)>, n| match best {
+                Some(best) if best.0 >= n.0 => Some(best),
+                _ => Some(n)
+            })
+            .map(|n| n.1)
+    }
+
+    fn extracted_channels(config: &FrameConfig, separators: Separators, lines: &[&str]) -> usize {
+        let previews = Self::dry_run(config, separators, lines);
+        let errors = previews.iter().filter(|n| matches!(n, ParsePreview::Error(_))).count();
+        let values: Vec<usize> = previews.iter()
+            .filter_map(|n| match n {
+                ParsePreview::Values(values) => Some(values.len()),
+                _ => None
+            })
+            .collect();
+        match errors >= values.len() {
+            true => 0,
+            false => values.into_iter().max().unwrap_or_default()
+        }
+    }
+
+    fn preview(&mut self, line: &str) -> ParsePreview {
+        let named = |parser: &Self, values: Vec<Option<f64>>| values.into_iter()
+            .enumerate()
+            .filter_map(|(i, n)| Some((parser.column_name(i), n?)))
+            .collect();
+        if self.frame.config.mode == FrameMode::Line {
+            return match self.parse_values(line) {
+                Ok(values) if values.is_empty() => ParsePreview::NoMatch,
+                Ok(values) => ParsePreview::Values(named(self, values)),
+                Err(e) => ParsePreview::Error(e)
+            };
+        }
+        match self.parse_frame(line) {
+            FrameLine::Pending(column, value) => ParsePreview::Pending(self.column_name(column), value),
+            FrameLine::Complete(values) => ParsePreview::Values(named(self, values.into_iter().map(Some).collect())),
+            FrameLine::Unrelated => ParsePreview::NoMatch
+        }
+    }
+
+    fn column_name(&self, column: usize) -> String {
+        match self.label(column) {
+            Some(label) => label.to_owned(),
+            None => format!("Slot {}", column + 1)
+        }
+    }
+
+    pub fn parse_values(&mut self, line: &str) -> Result<Vec<Option<f64>>, ParseError> {
+        self.stats.lines += 1;
+        self.dropped.clear();
+        let res = self.parse_line(line);
+        self.stats.dropped_tokens += self.dropped.len() as u64;
+        match &res {
+            Ok(values) if !values.is_empty() => self.stats.parsed += 1,
+            Err(ParseError::ColumnMismatch(..)) => self.stats.mismatches += 1,
+            _ => {}
+        }
+        let reason = match &res {
+            Err(e) => Some(e.to_string()),
+            Ok(_) if !self.dropped.is_empty() => Some(format!("Ignored {}", self.dropped.iter()
+                .map(|n| format!("'{}'", n))
+                .collect::<Vec<_>>()
+                .join(", "))),
+            Ok(_) => None
+        };
+        if let Some(reason) = reason {
+            if self.rejected.len() >= MAX_REJECTED_LINES {
+                self.rejected.pop_front();
+            }
+            self.rejected.push_back(RejectedLine {
+                line: line.to_owned(),
+                reason
+            });
+        }
+        res
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<Vec<Option<f64>>, ParseError> {
+        if self.frame.config.mode != FrameMode::Line {
+            return Ok(match self.parse_frame(line) {
+                FrameLine::Complete(values) => values.into_iter().map(Some).collect(),
+                _ => Vec::new()
+            });
+        }
+
+        let line = checksum::strip(self.checksum, line).ok_or(ParseError::ChecksumFailed)?;
+        static PAIR: OnceLock<Regex> = OnceLock::new();
+        let pair = PAIR.get_or_init(|| Regex::new(LABELED_PAIR).unwrap());
+        let mut fields: Vec<(Option<&str>, f64)> = Vec::new();
+        let separators = self.separators;
+        let columns: Vec<Cow<str>> = separators.fields(line).into_iter()
+            .map(|n| separators.normalize(n))
+            .collect();
+        for col in &columns {
             if let Ok(v) = col.trim().parse::<f64>() {
-                columns += 1;
-                res.push(v);
+                fields.push((None, v));
+            } else if let Some(captures) = pair.captures(col) {
+                let label = captures.get(1).map(|n| n.as_str());
+                if let Ok(v) = captures[2].parse::<f64>() {
+                    fields.push((label, v));
+                }
+            } else if !col.trim().is_empty() {
+                self.dropped.push(col.trim().to_owned());
             }
         }
 
-        if self.columns != 0 && self.columns != columns {
-            return Err(ParseError::ColumnMismatch(self.columns, columns));
+        if self.checksum == Checksum::LastColumnSum && !fields.is_empty() {
+            let text = columns.iter().rev().map(|n| n.trim()).find(|n| !n.is_empty()).unwrap_or_default();
+            let sum = fields.pop().map_or(0.0, |n| n.1);
+            let values: Vec<f64> = fields.iter().map(|n| n.1).collect();
+            if fields.is_empty() || !checksum::sum_matches(&values, sum, text) {
+                return Err(ParseError::ChecksumFailed);
+            }
+        }
+
+        if self.clock.unit.is_some() && !fields.is_empty() {
+            let ticks = fields.remove(0).1;
+            self.clock.update(ticks);
+        }
+
+        let max = self.max_columns();
+        if fields.len() > max {
+            return Err(ParseError::TooManyColumns(fields.len(), max));
+        }
+        let strict = self.frame.config.strict_columns;
+        if strict && self.columns != 0 && self.columns != fields.len() {
+            return Err(ParseError::ColumnMismatch(self.columns, fields.len()));
+        }
+        if fields.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.columns == 0 {
+            self.layout = self.layout_for(&fields);
+            self.columns = fields.len();
+        } else if !strict {
+            self.adapt_layout(&fields)?;
+        }
+        if self.width != 0 && self.width != fields.len() {
+            self.width_change = Some((self.width, fields.len()));
+        }
+        self.width = fields.len();
+
+        let mut res = vec![None; self.columns];
+        let mut unlabeled = self.layout.iter()
+            .enumerate()
+            .filter(|(_, n)| n.is_none())
+            .map(|(i, _)| i);
+        for (label, value) in fields.iter().copied() {
+            let index = match label {
+                Some(label) => self.layout.iter().position(|n| n.as_deref() == Some(label))
+                    .ok_or_else(|| ParseError::UnknownLabel(label.to_owned()))?,
+                None => unlabeled.next()
+                    .ok_or_else(|| self.missing_label(&fields))?
+            };
+            res[index] = Some(value);
         }
-        self.columns = columns;
 
         Ok(res)
     }
+
+    fn adapt_layout(&mut self, fields: &[(Option<&str>, f64)]) -> Result<(), ParseError> {
+        let mut added: Vec<&str> = Vec::new();
+        for label in fields.iter().filter_map(|n| n.0) {
+            if !self.layout.iter().any(|n| n.as_deref() == Some(label)) && !added.contains(&label) {
+                added.push(label);
+            }
+        }
+        let unlabeled = fields.iter().filter(|n| n.0.is_none()).count();
+        let free = self.layout.iter().filter(|n| n.is_none()).count();
+        let columns = self.layout.len() + added.len() + unlabeled.saturating_sub(free);
+        if columns > self.max_columns() {
+            return Err(ParseError::TooManyColumns(columns, self.max_columns()));
+        }
+        self.layout.extend(added.into_iter().map(|n| Some(n.to_owned())));
+        self.layout.resize(columns, None);
+        self.columns = self.layout.len();
+        Ok(())
+    }
+
+    fn layout_for(&self, fields: &[(Option<&str>, f64)]) -> Vec<Option<String>> {
+        let mut layout: Vec<Option<String>> = vec![None; fields.len()];
+        let mut placed = vec![false; fields.len()];
+        let mut rest = Vec::new();
+        for (label, _) in fields {
+            let known = label.and_then(|label| self.known.iter().position(|n| n == label))
+                .filter(|i| *i < placed.len() && !placed[*i]);
+            match known {
+                Some(i) => {
+                    layout[i] = label.map(str::to_owned);
+                    placed[i] = true;
+                },
+                None => rest.push(*label)
+            }
+        }
+        let mut rest = rest.into_iter();
+        for (column, placed) in layout.iter_mut().zip(placed) {
+            if !placed {
+                *column = rest.next().flatten().map(str::to_owned);
+            }
+        }
+        layout
+    }
+
+    fn missing_label(&self, fields: &[(Option<&str>, f64)]) -> ParseError {
+        let missing = self.layout.iter()
+            .flatten()
+            .find(|label| !fields.iter().any(|n| n.0 == Some(label.as_str())));
+        match missing {
+            Some(label) => ParseError::MissingLabel(label.clone()),
+            None => ParseError::ColumnMismatch(self.columns, fields.len())
+        }
+    }
+
+    fn parse_frame(&mut self, line: &str) -> FrameLine {
+        let frame = &mut self.frame;
+        if frame.config.mode == FrameMode::Marker && frame.is_marker(line) {
+            if frame.lines > 0 {
+                frame.source.push(line.to_owned());
+            }
+            return frame.complete();
+        }
+
+        let source = line;
+        let line = self.separators.normalize(line);
+        let Some(captures) = labeled_value().captures(&line) else {
+            return FrameLine::Unrelated;
+        };
+        let Ok(value) = captures[2].parse::<f64>() else {
+            return FrameLine::Unrelated;
+        };
+        let index = match frame.labels.iter().position(|n| *n == captures[1]) {
+            Some(index) => index,
+            None if frame.labels.len() >= frame.config.max_columns.max(1) => return FrameLine::Unrelated,
+            None => {
+                frame.labels.push(captures[1].to_owned());
+                frame.labels.len() - 1
+            }
+        };
+        frame.pending.resize(frame.labels.len(), None);
+        frame.pending[index] = Some(value);
+        frame.source.push(source.to_owned());
+        frame.lines += 1;
+        self.columns = frame.labels.len();
+
+        match frame.config.mode == FrameMode::LineCount && frame.lines >= frame.config.lines.max(1) {
+            true => frame.complete(),
+            false => FrameLine::Pending(index, value)
+        }
+    }
+}
+
+impl DeviceClock {
+    fn update(&mut self, ticks: f64) {
+        let Some(unit) = self.unit else {
+            return;
+        };
+        match self.last {
+            None => self.base = -ticks,
+            Some(last) if ticks < last && last - ticks > COUNTER_RANGE / 2.0 => self.base += COUNTER_RANGE,
+            Some(last) if ticks < last => self.base += last - ticks,
+            Some(_) => {}
+        }
+        self.last = Some(ticks);
+        self.pending = Some(unit.seconds(ticks + self.base));
+    }
+}
+
+fn labeled_value() -> &'static Regex {
+    static LABELED: OnceLock<Regex> = OnceLock::new();
+    LABELED.get_or_init(|| Regex::new(LABELED_VALUE).unwrap())
+}
+
+impl Frame {
+    fn is_marker(&self, line: &str) -> bool {
+        let line = line.trim();
+        match &self.marker {
+            Some(marker) => marker.is_match(line),
+            None => line == self.config.marker.trim()
+        }
+    }
+
+    fn complete(&mut self) -> FrameLine {
+        if self.lines == 0 {
+            return FrameLine::Unrelated;
+        }
+        self.last.resize(self.labels.len(), 0.0);
+        for (last, value) in self.last.iter_mut().zip(&self.pending) {
+            if let Some(value) = value {
+                *last = *value;
+            }
+        }
+        self.completed = std::mem::take(&mut self.source);
+        self.clear();
+        FrameLine::Complete(self.last.clone())
+    }
+
+    fn clear(&mut self) {
+        self.pending.clear();
+        self.source.clear();
+        self.lines = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggest(mode: FrameMode, lines: &[&str]) -> Option<FrameConfig> {
+        let config = FrameConfig { mode, ..FrameConfig::default() };
+        SerialParser::suggest_frame(&config, Separators::default(), lines)
+    }
+
+    #[test]
+    fn column_limit() {
+        let mut parser = SerialParser::new();
+        let wide: Vec<String> = (0..10_000).map(|n| n.to_string()).collect();
+        let wide = wide.join(",");
+        for warn in [true, false] {
+            let e = parser.parse_values(&wide).unwrap_err();
+            assert!(matches!(e, ParseError::TooManyColumns(10_000, 64)));
+            assert_eq!(parser.should_warn(&e), warn);
+            assert_eq!(parser.columns(), 0);
+        }
+        assert_eq!(parser.parse_values("1,2,3").unwrap(), [Some(1.0), Some(2.0), Some(3.0)]);
+        assert_eq!(parser.columns(), 3);
+        assert!(matches!(parser.accept_values(vec![0.0; 65]), Err(ParseError::TooManyColumns(65, 64))));
+    }
+
+    #[test]
+    fn suggest_frame_csv() {
+        let lines = ["1,2,3", "4,5,6", "7,8,9"];
+        assert!(suggest(FrameMode::Line, &lines).is_none());
+        let suggestion = suggest(FrameMode::Marker, &lines).expect("no suggestion for csv lines");
+        assert!(suggestion.mode == FrameMode::Line);
+        assert!(suggest(FrameMode::LineCount, &lines).is_some_and(|n| n.mode == FrameMode::Line));
+    }
+
+    #[test]
+    fn suggest_frame_labeled() {
+        let lines = ["temp: 20.5, hum: 40", "temp: 20.6, hum: 41", "temp: 20.7, hum: 42"];
+        assert!(suggest(FrameMode::Line, &lines).is_none());
+        assert!(suggest(FrameMode::Marker, &lines).is_some_and(|n| n.mode == FrameMode::Line));
+    }
+
+    #[test]
+    fn suggest_frame_marker() {
+        let lines = ["temp: 20.5", "hum: 40", "END", "temp: 20.6", "hum: 41", "END"];
+        let suggestion = suggest(FrameMode::Line, &lines).expect("no suggestion for marker frames");
+        assert!(suggestion.mode == FrameMode::Marker);
+        assert_eq!(suggestion.marker, "END");
+        assert!(suggest(FrameMode::Marker, &lines).is_none());
+    }
+
+    #[test]
+    fn suggest_frame_unmarked_labeled() {
+        let lines = ["temp: 20.5", "hum: 40", "press: 1013", "temp: 20.6", "hum: 41", "press: 1012"];
+        let suggestion = suggest(FrameMode::Line, &lines).expect("no suggestion for labeled lines");
+        assert!(suggestion.mode == FrameMode::LineCount);
+        assert_eq!(suggestion.lines, 3);
+    }
+
+    #[test]
+    fn suggest_frame_unparsable() {
+        assert!(suggest(FrameMode::Line, &["hello", "world"]).is_none());
+        assert!(suggest(FrameMode::Line, &[]).is_none());
+        assert!(suggest(FrameMode::Line, &["1", "2", "3"]).is_none());
+        assert!(suggest(FrameMode::Line, &["temp: 20.5", "temp: 20.6"]).is_none());
+    }
 }
@@ -1,13 +1,17 @@
+use crate::clock::CaptureClock;
+use regex::Regex;
 use serialport::{self, DataBits, SerialPort};
 use std::collections::VecDeque;
 use std::fmt::Display;
-use std::io::ErrorKind;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::io::{ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 use serde::{Serialize, Deserialize};
+use crate::source::{NetworkAddress, Source};
 
 #[derive(Debug)]
 pub enum SerialError {
@@ -15,15 +19,42 @@ pub enum SerialError {
     UnsupportedStopBits(u8),
     OpenError(String),
     WriteDtrError,
+    WriteRtsError,
     PortNotOpen,
     AlreadyOpen,
     AlreadyReading,
     ReadError(String),
+    InvalidData(u32),
+    WriteError(String),
+    ReadOnly,
+    EndOfStream,
+    Unresponsive(Duration),
+    InvalidStartPattern(String),
+    NoControlLines,
+    ControlError(String),
 }
 
 impl Display for SerialError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Self::UnsupportedDataBits(bits) => write!(f, "Unsupported data bits: {}", bits),
+            Self::UnsupportedStopBits(bits) => write!(f, "Unsupported stop bits: {}", bits),
+            Self::OpenError(e) => write!(f, "Could not open port: {}", e),
+            Self::WriteDtrError => write!(f, "Could not write DTR"),
+            Self::WriteRtsError => write!(f, "Could not write RTS"),
+            Self::PortNotOpen => write!(f, "Port is not open"),
+            Self::AlreadyOpen => write!(f, "Port is already open"),
+            Self::AlreadyReading => write!(f, "Port is already being read"),
+            Self::ReadError(e) => write!(f, "Read error: {}", e),
+            Self::InvalidData(lines) => write!(f, "Received {} invalid lines in a row, check the baud rate", lines),
+            Self::WriteError(e) => write!(f, "Write error: {}", e),
+            Self::ReadOnly => write!(f, "Input source is read-only"),
+            Self::EndOfStream => write!(f, "End of input"),
+            Self::Unresponsive(limit) => write!(f, "Reader did not respond for {:.1}s", limit.as_secs_f64()),
+            Self::InvalidStartPattern(e) => write!(f, "Invalid start pattern: {}", e),
+            Self::NoControlLines => write!(f, "Input source has no control lines"),
+            Self::ControlError(e) => write!(f, "Control line error: {}", e)
+        }
     }
 }
 
@@ -76,14 +107,397 @@ impl Display for StartMode {
     }
 }
 
-enum ReadError {
+#[derive(PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum StartMatch {
+    #[default]
+    EndsWith,
+    Contains,
+    Exact,
+    Regex
+}
+
+impl Display for StartMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EndsWith => write!(f, "Ends with"),
+            Self::Contains => write!(f, "Contains"),
+            Self::Exact => write!(f, "Exact"),
+            Self::Regex => write!(f, "Regex")
+        }
+    }
+}
+
+impl StartMatch {
+    pub fn matches(&self, line: &str, msg: &str, regex: Option<&Regex>) -> bool {
+        let msg = msg.trim();
+        if msg.is_empty() {
+            return true;
+        }
+        line.split(['\r', '\n'])
+            .map(str::trim)
+            .any(|line| match self {
+                Self::EndsWith => line.ends_with(msg),
+                Self::Contains => line.contains(msg),
+                Self::Exact => line == msg,
+                Self::Regex => regex.is_some_and(|regex| regex.is_match(line))
+            })
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ControlLine {
+    Dtr(bool),
+    Rts(bool),
+    Break(Duration),
+    Wait(Duration)
+}
+
+impl Display for ControlLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = |on: bool| match on {
+            true => "on",
+            false => "off"
+        };
+        match self {
+            Self::Dtr(on) => write!(f, "DTR {}", state(*on)),
+            Self::Rts(on) => write!(f, "RTS {}", state(*on)),
+            Self::Break(duration) => write!(f, "Break ({:?})", duration),
+            Self::Wait(duration) => write!(f, "Wait ({:?})", duration)
+        }
+    }
+}
+
+impl ControlLine {
+    pub const BREAK: Duration = Duration::from_millis(250);
+    pub const ESP32_RESET: &'static [ControlLine] = &[
+        ControlLine::Dtr(false),
+        ControlLine::Rts(true),
+        ControlLine::Wait(Duration::from_millis(100)),
+        ControlLine::Rts(false)
+    ];
+}
+
+pub trait PortControl {
+    fn set_control(&mut self, _control: ControlLine) -> std::io::Result<()> {
+        Err(std::io::Error::new(ErrorKind::Unsupported, "no control lines"))
+    }
+}
+
+impl PortControl for Box<dyn SerialPort> {
+    fn set_control(&mut self, control: ControlLine) -> std::io::Result<()> {
+        match control {
+            ControlLine::Dtr(on) => self.write_data_terminal_ready(on)?,
+            ControlLine::Rts(on) => self.write_request_to_send(on)?,
+            ControlLine::Break(duration) => {
+                self.set_break()?;
+                thread::sleep(duration);
+                self.clear_break()?;
+            },
+            ControlLine::Wait(duration) => thread::sleep(duration)
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum FieldType {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    F64
+}
+
+impl Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl FieldType {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 | Self::F32 => 4,
+            Self::F64 => 8
+        }
+    }
+
+    fn decode(&self, bytes: &[u8]) -> f64 {
+        match self {
+            Self::U8 => bytes[0] as f64,
+            Self::I8 => bytes[0] as i8 as f64,
+            Self::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            Self::I16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            Self::U32 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            Self::I32 => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            Self::F32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            Self::F64 => f64::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]])
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum Protocol {
+    #[default]
+    Ascii,
+    BinaryFrames {
+        sync: Vec<u8>,
+        fields: usize,
+        ty: FieldType
+    }
+}
+
+impl Display for Protocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ascii => write!(f, "ASCII"),
+            Self::BinaryFrames { .. } => write!(f, "Binary frames")
+        }
+    }
+}
+
+struct FrameDecoder {
+    sync: Vec<u8>,
+    fields: usize,
+    ty: FieldType,
+    buffer: Vec<u8>,
+    resyncs: u64
+}
+
+impl FrameDecoder {
+    fn new(sync: Vec<u8>, fields: usize, ty: FieldType) -> Self {
+        Self {
+            sync,
+            fields: fields.max(1),
+            ty,
+            buffer: Vec::new(),
+            resyncs: 0
+        }
+    }
+
+    fn frame_len(&self) -> usize {
+        self.sync.len() + self.fields * self.ty.size()
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    fn next_frame(&mut self) -> Option<Vec<f64>> {
+        if !self.sync.is_empty() {
+            match self.buffer.windows(self.sync.len()).position(|n| n == self.sync) {
+                Some(start) => {
+                    if start > 0 {
+                        self.resyncs += 1;
+                    }
+                    self.buffer.drain(..start);
+                },
+                None => {
+                    let keep = usize::min(self.buffer.len(), self.sync.len() - 1);
+                    self.buffer.drain(..self.buffer.len() - keep);
+                    return None;
+                }
+            }
+        }
+        let len = self.frame_len();
+        if self.buffer.len() < len {
+            return None;
+        }
+        let values = self.buffer[self.sync.len()..len]
+            .chunks_exact(self.ty.size())
+            .map(|n| self.ty.decode(n))
+            .collect();
+        self.buffer.drain(..len);
+        Some(values)
+    }
+}
+
+pub enum ReadError {
     Timeout,
+    EndOfStream,
+    Overflow,
     Other(String)
 }
 
+pub trait LineSource: Send {
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, ReadError>;
+
+    fn read_bytes(&mut self, _buf: &mut [u8]) -> Result<usize, ReadError> {
+        Err(ReadError::Other(String::from("Source does not support binary frames")))
+    }
+
+    fn write_all(&mut self, _data: &[u8]) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    fn set_terminator(&mut self, _terminator: &[u8]) {}
+
+    fn set_control(&mut self, _control: ControlLine) -> std::io::Result<()> {
+        Err(std::io::Error::new(ErrorKind::Unsupported, "no control lines"))
+    }
+
+    fn timestamp(&self) -> Option<f64> {
+        None
+    }
+
+    fn pending(&self) -> &[u8] {
+        &[]
+    }
+}
+
 pub struct Line {
     pub t: f64,
     pub content: String,
+    pub values: Option<Vec<f64>>,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct LineFaults {
+    pub invalid: u64,
+    pub truncated: u64,
+    pub resyncs: u64
+}
+
+#[derive(Clone, Default)]
+pub struct TimingStats {
+    pub lines: u64,
+    pub bytes: u64,
+    pub histogram: [u64; TimingStats::BUCKETS.len() + 1],
+    pub interval_sum: f64,
+    pub worst_gap: f64,
+    pub last_latency: Duration,
+    pub max_latency: Duration,
+    pub faults: LineFaults,
+    last_t: Option<f64>,
+    gaps: VecDeque<(f64, f64)>,
+}
+
+impl TimingStats {
+    pub const BUCKETS: &'static [f64] = &[0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0];
+    pub const GAP_WINDOW: f64 = 60.0;
+
+    fn record_loop(&mut self, latency: Duration) {
+        self.last_latency = latency;
+        self.max_latency = self.max_latency.max(latency);
+    }
+
+    fn record_line(&mut self, t: f64) {
+        self.lines += 1;
+        if let Some(last_t) = self.last_t.replace(t) {
+            let gap = t - last_t;
+            let bucket = Self::BUCKETS.partition_point(|n| *n <= gap);
+            self.histogram[bucket] += 1;
+            self.interval_sum += gap;
+
+            while self.gaps.back().is_some_and(|n| n.1 <= gap) {
+                self.gaps.pop_back();
+            }
+            self.gaps.push_back((t, gap));
+            while self.gaps.front().is_some_and(|n| t - n.0 > Self::GAP_WINDOW) {
+                self.gaps.pop_front();
+            }
+            self.worst_gap = self.gaps.front().map_or(0.0, |n| n.1);
+        }
+    }
+
+    pub fn mean_interval(&self) -> f64 {
+        match self.lines > 1 {
+            true => self.interval_sum / (self.lines - 1) as f64,
+            false => 0.0
+        }
+    }
+
+    pub fn bucket_label(index: usize) -> String {
+        match (index.checked_sub(1).and_then(|n| Self::BUCKETS.get(n)), Self::BUCKETS.get(index)) {
+            (None, Some(upper)) => format!("<{}ms", upper * 1000.0),
+            (Some(lower), Some(upper)) => format!("{}-{}ms", lower * 1000.0, upper * 1000.0),
+            (Some(lower), None) => format!(">{}ms", lower * 1000.0),
+            (None, None) => String::new()
+        }
+    }
+
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Lines: {}\nMean interval: {:.3}ms\nWorst gap ({}s): {:.3}ms\nLoop latency: {:?} (max {:?})\n",
+            self.lines,
+            self.mean_interval() * 1000.0,
+            Self::GAP_WINDOW,
+            self.worst_gap * 1000.0,
+            self.last_latency,
+            self.max_latency);
+        for (i, count) in self.histogram.iter().enumerate() {
+            summary.push_str(&format!("{}: {}\n", Self::bucket_label(i), count));
+        }
+        summary
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct StartProgress {
+    pub started: bool,
+    pub bytes: u64,
+    pub partial: u64,
+    pub last_line: String,
+}
+
+impl StartProgress {
+    pub const PREVIEW_CHARS: usize = 48;
+
+    pub fn received(&self) -> u64 {
+        self.bytes + self.partial
+    }
+
+    fn record(&mut self, line: &str, bytes: usize) {
+        self.bytes += bytes as u64;
+        self.partial = 0;
+        if !line.is_empty() {
+            self.preview(line);
+        }
+    }
+
+    fn record_partial(&mut self, pending: &[u8]) {
+        self.partial = pending.len() as u64;
+        let line = String::from_utf8_lossy(pending);
+        if !line.trim().is_empty() {
+            self.preview(line.trim());
+        }
+    }
+
+    fn preview(&mut self, line: &str) {
+        let skip = line.chars().count().saturating_sub(Self::PREVIEW_CHARS);
+        self.last_line = line.chars().skip(skip).collect();
+    }
+}
+
+struct Heartbeat {
+    origin: Instant,
+    last: AtomicU64
+}
+
+impl Heartbeat {
+    fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            last: AtomicU64::new(0)
+        }
+    }
+
+    fn beat(&self) {
+        self.last.store(self.origin.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    fn age(&self) -> Duration {
+        self.origin.elapsed().saturating_sub(Duration::from_millis(self.last.load(Ordering::Relaxed)))
+    }
 }
 
 pub struct SerialConfig {
@@ -93,104 +507,264 @@ pub struct SerialConfig {
     pub parity: Parity,
     pub stop_bits: u8,
     pub flow_ctrl: FlowCtrl,
+    pub dtr: bool,
+    pub rts: bool,
     pub timeout: Duration,
+    pub terminator: Vec<u8>,
+    pub protocol: Protocol,
+    pub max_bad_lines: u32,
 }
 
 pub struct SerialReader {
     config: SerialConfig,
-    port: Option<Box<dyn serialport::SerialPort>>,
+    port: Option<Box<dyn LineSource>>,
     lines: Arc<Mutex<VecDeque<Result<Line, SerialError>>>>,
+    timing: Arc<Mutex<TimingStats>>,
+    progress: Arc<Mutex<StartProgress>>,
+    writer: Option<Sender<Vec<u8>>>,
+    writable: bool,
+    control: Option<Sender<ControlLine>>,
+    control_lines: Option<(bool, bool)>,
+    control_errors: Arc<Mutex<VecDeque<SerialError>>>,
+    clock: CaptureClock,
     worker_thread: Option<JoinHandle<()>>,
     stop: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    source_t: Arc<Mutex<Option<f64>>>,
+    heartbeat: Arc<Heartbeat>,
 }
 
 impl SerialReader {
-    pub fn new(config: SerialConfig) -> SerialReader {
+    pub fn new(config: SerialConfig, clock: CaptureClock) -> SerialReader {
         SerialReader {
-            config: config,
+            config,
             port: None,
             lines: Arc::new(Mutex::new(VecDeque::new())),
+            timing: Arc::new(Mutex::new(TimingStats::default())),
+            progress: Arc::new(Mutex::new(StartProgress::default())),
+            writer: None,
+            writable: false,
+            control: None,
+            control_lines: None,
+            control_errors: Arc::new(Mutex::new(VecDeque::new())),
+            clock,
             worker_thread: None,
             stop: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            source_t: Arc::new(Mutex::new(None)),
+            heartbeat: Arc::new(Heartbeat::new()),
         }
     }
 
-    pub fn open(&mut self, dtr: bool) -> Result<(), SerialError> {
+    pub fn open(&mut self) -> Result<(), SerialError> {
         if self.is_open() {
             return Err(SerialError::AlreadyOpen);
         }
 
-        let config = &self.config;
-        let port = serialport::new(&config.port, config.baud_rate)
-            .data_bits(match config.data_bits {
-                5 => DataBits::Five,
-                6 => DataBits::Six,
-                7 => DataBits::Seven,
-                8 => DataBits::Eight,
-                _ => return Err(SerialError::UnsupportedDataBits(config.data_bits)),
-            })
-            .parity(match config.parity {
-                Parity::Odd => serialport::Parity::Odd,
-                Parity::Even => serialport::Parity::Even,
-                Parity::None => serialport::Parity::None,
-            })
-            .stop_bits(match config.stop_bits {
-                1 => serialport::StopBits::One,
-                2 => serialport::StopBits::Two,
-                _ => return Err(SerialError::UnsupportedStopBits(config.stop_bits)),
-            })
-            .flow_control(match config.flow_ctrl {
-                FlowCtrl::None => serialport::FlowControl::None,
-                FlowCtrl::Software => serialport::FlowControl::Software,
-                FlowCtrl::Hardware => serialport::FlowControl::Hardware
-            })
-            .timeout(config.timeout);
-
-        let mut p = port
-            .open()
-            .map_err(|e| SerialError::OpenError(e.to_string()))?;
-        p.write_data_terminal_ready(dtr)
-            .map_err(|_| SerialError::WriteDtrError)?;
-        self.port = Some(p);
+        self.port = Some(match NetworkAddress::parse(&self.config.port) {
+            Some(address) => address.open()?,
+            None => {
+                let port = open_port(&self.config)?;
+                self.control_lines = Some((self.config.dtr, self.config.rts));
+                Box::new(StreamLines::new(port))
+            }
+        });
         Ok(())
     }
 
-    pub fn begin_read(&mut self, start_mode: StartMode) -> Result<(), SerialError> {
+    pub fn open_source(&mut self, source: &Source) -> Result<(), SerialError> {
+        match source {
+            Source::Serial => self.open(),
+            _ if self.is_open() => Err(SerialError::AlreadyOpen),
+            _ => {
+                self.port = Some(source.open(&self.paused)?);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn begin_read(&mut self, start_mode: StartMode, start_match: StartMatch) -> Result<(), SerialError> {
         if self.worker_thread.is_some() {
             return Err(SerialError::AlreadyReading);
         }
+        let start_regex = match (&start_mode, start_match) {
+            (StartMode::Message(msg), StartMatch::Regex) if !msg.trim().is_empty() => {
+                Some(Regex::new(msg.trim()).map_err(|e| SerialError::InvalidStartPattern(e.to_string()))?)
+            }
+            _ => None
+        };
 
         let mut port = match self.port.take() {
             Some(p) => p,
             None => return Err(SerialError::PortNotOpen),
         };
 
+        self.writable = port.writable();
+        port.set_terminator(&self.config.terminator);
         let lines = Arc::clone(&self.lines);
+        let timing = Arc::clone(&self.timing);
+        let progress = Arc::clone(&self.progress);
         let stop = Arc::clone(&self.stop);
+        let heartbeat = Arc::clone(&self.heartbeat);
+        let source_t = Arc::clone(&self.source_t);
+        let control_errors = Arc::clone(&self.control_errors);
+        heartbeat.beat();
+        let (writer, write_queue) = mpsc::channel::<Vec<u8>>();
+        let (control, control_queue) = mpsc::channel::<ControlLine>();
+        let start_time = Instant::now();
+        let clock = self.clock.clone();
+        let mut decoder = match &self.config.protocol {
+            Protocol::Ascii => None,
+            Protocol::BinaryFrames { sync, fields, ty } => Some(FrameDecoder::new(sync.clone(), *fields, *ty))
+        };
+        let max_bad_lines = self.config.max_bad_lines;
         let handle = thread::spawn(move || {
             let mut line_buf = String::new();
-            let start_time = Instant::now();
-            let start_off = match start_mode {
-                StartMode::Delay(delay) => delay.as_secs_f64(),
-                _ => 0.0
-            };
+            let mut bad_lines = 0;
+            let mut byte_buf = [0u8; 1024];
             let mut started = matches!(start_mode, StartMode::Immediate);
+            let mut reported_start = started;
+            if let Ok(mut progress) = progress.lock() {
+                progress.started = started;
+            }
+            let mut loop_start: Option<Instant> = None;
             loop {
+                heartbeat.beat();
+                let now = Instant::now();
+                if let Some(last) = loop_start.replace(now) {
+                    if let Ok(mut timing) = timing.lock() {
+                        timing.record_loop(now - last);
+                    }
+                }
                 if stop.load(Ordering::Relaxed) {
                     break;
                 }
 
+                if let Err(e) = write_pending(&mut port, &write_queue) {
+                    if let Ok(mut locked_lines) = lines.lock() {
+                        locked_lines.push_back(Err(SerialError::WriteError(e.to_string())));
+                    }
+                    break;
+                }
+                control_pending(&mut port, &control_queue, &control_errors);
+
+                if let Some(decoder) = &mut decoder {
+                    let read = match port.read_bytes(&mut byte_buf) {
+                        Ok(0) => Err(SerialError::EndOfStream),
+                        Ok(read) => Ok(read),
+                        Err(ReadError::Timeout | ReadError::Overflow) => Ok(0),
+                        Err(ReadError::EndOfStream) => Err(SerialError::EndOfStream),
+                        Err(ReadError::Other(e_str)) => Err(SerialError::ReadError(e_str))
+                    };
+                    let read = match read {
+                        Ok(read) => read,
+                        Err(e) => {
+                            if let Ok(mut locked_lines) = lines.lock() {
+                                locked_lines.push_back(Err(e));
+                            }
+                            break;
+                        }
+                    };
+                    let read_at = Instant::now();
+                    let t = read_at - start_time;
+                    if let Ok(mut timing) = timing.lock() {
+                        timing.bytes += read as u64;
+                    }
+                    if !started {
+                        if let Ok(mut progress) = progress.lock() {
+                            progress.record("", read);
+                        }
+                        started = match start_mode {
+                            StartMode::Delay(delay) => t >= delay,
+                            _ => true
+                        };
+                        if !started {
+                            continue;
+                        }
+                        if let Ok(mut progress) = progress.lock() {
+                            progress.started = true;
+                        }
+                    }
+                    decoder.push(&byte_buf[..read]);
+                    let line_t = clock.at(read_at);
+                    while let Some(values) = decoder.next_frame() {
+                        let content = values.iter()
+                            .map(|n| n.to_string())
+                            .collect::<Vec<String>>()
+                            .join(",");
+                        if let Ok(mut locked_lines) = lines.lock() {
+                            locked_lines.push_back(Ok(Line {
+                                t: line_t,
+                                content,
+                                values: Some(values)
+                            }));
+                        }
+                        if let Ok(mut timing) = timing.lock() {
+                            timing.record_line(line_t);
+                        }
+                    }
+                    if let Ok(mut timing) = timing.lock() {
+                        timing.faults.resyncs = decoder.resyncs;
+                    }
+                    continue;
+                }
+
                 line_buf.clear();
-                let res = read_line(&mut port, &mut line_buf);
+                let res = match port.read_line(&mut line_buf) {
+                    Err(ReadError::EndOfStream) => Err(SerialError::EndOfStream),
+                    Err(ReadError::Other(e_str)) => Err(SerialError::ReadError(e_str)),
+                    res => Ok(res)
+                };
+                let res = match res {
+                    Ok(res) => res,
+                    Err(e) => {
+                        if let Ok(mut locked_lines) = lines.lock() {
+                            locked_lines.push_back(Err(e));
+                        }
+                        break;
+                    }
+                };
+                let (invalid, truncated) = match &res {
+                    Ok(_) => (line_buf.contains(char::REPLACEMENT_CHARACTER), false),
+                    Err(ReadError::Overflow) => (false, true),
+                    Err(_) => (false, false)
+                };
+                bad_lines = match (invalid || truncated, &res) {
+                    (true, _) => bad_lines + 1,
+                    (false, Ok(_)) => 0,
+                    (false, Err(_)) => bad_lines
+                };
+                if invalid || truncated {
+                    if let Ok(mut timing) = timing.lock() {
+                        timing.faults.invalid += invalid as u64;
+                        timing.faults.truncated += truncated as u64;
+                    }
+                }
+                if max_bad_lines > 0 && bad_lines >= max_bad_lines {
+                    if let Ok(mut locked_lines) = lines.lock() {
+                        locked_lines.push_back(Err(SerialError::InvalidData(bad_lines)));
+                    }
+                    break;
+                }
                 let line = line_buf.trim();
-                let t = start_time.elapsed();
+                let read_at = Instant::now();
+                let t = read_at - start_time;
+                if let Ok(mut timing) = timing.lock() {
+                    timing.bytes += line_buf.len() as u64;
+                }
 
+                if !started {
+                    if let Ok(mut progress) = progress.lock() {
+                        progress.record(line, line_buf.len() + res.is_ok() as usize);
+                        progress.record_partial(port.pending());
+                    }
+                }
                 started |= match start_mode {
                     StartMode::Immediate => true,
                     StartMode::Delay(delay) => t >= delay,
                     StartMode::Message(ref msg) => {
                         let was_started = started;
-                        started |= line.ends_with(msg);
+                        started |= start_match.matches(line, msg, start_regex.as_ref());
                         if !was_started {
                             continue;
                         }
@@ -200,33 +774,108 @@ impl SerialReader {
                 if !started {
                     continue;
                 }
+                if !reported_start {
+                    reported_start = true;
+                    if let Ok(mut progress) = progress.lock() {
+                        progress.started = true;
+                    }
+                }
 
                 match res {
                     Ok(0) => break,
                     Ok(_) => {
+                        let line_t = match port.timestamp() {
+                            Some(t) => {
+                                if let Ok(mut source_t) = source_t.lock() {
+                                    *source_t = Some(t);
+                                }
+                                t
+                            },
+                            None => clock.at(read_at)
+                        };
                         if let Ok(mut locked_lines) = lines.lock() {
                             locked_lines.push_back(Ok(Line {
-                                t: t.as_secs_f64() - start_off,
+                                t: line_t,
                                 content: line.to_owned(),
+                                values: None,
                             }));
                         }
-                    },
-                    Err(e) => {
-                        if let ReadError::Other(e_str) = e {
-                            if let Ok(mut locked_lines) = lines.lock() {
-                                locked_lines.push_back(Err(SerialError::ReadError(e_str)));
-                            }
-                            break;
+                        if let Ok(mut timing) = timing.lock() {
+                            timing.record_line(line_t);
                         }
-                    }
+                    },
+                    Err(_) => {}
                 }
             }
         });
 
         self.worker_thread = Some(handle);
+        self.writer = Some(writer);
+        self.control = Some(control);
+        Ok(())
+    }
+
+    pub fn set_dtr(&mut self, on: bool) -> Result<(), SerialError> {
+        self.send_control(ControlLine::Dtr(on))
+    }
+
+    pub fn set_rts(&mut self, on: bool) -> Result<(), SerialError> {
+        self.send_control(ControlLine::Rts(on))
+    }
+
+    pub fn send_break(&mut self, duration: Duration) -> Result<(), SerialError> {
+        self.send_control(ControlLine::Break(duration))
+    }
+
+    pub fn reset_esp32(&mut self) -> Result<(), SerialError> {
+        ControlLine::ESP32_RESET.iter().try_for_each(|n| self.send_control(*n))
+    }
+
+    fn send_control(&mut self, control: ControlLine) -> Result<(), SerialError> {
+        let (sender, lines) = match (&self.control, &mut self.control_lines) {
+            (Some(sender), Some(lines)) => (sender, lines),
+            (Some(_), None) => return Err(SerialError::NoControlLines),
+            (None, _) => return Err(SerialError::PortNotOpen)
+        };
+        sender.send(control).map_err(|_| SerialError::PortNotOpen)?;
+        match control {
+            ControlLine::Dtr(on) => lines.0 = on,
+            ControlLine::Rts(on) => lines.1 = on,
+            _ => {}
+        }
         Ok(())
     }
 
+    pub fn control_lines(&self) -> Option<(bool, bool)> {
+        self.control.as_ref().and(self.control_lines)
+    }
+
+    pub fn control_error(&self) -> Option<SerialError> {
+        self.control_errors.lock().ok()?.pop_front()
+    }
+
+    pub fn write(&self, data: &[u8]) -> Result<(), SerialError> {
+        match &self.writer {
+            Some(_) if !self.writable => Err(SerialError::ReadOnly),
+            Some(writer) => writer.send(data.to_vec()).map_err(|_| SerialError::PortNotOpen),
+            None => Err(SerialError::PortNotOpen)
+        }
+    }
+
+    pub fn elapsed(&self) -> f64 {
+        if let Some(t) = self.source_t.lock().ok().and_then(|n| *n) {
+            return t;
+        }
+        match self.worker_thread {
+            Some(_) => self.clock.now(),
+            None => 0.0
+        }
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
     fn stop_read(&mut self) {
         self.stop.store(true, Ordering::Relaxed);
         if let Some(handle) = self.worker_thread.take() {
@@ -234,6 +883,26 @@ impl SerialReader {
         }
     }
 
+    pub fn stalled_for(&self) -> Option<Duration> {
+        self.worker_thread.as_ref()
+            .filter(|n| !n.is_finished())
+            .map(|_| self.heartbeat.age())
+    }
+
+    pub fn abandon(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.worker_thread = None;
+        self.writer = None;
+        self.control = None;
+    }
+
+    pub fn watchdog(&mut self, limit: Option<Duration>) -> Option<SerialError> {
+        let limit = limit?;
+        self.stalled_for().filter(|n| *n >= limit)?;
+        self.abandon();
+        Some(SerialError::Unresponsive(limit))
+    }
+
     pub fn get_line(&mut self) -> Option<Result<Line, SerialError>> {
         if let Ok(mut lines) = self.lines.lock() {
             return lines.pop_front();
@@ -241,6 +910,28 @@ impl SerialReader {
         None
     }
 
+    pub fn timing(&self) -> TimingStats {
+        match self.timing.lock() {
+            Ok(timing) => timing.clone(),
+            Err(_) => TimingStats::default()
+        }
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.timing.lock().map_or(0, |n| n.bytes)
+    }
+
+    pub fn line_faults(&self) -> LineFaults {
+        self.timing.lock().map_or(LineFaults::default(), |n| n.faults)
+    }
+
+    pub fn start_progress(&self) -> StartProgress {
+        match self.progress.lock() {
+            Ok(progress) => progress.clone(),
+            Err(_) => StartProgress::default()
+        }
+    }
+
     pub fn is_open(&self) -> bool {
         self.port.is_some() || self.worker_thread.is_some()
     }
@@ -252,25 +943,337 @@ impl Drop for SerialReader {
     }
 }
 
-fn read_line(port: &mut Box<dyn SerialPort>, buf: &mut String) -> Result<usize, ReadError> {
-    let mut buffer = [b'\0'];
-    let mut nread = 0;
-    loop {
-        let read = port.read(&mut buffer).map_err(|e: std::io::Error| match e.kind() {
-            ErrorKind::TimedOut => ReadError::Timeout,
+pub struct StreamLines<P> {
+    port: P,
+    pending: Vec<u8>,
+    terminator: Vec<u8>,
+    overflowed: bool
+}
+
+impl<P: Read + Write + Send + PortControl> StreamLines<P> {
+    const MAX_LINE_BYTES: usize = 4096;
+
+    pub fn new(port: P) -> Self {
+        Self {
+            port,
+            pending: Vec::new(),
+            terminator: b"\n".to_vec(),
+            overflowed: false
+        }
+    }
+
+    fn take_line(&mut self, buf: &mut String) -> Option<Result<usize, ReadError>> {
+        loop {
+            let Some(start) = self.pending.windows(self.terminator.len()).position(|n| n == self.terminator) else {
+                if self.pending.len() > Self::MAX_LINE_BYTES {
+                    self.pending.clear();
+                    self.overflowed = true;
+                    return Some(Err(ReadError::Overflow));
+                }
+                return None;
+            };
+            let line: Vec<u8> = self.pending.drain(..start + self.terminator.len()).collect();
+            if std::mem::take(&mut self.overflowed) {
+                continue;
+            }
+            let text: Vec<u8> = line[..start].iter().copied().filter(|n| *n != b'\r' && *n != b'\n').collect();
+            buf.push_str(&String::from_utf8_lossy(&text));
+            return Some(Ok(line.len()));
+        }
+    }
+}
+
+impl<P: Read + Write + Send + PortControl> LineSource for StreamLines<P> {
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, ReadError> {
+        let mut bytes = [0u8; 256];
+        loop {
+            if let Some(res) = self.take_line(buf) {
+                return res;
+            }
+            let read = match self.read_bytes(&mut bytes)? {
+                0 => return Err(ReadError::EndOfStream),
+                read => read
+            };
+            self.pending.extend_from_slice(&bytes[..read]);
+        }
+    }
+
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, ReadError> {
+        self.port.read(buf).map_err(|e| match e.kind() {
+            ErrorKind::TimedOut | ErrorKind::WouldBlock | ErrorKind::Interrupted => ReadError::Timeout,
             _ => ReadError::Other(e.to_string())
-        })?;
-        if read != 1 {
-            return Err(ReadError::Other(String::from("Unexpected byte amount!")))
+        })
+    }
+
+    fn write_all(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.port.write_all(data)?;
+        self.port.flush()
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn set_terminator(&mut self, terminator: &[u8]) {
+        if !terminator.is_empty() {
+            self.terminator = terminator.to_vec();
         }
-        let c = match char::from_u32(buffer[0] as u32) {
-            Some(c) => c,
-            None => return Err(ReadError::Other(String::from("Byte is not a valid ASCII character!")))
+    }
+
+    fn set_control(&mut self, control: ControlLine) -> std::io::Result<()> {
+        self.port.set_control(control)
+    }
+
+    fn pending(&self) -> &[u8] {
+        &self.pending
+    }
+}
+
+pub fn open_port(config: &SerialConfig) -> Result<Box<dyn SerialPort>, SerialError> {
+    let port = serialport::new(&config.port, config.baud_rate)
+        .data_bits(match config.data_bits {
+            5 => DataBits::Five,
+            6 => DataBits::Six,
+            7 => DataBits::Seven,
+            8 => DataBits::Eight,
+            _ => return Err(SerialError::UnsupportedDataBits(config.data_bits)),
+        })
+        .parity(match config.parity {
+            Parity::Odd => serialport::Parity::Odd,
+            Parity::Even => serialport::Parity::Even,
+            Parity::None => serialport::Parity::None,
+        })
+        .stop_bits(match config.stop_bits {
+            1 => serialport::StopBits::One,
+            2 => serialport::StopBits::Two,
+            _ => return Err(SerialError::UnsupportedStopBits(config.stop_bits)),
+        })
+        .flow_control(match config.flow_ctrl {
+            FlowCtrl::None => serialport::FlowControl::None,
+            FlowCtrl::Software => serialport::FlowControl::Software,
+            FlowCtrl::Hardware => serialport::FlowControl::Hardware
+        })
+        .timeout(config.timeout);
+
+    let mut p = port
+        .open()
+        .map_err(|e| SerialError::OpenError(e.to_string()))?;
+    p.write_data_terminal_ready(config.dtr)
+        .map_err(|_| SerialError::WriteDtrError)?;
+    if config.flow_ctrl != FlowCtrl::Hardware {
+        p.write_request_to_send(config.rts)
+            .map_err(|_| SerialError::WriteRtsError)?;
+    }
+    Ok(p)
+}
+
+fn control_pending(port: &mut Box<dyn LineSource>, queue: &Receiver<ControlLine>, errors: &Mutex<VecDeque<SerialError>>) {
+    while let Ok(control) = queue.try_recv() {
+        if let Err(e) = port.set_control(control) {
+            if let Ok(mut errors) = errors.lock() {
+                errors.push_back(SerialError::ControlError(format!("{}: {}", control, e)));
+            }
+        }
+    }
+}
+
+fn write_pending(port: &mut Box<dyn LineSource>, queue: &Receiver<Vec<u8>>) -> std::io::Result<()> {
+    while let Ok(data) = queue.try_recv() {
+        port.write_all(&data)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: Duration = Duration::from_millis(100);
+
+    struct BlockingSource {
+        entered: Sender<()>
+    }
+
+    impl LineSource for BlockingSource {
+        fn read_line(&mut self, _buf: &mut String) -> Result<usize, ReadError> {
+            let _ = self.entered.send(());
+            loop {
+                thread::park();
+            }
+        }
+    }
+
+    struct ChunkedPort {
+        chunks: VecDeque<&'static [u8]>
+    }
+
+    impl Read for ChunkedPort {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.chunks.pop_front() {
+                Some(chunk) => {
+                    buf[..chunk.len()].copy_from_slice(chunk);
+                    Ok(chunk.len())
+                },
+                None => Err(std::io::Error::from(ErrorKind::TimedOut))
+            }
+        }
+    }
+
+    impl Write for ChunkedPort {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl PortControl for ChunkedPort {}
+
+    struct IdleSource;
+
+    impl LineSource for IdleSource {
+        fn read_line(&mut self, _buf: &mut String) -> Result<usize, ReadError> {
+            thread::sleep(Duration::from_millis(10));
+            Err(ReadError::Timeout)
+        }
+    }
+
+    fn reader(source: Box<dyn LineSource>) -> SerialReader {
+        let config = SerialConfig {
+            port: String::from("mock"),
+            baud_rate: 9600,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: 1,
+            flow_ctrl: FlowCtrl::None,
+            dtr: true,
+            rts: true,
+            timeout: Duration::from_millis(10),
+            terminator: vec![b'\n'],
+            protocol: Protocol::Ascii,
+            max_bad_lines: 20
         };
-        if c == '\n' {
-            return Ok(nread);
+        let mut reader = SerialReader::new(config, CaptureClock::default());
+        reader.port = Some(source);
+        reader.begin_read(StartMode::Immediate, StartMatch::default()).unwrap();
+        reader
+    }
+
+    #[test]
+    fn start_match_trailing_whitespace() {
+        let cases = [
+            (StartMatch::EndsWith, "boot ok  \t", "ok", true),
+            (StartMatch::EndsWith, "boot ok", "  ok \t", true),
+            (StartMatch::Exact, "  READY  ", "READY", true),
+            (StartMatch::Exact, "READY!", "READY", false),
+            (StartMatch::Contains, "x READY y ", " READY ", true),
+            (StartMatch::EndsWith, "ok then", "ok", false),
+            (StartMatch::EndsWith, "anything", "   ", true)
+        ];
+        for (start_match, line, msg, expected) in cases {
+            assert_eq!(start_match.matches(line, msg, None), expected, "{start_match} {line:?} {msg:?}");
+        }
+    }
+
+    #[test]
+    fn start_match_cr_terminated() {
+        let cases = [
+            (StartMatch::EndsWith, "boot ok\r", true),
+            (StartMatch::Exact, "ok\r\n", true),
+            (StartMatch::Exact, "noise\rok", true),
+            (StartMatch::Exact, "ok\rnoise", true),
+            (StartMatch::EndsWith, "\r\r", false),
+            (StartMatch::Contains, "o\rk", false)
+        ];
+        for (start_match, line, expected) in cases {
+            assert_eq!(start_match.matches(line, "ok", None), expected, "{start_match} {line:?}");
+        }
+        let regex = Regex::new("^ok$").unwrap();
+        assert!(StartMatch::Regex.matches("ok\r", "^ok$", Some(&regex)));
+        assert!(!StartMatch::Regex.matches("ok\r", "^ok$", None));
+    }
+
+    #[test]
+    fn start_match_multi_byte() {
+        let msg = "Grüße \u{2713}";
+        assert!(StartMatch::EndsWith.matches("Status: Grüße \u{2713}\r", msg, None));
+        assert!(StartMatch::Exact.matches("Grüße \u{2713}", msg, None));
+        assert!(StartMatch::Contains.matches("\u{1F600}Grüße \u{2713}\u{1F600}", msg, None));
+        assert!(!StartMatch::EndsWith.matches("Grüße \u{2717}", msg, None));
+        assert!(!StartMatch::Exact.matches("Gruße \u{2713}", msg, None));
+        let regex = Regex::new("ü+ß").unwrap();
+        assert!(StartMatch::Regex.matches("Grüüße", "ü+ß", Some(&regex)));
+
+        let mut progress = StartProgress::default();
+        let line = "\u{2713}".repeat(StartProgress::PREVIEW_CHARS + 10);
+        progress.record(&line, line.len());
+        assert_eq!(progress.last_line.chars().count(), StartProgress::PREVIEW_CHARS);
+        assert_eq!(progress.bytes, line.len() as u64);
+    }
+
+    #[test]
+    fn partial_line_survives_timeouts() {
+        let mut port = StreamLines::new(ChunkedPort {
+            chunks: VecDeque::from([b"boot".as_slice()])
+        });
+        let mut progress = StartProgress::default();
+        let mut buf = String::new();
+        for _ in 0..3 {
+            assert!(matches!(port.read_line(&mut buf), Err(ReadError::Timeout)));
+            progress.record(&buf, buf.len());
+            progress.record_partial(port.pending());
         }
-        nread += 1;
-        buf.push(c);
+        assert_eq!(progress.received(), 4);
+        assert_eq!(progress.last_line, "boot");
+
+        port.port.chunks.push_back(b"ing\r\nre");
+        assert!(matches!(port.read_line(&mut buf), Ok(9)));
+        assert_eq!(buf, "booting");
+        progress.record(&buf, 9);
+        progress.record_partial(port.pending());
+        assert_eq!(progress.received(), 11);
+        assert_eq!(progress.last_line, "re");
+    }
+
+    #[test]
+    fn watchdog_abandons_blocked_reader() {
+        let (entered, on_entered) = mpsc::channel();
+        let mut reader = reader(Box::new(BlockingSource { entered }));
+        on_entered.recv_timeout(Duration::from_secs(5)).expect("reader never called read_line");
+
+        assert!(reader.watchdog(Some(LIMIT)).is_none());
+        thread::sleep(LIMIT * 2);
+        assert!(reader.watchdog(None).is_none());
+        assert!(matches!(reader.watchdog(Some(LIMIT)), Some(SerialError::Unresponsive(limit)) if limit == LIMIT));
+        assert!(!reader.is_open());
+        assert!(reader.stalled_for().is_none());
+        assert!(reader.write(b"x").is_err());
+
+        let (dropped, on_dropped) = mpsc::channel();
+        thread::spawn(move || {
+            drop(reader);
+            let _ = dropped.send(());
+        });
+        on_dropped.recv_timeout(Duration::from_secs(5)).expect("dropping the abandoned reader deadlocked");
+    }
+
+    #[test]
+    fn watchdog_ignores_idle_reader() {
+        let mut reader = reader(Box::new(IdleSource));
+        thread::sleep(LIMIT * 2);
+        assert!(reader.watchdog(Some(LIMIT)).is_none());
+        assert!(reader.is_open());
+    }
+
+    #[test]
+    fn loop_latency_covers_blocking_read() {
+        let reader = reader(Box::new(IdleSource));
+        thread::sleep(LIMIT);
+        let timing = reader.timing();
+        assert_eq!(timing.lines, 0);
+        assert!(timing.last_latency >= Duration::from_millis(10), "{:?}", timing.last_latency);
+        assert!(timing.max_latency >= timing.last_latency);
     }
 }
@@ -0,0 +1,69 @@
+use crate::export;
+use crate::format;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub struct PlotSnapshot {
+    pub name: String,
+    pub image: Option<egui::ColorImage>,
+    pub columns: Option<Columns>
+}
+
+pub struct Columns {
+    pub names: Vec<String>,
+    pub values: Vec<Vec<[f64; 2]>>
+}
+
+#[derive(Serialize)]
+struct Metadata<'a> {
+    config_locked: bool,
+    plots: Vec<&'a str>
+}
+
+impl Columns {
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        let names: Vec<&str> = self.names.iter().map(String::as_str).collect();
+        export::write_csv(path, &names, &self.values, None)
+    }
+}
+
+pub fn write(parent: &Path, plots: &[PlotSnapshot], config_locked: bool) -> io::Result<(PathBuf, usize)> {
+    let dir = parent.join(format!("snapshot_{}", format::file_timestamp_utc()));
+    fs::create_dir_all(&dir)?;
+    let metadata = Metadata {
+        config_locked,
+        plots: plots.iter().map(|n| n.name.as_str()).collect()
+    };
+    serde_json::to_writer_pretty(fs::File::create(dir.join("metadata.json"))?, &metadata)?;
+    let mut files = 1;
+    for (i, plot) in plots.iter().enumerate() {
+        let stem = format!("{:02}_{}", i + 1, file_name(&plot.name));
+        if let Some(image) = &plot.image {
+            let path = dir.join(format!("{}.png", stem));
+            image::save_buffer(&path, image.as_raw(), image.width() as u32, image.height() as u32, image::ColorType::Rgba8)
+                .map_err(io::Error::other)?;
+            files += 1;
+        }
+        if let Some(columns) = &plot.columns {
+            columns.write_csv(&dir.join(format!("{}.csv", stem)))?;
+            files += 1;
+        }
+    }
+    Ok((dir, files))
+}
+
+fn file_name(name: &str) -> String {
+    let name: String = name.trim()
+        .chars()
+        .map(|c| match c.is_alphanumeric() || c == '-' {
+            true => c,
+            false => '_'
+        })
+        .collect();
+    match name.is_empty() {
+        true => String::from("plot"),
+        false => name
+    }
+}
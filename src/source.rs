@@ -0,0 +1,378 @@
+use crate::recording::{RecordedLine, ReplaySpeed};
+use crate::serial_reader::{LineSource, PortControl, ReadError, SerialError, StreamLines};
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Seek, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const STDIN_TIMEOUT: Duration = Duration::from_millis(50);
+const DEMO_INTERVAL: Duration = Duration::from_millis(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const MAX_DATAGRAM: usize = 65536;
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Source {
+    Serial,
+    Stdin,
+    Replay {
+        path: PathBuf,
+        looping: bool
+    },
+    Recording {
+        path: PathBuf,
+        speed: ReplaySpeed
+    },
+    Demo
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serial => write!(f, "Serial"),
+            Self::Stdin => write!(f, "stdin"),
+            Self::Replay { path, .. } => write!(f, "{}", path.display()),
+            Self::Recording { path, speed } => write!(f, "{} ({})", path.display(), speed),
+            Self::Demo => write!(f, "demo")
+        }
+    }
+}
+
+impl Source {
+    pub fn open(&self, paused: &Arc<AtomicBool>) -> Result<Box<dyn LineSource>, SerialError> {
+        match self {
+            Self::Serial => Err(SerialError::PortNotOpen),
+            Self::Stdin => Ok(Box::new(StdinSource::new())),
+            Self::Replay { path, looping } => {
+                let file = File::open(path)
+                    .map_err(|e| SerialError::OpenError(format!("{} ({})", path.display(), e)))?;
+                Ok(Box::new(ReplaySource {
+                    reader: BufReader::new(file),
+                    looping: *looping
+                }))
+            },
+            Self::Recording { path, speed } => {
+                let file = File::open(path)
+                    .map_err(|e| SerialError::OpenError(format!("{} ({})", path.display(), e)))?;
+                Ok(Box::new(RecordingSource {
+                    reader: BufReader::new(file),
+                    speed: *speed,
+                    paused: Arc::clone(paused),
+                    start: None,
+                    paused_since: None,
+                    first: None,
+                    next: None,
+                    t: None
+                }))
+            },
+            Self::Demo => Ok(Box::new(DemoSource {
+                start: Instant::now(),
+                sample: 0
+            }))
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum NetworkProtocol {
+    Tcp,
+    Udp
+}
+
+impl Display for NetworkProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tcp => write!(f, "TCP client"),
+            Self::Udp => write!(f, "UDP listen")
+        }
+    }
+}
+
+impl NetworkProtocol {
+    fn scheme(&self) -> &'static str {
+        match self {
+            Self::Tcp => "tcp://",
+            Self::Udp => "udp://"
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct NetworkAddress {
+    pub protocol: NetworkProtocol,
+    pub host: String,
+    pub port: u16
+}
+
+impl Default for NetworkAddress {
+    fn default() -> Self {
+        Self {
+            protocol: NetworkProtocol::Tcp,
+            host: String::from("localhost"),
+            port: 2000
+        }
+    }
+}
+
+impl Display for NetworkAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}:{}", self.protocol.scheme(), self.host, self.port)
+    }
+}
+
+impl NetworkAddress {
+    pub fn parse(port: &str) -> Option<Self> {
+        let (protocol, address) = [NetworkProtocol::Tcp, NetworkProtocol::Udp].into_iter()
+            .find_map(|n| Some((n, port.strip_prefix(n.scheme())?)))?;
+        let (host, port) = address.rsplit_once(':')?;
+        Some(Self {
+            protocol,
+            host: host.to_owned(),
+            port: port.parse().ok()?
+        })
+    }
+
+    pub fn open(&self) -> Result<Box<dyn LineSource>, SerialError> {
+        let open_error = |e: std::io::Error| SerialError::OpenError(format!("{} ({})", self, e));
+        match self.protocol {
+            NetworkProtocol::Tcp => {
+                let address = self.resolve().map_err(open_error)?;
+                let stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT).map_err(open_error)?;
+                stream.set_read_timeout(Some(STDIN_TIMEOUT)).map_err(open_error)?;
+                _ = stream.set_nodelay(true);
+                Ok(Box::new(StreamLines::new(TcpPeer(stream))))
+            },
+            NetworkProtocol::Udp => {
+                let host = match self.host.trim() {
+                    "" => "0.0.0.0",
+                    host => host
+                };
+                let socket = UdpSocket::bind((host, self.port)).map_err(open_error)?;
+                socket.set_read_timeout(Some(STDIN_TIMEOUT)).map_err(open_error)?;
+                Ok(Box::new(StreamLines::new(UdpPeer {
+                    socket,
+                    peer: None,
+                    datagram: Vec::new()
+                })))
+            }
+        }
+    }
+
+    fn resolve(&self) -> std::io::Result<SocketAddr> {
+        (self.host.trim(), self.port).to_socket_addrs()?
+            .next()
+            .ok_or_else(|| std::io::Error::new(ErrorKind::NotFound, "host not found"))
+    }
+}
+
+struct TcpPeer(TcpStream);
+
+impl Read for TcpPeer {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self.0.read(buf)? {
+            0 => Err(std::io::Error::new(ErrorKind::ConnectionAborted, "connection closed by the remote host")),
+            read => Ok(read)
+        }
+    }
+}
+
+impl Write for TcpPeer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl PortControl for TcpPeer {}
+
+struct UdpPeer {
+    socket: UdpSocket,
+    peer: Option<SocketAddr>,
+    datagram: Vec<u8>
+}
+
+impl Read for UdpPeer {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.datagram.is_empty() {
+            let mut datagram = vec![0u8; MAX_DATAGRAM];
+            let (read, peer) = self.socket.recv_from(&mut datagram)?;
+            datagram.truncate(read);
+            self.datagram = datagram;
+            self.peer = Some(peer);
+            if read == 0 {
+                return Err(std::io::Error::new(ErrorKind::WouldBlock, "empty datagram"));
+            }
+        }
+        let read = usize::min(buf.len(), self.datagram.len());
+        buf[..read].copy_from_slice(&self.datagram[..read]);
+        self.datagram.drain(..read);
+        Ok(read)
+    }
+}
+
+impl Write for UdpPeer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.peer {
+            Some(peer) => self.socket.send_to(buf, peer),
+            None => Err(std::io::Error::new(ErrorKind::NotConnected, "nothing received yet, the sender is unknown"))
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl PortControl for UdpPeer {}
+
+struct StdinSource {
+    lines: Receiver<std::io::Result<String>>
+}
+
+impl StdinSource {
+    fn new() -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in std::io::stdin().lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            lines
+        }
+    }
+}
+
+impl LineSource for StdinSource {
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, ReadError> {
+        match self.lines.recv_timeout(STDIN_TIMEOUT) {
+            Ok(Ok(line)) => {
+                buf.push_str(&line);
+                Ok(line.len())
+            },
+            Ok(Err(e)) => Err(ReadError::Other(e.to_string())),
+            Err(RecvTimeoutError::Timeout) => Err(ReadError::Timeout),
+            Err(RecvTimeoutError::Disconnected) => Err(ReadError::EndOfStream)
+        }
+    }
+}
+
+struct ReplaySource {
+    reader: BufReader<File>,
+    looping: bool
+}
+
+impl LineSource for ReplaySource {
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, ReadError> {
+        let read = self.reader.read_line(buf).map_err(|e| ReadError::Other(e.to_string()))?;
+        if read > 0 || !self.looping {
+            return match read {
+                0 => Err(ReadError::EndOfStream),
+                _ => Ok(read)
+            };
+        }
+        self.reader.rewind().map_err(|e| ReadError::Other(e.to_string()))?;
+        match self.reader.read_line(buf).map_err(|e| ReadError::Other(e.to_string()))? {
+            0 => Err(ReadError::EndOfStream),
+            read => Ok(read)
+        }
+    }
+}
+
+struct RecordingSource {
+    reader: BufReader<File>,
+    speed: ReplaySpeed,
+    paused: Arc<AtomicBool>,
+    start: Option<Instant>,
+    paused_since: Option<Instant>,
+    first: Option<f64>,
+    next: Option<RecordedLine>,
+    t: Option<f64>
+}
+
+impl RecordingSource {
+    fn read_record(&mut self) -> Result<RecordedLine, ReadError> {
+        let mut text = String::new();
+        loop {
+            text.clear();
+            if self.reader.read_line(&mut text).map_err(|e| ReadError::Other(e.to_string()))? == 0 {
+                return Err(ReadError::EndOfStream);
+            }
+            if text.trim().is_empty() {
+                continue;
+            }
+            return serde_json::from_str(&text).map_err(|e| ReadError::Other(format!("Invalid recording ({})", e)));
+        }
+    }
+}
+
+impl LineSource for RecordingSource {
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, ReadError> {
+        if self.paused.load(Ordering::Relaxed) {
+            self.paused_since.get_or_insert_with(Instant::now);
+            thread::sleep(STDIN_TIMEOUT);
+            return Err(ReadError::Timeout);
+        }
+        if let (Some(since), Some(start)) = (self.paused_since.take(), &mut self.start) {
+            *start += since.elapsed();
+        }
+        let record = match self.next.take() {
+            Some(record) => record,
+            None => self.read_record()?
+        };
+        let first = *self.first.get_or_insert(record.t);
+        let start = *self.start.get_or_insert_with(Instant::now);
+        if let Some(factor) = self.speed.factor() {
+            let due = Duration::from_secs_f64(((record.t - first) / factor).max(0.0));
+            let elapsed = start.elapsed();
+            if elapsed < due {
+                thread::sleep(Duration::min(due - elapsed, STDIN_TIMEOUT));
+                if start.elapsed() < due {
+                    self.next = Some(record);
+                    return Err(ReadError::Timeout);
+                }
+            }
+        }
+        self.t = Some(record.t - first);
+        buf.push_str(&record.line);
+        Ok(record.line.len() + 1)
+    }
+
+    fn timestamp(&self) -> Option<f64> {
+        self.t
+    }
+}
+
+struct DemoSource {
+    start: Instant,
+    sample: u32
+}
+
+impl LineSource for DemoSource {
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, ReadError> {
+        let due = DEMO_INTERVAL * self.sample;
+        let elapsed = self.start.elapsed();
+        if elapsed < due {
+            thread::sleep(Duration::min(due - elapsed, STDIN_TIMEOUT));
+            if self.start.elapsed() < due {
+                return Err(ReadError::Timeout);
+            }
+        }
+
+        let t = due.as_secs_f64();
+        let phase = std::f64::consts::TAU * t;
+        buf.push_str(&format!("{:.4},{:.4},{:.4}", (phase * 0.5).sin(), (phase * 0.2).cos() * 2.0, t % 4.0 - 2.0));
+        self.sample += 1;
+        Ok(buf.len())
+    }
+}
@@ -0,0 +1,121 @@
+use std::f64::consts::TAU;
+use std::mem::size_of;
+
+const MIN_SAMPLES: usize = 16;
+
+#[derive(Default)]
+pub struct Spectrum {
+    re: Vec<f64>,
+    im: Vec<f64>,
+    window: Vec<f64>,
+    pub points: Vec<[f64; 2]>,
+    pub sample_rate: f64
+}
+
+impl Spectrum {
+    pub fn compute(&mut self, values: &[[f64; 2]], size: usize) -> bool {
+        let n = match values.len() >= size {
+            true => size,
+            false => prev_power_of_two(values.len())
+        };
+        if n < MIN_SAMPLES {
+            return false;
+        }
+        let values = &values[values.len() - n..];
+        let (t0, t1) = (values[0][0], values[n - 1][0]);
+        if t1 <= t0 {
+            return false;
+        }
+        let dt = (t1 - t0) / (n - 1) as f64;
+        self.sample_rate = 1.0 / dt;
+
+        if self.window.len() != n {
+            self.window.clear();
+            self.window.extend((0..n).map(|i| 0.5 - 0.5 * (TAU * i as f64 / (n - 1) as f64).cos()));
+        }
+        self.re.clear();
+        self.im.clear();
+        let mut cursor = 0;
+        for i in 0..n {
+            let t = t0 + dt * i as f64;
+            while cursor + 2 < n && values[cursor + 1][0] < t {
+                cursor += 1;
+            }
+            let (a, b) = (values[cursor], values[cursor + 1]);
+            let value = match b[0] > a[0] {
+                true => a[1] + (b[1] - a[1]) * ((t - a[0]) / (b[0] - a[0])).clamp(0.0, 1.0),
+                false => b[1]
+            };
+            self.re.push(value);
+            self.im.push(0.0);
+        }
+        let mean = self.re.iter().sum::<f64>() / n as f64;
+        for (value, w) in self.re.iter_mut().zip(&self.window) {
+            *value = (*value - mean) * w;
+        }
+        fft(&mut self.re, &mut self.im);
+
+        let gain = 2.0 / self.window.iter().sum::<f64>();
+        self.points.clear();
+        self.points.extend((0..=n / 2).map(|k| {
+            let magnitude = self.re[k].hypot(self.im[k]) * gain;
+            [k as f64 * self.sample_rate / n as f64, magnitude]
+        }));
+        true
+    }
+
+    pub fn estimate(&self) -> usize {
+        size_of::<Spectrum>()
+            + (self.re.capacity() + self.im.capacity() + self.window.capacity()) * size_of::<f64>()
+            + self.points.capacity() * size_of::<[f64; 2]>()
+    }
+
+    pub fn release(&mut self) {
+        self.re = Vec::new();
+        self.im = Vec::new();
+        self.window = Vec::new();
+        self.points.shrink_to_fit();
+    }
+}
+
+fn prev_power_of_two(n: usize) -> usize {
+    match n {
+        0 => 0,
+        n => 1 << n.ilog2()
+    }
+}
+
+fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let angle = -TAU / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let tr = re[b] * cos - im[b] * sin;
+                let ti = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::VecDeque;
+
+#[derive(Default)]
+pub struct WindowStats {
+    samples: VecDeque<[f64; 2]>,
+    min: VecDeque<[f64; 2]>,
+    max: VecDeque<[f64; 2]>,
+    sum: f64
+}
+
+impl WindowStats {
+    pub fn push(&mut self, t: f64, value: f64, window: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.samples.push_back([t, value]);
+        self.sum += value;
+        while self.min.back().is_some_and(|n| n[1] >= value) {
+            self.min.pop_back();
+        }
+        self.min.push_back([t, value]);
+        while self.max.back().is_some_and(|n| n[1] <= value) {
+            self.max.pop_back();
+        }
+        self.max.push_back([t, value]);
+
+        while let Some(first) = self.samples.front().copied().filter(|n| t - n[0] > window) {
+            self.samples.pop_front();
+            self.sum -= first[1];
+        }
+        while self.min.front().is_some_and(|n| t - n[0] > window) {
+            self.min.pop_front();
+        }
+        while self.max.front().is_some_and(|n| t - n[0] > window) {
+            self.max.pop_front();
+        }
+        if self.samples.len() == 1 {
+            self.sum = value;
+        }
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min.front().map(|n| n[1])
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max.front().map(|n| n[1])
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        match self.samples.len() {
+            0 => None,
+            n => Some(self.sum / n as f64)
+        }
+    }
+
+    pub fn rate(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(first), Some(last)) if last[0] > first[0] => (self.samples.len() - 1) as f64 / (last[0] - first[0]),
+            _ => 0.0
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.min.clear();
+        self.max.clear();
+        self.sum = 0.0;
+    }
+}
@@ -1,22 +1,52 @@
 use crate::app::SerialMonitorApp;
-use crate::data::{InputSlot, PlotConfig, PlotData, PlotMode, PlotScaleMode};
-use crate::serial_reader::{FlowCtrl, Parity, StartMode};
+use crate::command::{self, Command};
+use crate::data::{Anomaly, Calibration, Checksum, ColorMap, ConnectionConfig, ConsoleLine, Deadband, DecimalSeparator, Decimation, DecimationMode, Delimiter, DetachedWindow, FrameConfig, FrameMode, InputSlot, LineEnding, LineTerminator, LogFormat, LogRotation, LossConfig, PlotAxis, PlotConfig, PlotData, PlotKind, PlotMode, PlotScaleMode, RefLine, SendPreset, Sequence, SequenceStep, SerialMonitorData, Shading, ShadingColor, Smoothing, TableConfig, Theme, ThresholdAlarm, TimeFormat, TimestampUnit, UiConfig, ValueFormat, XyConfig};
+use crate::diagnostics::DiagnosticMode;
+use crate::format;
+use crate::help;
+use crate::importers::ImportSummary;
+use crate::memory;
+use crate::plot_engine;
+use crate::profiles;
+use crate::recording::{self, ReplaySpeed};
+use crate::serial_parser::{ParsePreview, SerialParser};
+use crate::snapshot::{self, Columns, PlotSnapshot};
+use crate::spectrum::Spectrum;
+use crate::serial_reader::{ControlLine, FieldType, FlowCtrl, Parity, Protocol, StartMatch, StartMode, TimingStats};
+use crate::source::{NetworkAddress, NetworkProtocol};
 use eframe::egui;
 use egui::emath::Numeric;
 use egui::{Align, Align2, Color32, Context, Id, Layout, Ui};
-use egui_plot::{Corner, Legend, Line, PlotBounds, PlotMemory, PlotPoints, VLine};
+use egui_plot::{Bar, BarChart, Corner, HLine, Legend, Line, LineStyle, MarkerShape, PlotBounds, PlotMemory, PlotPoints, Points, VLine};
 use egui::ecolor::linear_u8_from_linear_f32;
+use std::borrow::Cow;
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeSet, HashMap};
 use std::fmt::Display;
-use std::iter::zip;
 use std::ops::RangeInclusive;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const SIDEPANEL_WIDTH: f32 = 225.0;
 const DROPDOWN_WIDTH: f32 = 150.0;
 const STATUS_RADIUS: f32 = 6.0;
 const PLOT_MARGIN: f32 = 5.0;
+const TABLE_COLUMN_WIDTH: f32 = 90.0;
+const MARKER_DURATION: Duration = Duration::from_secs(10);
+const PRINT_LINE_WIDTH: f32 = 2.5;
+const PRINT_MIN_SATURATION: f32 = 0.75;
+const PRINT_MAX_VALUE: f32 = 0.45;
+const PREVIEW_LINES: usize = 10;
+const SHADING_ALPHA: f32 = 0.25;
+const ENVELOPE_ALPHA: f32 = 0.2;
+const RAW_ALPHA: f32 = 0.35;
+const ANOMALY_RADIUS: f32 = 3.5;
+const FLASH_DURATION: Duration = Duration::from_millis(1500);
+const MARKER_HOVER_DISTANCE: f32 = 4.0;
+const CURSOR_GRAB_DISTANCE: f32 = 5.0;
+const CURSOR_NAMES: [&str; 2] = ["A", "B"];
 
 const BAUD_RATES: &[u32] = &[
     300, 600, 750, 1200, 2400, 4800, 9600, 19200, 31250, 38400, 57600, 74880, 115200, 230400,
@@ -31,12 +61,74 @@ const START_MODES: &[StartMode] = &[
     StartMode::Delay(Duration::ZERO),
     StartMode::Message(String::new()),
 ];
+const START_MATCHES: &[StartMatch] = &[StartMatch::EndsWith, StartMatch::Contains, StartMatch::Exact, StartMatch::Regex];
+const LINE_ENDINGS: &[LineEnding] = &[LineEnding::None, LineEnding::Lf, LineEnding::CrLf];
+const CHECKSUMS: &[Checksum] = &[Checksum::None, Checksum::NmeaXor, Checksum::Crc8, Checksum::Crc16, Checksum::LastColumnSum];
+const TERMINATORS: &[LineTerminator] = &[LineTerminator::Lf, LineTerminator::CrLf, LineTerminator::Cr, LineTerminator::Custom];
+const DELIMITERS: &[Delimiter] = &[Delimiter::Comma, Delimiter::Semicolon, Delimiter::Tab, Delimiter::Space, Delimiter::Custom];
+const DECIMAL_SEPARATORS: &[DecimalSeparator] = &[DecimalSeparator::Point, DecimalSeparator::Comma];
+const NETWORK_PROTOCOLS: &[NetworkProtocol] = &[NetworkProtocol::Tcp, NetworkProtocol::Udp];
+const NETWORK_ENTRY: &str = "Network\u{2026}";
+const RECORDING_ENTRY: &str = "Open recording\u{2026}";
+const REPLAY_SPEEDS: &[ReplaySpeed] = &[ReplaySpeed::Half, ReplaySpeed::Normal, ReplaySpeed::Ten, ReplaySpeed::Instant];
+const TIMESTAMP_UNITS: &[TimestampUnit] = &[TimestampUnit::Milliseconds, TimestampUnit::Microseconds, TimestampUnit::Seconds];
+const FIELD_TYPES: &[FieldType] = &[
+    FieldType::U8,
+    FieldType::I8,
+    FieldType::U16,
+    FieldType::I16,
+    FieldType::U32,
+    FieldType::I32,
+    FieldType::F32,
+    FieldType::F64
+];
 const PLOT_MODES: &[PlotMode] = &[PlotMode::Continous, PlotMode::Cyclic];
+const DECIMATION_MODES: &[DecimationMode] = &[DecimationMode::Every, DecimationMode::Average];
 const SCALE_MODES: &[PlotScaleMode] = &[PlotScaleMode::Auto, PlotScaleMode::AutoMax, PlotScaleMode::Manual];
+const FRAME_MODES: &[FrameMode] = &[FrameMode::Line, FrameMode::Marker, FrameMode::LineCount];
+const LOG_FORMATS: &[LogFormat] = &[LogFormat::Raw, LogFormat::Csv];
+const LOG_ROTATIONS: &[LogRotation] = &[LogRotation::Never, LogRotation::Duration, LogRotation::Size];
+const VALUE_FORMATS: &[ValueFormat] = &[ValueFormat::Decimals, ValueFormat::Significant, ValueFormat::Engineering];
+const THEMES: &[Theme] = &[Theme::Dark, Theme::Light, Theme::System];
+const TIME_FORMATS: &[TimeFormat] = &[TimeFormat::Auto, TimeFormat::Seconds, TimeFormat::Milliseconds, TimeFormat::MinSec];
 
+const SHADING_COLORS: &[[f32; 3]] = &[
+    [0.25, 0.25, 0.25],
+    [0.9, 0.35, 0.1],
+    [0.1, 0.4, 0.9],
+    [0.15, 0.7, 0.2],
+    [0.8, 0.7, 0.1],
+    [0.6, 0.2, 0.8]
+];
+const SHORTCUTS: &[(egui::KeyboardShortcut, Shortcut)] = &[
+    (egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::F1), Shortcut::Help),
+    (egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::F2), Shortcut::Mark),
+    (egui::KeyboardShortcut::new(egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT), egui::Key::P), Shortcut::Palette),
+    (egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::Escape), Shortcut::Close)
+];
 const INFO_COLOR: Color32 = Color32::from_rgb(118, 184, 31);
 const WARNING_COLOR: Color32 = Color32::from_rgb(184, 138, 31);
 const ERROR_COLOR: Color32 = Color32::from_rgb(184, 54, 31);
+const MARKER_COLOR: Color32 = Color32::from_rgb(80, 160, 230);
+
+#[derive(Clone, Copy)]
+enum Shortcut {
+    Help,
+    Mark,
+    Palette,
+    Close
+}
+
+impl Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Help => write!(f, "Show or hide this help"),
+            Self::Mark => write!(f, "Add a marker with the label from the toolbar"),
+            Self::Palette => write!(f, "Open the command palette"),
+            Self::Close => write!(f, "Close the help, command palette, menus and renaming")
+        }
+    }
+}
 
 pub enum NotificationType {
     Info,
@@ -55,41 +147,411 @@ impl Notification {
     pub fn new(text: &str, duration: Duration, ntype: NotificationType) -> Self {
         Self {
             start: Instant::now(),
-            duration: duration,
+            duration,
             text: text.to_owned(),
-            ntype: ntype
+            ntype
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Compare {
+    a: usize,
+    b: usize,
+    autoscale: bool
+}
+
+struct CompareValues {
+    name: String,
+    values: Vec<[f64; 2]>,
+    autoscale: bool,
+    current: f64,
+    rms: f64,
+    max_abs: f64
+}
+
+#[derive(Default)]
+enum ConsoleMatcher {
+    #[default]
+    All,
+    Substring(String),
+    Regex(regex::Regex),
+    Invalid
+}
+
+#[derive(Default)]
+struct ConsoleFilter {
+    text: String,
+    regex: bool,
+    case_sensitive: bool,
+    matcher: ConsoleMatcher,
+    compiled: Option<(String, bool, bool)>,
+    scrolled_up: bool,
+    jump: bool
+}
+
+impl ConsoleFilter {
+    fn sync(&mut self) {
+        let key = (self.text.clone(), self.regex, self.case_sensitive);
+        if self.compiled.as_ref() == Some(&key) {
+            return;
+        }
+        self.matcher = match (self.regex, self.text.is_empty()) {
+            (_, true) => ConsoleMatcher::All,
+            (true, false) => match regex::RegexBuilder::new(&self.text).case_insensitive(!self.case_sensitive).build() {
+                Ok(regex) => ConsoleMatcher::Regex(regex),
+                Err(_) => ConsoleMatcher::Invalid
+            },
+            (false, false) => match self.case_sensitive {
+                true => ConsoleMatcher::Substring(self.text.clone()),
+                false => ConsoleMatcher::Substring(self.text.to_lowercase())
+            }
+        };
+        self.compiled = Some(key);
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        match &self.matcher {
+            ConsoleMatcher::Substring(pattern) => match self.case_sensitive {
+                true => line.contains(pattern.as_str()),
+                false => line.to_lowercase().contains(pattern.as_str())
+            },
+            ConsoleMatcher::Regex(regex) => regex.is_match(line),
+            ConsoleMatcher::All | ConsoleMatcher::Invalid => true
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        matches!(self.matcher, ConsoleMatcher::Substring(_) | ConsoleMatcher::Regex(_))
+    }
+}
+
+struct ConsoleFocus {
+    t: f64,
+    content: Option<String>,
+    start: Instant,
+    scrolled: bool
+}
+
+#[derive(Default)]
+struct Cursors {
+    t: [Option<f64>; 2],
+    dragging: Option<usize>
+}
+
+struct WatchRange {
+    t: f64,
+    min: f64,
+    max: f64
+}
+
+struct BulkEdit {
+    recolor: bool,
+    from: [f32; 3],
+    to: [f32; 3],
+    rescale: bool,
+    scale: f64,
+    offset: f64,
+    prefix: String,
+    suffix: String,
+    set_unit: bool,
+    unit: String,
+    set_precision: bool,
+    precision: u8,
+    hide: Option<bool>,
+    reset: bool
+}
+
+impl Default for BulkEdit {
+    fn default() -> Self {
+        Self {
+            recolor: false,
+            from: [0.9, 0.35, 0.1],
+            to: [0.1, 0.4, 0.9],
+            rescale: false,
+            scale: 1.0,
+            offset: 0.0,
+            prefix: String::new(),
+            suffix: String::new(),
+            set_unit: false,
+            unit: String::new(),
+            set_precision: false,
+            precision: InputSlot::default_precision(),
+            hide: None,
+            reset: false
+        }
+    }
+}
+
+#[derive(Default)]
+struct Palette {
+    query: String,
+    selected: usize
+}
+
+enum SlotChange {
+    Column(usize, usize),
+    Remove(usize)
+}
+
+struct SlotUndo {
+    slots: Vec<(usize, InputSlot)>,
+    hidden: Vec<(usize, String, bool)>
+}
+
+struct CalibrationDialog {
+    slot: usize,
+    raw: [Option<f64>; 2],
+    reference: [f64; 2],
+    average: bool,
+    seconds: f64,
+    capturing: Option<(usize, f64)>
+}
+
+#[derive(Clone, Copy)]
+pub enum PendingDialog {
+    SaveConfig,
+    LoadConfig,
+    ImportSettings,
+    ImportProfile,
+    ExportCsv,
+    ExportVisible(usize),
+    StartRecording,
+    OpenRecording,
+    SnapshotFolder(bool),
+    LogFolder
+}
+
+impl PendingDialog {
+    pub fn pick(&self) -> Option<PathBuf> {
+        let dialog = rfd::FileDialog::new();
+        match self {
+            Self::SaveConfig => dialog.add_filter("JSON", &["json"]).save_file(),
+            Self::LoadConfig | Self::ImportProfile => dialog.add_filter("JSON", &["json"]).pick_file(),
+            Self::ImportSettings => dialog
+                .add_filter("SerialPlot settings", &["ini", "plt"])
+                .add_filter("All files", &["*"])
+                .pick_file(),
+            Self::ExportCsv | Self::ExportVisible(_) => dialog.add_filter("CSV", &["csv"]).save_file(),
+            Self::StartRecording => dialog
+                .add_filter("Recording", &[recording::EXTENSION])
+                .set_file_name(format!("session.{}", recording::EXTENSION))
+                .save_file(),
+            Self::OpenRecording => dialog.add_filter("Recording", &[recording::EXTENSION]).pick_file(),
+            Self::SnapshotFolder(_) | Self::LogFolder => dialog.pick_folder()
         }
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+struct SnapshotCapture {
+    dir: PathBuf,
+    requested: bool,
+    rects: HashMap<usize, egui::Rect>
+}
+
+enum ProfileDialog {
+    Save(String),
+    Rename(String, String),
+    Delete(String)
+}
+
+struct PlotCapture {
+    plot_id: usize,
+    rect: Option<egui::Rect>,
+    visible: bool,
+    requested: bool,
+    print: bool
+}
+
+#[derive(Clone, Copy)]
+enum RowButton {
+    Preset(usize),
+    Sequence(usize)
+}
+
+#[derive(PartialEq, Clone, Debug)]
 enum PlotResponse {
     None,
     Reset,
-    Remove
+    Remove,
+    MoveUp,
+    MoveDown,
+    Maximize,
+    Detach,
+    Rename(String),
+    Axis(PlotAxis),
+    Watch(Vec<usize>, bool),
+    Slots(Option<Vec<usize>>),
+    Shading(Option<Shading>),
+    FftSize(usize),
+    Send(String),
+    Preset(usize),
+    Sequence(usize),
+    Stacking(bool, f64),
+    Smoothing(Option<Smoothing>),
+    Kind(PlotKind),
+    Xy(XyConfig),
+    Table(TableConfig),
+    Override(Option<PlotConfig>),
+    References(Vec<RefLine>, bool),
+    Promote(usize, usize)
 }
 
 pub struct SerialMonitorUI {
+    dialog: Option<PendingDialog>,
     notification: Option<Notification>,
     minor_notification: Option<Notification>,
-    plot_ranges: HashMap<usize, [f64; 2]>,
+    plot_ranges: HashMap<usize, HashMap<usize, [f64; 2]>>,
+    compares: HashMap<usize, Compare>,
+    watch_ranges: HashMap<(usize, usize), WatchRange>,
+    spectra: HashMap<(usize, usize), Spectrum>,
+    cursors: HashMap<usize, Cursors>,
+    context_time: Option<f64>,
+    console_focus: Option<ConsoleFocus>,
+    console_filters: HashMap<usize, ConsoleFilter>,
+    marker: Option<(f64, Instant)>,
+    timing_open: bool,
+    sequences_open: bool,
+    presets_open: bool,
+    confirm_preset: Option<usize>,
+    slots_open: bool,
+    slot_remap: bool,
+    confirm_slot_removal: Option<Vec<usize>>,
+    diagnostics_open: bool,
+    rejected_open: bool,
+    custom_bauds: Vec<u32>,
+    entry_text: Option<(&'static str, String)>,
+    profile_names: Option<Vec<String>>,
+    profile_dialog: Option<ProfileDialog>,
+    help_open: bool,
+    palette: Option<Palette>,
+    focused_plot: Option<usize>,
+    plot_command: Option<(usize, PlotResponse)>,
+    maximized: Option<usize>,
+    renaming: Option<(usize, String)>,
+    slot_focus: Option<usize>,
+    capture: Option<PlotCapture>,
+    snapshot: Option<SnapshotCapture>,
+    sync_anchors: HashMap<usize, f64>,
+    sync_bytes: Option<String>,
+    locked: bool,
+    send_text: String,
+    marker_text: String,
+    calibration: Option<CalibrationDialog>,
+    selected_slots: BTreeSet<usize>,
+    select_anchor: Option<usize>,
+    bulk_edit: Option<BulkEdit>,
+    import_summary: Option<ImportSummary>,
+    slot_undo: Option<SlotUndo>,
+    preview_custom: bool,
+    preview_text: String,
+    clipboard: Option<arboard::Clipboard>,
     ctx: Option<Context>
 }
 
 impl SerialMonitorUI {
     pub fn new(_context: &eframe::CreationContext<'_>) -> Self {
+        let first_start = !help::was_shown();
+        if first_start {
+            help::mark_shown();
+        }
         Self {
             notification: None,
             minor_notification: None,
             plot_ranges: HashMap::new(),
+            compares: HashMap::new(),
+            watch_ranges: HashMap::new(),
+            spectra: HashMap::new(),
+            cursors: HashMap::new(),
+            context_time: None,
+            console_focus: None,
+            console_filters: HashMap::new(),
+            marker: None,
+            timing_open: false,
+            sequences_open: false,
+            presets_open: false,
+            confirm_preset: None,
+            slots_open: false,
+            slot_remap: false,
+            confirm_slot_removal: None,
+            diagnostics_open: false,
+            rejected_open: false,
+            custom_bauds: Vec::new(),
+            entry_text: None,
+            profile_names: None,
+            profile_dialog: None,
+            help_open: first_start,
+            palette: None,
+            focused_plot: None,
+            plot_command: None,
+            maximized: None,
+            renaming: None,
+            slot_focus: None,
+            capture: None,
+            snapshot: None,
+            dialog: None,
+            sync_anchors: HashMap::new(),
+            sync_bytes: None,
+            locked: false,
+            send_text: String::new(),
+            marker_text: String::new(),
+            calibration: None,
+            selected_slots: BTreeSet::new(),
+            select_anchor: None,
+            bulk_edit: None,
+            import_summary: None,
+            slot_undo: None,
+            preview_custom: false,
+            preview_text: String::new(),
+            clipboard: None,
             ctx: None
         }
     }
 
-    pub fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame, app: &mut SerialMonitorApp) {
+    pub fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame, app: &mut SerialMonitorApp) {
         self.ctx = Some(ctx.clone());
+        self.apply_theme(ctx, frame, app.ui_config().theme);
+        self.locked = app.is_locked();
+        if self.marker.is_some_and(|(_, start)| start.elapsed() > MARKER_DURATION) {
+            self.marker = None;
+        }
+        if app.take_view_reset() {
+            self.plot_ranges.clear();
+            self.watch_ranges.clear();
+        }
+        if app.take_samples_cleared() {
+            self.cursors.clear();
+        }
+        if !self.capture.as_ref().is_some_and(|n| app.plots().iter().any(|p| p.id == n.plot_id && p.detached.is_some())) {
+            self.capture_plot(ctx);
+        }
+        self.take_snapshot(ctx, app);
+        self.shortcuts(ctx, app);
+        self.status_bar(ctx, app);
+        self.alarm_banner(ctx, app);
         self.config_panel(ctx, app);
         self.data_panel(ctx, app);
+        self.detached_plots(ctx, app);
+        self.spectra.retain(|key, _| app.plots().iter().any(|n| n.id == key.0 && n.kind == PlotKind::Spectrum));
+        app.track_spectra(self.spectra.values_mut());
+        self.timing_window(ctx, app);
+        self.sequences_window(ctx, app);
+        self.presets_window(ctx, app);
+        self.confirm_preset_window(ctx, app);
+        self.slots_window(ctx, app);
+        self.confirm_slot_window(ctx, app);
+        self.profile_window(ctx, app);
+        self.diagnostics_window(ctx, app);
+        self.rejected_window(ctx, app);
+        self.restore_window(ctx, app);
+        self.recovery_window(ctx, app);
+        self.frame_suggestion_window(ctx, app);
+        self.calibration_window(ctx, app);
+        self.bulk_edit_window(ctx, app);
+        self.import_window(ctx);
+        self.dropped_files(ctx, app);
+        self.help_window(ctx);
+        self.command_palette(ctx, app);
         self.notification(ctx);
         self.minor_notification(ctx);
         self.ctx = None;
@@ -102,6 +564,85 @@ impl SerialMonitorUI {
             });
         }
         self.plot_ranges.clear();
+        self.compares.clear();
+        self.watch_ranges.clear();
+        self.spectra.clear();
+        self.console_focus = None;
+        self.marker = None;
+        self.sync_anchors.clear();
+        self.maximized = None;
+        self.renaming = None;
+        self.profile_names = None;
+        self.profile_dialog = None;
+        self.focused_plot = None;
+        self.plot_command = None;
+        self.calibration = None;
+        self.selected_slots.clear();
+        self.select_anchor = None;
+        self.bulk_edit = None;
+        self.slot_undo = None;
+    }
+
+    pub fn take_dialog(&mut self) -> Option<PendingDialog> {
+        self.dialog.take()
+    }
+
+    pub fn finish_dialog(&mut self, dialog: PendingDialog, path: Option<PathBuf>, app: &mut SerialMonitorApp) {
+        let Some(path) = path else {
+            return;
+        };
+        let name = path.display().to_string();
+        let result = match dialog {
+            PendingDialog::SaveConfig => app.save_config(&path)
+                .map(|_| Some(format!("Saved config ({})", name)))
+                .map_err(|e| format!("Could not save config ({})", e)),
+            PendingDialog::LoadConfig => app.load_config_file(&path, self)
+                .map(|_| Some(String::from("Loaded config")))
+                .map_err(|e| format!("Could not load config ({})", e)),
+            PendingDialog::ImportSettings => app.import_settings(&path, self)
+                .map(|summary| {
+                    self.import_summary = Some(summary);
+                    None
+                })
+                .map_err(|e| format!("Could not import settings ({})", e)),
+            PendingDialog::ImportProfile => {
+                self.profile_names = None;
+                app.import_profile(&path)
+                    .map(|profile| Some(format!("Imported profile {}", profile)))
+                    .map_err(|e| format!("Could not import profile ({})", e))
+            },
+            PendingDialog::ExportCsv => app.export_csv(&path)
+                .map(|_| Some(format!("Exported samples ({})", name)))
+                .map_err(|e| format!("Could not export samples ({})", e)),
+            PendingDialog::ExportVisible(id) => match app.plots().iter().find(|n| n.id == id).and_then(|plot| snapshot_columns(plot, app)) {
+                Some(columns) => columns.write_csv(&path)
+                    .map(|_| Some(format!("Exported visible samples ({})", name)))
+                    .map_err(|e| format!("Could not export samples ({})", e)),
+                None => Err(String::from("Could not export samples (plot was removed)"))
+            },
+            PendingDialog::StartRecording => app.start_recording(&path)
+                .map(|_| Some(format!("Recording to {}", name)))
+                .map_err(|e| format!("Could not start recording ({})", e)),
+            PendingDialog::OpenRecording => app.open_recording(path)
+                .map(|_| None)
+                .map_err(|e| format!("Could not open recording ({})", e)),
+            PendingDialog::SnapshotFolder(start) => {
+                *app.snapshot_dir_mut() = name;
+                if start {
+                    self.start_snapshot(app);
+                }
+                Ok(None)
+            },
+            PendingDialog::LogFolder => {
+                app.log_config_mut().directory = name;
+                Ok(None)
+            }
+        };
+        match result {
+            Ok(Some(msg)) => self.set_notification(Notification::new(&msg, Duration::from_secs(5), NotificationType::Info), false),
+            Ok(None) => {},
+            Err(msg) => self.set_notification(Notification::new(&msg, Duration::from_secs(5), NotificationType::Error), false)
+        }
     }
 
     pub fn set_notification(&mut self, notification: Notification, minor: bool) {
@@ -111,430 +652,4913 @@ impl SerialMonitorUI {
         }
     }
 
-    fn config_panel(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
-        egui::SidePanel::left("ConnPanel")
-            .exact_width(SIDEPANEL_WIDTH)
-            .min_width(SIDEPANEL_WIDTH)
-            .max_width(SIDEPANEL_WIDTH)
-            .resizable(false)
-            .show(ctx, |ui| {
-                self.conn_panel(ctx, ui, app);
-                self.plot_panel(ctx, ui, app);
-                self.input_panel(ctx, ui, app);
-            });
-    }
-
-    fn conn_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
-        ui.add_space(5.0);
-        let frame = egui::Frame::window(&ctx.style())
-            .rounding(2.0);
-        frame.show(ui, |ui| {
-            ui.horizontal(|ui| {
-                let pos = egui::pos2(
-                    ui.next_widget_position().x + STATUS_RADIUS * 1.25,
-                    ui.available_height() + STATUS_RADIUS * 0.85,
-                );
-                let col = match app.is_connected() {
-                    true => egui::Color32::DARK_GREEN,
-                    false => egui::Color32::DARK_RED
-                };
-                ui.painter().circle_filled(pos, STATUS_RADIUS, col);
-                ui.add_space(STATUS_RADIUS * 3.0);
-                ui.heading("Connection");
-                ui.add_space(10.0);
-                let connect_btn_text = match app.is_connected() {
-                    true => "Disconnect",
-                    false => "Connect"
-                };
-                let connect_resp = ui.add_enabled(
-                    app.can_connect(),
-                    egui::Button::new(connect_btn_text).min_size(egui::Vec2::new(86.0, 0.0)));
-                if connect_resp.clicked() {
-                    if !app.is_connected() {
-                        if let Err(e) = app.connect_current() {
-                            self.set_notification(Notification::new(
-                                format!("Could not connect! ({})", e.to_string()).as_str(),
-                                Duration::from_secs(5),
-                                NotificationType::Error
-                            ), false);
-                        }
-                    } else {
-                        app.disconnect_current();
-                    }
-                }
-            });
-            ui.separator();
-
-            ui.set_enabled(!app.is_connected());
-            let devices = app.available_devices();
-            let config = app.conn_config();
-            option_dropdown(ui, "Device", devices.as_slice(), &mut config.port, 20.0);
-            option_dropdown(ui, "Baud", BAUD_RATES, &mut config.baud_rate, 27.0);
-            ui.separator();
+    fn capture_plot(&mut self, ctx: &egui::Context) {
+        let Some(capture) = &mut self.capture else {
+            return;
+        };
+        if !capture.requested {
+            capture.requested = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            return;
+        }
+        let screenshot = ctx.input(|i| i.events.iter().find_map(|n| match n {
+            egui::Event::Screenshot { image, .. } => Some(image.clone()),
+            _ => None
+        }));
+        let Some(screenshot) = screenshot else {
+            ctx.request_repaint();
+            return;
+        };
+        let Some(capture) = self.capture.take() else {
+            return;
+        };
+        let Some(rect) = capture.rect else {
+            return;
+        };
+        if !capture.visible {
+            self.set_notification(Notification::new("Could not copy plot image (scroll the plot fully into view and try again)", Duration::from_secs(5), NotificationType::Warning), true);
+            return;
+        }
 
-            option_dropdown(ui, "Data bits", DATA_BITS, &mut config.data_bits, 8.0);
-            option_dropdown(ui, "Parity", PARITIES, &mut config.parity, 24.5);
-            option_dropdown(ui, "Stop bits", STOP_BITS, &mut config.stop_bits, 8.0);
-            option_dropdown(ui, "Flow ctrl", FLOW_CTRLS, &mut config.flow_ctrl, 10.0);
-            ui.separator();
+        let image = screenshot.region(&rect, Some(ctx.pixels_per_point()));
+        let notification = match self.copy_image(&image) {
+            Ok(()) => Notification::new(&format!("Copied plot image ({}x{})", image.width(), image.height()), Duration::from_secs(3), NotificationType::Info),
+            Err(e) => match save_temp_image(&image) {
+                Ok(path) => {
+                    ctx.output_mut(|o| o.copied_text = path.clone());
+                    Notification::new(&format!("Image clipboard unavailable ({}), copied path instead: {}", e, path), Duration::from_secs(5), NotificationType::Warning)
+                },
+                Err(e) => Notification::new(&format!("Could not copy plot image ({})", e), Duration::from_secs(5), NotificationType::Error)
+            }
+        };
+        self.set_notification(notification, true);
+    }
 
-            option_dropdown(ui, "DTR", &[false, true], &mut config.dtr, 33.0);
-            option_dropdown(ui, "Start mode", START_MODES, &mut config.start_mode, -6.0);
-            if matches!(config.start_mode, StartMode::Delay(_)) {
-                drag_value(ui, "Delay (ms)", &mut config.start_delay, 0.0, 0..=100000, 0, "ms");
-            } else if matches!(config.start_mode, StartMode::Message(_)) {
-                ui.horizontal(|ui| {
-                    ui.label("Message");
-                    ui.add_space(7.0);
-                    ui.add(egui::TextEdit::singleline(&mut config.start_msg).desired_width(DROPDOWN_WIDTH - ui.style().spacing.item_spacing.x));
-                });
+    fn plot_rect(&mut self, ui: &Ui, plot_id: usize, rect: egui::Rect) {
+        if let Some(capture) = self.capture.as_mut().filter(|n| n.plot_id == plot_id) {
+            capture.rect = Some(rect);
+            capture.visible = ui.clip_rect().contains_rect(rect) && ui.ctx().screen_rect().contains_rect(rect);
+        }
+        if let Some(snapshot) = self.snapshot.as_mut().filter(|_| ui.ctx().viewport_id() == egui::ViewportId::ROOT) {
+            let visible = rect.intersect(ui.clip_rect());
+            if visible.is_positive() {
+                snapshot.rects.insert(plot_id, visible);
             }
+        }
+    }
+
+    fn start_snapshot(&mut self, app: &mut SerialMonitorApp) {
+        let dir = app.snapshot_dir_mut();
+        if dir.is_empty() || !Path::new(dir.as_str()).is_dir() {
+            self.dialog = Some(PendingDialog::SnapshotFolder(true));
+            return;
+        }
+        self.snapshot = Some(SnapshotCapture {
+            dir: PathBuf::from(dir.as_str()),
+            requested: false,
+            rects: HashMap::new()
         });
     }
 
-    fn plot_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
-        ui.add_space(5.0);
-        let frame = egui::Frame::window(&ctx.style())
-            .rounding(2.0);
-        frame.show(ui, |ui| {
-            ui.horizontal(|ui| {
-                ui.heading("Plot Settings");
-                ui.add_space(ui.available_width());
-            });
-            ui.separator();
+    fn take_snapshot(&mut self, ctx: &egui::Context, app: &SerialMonitorApp) {
+        let Some(snapshot) = &mut self.snapshot else {
+            return;
+        };
+        if !snapshot.requested {
+            snapshot.requested = true;
+            ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+            return;
+        }
+        let screenshot = ctx.input(|i| i.events.iter().find_map(|n| match n {
+            egui::Event::Screenshot { image, .. } => Some(image.clone()),
+            _ => None
+        }));
+        let Some(screenshot) = screenshot else {
+            ctx.request_repaint();
+            return;
+        };
+        let Some(snapshot) = self.snapshot.take() else {
+            return;
+        };
 
-            let config = app.plot_config_mut();
-            option_dropdown(ui, "Mode", PLOT_MODES, &mut config.mode, 24.0);
-            drag_value(ui, "Window (s)", &mut config.window, -3.5, 0.0..=SerialMonitorApp::STORED_DURATION, 2, "s");
-            option_dropdown(ui, "Scale", SCALE_MODES, &mut config.scale_mode, 29.0);
-            if config.scale_mode == PlotScaleMode::Manual {
-                drag_value(ui, "Min", &mut config.y_min, 36.0, f64::MIN..=config.y_max, 2, "");
-                drag_value(ui, "Max", &mut config.y_max, 33.5, config.y_min..=f64::MAX, 2, "");
-            }
-        });
+        let plots: Vec<PlotSnapshot> = app.plots().iter()
+            .filter(|n| n.kind != PlotKind::Console)
+            .map(|plot| PlotSnapshot {
+                name: plot.name.clone(),
+                image: snapshot.rects.get(&plot.id).map(|rect| screenshot.region(rect, Some(ctx.pixels_per_point()))),
+                columns: snapshot_columns(plot, app)
+            })
+            .collect();
+        let notification = match snapshot::write(&snapshot.dir, &plots, app.is_locked()) {
+            Ok((path, files)) => Notification::new(&format!("Saved snapshot ({} files) to {}", files, path.display()), Duration::from_secs(5), NotificationType::Info),
+            Err(e) => Notification::new(&format!("Could not save snapshot ({})", e), Duration::from_secs(5), NotificationType::Error)
+        };
+        self.set_notification(notification, false);
     }
 
-    fn input_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
-        ui.add_space(5.0);
-        let frame = egui::Frame::window(&ctx.style())
-            .rounding(2.0);
-        frame.show(ui, |ui| {
-            ui.horizontal(|ui| {
-                ui.heading("Serial Input");
-                ui.add_space(ui.available_width());
-            });
-            ui.separator();
+    fn is_printing(&self, plot_id: usize) -> bool {
+        self.capture.as_ref().is_some_and(|n| n.plot_id == plot_id && n.print && n.requested)
+    }
 
-            if app.is_connected() && app.has_input() {
-                egui::ScrollArea::vertical().show(ui, |ui| {
-                    let columns = app.input_columns();
-                    for (i, slot) in app.input_slots_mut().iter_mut().enumerate() {
-                        if i < columns {
-                            ui.horizontal(|ui| {
-                                ui.color_edit_button_rgb(&mut slot.color);
-                                egui::TextEdit::singleline(&mut slot.name).desired_width(100.0).show(ui);
-                                ui.separator();
-                                ui.label(format!("{:.2}", slot.value));
-                                ui.add_space(10.0);
-                            });
-                        }
+    fn copy_image(&mut self, image: &egui::ColorImage) -> Result<(), arboard::Error> {
+        let clipboard = match &mut self.clipboard {
+            Some(clipboard) => clipboard,
+            None => self.clipboard.insert(arboard::Clipboard::new()?)
+        };
+        clipboard.set_image(arboard::ImageData {
+            width: image.width(),
+            height: image.height(),
+            bytes: Cow::Borrowed(image.as_raw())
+        })
+    }
+
+    fn alarm_banner(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let alarms: Vec<(usize, String)> = app.active_alarms().into_iter().map(|(i, n)| (i, n.to_owned())).collect();
+        if alarms.is_empty() {
+            return;
+        }
+        let frame = egui::Frame::side_top_panel(&ctx.style())
+            .fill(ERROR_COLOR.gamma_multiply(0.25));
+        egui::TopBottomPanel::top("Alarms").frame(frame).show(ctx, |ui| {
+            for (i, msg) in &alarms {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(msg).strong().color(ui.visuals().strong_text_color()));
+                    if ui.small_button("Acknowledge").clicked() {
+                        app.acknowledge_alarm(Some(*i));
                     }
                 });
-            } else {
-                ui.label("Waiting for input...");
+            }
+            if alarms.len() > 1 && ui.button("Acknowledge all").clicked() {
+                app.acknowledge_alarm(None);
             }
         });
     }
 
-    fn data_panel(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
-        egui::CentralPanel::default().show(ctx, |ui| {
+    fn status_bar(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        egui::TopBottomPanel::bottom("StatusBar").show(ctx, |ui| {
             ui.horizontal(|ui| {
-                if ui.button("Add Plot").clicked() {
-                    app.add_plot();
+                match app.connection_time() {
+                    Some(elapsed) => ui.label(format!("Connected to {} ({:.0}s)", app.connected_port(), elapsed.as_secs_f32())),
+                    None => ui.label("Disconnected")
+                };
+                ui.separator();
+                ui.label(format!("Memory ~{:.1} / {:.0} MB",
+                    app.memory_usage() as f64 / memory::BYTES_PER_MB,
+                    app.plot_config().memory_budget));
+                ui.separator();
+                let metrics = app.metrics();
+                let (lines, errors) = (metrics.lines.load(Ordering::Relaxed), metrics.parse_errors.load(Ordering::Relaxed));
+                let text = format!("{} lines, {} parse errors", lines, errors);
+                let faults = app.fault_stats();
+                let mut hover = format!("{} dropped, {:.1} KB read",
+                    metrics.dropped.load(Ordering::Relaxed),
+                    metrics.bytes.load(Ordering::Relaxed) as f64 / 1024.0);
+                for (port, stats) in &faults {
+                    hover.push_str(&format!("\n{}: {}", port, stats.summary()));
                 }
-                let btn = egui::Button::new("Add Console");
-                if ui.add_enabled(!app.has_console(), btn).clicked() {
-                    app.add_console();
+                match errors == 0 && faults.iter().all(|n| n.1.total() == 0) {
+                    true => ui.label(text),
+                    false => ui.colored_label(WARNING_COLOR, text)
+                }.on_hover_text(hover);
+                ui.separator();
+                if app.is_locked() {
+                    ui.label("Configuration locked");
+                    ui.separator();
                 }
-                let btn_text = match app.is_paused() {
-                    true => "Continue",
-                    false => "Pause"
-                };
-                let btn = egui::Button::new(btn_text);
-                if ui.add_enabled(app.is_connected(), btn).clicked() {
-                    app.set_paused(!app.is_paused());
-                }
-                if ui.button("Save Config").clicked() {
-                    match app.save_config_to_file() {
-                        Ok(path) => if path.is_some() {
-                            self.set_notification(Notification::new(
-                                &format!("Saved config ({})", path.unwrap()),
-                                Duration::from_secs(5),
-                                NotificationType::Info
-                            ), false)
-                        },
-                        Err(e) => self.set_notification(Notification::new(
-                            &format!("Could not save config ({})", e.to_string()),
-                            Duration::from_secs(5),
-                            NotificationType::Error
-                        ), false)
-                    }
-                }
-                if ui.button("Load Config").clicked() {
-                    match app.load_config_from_file(self) {
-                        Ok(true) => self.set_notification(Notification::new(
-                            &format!("Loaded config"),
-                            Duration::from_secs(5),
-                            NotificationType::Info
-                        ), false),
-                        Ok(false) => (),
-                        Err(e) => self.set_notification(Notification::new(
-                            &format!("Could not load config ({})", e.to_string()),
-                            Duration::from_secs(5),
-                            NotificationType::Error
-                        ), false)
-                    }
+                if let Some((_, loss)) = app.loss() {
+                    let text = format!("Loss {} ({:.2}%)", loss.lost, loss.rate() * 100.0);
+                    match loss.lost {
+                        0 => ui.label(text),
+                        _ => ui.colored_label(WARNING_COLOR, text)
+                    }.on_hover_text(loss.summary());
+                    ui.separator();
                 }
-                ui.add_space(ui.available_width());
-            });
-            ui.separator();
-            
-            egui::ScrollArea::vertical().show(ui, |ui| {
-                let frame = egui::Frame::none()
-                    .inner_margin(0.0)
-                    .outer_margin(0.0);
-                let mut i = 0;
-                while i < app.plots().len() {
-                    let mut inc = 1;
-                    let res = egui::TopBottomPanel::top(format!("PlotContainer_{}", app.plots()[i].id))
-                        .frame(frame)
-                        .default_height(app.plots()[i].height)
-                        .min_height(128.0)
-                        .resizable(true)
-                        .show_inside(ui, |ui| {
-                            let (resp, hidden) = match app.plots()[i].console {
-                                true => (self.console(ctx, ui, &app.plots()[i], app.console_lines()), None),
-                                false => self.plot(ctx, ui, app.plot_config(), &app.plots()[i], app.input_slots(), app.raw_values(), app.zoom_enabled())
-                            };
-                            match resp {
-                                PlotResponse::Reset => {
-                                    self.plot_ranges.remove(&app.plots()[i].id);
-                                    app.reset_plot(i);
-                                },
-                                PlotResponse::Remove => {
-                                    app.remove_plot(i);
-                                    inc = 0;
-                                },
-                                _ => if let Some(h) = hidden {
-                                    app.plots_mut()[i].hidden = h;
-                                }
-                            }
-                        });
-                    if inc != 0 {
-                        app.plots_mut()[i].height = res.response.rect.height();
-                    }
-                        
-                    i += inc;
+                if let Some((file, size)) = app.log_status() {
+                    ui.label(format!("Logging to {} ({:.2} MB)", file, size as f64 / memory::BYTES_PER_MB));
+                    ui.separator();
+                }
+                if ui.small_button("Timing").clicked() {
+                    self.run_command(app, Command::Timing);
+                }
+                if ui.small_button("Diagnostics").clicked() {
+                    self.run_command(app, Command::Diagnostics);
+                }
+                if ui.small_button("?").on_hover_text("Help (F1)").clicked() {
+                    self.run_command(app, Command::Help);
                 }
             });
         });
     }
-    
-    fn notification(&mut self, ctx: &egui::Context) {
-        if let Some(notification) = &self.notification {
-            if notification.start.elapsed() > notification.duration {
-                self.notification = None;
-                return;
+
+    fn shortcuts(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        for (key, shortcut) in SHORTCUTS {
+            if !ctx.input(|i| i.modifiers.matches_logically(key.modifiers) && i.key_pressed(key.logical_key)) {
+                continue;
+            }
+            match shortcut {
+                Shortcut::Help => self.run_command(app, Command::Help),
+                Shortcut::Mark => self.run_command(app, Command::AddMarker),
+                Shortcut::Palette => self.palette = match self.palette {
+                    Some(_) => None,
+                    None => Some(Palette::default())
+                },
+                Shortcut::Close => {
+                    self.help_open = false;
+                    self.palette = None;
+                }
             }
-            render_notification(ctx, notification, egui::Pos2::new(ctx.available_rect().center().x, 75.0), false);
         }
     }
 
-    fn minor_notification(&mut self, ctx: &egui::Context) {
-        if let Some(notification) = &self.minor_notification {
-            if notification.start.elapsed() > notification.duration {
-                self.notification = None;
-                return;
-            }
-            render_notification(ctx, notification, ctx.available_rect().right_top(), true);
+    fn command_state(&self, app: &SerialMonitorApp, command: Command) -> Result<(), &'static str> {
+        let focused = self.focused_plot.and_then(|id| app.plots().iter().position(|n| n.id == id));
+        match command {
+            Command::Connect if !app.can_connect() && !app.is_reconnecting() => Err("No port selected"),
+            Command::Pause | Command::AddMarker | Command::RestartCapture if !app.is_connected() => Err("Not connected"),
+            Command::Record if !app.is_connected() && !app.is_recording() => Err("Not connected"),
+            Command::AddPlot | Command::AddWatch | Command::AddSpectrum | Command::AddTable | Command::AddConsole | Command::LoadConfig | Command::ImportSettings if self.locked => Err("Configuration locked"),
+            Command::AddConsole if app.has_console() => Err("There already is a console"),
+            Command::ExportCsv | Command::Snapshot if app.raw_values().is_empty() => Err("No samples to export"),
+            n if n.is_plot_action() && focused.is_none() => Err("No plot focused, hover a plot first"),
+            n if n.is_plot_action() && self.locked => Err("Configuration locked"),
+            Command::MaximizePlot if focused.is_some_and(|n| app.plots()[n].detached.is_some()) => Err("The plot is in its own window"),
+            Command::MovePlotUp if focused == Some(0) => Err("Already the first plot"),
+            Command::MovePlotDown if focused.is_some_and(|n| n + 1 >= app.plots().len()) => Err("Already the last plot"),
+            _ => Ok(())
         }
     }
 
-    fn plot(&mut self, ctx: &egui::Context, ui: &mut Ui, config: &PlotConfig, plot: &PlotData, input_slots: &Vec<InputSlot>, input_values: &Vec<Vec<[f64; 2]>>, zoom_enabled: bool) -> (PlotResponse, Option<Vec<usize>>) {
-        ui.add_space(PLOT_MARGIN);
-    
-        let result = self.plot_header(ui, plot);
-        if result == PlotResponse::Remove {
-            return (result, None);
+    fn run_command(&mut self, app: &mut SerialMonitorApp, command: Command) {
+        let plot_response = match command {
+            Command::ResetPlot => PlotResponse::Reset,
+            Command::MaximizePlot => PlotResponse::Maximize,
+            Command::DetachPlot => PlotResponse::Detach,
+            Command::MovePlotUp => PlotResponse::MoveUp,
+            Command::MovePlotDown => PlotResponse::MoveDown,
+            Command::RemovePlot => PlotResponse::Remove,
+            _ => PlotResponse::None
+        };
+        match command {
+            Command::Connect => match app.is_connected() || app.is_reconnecting() {
+                true => app.disconnect_current(),
+                false => if let Err(e) = app.connect_current() {
+                    self.set_notification(Notification::new(
+                        format!("Could not connect! ({})", e).as_str(),
+                        Duration::from_secs(5),
+                        NotificationType::Error
+                    ), false);
+                }
+            },
+            Command::Pause => app.set_paused(!app.is_paused()),
+            Command::AddPlot => app.add_plot(),
+            Command::AddWatch => app.add_watch(),
+            Command::AddSpectrum => app.add_spectrum(),
+            Command::AddTable => app.add_table(),
+            Command::AddConsole => app.add_console(),
+            Command::AddMarker => self.add_marker(app),
+            Command::ClearData => app.clear_data(),
+            Command::RestartCapture => app.restart_capture(),
+            Command::Record => match app.is_recording() {
+                true => app.stop_recording(),
+                false => self.dialog = Some(PendingDialog::StartRecording)
+            },
+            Command::OpenRecording => self.dialog = Some(PendingDialog::OpenRecording),
+            Command::Snapshot => self.start_snapshot(app),
+            Command::ExportCsv => self.dialog = Some(PendingDialog::ExportCsv),
+            Command::SaveConfig => self.dialog = Some(PendingDialog::SaveConfig),
+            Command::LoadConfig => self.dialog = Some(PendingDialog::LoadConfig),
+            Command::ImportSettings => self.dialog = Some(PendingDialog::ImportSettings),
+            Command::Settings => {
+                let collapsed = app.sidepanel_collapsed_mut();
+                *collapsed = !*collapsed;
+            },
+            Command::Lock => {
+                app.set_locked(!self.locked);
+                self.locked = app.is_locked();
+            },
+            Command::Sequences => self.sequences_open = !self.sequences_open,
+            Command::Presets => self.presets_open = !self.presets_open,
+            Command::Slots => self.slots_open = !self.slots_open,
+            Command::Timing => self.timing_open = !self.timing_open,
+            Command::Diagnostics => self.diagnostics_open = !self.diagnostics_open,
+            Command::Help => self.help_open = !self.help_open,
+            Command::ResetPlot | Command::MaximizePlot | Command::DetachPlot | Command::MovePlotUp | Command::MovePlotDown | Command::RemovePlot => {
+                self.plot_command = self.focused_plot.map(|id| (id, plot_response));
+            }
         }
-    
-        let plt_id = format!("Plot_{}", plot.id);
-        let empty = input_values.is_empty();
+    }
 
-        let hidden = plot.hidden.iter().map(|n| match input_slots.get(*n) {
-            Some(slot) => slot.name.clone(),
-            None => String::new()
-        });
-        let mut legend = Legend::default().position(Corner::LeftTop);
-        if !empty {
-            legend = legend.hidden_items(hidden);
+    fn command_palette(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(mut palette) = self.palette.take() else {
+            return;
+        };
+        let commands: Vec<(Command, Result<(), &str>)> = command::search(&palette.query).into_iter()
+            .map(|n| (n, self.command_state(app, n)))
+            .collect();
+        let (up, down, enter) = ctx.input(|i| (i.key_pressed(egui::Key::ArrowUp), i.key_pressed(egui::Key::ArrowDown), i.key_pressed(egui::Key::Enter)));
+        palette.selected = match (up, down) {
+            (true, false) => palette.selected.saturating_sub(1),
+            (false, true) => palette.selected + 1,
+            _ => palette.selected
+        }.min(commands.len().saturating_sub(1));
+        let focused = self.focused_plot
+            .and_then(|id| app.plots().iter().find(|n| n.id == id))
+            .map(|n| n.name.clone());
+
+        let mut run = match enter {
+            true => commands.get(palette.selected).copied(),
+            false => None
+        };
+        let query = palette.query.clone();
+        egui::Window::new("Command Palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_TOP, egui::Vec2::new(0.0, 40.0))
+            .fixed_size(egui::Vec2::new(380.0, 0.0))
+            .show(ctx, |ui| {
+                let resp = ui.add(egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Type a command")
+                    .desired_width(f32::INFINITY));
+                resp.request_focus();
+                ui.separator();
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    if commands.is_empty() {
+                        ui.weak(format!("No command matches '{}'", query));
+                    }
+                    for (i, (command, state)) in commands.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let resp = ui.add_enabled(state.is_ok(), egui::SelectableLabel::new(i == palette.selected, command.to_string()));
+                            if i == palette.selected && (up || down) {
+                                resp.scroll_to_me(None);
+                            }
+                            if resp.clicked() {
+                                run = Some((*command, *state));
+                            }
+                            match (state, &focused) {
+                                (Err(reason), _) => ui.weak(*reason),
+                                (Ok(()), Some(name)) if command.is_plot_action() => ui.weak(name),
+                                _ => ui.label("")
+                            };
+                        });
+                    }
+                });
+            });
+        if palette.query != query {
+            palette.selected = 0;
+        }
+        match run {
+            Some((command, Ok(()))) => self.run_command(app, command),
+            Some((_, Err(reason))) => {
+                self.set_notification(Notification::new(reason, Duration::from_secs(2), NotificationType::Warning), true);
+                self.palette = Some(palette);
+            },
+            None => self.palette = Some(palette)
         }
+    }
 
-        egui_plot::Plot::new(&plt_id)
-            .id(Id::new(&plt_id))
+    fn add_marker(&mut self, app: &mut SerialMonitorApp) {
+        app.add_marker(&self.marker_text);
+        self.marker_text.clear();
+    }
+
+    fn help_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.help_open;
+        egui::Window::new("Help")
+            .open(&mut open)
+            .default_size(egui::Vec2::new(460.0, 480.0))
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (title, text) in help::SECTIONS {
+                        ui.strong(*title);
+                        ui.label(*text);
+                        ui.add_space(6.0);
+                    }
+
+                    ui.separator();
+                    ui.strong("Arduino examples");
+                    for (title, code) in help::EXAMPLES {
+                        ui.horizontal(|ui| {
+                            ui.label(*title);
+                            if ui.small_button("Copy").clicked() {
+                                ui.output_mut(|o| o.copied_text = code.to_string());
+                            }
+                        });
+                        ui.code(*code);
+                    }
+
+                    ui.separator();
+                    ui.strong("Keyboard shortcuts");
+                    egui::Grid::new("Shortcuts").striped(true).show(ui, |ui| {
+                        for (key, shortcut) in SHORTCUTS {
+                            ui.monospace(ctx.format_shortcut(key));
+                            ui.label(shortcut.to_string());
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.help_open = open;
+    }
+
+    fn timing_window(&mut self, ctx: &egui::Context, app: &SerialMonitorApp) {
+        let mut open = self.timing_open;
+        egui::Window::new("Timing Diagnostics")
+            .open(&mut open)
+            .default_size(egui::Vec2::new(420.0, 320.0))
+            .show(ctx, |ui| {
+                let Some(stats) = app.timing_stats() else {
+                    ui.label("Not connected");
+                    return;
+                };
+                egui::Grid::new("TimingGrid").num_columns(2).show(ui, |ui| {
+                    ui.label("Lines");
+                    ui.label(stats.lines.to_string());
+                    ui.end_row();
+                    ui.label("Mean interval");
+                    ui.label(format!("{:.3}ms", stats.mean_interval() * 1000.0));
+                    ui.end_row();
+                    ui.label(format!("Worst gap ({}s)", TimingStats::GAP_WINDOW));
+                    ui.label(format!("{:.3}ms", stats.worst_gap * 1000.0));
+                    ui.end_row();
+                    ui.label("Loop latency");
+                    ui.label(format!("{:?} (max {:?})", stats.last_latency, stats.max_latency));
+                    ui.end_row();
+                    if let Some((_, loss)) = app.loss() {
+                        ui.label("Packet loss");
+                        ui.label(format!("{} of {} ({:.2}%)", loss.lost, loss.received + loss.lost, loss.rate() * 100.0));
+                        ui.end_row();
+                    }
+                });
+                if ui.button("Copy summary").clicked() {
+                    let mut summary = stats.summary();
+                    if let Some((_, loss)) = app.loss() {
+                        summary.push_str(&format!("{}\n", loss.summary()));
+                    }
+                    ui.output_mut(|o| o.copied_text = summary);
+                }
+                ui.separator();
+
+                let bars = stats.histogram.iter()
+                    .enumerate()
+                    .map(|(i, count)| Bar::new(i as f64, *count as f64).name(TimingStats::bucket_label(i)))
+                    .collect();
+                egui_plot::Plot::new("TimingHistogram")
+                    .x_axis_formatter(|grid_pt, _, _| match grid_pt.value >= 0.0 && grid_pt.value.fract() == 0.0 {
+                        true => TimingStats::bucket_label(grid_pt.value as usize),
+                        false => String::new()
+                    })
+                    .allow_scroll(false)
+                    .allow_zoom(false)
+                    .allow_drag(false)
+                    .show(ui, |ui| {
+                        ui.bar_chart(BarChart::new(bars).color(INFO_COLOR));
+                    });
+            });
+        self.timing_open = open;
+    }
+
+    fn sequences_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let mut open = self.sequences_open;
+        egui::Window::new("Sequences")
+            .open(&mut open)
+            .default_size(egui::Vec2::new(480.0, 360.0))
+            .show(ctx, |ui| {
+                match app.sequence_progress() {
+                    Some((name, step, steps)) => {
+                        let text = format!("Running {} (step {}/{})", name, step, steps);
+                        ui.horizontal(|ui| {
+                            ui.label(text);
+                            if ui.button("Stop").clicked() {
+                                app.stop_sequence();
+                            }
+                        });
+                    },
+                    None => {
+                        ui.label("No sequence running");
+                    }
+                }
+                ui.separator();
+
+                let connected = app.is_connected();
+                let running = app.sequence_progress().is_some();
+                let mut run = None;
+                let mut remove = None;
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, sequence) in app.sequences_mut().iter_mut().enumerate() {
+                        ui.push_id(i, |ui| {
+                            ui.horizontal(|ui| {
+                                egui::TextEdit::singleline(&mut sequence.name).desired_width(150.0).show(ui);
+                                if ui.add_enabled(connected && !running, egui::Button::new("Run")).clicked() {
+                                    run = Some(i);
+                                }
+                                ui.checkbox(&mut sequence.button, "Button")
+                                    .on_hover_text("Show this sequence next to the send presets");
+                                if ui.button("Delete").clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                            sequence_steps(ui, &mut sequence.steps);
+                        });
+                        ui.separator();
+                    }
+                    if ui.button("Add sequence").clicked() {
+                        let name = format!("Sequence {}", app.sequences_mut().len() + 1);
+                        app.sequences_mut().push(Sequence::new(&name));
+                    }
+                });
+                if let Some(index) = remove {
+                    app.sequences_mut().remove(index);
+                }
+                if let Some(index) = run {
+                    app.start_sequence(index);
+                }
+            });
+        self.sequences_open = open;
+    }
+
+    fn presets_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let mut open = self.presets_open;
+        egui::Window::new("Send presets")
+            .open(&mut open)
+            .default_size(egui::Vec2::new(480.0, 300.0))
+            .show(ctx, |ui| {
+                let mut remove = None;
+                let mut swap = None;
+                let count = app.presets().len();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, preset) in app.presets_mut().iter_mut().enumerate() {
+                        ui.push_id(("Preset", i), |ui| {
+                            ui.horizontal(|ui| {
+                                egui::TextEdit::singleline(&mut preset.label)
+                                    .hint_text("Label")
+                                    .desired_width(90.0)
+                                    .show(ui);
+                                egui::TextEdit::singleline(&mut preset.payload)
+                                    .hint_text("Payload")
+                                    .desired_width(150.0)
+                                    .show(ui);
+                                egui::ComboBox::new("LineEnding", "")
+                                    .selected_text(preset.line_ending.to_string())
+                                    .width(50.0)
+                                    .show_ui(ui, |ui| {
+                                        for ending in LINE_ENDINGS {
+                                            ui.selectable_value(&mut preset.line_ending, *ending, ending.to_string());
+                                        }
+                                    });
+                                ui.checkbox(&mut preset.confirm, "Confirm")
+                                    .on_hover_text("Ask before sending");
+                                if ui.add_enabled(i > 0, egui::Button::new("\u{2191}").small()).clicked() {
+                                    swap = Some((i, i - 1));
+                                }
+                                if ui.add_enabled(i + 1 < count, egui::Button::new("\u{2193}").small()).clicked() {
+                                    swap = Some((i, i + 1));
+                                }
+                                if ui.small_button("x").clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                        });
+                    }
+                    if ui.button("Add preset").clicked() {
+                        let label = format!("Preset {}", count + 1);
+                        app.presets_mut().push(SendPreset::new(&label));
+                    }
+                });
+                if let Some((a, b)) = swap {
+                    app.presets_mut().swap(a, b);
+                }
+                if let Some(index) = remove {
+                    app.presets_mut().remove(index);
+                }
+            });
+        self.presets_open = open;
+    }
+
+    fn confirm_preset_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(index) = self.confirm_preset else {
+            return;
+        };
+        let Some(preset) = app.presets().get(index) else {
+            self.confirm_preset = None;
+            return;
+        };
+        let mut send = false;
+        let mut cancel = false;
+        egui::Window::new(format!("Send {}?", preset.label))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&preset.payload).monospace());
+                ui.horizontal(|ui| {
+                    send = ui.add_enabled(app.is_connected(), egui::Button::new("Send")).clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if send {
+            self.confirm_preset = None;
+            self.send_preset(app, index, true);
+        } else if cancel {
+            self.confirm_preset = None;
+        }
+    }
+
+    fn send_preset(&mut self, app: &mut SerialMonitorApp, index: usize, confirmed: bool) {
+        if !confirmed && app.presets().get(index).is_some_and(|n| n.confirm) {
+            self.confirm_preset = Some(index);
+            return;
+        }
+        if let Err(e) = app.send_preset(index) {
+            self.set_notification(Notification::new(&format!("Could not send ({})", e), Duration::from_secs(5), NotificationType::Error), false);
+        }
+    }
+
+    fn slots_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let mut open = self.slots_open;
+        let mut change = None;
+        let max_columns = app.frame_config_mut().max_columns;
+        egui::Window::new("Input Slots")
+            .open(&mut open)
+            .default_size(egui::Vec2::new(360.0, 300.0))
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(!self.locked, |ui| {
+                    ui.checkbox(&mut self.slot_remap, "Remap columns")
+                        .on_hover_text("Assign the data column of every slot explicitly");
+                    ui.separator();
+                    let slots = app.input_slots();
+                    if slots.is_empty() {
+                        ui.label("No input slots yet");
+                        return;
+                    }
+                    let mut order: Vec<usize> = (0..slots.len()).collect();
+                    order.sort_by(|l, r| (&slots[*l].device, slots[*l].index).cmp(&(&slots[*r].device, slots[*r].index)));
+                    let neighbor = |i: usize, k: Option<usize>| k.and_then(|k| order.get(k))
+                        .filter(|n| slots[**n].device == slots[i].device)
+                        .map(|n| slots[*n].index);
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        egui::Grid::new("SlotsGrid").num_columns(4).striped(true).show(ui, |ui| {
+                            for (k, &i) in order.iter().enumerate() {
+                                let slot = &slots[i];
+                                let mapped = app.slot_mapped(i);
+                                ui.horizontal(|ui| {
+                                    let (rect, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+                                    ui.painter().rect_filled(rect, 2.0, rgb_color(slot.color));
+                                    let name = egui::RichText::new(&slot.name);
+                                    match mapped {
+                                        true => ui.label(name),
+                                        false => ui.label(name.weak()).on_hover_text("Not receiving a column of the current data")
+                                    };
+                                });
+                                match self.slot_remap {
+                                    true => {
+                                        let mut column = slot.index + 1;
+                                        if ui.add(egui::DragValue::new(&mut column).clamp_range(1..=max_columns).prefix("Column ")).changed() {
+                                            change = Some(SlotChange::Column(i, column - 1));
+                                        }
+                                    },
+                                    false => {
+                                        ui.label(format!("Column {}", slot.index + 1));
+                                    }
+                                }
+                                ui.horizontal(|ui| {
+                                    let (previous, next) = (neighbor(i, k.checked_sub(1)), neighbor(i, Some(k + 1)));
+                                    if ui.add_enabled(previous.is_some(), egui::Button::new("\u{2191}").small()).on_hover_text("Swap columns with the slot above").clicked() {
+                                        change = previous.map(|n| SlotChange::Column(i, n));
+                                    }
+                                    if ui.add_enabled(next.is_some(), egui::Button::new("\u{2193}").small()).on_hover_text("Swap columns with the slot below").clicked() {
+                                        change = next.map(|n| SlotChange::Column(i, n));
+                                    }
+                                });
+                                if ui.add_enabled(!mapped, egui::Button::new("x").small())
+                                    .on_hover_text("Delete the slot")
+                                    .on_disabled_hover_text("The slot receives a column of the current data")
+                                    .clicked() {
+                                    change = Some(SlotChange::Remove(i));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                });
+            });
+        self.slots_open = open;
+        match change {
+            Some(SlotChange::Column(slot, column)) => app.set_slot_column(slot, column),
+            Some(SlotChange::Remove(slot)) => self.request_slot_removal(app, vec![slot]),
+            None => {}
+        }
+    }
+
+    fn confirm_slot_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(indices) = &self.confirm_slot_removal else {
+            return;
+        };
+        let (title, text) = match indices.as_slice() {
+            [index] => match app.input_slots().get(*index) {
+                Some(slot) => (format!("Delete {}?", slot.name), "The slot still has plotted data, it is deleted with the slot."),
+                None => {
+                    self.confirm_slot_removal = None;
+                    return;
+                }
+            },
+            _ => (format!("Delete {} slots?", indices.len()), "Some slots still have plotted data, it is deleted with them.")
+        };
+        let mut remove = false;
+        let mut cancel = false;
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(text);
+                ui.horizontal(|ui| {
+                    remove = ui.button("Delete").clicked();
+                    cancel = ui.button("Cancel").clicked();
+                });
+            });
+        if remove {
+            if let Some(indices) = self.confirm_slot_removal.take() {
+                self.remove_slots(app, indices);
+            }
+        } else if cancel {
+            self.confirm_slot_removal = None;
+        }
+    }
+
+    fn request_slot_removal(&mut self, app: &mut SerialMonitorApp, indices: Vec<usize>) {
+        match indices.iter().any(|i| app.raw_values().get(*i).is_some_and(|n| !n.is_empty())) {
+            true => self.confirm_slot_removal = Some(indices),
+            false => self.remove_slots(app, indices)
+        }
+    }
+
+    fn remove_slots(&mut self, app: &mut SerialMonitorApp, mut indices: Vec<usize>) {
+        indices.sort_unstable();
+        indices.dedup();
+        for slot in indices.into_iter().rev() {
+            app.remove_input_slot(slot);
+        }
+        self.selected_slots.clear();
+        self.slot_undo = None;
+        self.calibration = None;
+        self.slot_focus = None;
+    }
+
+    fn diagnostics_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let mut open = self.diagnostics_open;
+        egui::Window::new("Diagnostics")
+            .open(&mut open)
+            .default_size(egui::Vec2::new(380.0, 240.0))
+            .show(ctx, |ui| {
+                let config = app.conn_config();
+                ui.label(format!("{} at {} baud, {}{}{}", config.port, config.baud_rate, config.data_bits, config.parity.to_string().chars().next().unwrap_or('N'), config.stop_bits));
+                ui.separator();
+                ui.label("Loopback: bridge the TX and RX pins of the adapter, then run the test.");
+                ui.label("Listen: checks whether the device sends any bytes within 5 seconds.");
+                let state = app.diagnostic();
+                let running = state.as_ref().is_some_and(|(_, state)| state.result.is_none());
+                ui.horizontal(|ui| {
+                    let idle = !running && !app.is_connected() && app.can_connect();
+                    for mode in [DiagnosticMode::Loopback, DiagnosticMode::Listen] {
+                        if ui.add_enabled(idle, egui::Button::new(mode.to_string())).clicked() {
+                            if let Err(e) = app.start_diagnostic(mode) {
+                                self.set_notification(Notification::new(&e.to_string(), Duration::from_secs(5), NotificationType::Error), false);
+                            }
+                        }
+                    }
+                    if running && ui.button("Stop").clicked() {
+                        app.stop_diagnostic();
+                    }
+                });
+                if app.is_connected() {
+                    ui.label(egui::RichText::new("Disconnect to run diagnostics").weak());
+                }
+                ui.separator();
+
+                match state {
+                    Some((mode, state)) => match state.result {
+                        None => {
+                            ui.add(egui::ProgressBar::new(state.progress).text(format!("{} running\u{2026}", mode)));
+                        },
+                        Some(Err(e)) => {
+                            ui.colored_label(ERROR_COLOR, format!("{} failed ({})", mode, e));
+                        },
+                        Some(Ok(report)) => {
+                            egui::Grid::new("DiagnosticsGrid").num_columns(2).show(ui, |ui| {
+                                if report.mode == DiagnosticMode::Loopback {
+                                    ui.label("Sent");
+                                    ui.label(format!("{} bytes", report.sent));
+                                    ui.end_row();
+                                }
+                                ui.label("Received");
+                                ui.label(format!("{} bytes", report.received));
+                                ui.end_row();
+                                if report.mode == DiagnosticMode::Loopback {
+                                    ui.label("Error rate");
+                                    ui.label(format!("{:.2}%", report.error_rate() * 100.0));
+                                    ui.end_row();
+                                }
+                                ui.label("Throughput");
+                                ui.label(format!("{:.0} B/s", report.throughput()));
+                                ui.end_row();
+                            });
+                            let color = match (report.mode, report.received, report.errors) {
+                                (_, 0, _) => WARNING_COLOR,
+                                (DiagnosticMode::Loopback, _, 0) | (DiagnosticMode::Listen, _, _) => INFO_COLOR,
+                                _ => WARNING_COLOR
+                            };
+                            ui.colored_label(color, report.conclusion());
+                        }
+                    },
+                    None => {
+                        ui.label("No test run yet");
+                    }
+                }
+            });
+        self.diagnostics_open = open;
+    }
+
+    fn rejected_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let mut open = self.rejected_open;
+        egui::Window::new("Rejected lines")
+            .open(&mut open)
+            .default_size(egui::Vec2::new(480.0, 280.0))
+            .show(ctx, |ui| {
+                let rejected = app.rejected_lines();
+                let text = |port: Option<&str>, line: &str| match port {
+                    Some(port) => format!("[{}] {}", port, line),
+                    None => line.to_owned()
+                };
+                ui.horizontal(|ui| {
+                    ui.label(format!("Last {} lines that failed to parse or had ignored values", rejected.len()));
+                    if ui.add_enabled(!rejected.is_empty(), egui::Button::new("Copy")).clicked() {
+                        let copied = rejected.iter()
+                            .map(|(port, n)| format!("{}\t{}", text(*port, &n.line), n.reason))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        ui.output_mut(|o| o.copied_text = copied);
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().auto_shrink([false, false]).stick_to_bottom(true).show(ui, |ui| {
+                    for (port, n) in &rejected {
+                        ui.label(egui::RichText::new(text(*port, &n.line)).monospace());
+                        ui.label(egui::RichText::new(&n.reason).small().weak());
+                    }
+                });
+            });
+        self.rejected_open = open;
+    }
+
+    fn restore_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(modified) = app.pending_restore() else {
+            return;
+        };
+        let age = modified.elapsed().unwrap_or_default().as_secs() / 60;
+        egui::Window::new("Restore autosave")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("An autosaved configuration from {} min ago is newer than the loaded one.", age));
+                ui.horizontal(|ui| {
+                    if ui.button("Restore").clicked() {
+                        app.restore_autosave(self);
+                    }
+                    if ui.button("Discard").clicked() {
+                        app.discard_autosave();
+                    }
+                });
+            });
+    }
+
+    fn recovery_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        if app.pending_restore().is_some() {
+            return;
+        }
+        let Some(dir) = app.pending_recovery().map(|n| n.display().to_string()) else {
+            return;
+        };
+        egui::Window::new("Recover black box")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("The black box of the last session was not closed cleanly.");
+                ui.label(egui::RichText::new(format!("Kept in {}", dir)).weak());
+                ui.horizontal(|ui| {
+                    if ui.button("Import").clicked() {
+                        app.import_recovery();
+                    }
+                    if ui.button("Discard").clicked() {
+                        app.discard_recovery();
+                    }
+                });
+            });
+    }
+
+    fn frame_suggestion_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(mode) = app.frame_suggestion().map(|n| n.mode) else {
+            return;
+        };
+        let text = format!("The parser mode '{}' extracts few or no values from the first {} lines, but '{}' would extract more.",
+            app.frame_config_mut().mode, SerialMonitorApp::PROBE_LINES, mode);
+        egui::Window::new("Parser mode mismatch")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(text);
+                ui.horizontal(|ui| {
+                    if ui.button(format!("Switch to '{}'", mode)).clicked() {
+                        app.accept_frame_suggestion();
+                    }
+                    if ui.button("Switch and save\u{2026}").clicked() {
+                        app.accept_frame_suggestion();
+                        self.dialog = Some(PendingDialog::SaveConfig);
+                    }
+                    if ui.button("Ignore").clicked() {
+                        app.dismiss_frame_suggestion();
+                    }
+                });
+            });
+    }
+
+    fn select_slot(&mut self, index: usize, modifiers: egui::Modifiers) {
+        match (modifiers.command, modifiers.shift, self.select_anchor) {
+            (_, true, Some(anchor)) => {
+                self.selected_slots.extend(usize::min(anchor, index)..=usize::max(anchor, index));
+            },
+            (true, _, _) => {
+                if !self.selected_slots.remove(&index) {
+                    self.selected_slots.insert(index);
+                }
+                self.select_anchor = Some(index);
+            },
+            _ => {
+                self.selected_slots.clear();
+                self.select_anchor = Some(index);
+            }
+        }
+    }
+
+    fn import_window(&mut self, ctx: &egui::Context) {
+        let Some(summary) = &self.import_summary else {
+            return;
+        };
+        let mut open = true;
+        let mut close = false;
+        egui::Window::new(format!("Imported {} settings", summary.format))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&summary.file).weak());
+                ui.separator();
+                ui.label(format!("Mapped {} setting(s)", summary.mapped.len()));
+                for text in &summary.mapped {
+                    ui.label(format!("\u{2022} {}", text));
+                }
+                if !summary.unmapped.is_empty() {
+                    ui.separator();
+                    ui.colored_label(WARNING_COLOR, format!("{} setting(s) could not be mapped", summary.unmapped.len()));
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for text in &summary.unmapped {
+                            ui.label(egui::RichText::new(text).small());
+                        }
+                    });
+                }
+                ui.separator();
+                close = ui.button("Close").clicked();
+            });
+        if !open || close {
+            self.import_summary = None;
+        }
+    }
+
+    fn dropped_files(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(path) = ctx.input(|i| i.raw.dropped_files.iter().find_map(|n| n.path.clone())) else {
+            return;
+        };
+        if self.locked {
+            self.set_notification(Notification::new("Configuration locked", Duration::from_secs(5), NotificationType::Warning), false);
+            return;
+        }
+        let native = path.extension().is_some_and(|n| n.eq_ignore_ascii_case("json"));
+        let result = match native {
+            true => SerialMonitorData::deserialize(&path).map(|config| {
+                app.load_config(config, self);
+                Notification::new("Loaded config", Duration::from_secs(5), NotificationType::Info)
+            }),
+            false => app.import_settings(&path, self).map(|summary| {
+                self.import_summary = Some(summary);
+                Notification::new("Imported settings", Duration::from_secs(3), NotificationType::Info)
+            })
+        };
+        match result {
+            Ok(notification) => self.set_notification(notification, false),
+            Err(e) => self.set_notification(Notification::new(&format!("Could not open {} ({})", path.display(), e), Duration::from_secs(5), NotificationType::Error), false)
+        }
+    }
+
+    fn bulk_edit_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(edit) = &mut self.bulk_edit else {
+            return;
+        };
+        let mut open = true;
+        let mut apply = false;
+        let mut undo = false;
+        let mut delete = false;
+        let count = self.selected_slots.len();
+        egui::Window::new(format!("Edit {} slots", count))
+            .open(&mut open)
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut edit.recolor, "Color gradient");
+                    ui.add_enabled_ui(edit.recolor, |ui| {
+                        ui.color_edit_button_rgb(&mut edit.from);
+                        ui.label("\u{2192}");
+                        ui.color_edit_button_rgb(&mut edit.to);
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut edit.rescale, "Scale");
+                    ui.add_enabled_ui(edit.rescale, |ui| {
+                        ui.add(egui::DragValue::new(&mut edit.scale).speed(0.01));
+                        ui.label("Offset");
+                        ui.add(egui::DragValue::new(&mut edit.offset).speed(0.01));
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Name prefix");
+                    egui::TextEdit::singleline(&mut edit.prefix).desired_width(60.0).show(ui);
+                    ui.label("suffix");
+                    egui::TextEdit::singleline(&mut edit.suffix).desired_width(60.0).show(ui);
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut edit.set_unit, "Unit");
+                    ui.add_enabled_ui(edit.set_unit, |ui| {
+                        egui::TextEdit::singleline(&mut edit.unit).desired_width(60.0).show(ui);
+                    });
+                    ui.checkbox(&mut edit.set_precision, "Decimals");
+                    ui.add_enabled_ui(edit.set_precision, |ui| {
+                        ui.add(egui::DragValue::new(&mut edit.precision).clamp_range(0..=InputSlot::MAX_PRECISION));
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Plots");
+                    ui.selectable_value(&mut edit.hide, None, "Unchanged");
+                    ui.selectable_value(&mut edit.hide, Some(false), "Show");
+                    ui.selectable_value(&mut edit.hide, Some(true), "Hide");
+                });
+                ui.checkbox(&mut edit.reset, "Reset time shift, filters, calibration and alarms");
+                ui.separator();
+                ui.horizontal(|ui| {
+                    apply = ui.add_enabled(count > 0, egui::Button::new("Apply")).clicked();
+                    undo = ui.add_enabled(self.slot_undo.is_some(), egui::Button::new("Undo")).clicked();
+                    delete = ui.add_enabled(count > 0, egui::Button::new("Delete selected")).clicked();
+                });
+            });
+        if apply {
+            self.apply_bulk_edit(ctx, app);
+        }
+        if delete {
+            let selected: Vec<usize> = self.selected_slots.iter().copied().filter(|n| *n < app.input_slots().len()).collect();
+            self.request_slot_removal(app, selected);
+        }
+        if undo {
+            self.undo_bulk_edit(ctx, app);
+        }
+        if !open {
+            self.bulk_edit = None;
+        }
+    }
+
+    fn apply_bulk_edit(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(edit) = &self.bulk_edit else {
+            return;
+        };
+        let plot_ids: Vec<usize> = app.plots().iter().filter(|n| n.is_graph()).map(|n| n.id).collect();
+        let selected: Vec<usize> = self.selected_slots.iter().copied().filter(|n| *n < app.input_slots().len()).collect();
+        let mut undo = SlotUndo {
+            slots: Vec::new(),
+            hidden: Vec::new()
+        };
+        for (k, index) in selected.iter().enumerate() {
+            let slot = &mut app.input_slots_mut()[*index];
+            undo.slots.push((*index, slot.clone()));
+            for id in &plot_ids {
+                let plot_id = Id::new(format!("Plot_{}", id));
+                let hidden = PlotMemory::load(ctx, plot_id).is_some_and(|mem| mem.hidden_items.contains(&slot.legend_name()));
+                undo.hidden.push((*id, slot.legend_name(), hidden));
+            }
+
+            if edit.recolor {
+                let t = match selected.len() {
+                    1 => 0.0,
+                    n => k as f32 / (n - 1) as f32
+                };
+                slot.color = [0, 1, 2].map(|c| edit.from[c] + (edit.to[c] - edit.from[c]) * t);
+            }
+            if edit.rescale {
+                slot.scale = edit.scale;
+                slot.offset = edit.offset;
+                slot.calibration = None;
+            }
+            if !edit.prefix.is_empty() || !edit.suffix.is_empty() {
+                slot.name = format!("{}{}{}", edit.prefix, slot.name, edit.suffix);
+            }
+            if edit.set_unit {
+                slot.unit = edit.unit.trim().to_owned();
+            }
+            if edit.set_precision {
+                slot.precision = edit.precision;
+            }
+            if edit.reset {
+                slot.time_shift_ms = 0.0;
+                slot.stale_after = None;
+                slot.calibration = None;
+                slot.deadband = None;
+                slot.anomaly = None;
+                slot.alarm = None;
+                slot.color_map = None;
+            }
+            if let Some(hide) = edit.hide {
+                for id in &plot_ids {
+                    set_item_hidden(ctx, Id::new(format!("Plot_{}", id)), &slot.legend_name(), hide);
+                }
+            }
+        }
+        self.slot_undo = Some(undo);
+        if let Some(edit) = &mut self.bulk_edit {
+            edit.prefix.clear();
+            edit.suffix.clear();
+        }
+    }
+
+    fn undo_bulk_edit(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(undo) = self.slot_undo.take() else {
+            return;
+        };
+        for (index, slot) in undo.slots {
+            if let Some(current) = app.input_slots_mut().get_mut(index) {
+                let value = current.value;
+                *current = slot;
+                current.value = value;
+            }
+        }
+        for (id, name, hidden) in undo.hidden {
+            set_item_hidden(ctx, Id::new(format!("Plot_{}", id)), &name, hidden);
+        }
+    }
+
+    fn calibration_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(dialog) = &mut self.calibration else {
+            return;
+        };
+        let Some(slot) = app.input_slots().get(dialog.slot) else {
+            self.calibration = None;
+            return;
+        };
+        let values = app.raw_values().get(dialog.slot).map(|n| n.as_slice()).unwrap_or_default();
+        if let Some((point, t0)) = dialog.capturing {
+            if values.last().is_some_and(|n| n[0] - t0 >= dialog.seconds) {
+                let samples: Vec<f64> = values.iter()
+                    .rev()
+                    .take_while(|n| n[0] > t0)
+                    .map(|n| n[1])
+                    .collect();
+                dialog.raw[point] = Some(samples.iter().sum::<f64>() / samples.len() as f64);
+                dialog.capturing = None;
+            }
+        }
+
+        let mut open = true;
+        let mut apply = None;
+        let mut reset = false;
+        let mut cancel = false;
+        egui::Window::new(format!("Calibrate {}", slot.name))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut dialog.average, "Average raw readings over");
+                    ui.add_enabled(dialog.average, egui::DragValue::new(&mut dialog.seconds).speed(0.1).clamp_range(0.1..=60.0).suffix("s"));
+                });
+                ui.separator();
+                egui::Grid::new("CalibrationGrid").num_columns(4).show(ui, |ui| {
+                    ui.label("");
+                    ui.label("Raw");
+                    ui.label("Reference");
+                    ui.end_row();
+                    for i in 0..2 {
+                        ui.label(format!("Point {}", i + 1));
+                        match dialog.raw[i] {
+                            Some(raw) => ui.label(format!("{:.4}", raw)),
+                            None => ui.label("-")
+                        };
+                        ui.add(egui::DragValue::new(&mut dialog.reference[i]).speed(0.1));
+                        let capturing = dialog.capturing.is_some_and(|(point, _)| point == i);
+                        let text = if capturing { "Capturing\u{2026}" } else { "Capture" };
+                        if ui.add_enabled(dialog.capturing.is_none() && !values.is_empty(), egui::Button::new(text)).clicked() {
+                            let last = values[values.len() - 1];
+                            match dialog.average {
+                                true => dialog.capturing = Some((i, last[0])),
+                                false => dialog.raw[i] = Some(last[1])
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+
+                let calibration = match dialog.raw {
+                    [Some(a), Some(b)] => Some(Calibration {
+                        raw: [a, b],
+                        reference: dialog.reference
+                    }),
+                    _ => None
+                };
+                let fit = calibration.as_ref().and_then(|n| n.fit());
+                match (&calibration, fit) {
+                    (Some(calibration), Some((scale, offset))) => {
+                        ui.label(format!("Scale {:.6}   Offset {:.6}", scale, offset));
+                        for raw in calibration.raw {
+                            ui.label(format!("{:.4} \u{2192} {:.4}", raw, raw * scale + offset));
+                        }
+                    },
+                    (Some(_), None) => {
+                        ui.colored_label(WARNING_COLOR, "The raw points are nearly identical, use two references further apart");
+                    },
+                    _ => {
+                        ui.label("Capture both points to compute the fit");
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(fit.is_some(), egui::Button::new("Apply")).clicked() {
+                        apply = calibration.zip(fit);
+                    }
+                    if ui.button("Reset").clicked() {
+                        reset = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        let index = dialog.slot;
+        let close = !open || cancel || reset || apply.is_some();
+        if let Some(slot) = app.input_slots_mut().get_mut(index) {
+            if let Some((calibration, (scale, offset))) = apply {
+                slot.scale = scale;
+                slot.offset = offset;
+                slot.calibration = Some(calibration);
+            } else if reset {
+                slot.scale = 1.0;
+                slot.offset = 0.0;
+                slot.calibration = None;
+            }
+        }
+        if close {
+            self.calibration = None;
+        }
+    }
+
+    fn config_panel(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        if *app.sidepanel_collapsed_mut() {
+            return;
+        }
+        egui::SidePanel::left("ConnPanel")
+            .exact_width(SIDEPANEL_WIDTH)
+            .min_width(SIDEPANEL_WIDTH)
+            .max_width(SIDEPANEL_WIDTH)
+            .resizable(false)
+            .show(ctx, |ui| {
+                self.profile_bar(ui, app);
+                self.conn_panel(ctx, ui, app);
+                if !app.plots().iter().any(|n| n.kind == PlotKind::Console) {
+                    self.presets_panel(ctx, ui, app);
+                }
+                let locked = self.locked;
+                let resp = ui.add_enabled_ui(!locked, |ui| {
+                    self.devices_panel(ctx, ui, app);
+                    self.disconnect_panel(ctx, ui, app);
+                    self.plot_panel(ctx, ui, app);
+                    self.parser_panel(ctx, ui, app);
+                    self.parse_stats_panel(ctx, ui, app);
+                    self.log_panel(ctx, ui, app);
+                    self.appearance_panel(ctx, ui, app);
+                    self.input_panel(ctx, ui, app);
+                }).response;
+                self.locked_hint(ui, resp.rect);
+            });
+    }
+
+    fn profile_bar(&mut self, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let names = match &self.profile_names {
+            Some(names) => names.clone(),
+            None => {
+                let names = profiles::names().unwrap_or_else(|e| {
+                    self.set_notification(Notification::new(&format!("Could not read profiles ({})", e), Duration::from_secs(5), NotificationType::Error), false);
+                    Vec::new()
+                });
+                self.profile_names.insert(names).clone()
+            }
+        };
+        let current = app.profile().map(String::from);
+        let mut switch = None;
+        let mut import = false;
+        let locked = self.locked;
+        let resp = ui.add_enabled_ui(!locked, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Profile");
+                ui.add_space(19.0);
+                egui::ComboBox::new("Profile", "")
+                    .selected_text(current.as_deref().unwrap_or("None"))
+                    .width(DROPDOWN_WIDTH - 28.0)
+                    .show_ui(ui, |ui| {
+                        if names.is_empty() {
+                            ui.label("No profiles saved");
+                        }
+                        for name in &names {
+                            if ui.selectable_label(current.as_ref() == Some(name), name).clicked() {
+                                switch = Some(name.clone());
+                            }
+                        }
+                    });
+                ui.menu_button("\u{2026}", |ui| {
+                    close_menu_on_escape(ui);
+                    if ui.button("Save as profile\u{2026}").clicked() {
+                        self.profile_dialog = Some(ProfileDialog::Save(current.clone().unwrap_or_default()));
+                        ui.close_menu();
+                    }
+                    if let Some(name) = &current {
+                        if ui.button("Rename\u{2026}").clicked() {
+                            self.profile_dialog = Some(ProfileDialog::Rename(name.clone(), name.clone()));
+                            ui.close_menu();
+                        }
+                        if ui.button("Delete").clicked() {
+                            self.profile_dialog = Some(ProfileDialog::Delete(name.clone()));
+                            ui.close_menu();
+                        }
+                    }
+                    ui.separator();
+                    if ui.button("Import JSON config\u{2026}").on_hover_text("Add a saved config file as a new profile").clicked() {
+                        import = true;
+                        ui.close_menu();
+                    }
+                });
+            });
+        }).response;
+        self.locked_hint(ui, resp.rect);
+        if let Some(name) = switch.filter(|n| current.as_ref() != Some(n)) {
+            let notification = match app.load_profile(&name, self) {
+                Ok(()) => Notification::new(&format!("Loaded profile {}", name), Duration::from_secs(5), NotificationType::Info),
+                Err(e) => Notification::new(&format!("Could not load profile ({})", e), Duration::from_secs(5), NotificationType::Error)
+            };
+            self.set_notification(notification, false);
+        }
+        if import {
+            self.dialog = Some(PendingDialog::ImportProfile);
+        }
+    }
+
+    fn profile_window(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let Some(mut dialog) = self.profile_dialog.take() else {
+            return;
+        };
+        let names = self.profile_names.clone().unwrap_or_default();
+        let title = match dialog {
+            ProfileDialog::Save(_) => "Save as profile",
+            ProfileDialog::Rename(..) => "Rename profile",
+            ProfileDialog::Delete(_) => "Delete profile"
+        };
+        let mut apply = false;
+        let mut cancel = ctx.input(|i| i.key_pressed(egui::Key::Escape));
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                let mut enter = false;
+                if let ProfileDialog::Save(name) | ProfileDialog::Rename(_, name) = &mut dialog {
+                    let resp = ui.add(egui::TextEdit::singleline(name).hint_text("Profile name"));
+                    if !resp.has_focus() && !resp.lost_focus() {
+                        resp.request_focus();
+                    }
+                    enter = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                }
+                let taken = |name: &str| names.iter().any(|n| n == name.trim());
+                let valid = match &dialog {
+                    ProfileDialog::Save(name) => {
+                        if taken(name) {
+                            ui.colored_label(WARNING_COLOR, "Replaces the saved profile with this name");
+                        }
+                        !name.trim().is_empty()
+                    },
+                    ProfileDialog::Rename(old, name) => {
+                        let duplicate = taken(name) && name.trim() != old;
+                        if duplicate {
+                            ui.colored_label(ERROR_COLOR, "A profile with this name already exists");
+                        }
+                        !name.trim().is_empty() && !duplicate
+                    },
+                    ProfileDialog::Delete(name) => {
+                        ui.label(format!("Delete the profile {}? The current configuration stays loaded.", name));
+                        true
+                    }
+                };
+                ui.horizontal(|ui| {
+                    let text = match dialog {
+                        ProfileDialog::Delete(_) => "Delete",
+                        _ => "Save"
+                    };
+                    let clicked = ui.add_enabled(valid, egui::Button::new(text)).clicked();
+                    apply = valid && (enter || clicked);
+                    cancel |= ui.button("Cancel").clicked();
+                });
+            });
+        if cancel {
+            return;
+        }
+        if !apply {
+            self.profile_dialog = Some(dialog);
+            return;
+        }
+        let result = match &dialog {
+            ProfileDialog::Save(name) => app.save_profile(name.trim()).map(|_| Some(format!("Saved profile {}", name.trim()))),
+            ProfileDialog::Rename(old, name) if name.trim() == old => Ok(None),
+            ProfileDialog::Rename(old, name) => app.rename_profile(old, name.trim()).map(|_| Some(format!("Renamed profile to {}", name.trim()))),
+            ProfileDialog::Delete(name) => app.delete_profile(name).map(|_| Some(format!("Deleted profile {}", name)))
+        };
+        match result {
+            Ok(None) => (),
+            Ok(Some(msg)) => self.set_notification(Notification::new(&msg, Duration::from_secs(5), NotificationType::Info), false),
+            Err(e) => {
+                self.set_notification(Notification::new(&format!("Could not update profiles ({})", e), Duration::from_secs(5), NotificationType::Error), false);
+                self.profile_dialog = Some(dialog);
+            }
+        }
+        self.profile_names = None;
+    }
+
+    fn conn_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                status_dot(ui, app.is_connected());
+                ui.heading("Connection");
+                ui.add_space(10.0);
+                self.connect_button(ui, app);
+            });
+            ui.separator();
+
+            if let (Some(progress), Some(elapsed)) = (app.start_progress(), app.connection_time()) {
+                ui.label(format!("Waiting for start\u{2026} ({:.1}s)", elapsed.as_secs_f32()));
+                ui.label(format!("Received {} bytes", progress.received()));
+                if !progress.last_line.is_empty() {
+                    let config = app.conn_config();
+                    if let StartMode::Message(_) = config.start_mode {
+                        ui.label(format!("Last line ({} \"{}\" did not match)", config.start_match.to_string().to_lowercase(), config.start_msg.trim()));
+                    }
+                    ui.label(egui::RichText::new(progress.last_line.escape_debug().to_string()).monospace().weak());
+                }
+                ui.separator();
+            }
+            self.control_buttons(ui, app);
+            ui.separator();
+
+            let start = ui.cursor().min;
+            ui.set_enabled(!app.is_connected() && !self.locked);
+            let mut devices = app.available_devices();
+            let config = app.conn_config();
+            if NetworkAddress::parse(&config.port).is_some() {
+                devices.push(config.port.clone());
+            }
+            devices.push(String::from(NETWORK_ENTRY));
+            devices.push(String::from(RECORDING_ENTRY));
+            let previous = config.port.clone();
+            option_dropdown(ui, "Device", devices.as_slice(), &mut config.port, 20.0);
+            if config.port == NETWORK_ENTRY {
+                config.port = NetworkAddress::default().to_string();
+            }
+            let open_recording = config.port == RECORDING_ENTRY;
+            if open_recording {
+                config.port = previous;
+            }
+            if let Some(mut address) = NetworkAddress::parse(&config.port) {
+                option_dropdown(ui, "Protocol", NETWORK_PROTOCOLS, &mut address.protocol, 11.0);
+                ui.horizontal(|ui| {
+                    ui.label("Host");
+                    ui.add_space(27.0);
+                    egui::TextEdit::singleline(&mut address.host).desired_width(DROPDOWN_WIDTH).show(ui);
+                }).response.on_hover_text("Host to connect to, or the local address to listen on for UDP (empty for all)");
+                drag_value(ui, "Port", &mut address.port, 29.0, 1..=u16::MAX, 0, "");
+                config.port = address.to_string();
+            }
+            if open_recording {
+                self.run_command(app, Command::OpenRecording);
+            }
+            let config = app.conn_config();
+            let bauds = baud_options(&self.custom_bauds, config.baud_rate);
+            if let Some(baud) = editable_dropdown(ui, "Baud", &bauds, &mut config.baud_rate, &mut self.entry_text, 27.0, |n| n > 0) {
+                if !bauds.contains(&baud) {
+                    self.custom_bauds.push(baud);
+                }
+            }
+            if !BAUD_RATES.contains(&config.baud_rate) {
+                ui.label(egui::RichText::new("Non-standard rate, the adapter or OS may reject it").small().color(WARNING_COLOR));
+            }
+            ui.separator();
+
+            editable_dropdown(ui, "Data bits", DATA_BITS, &mut config.data_bits, &mut self.entry_text, 8.0, |n| n > 0);
+            if !(5..=8).contains(&config.data_bits) {
+                ui.label(egui::RichText::new("Serial ports support 5-8 data bits").small().color(WARNING_COLOR));
+            }
+            option_dropdown(ui, "Parity", PARITIES, &mut config.parity, 24.5);
+            option_dropdown(ui, "Stop bits", STOP_BITS, &mut config.stop_bits, 8.0);
+            option_dropdown(ui, "Flow ctrl", FLOW_CTRLS, &mut config.flow_ctrl, 10.0);
+            ui.separator();
+
+            option_dropdown(ui, "DTR", &[false, true], &mut config.dtr, 33.0);
+            option_dropdown(ui, "RTS", &[false, true], &mut config.rts, 33.0);
+            option_dropdown(ui, "Line end", LINE_ENDINGS, &mut config.line_ending, 11.0);
+            option_dropdown(ui, "Terminator", TERMINATORS, &mut config.terminator, 2.0);
+            if config.terminator == LineTerminator::Custom {
+                ui.horizontal(|ui| {
+                    ui.label("Character");
+                    ui.add_space(5.0);
+                    let valid = config.custom_terminator.chars().count() == 1;
+                    let resp = ui.add(egui::TextEdit::singleline(&mut config.custom_terminator)
+                        .char_limit(1)
+                        .text_color_opt((!valid).then_some(ERROR_COLOR))
+                        .desired_width(DROPDOWN_WIDTH - ui.style().spacing.item_spacing.x));
+                    if !valid {
+                        resp.on_hover_text("Enter a single character");
+                    }
+                });
+            }
+            option_dropdown(ui, "Delimiter", DELIMITERS, &mut config.delimiter, 8.0);
+            if config.delimiter == Delimiter::Custom {
+                ui.horizontal(|ui| {
+                    ui.label("Character");
+                    ui.add_space(5.0);
+                    let valid = config.custom_delimiter.chars().count() == 1;
+                    let resp = ui.add(egui::TextEdit::singleline(&mut config.custom_delimiter)
+                        .char_limit(1)
+                        .text_color_opt((!valid).then_some(ERROR_COLOR))
+                        .desired_width(DROPDOWN_WIDTH - ui.style().spacing.item_spacing.x));
+                    if !valid {
+                        resp.on_hover_text("Enter a single character");
+                    }
+                });
+            }
+            option_dropdown(ui, "Decimal", DECIMAL_SEPARATORS, &mut config.decimal, 15.0);
+            option_dropdown(ui, "Checksum", CHECKSUMS, &mut config.checksum, 6.0);
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut config.timestamp_column, "First column is timestamp")
+                    .on_hover_text("Use the first value of every line as the time (device clock, starting at 0) instead of the arrival time. 32-bit counters like millis() are unwrapped");
+                ui.add_enabled_ui(config.timestamp_column, |ui| {
+                    egui::ComboBox::from_id_source("TimestampUnit")
+                        .selected_text(config.timestamp_unit.to_string())
+                        .width(40.0)
+                        .show_ui(ui, |ui| {
+                            for unit in TIMESTAMP_UNITS {
+                                ui.selectable_value(&mut config.timestamp_unit, *unit, unit.to_string());
+                            }
+                        });
+                });
+            });
+            drag_value(ui, "Bad lines", &mut config.max_bad_lines, 0.0, 0..=1000, 0, "");
+            drag_value(ui, "Watchdog", &mut config.watchdog_secs, 0.0, 0.0..=600.0, 1, "s");
+            drag_value(ui, "Read timeout", &mut config.read_timeout_ms, 0.0, 1..=10000, 0, "ms");
+            ui.checkbox(&mut config.auto_reconnect, "Reconnect automatically");
+            ui.add_enabled(config.auto_reconnect, egui::Checkbox::new(&mut config.continue_time, "Continue timestamps"));
+            option_dropdown(ui, "Start mode", START_MODES, &mut config.start_mode, -6.0);
+            if matches!(config.start_mode, StartMode::Delay(_)) {
+                drag_value(ui, "Delay (ms)", &mut config.start_delay, 0.0, 0..=100000, 0, "ms");
+            } else if matches!(config.start_mode, StartMode::Message(_)) {
+                ui.horizontal(|ui| {
+                    ui.label("Message");
+                    ui.add_space(7.0);
+                    let valid = config.start_match != StartMatch::Regex || regex::Regex::new(config.start_msg.trim()).is_ok();
+                    ui.add(egui::TextEdit::singleline(&mut config.start_msg)
+                        .desired_width(DROPDOWN_WIDTH - ui.style().spacing.item_spacing.x)
+                        .text_color_opt((!valid).then_some(ERROR_COLOR)));
+                });
+                option_dropdown(ui, "Match", START_MATCHES, &mut config.start_match, 23.0);
+            }
+            ui.horizontal(|ui| {
+                ui.label("On connect");
+                ui.add_space(-1.0);
+                ui.add(egui::TextEdit::singleline(&mut config.connect_message)
+                    .hint_text("Send nothing")
+                    .desired_width(DROPDOWN_WIDTH - 60.0 - ui.style().spacing.item_spacing.x * 2.0));
+                egui::ComboBox::new("ConnectEnding", "")
+                    .selected_text(config.connect_ending.to_string())
+                    .width(60.0)
+                    .show_ui(ui, |ui| {
+                        for ending in LINE_ENDINGS {
+                            ui.selectable_value(&mut config.connect_ending, *ending, ending.to_string());
+                        }
+                    });
+            });
+            ui.separator();
+
+            self.protocol_settings(ui, &mut config.protocol);
+            self.locked_hint(ui, egui::Rect::from_min_max(start, ui.min_rect().max));
+        });
+    }
+
+    fn protocol_settings(&mut self, ui: &mut Ui, protocol: &mut Protocol) {
+        ui.horizontal(|ui| {
+            ui.label("Protocol");
+            ui.add_space(12.0);
+            egui::ComboBox::new("Protocol", "")
+                .selected_text(protocol.to_string())
+                .width(DROPDOWN_WIDTH)
+                .show_ui(ui, |ui| {
+                    let options = [
+                        Protocol::Ascii,
+                        Protocol::BinaryFrames { sync: vec![0xAA, 0x55], fields: 4, ty: FieldType::F32 }
+                    ];
+                    for option in options {
+                        let selected = std::mem::discriminant(protocol) == std::mem::discriminant(&option);
+                        if ui.selectable_label(selected, option.to_string()).clicked() && !selected {
+                            *protocol = option;
+                        }
+                    }
+                });
+        });
+        let Protocol::BinaryFrames { sync, fields, ty } = protocol else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.label("Sync");
+            ui.add_space(31.0);
+            let text = self.sync_bytes.get_or_insert_with(|| sync.iter()
+                .map(|n| format!("{:02X}", n))
+                .collect::<Vec<String>>()
+                .join(" "));
+            let resp = ui.add(egui::TextEdit::singleline(text)
+                .hint_text("e.g. AA 55")
+                .desired_width(DROPDOWN_WIDTH - ui.style().spacing.item_spacing.x));
+            if resp.changed() {
+                let bytes: Result<Vec<u8>, _> = text.split_whitespace()
+                    .map(|n| u8::from_str_radix(n.trim_start_matches("0x"), 16))
+                    .collect();
+                if let Ok(bytes) = bytes {
+                    *sync = bytes;
+                }
+            }
+            if !resp.has_focus() {
+                self.sync_bytes = None;
+            }
+        });
+        drag_value(ui, "Fields", fields, 25.0, 1..=64, 0, "");
+        option_dropdown(ui, "Type", FIELD_TYPES, ty, 30.0);
+        let size = sync.len() + *fields * ty.size();
+        ui.label(egui::RichText::new(format!("Little-endian, {} bytes per frame", size)).weak());
+    }
+
+    fn locked_hint(&mut self, ui: &Ui, rect: egui::Rect) {
+        if self.locked && ui.input(|i| i.pointer.any_click()) && ui.rect_contains_pointer(rect) {
+            self.set_notification(Notification::new("Configuration locked", Duration::from_secs(2), NotificationType::Warning), true);
+        }
+    }
+
+    fn connect_button(&mut self, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        let connect_btn_text = match (app.is_connected(), app.is_reconnecting()) {
+            (true, _) => "Disconnect",
+            (false, true) => "Cancel",
+            (false, false) => "Connect"
+        };
+        let connect_resp = ui.add_enabled(
+            app.can_connect() || app.is_reconnecting(),
+            egui::Button::new(connect_btn_text).min_size(egui::Vec2::new(86.0, 0.0)));
+        if connect_resp.clicked() {
+            self.run_command(app, Command::Connect);
+        }
+    }
+
+    fn control_buttons(&mut self, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        let lines = app.control_lines();
+        let config = app.conn_config();
+        let (mut dtr, mut rts) = lines.unwrap_or((config.dtr, config.rts));
+        let mut result = None;
+        ui.add_enabled_ui(lines.is_some(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.toggle_value(&mut dtr, "DTR").on_hover_text("Data Terminal Ready").clicked() {
+                    result = Some(app.set_dtr(dtr));
+                }
+                if ui.toggle_value(&mut rts, "RTS").on_hover_text("Request To Send").clicked() {
+                    result = Some(app.set_rts(rts));
+                }
+                if ui.button("Break").on_hover_text(format!("Send a break signal ({:?})", ControlLine::BREAK)).clicked() {
+                    result = Some(app.send_break());
+                }
+            });
+            if ui.button("Reset target (ESP32 sequence)").on_hover_text("Pulse RTS with DTR off to restart an ESP32 into its program").clicked() {
+                result = Some(app.reset_target());
+            }
+        }).response.on_disabled_hover_text("Only available while connected to a serial port");
+        if let Some(Err(e)) = result {
+            self.set_notification(Notification::new(&e.to_string(), Duration::from_secs(5), NotificationType::Warning), true);
+        }
+    }
+
+    fn quick_connect(&mut self, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        let connected = app.is_connected();
+        let devices = app.available_devices();
+        let config = app.conn_config();
+        let locked = self.locked;
+        ui.add_enabled_ui(!connected && !locked, |ui| {
+            egui::ComboBox::new("QuickPort", "")
+                .selected_text(config.port.clone())
+                .width(DROPDOWN_WIDTH * 0.75)
+                .show_ui(ui, |ui| {
+                    for device in &devices {
+                        ui.selectable_value(&mut config.port, device.clone(), device);
+                    }
+                });
+            egui::ComboBox::new("QuickBaud", "")
+                .selected_text(config.baud_rate.to_string())
+                .width(DROPDOWN_WIDTH * 0.5)
+                .show_ui(ui, |ui| {
+                    for baud in &baud_options(&self.custom_bauds, config.baud_rate) {
+                        ui.selectable_value(&mut config.baud_rate, *baud, baud.to_string());
+                    }
+                });
+        });
+        self.connect_button(ui, app);
+        status_dot(ui, connected);
+    }
+
+    fn plot_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Plot Settings");
+                ui.add_space(ui.available_width());
+            });
+            ui.separator();
+
+            let config = app.plot_config_mut();
+            option_dropdown(ui, "Mode", PLOT_MODES, &mut config.mode, 24.0);
+            drag_value(ui, "Window (s)", &mut config.window, -3.5, 0.0..=config.history_secs, 2, "s");
+            if config.mode == PlotMode::Cyclic {
+                let names: Vec<String> = app.input_slots().iter().map(|n| n.name.clone()).collect();
+                let config = app.plot_config_mut();
+                ui.horizontal(|ui| {
+                    ui.label("Sync to");
+                    ui.add_space(17.0);
+                    let selected = config.sync_channel
+                        .and_then(|n| names.get(n).cloned())
+                        .unwrap_or(String::from("Off"));
+                    egui::ComboBox::new("SyncChannel", "")
+                        .selected_text(selected)
+                        .width(DROPDOWN_WIDTH - 20.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut config.sync_channel, None, "Off");
+                            for (i, name) in names.iter().enumerate() {
+                                ui.selectable_value(&mut config.sync_channel, Some(i), name);
+                            }
+                        });
+                });
+                if config.sync_channel.is_some() {
+                    drag_value(ui, "Threshold", &mut config.sync_threshold, 2.0, f64::MIN..=f64::MAX, 2, "");
+                }
+            }
+            let config = app.plot_config_mut();
+            option_dropdown(ui, "Scale", SCALE_MODES, &mut config.scale_mode, 29.0);
+            if config.scale_mode == PlotScaleMode::Manual {
+                drag_value(ui, "Min", &mut config.y_min, 36.0, f64::MIN..=config.y_max, 2, "");
+                drag_value(ui, "Max", &mut config.y_max, 33.5, config.y_min..=f64::MAX, 2, "");
+            }
+            drag_value(ui, "History", &mut config.history_secs, 15.0, 1.0..=86400.0, 0, "s");
+            config.window = config.window.min(config.history_secs);
+            let mut decimate = config.decimation.is_some();
+            ui.checkbox(&mut decimate, "Decimate input")
+                .on_hover_text("Store fewer samples of fast streams, applies to new samples and does not affect the console");
+            match (decimate, config.decimation.is_some()) {
+                (true, false) => config.decimation = Some(Decimation::default()),
+                (false, true) => config.decimation = None,
+                _ => {}
+            }
+            if let Some(decimation) = &mut config.decimation {
+                option_dropdown(ui, "Keep", DECIMATION_MODES, &mut decimation.mode, 30.0);
+                match decimation.mode {
+                    DecimationMode::Every => drag_value(ui, "N", &mut decimation.every, 44.0, 2..=100000, 0, ""),
+                    DecimationMode::Average => drag_value(ui, "Bucket", &mut decimation.bucket_ms, 22.0, Decimation::MIN_BUCKET_MS..=60000.0, 2, "ms")
+                }
+            }
+            drag_value(ui, "Console", &mut config.console_lines, 11.0, 16..=1000000, 0, " lines");
+            drag_value(ui, "Memory", &mut config.memory_budget, 10.0, 1.0..=16384.0, 0, "MB");
+            drag_value(ui, "Stale after", &mut config.stale_after, -1.0, 0.1..=3600.0, 1, "s");
+            ui.checkbox(&mut config.hide_new_channels, "Hide new channels in existing plots")
+                .on_hover_text("Channels that appear while connected start hidden and do not affect autoscaling");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut config.raw_lines, "Keep raw lines for")
+                    .on_hover_text("Keep the received line of every parsed row to show it when hovering plots and in exports");
+                ui.add_enabled(config.raw_lines, egui::DragValue::new(&mut config.raw_retention).clamp_range(1.0..=86400.0).suffix("s"));
+            });
+        });
+    }
+
+    fn parser_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(egui::RichText::new("Parser").heading())
+                .id_source("ParserPanel")
+                .show(ui, |ui| {
+                    let discarded = app.discarded_frames();
+                    let config = app.frame_config_mut();
+                    option_dropdown(ui, "Frame", FRAME_MODES, &mut config.mode, 20.0);
+                    match config.mode {
+                        FrameMode::Marker => {
+                            ui.horizontal(|ui| {
+                                ui.label("End marker");
+                                egui::TextEdit::singleline(&mut config.marker).desired_width(DROPDOWN_WIDTH - 30.0).show(ui);
+                            });
+                        },
+                        FrameMode::LineCount => drag_value(ui, "Lines", &mut config.lines, 42.0, 1..=64, 0, ""),
+                        FrameMode::Line => {}
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Mark prefix");
+                        egui::TextEdit::singleline(&mut config.mark_prefix).desired_width(DROPDOWN_WIDTH - 30.0).show(ui);
+                    }).response.on_hover_text("Lines starting with this prefix add a marker instead of values (empty to disable)");
+                    if config.mode == FrameMode::Line {
+                        ui.checkbox(&mut config.strict_columns, "Strict columns")
+                            .on_hover_text("Reject lines with a different number of values instead of adding or leaving out columns");
+                    }
+                    ui.scope(|ui| drag_value(ui, "Max channels", &mut config.max_columns, 6.0, 1..=4096, 0, ""))
+                        .response.on_hover_text("Lines with more values are ignored so a misbehaving device can not create thousands of slots");
+                    if config.mode != FrameMode::Line && discarded > 0 {
+                        ui.label(egui::RichText::new(format!("{} incomplete frame(s) discarded", discarded)).weak());
+                    }
+                    let config = config.clone();
+                    egui::CollapsingHeader::new("Preview")
+                        .id_source("ParserPreview")
+                        .show(ui, |ui| self.parser_preview(ui, &config, app));
+                    ui.separator();
+                    ui.label("Event channels");
+                    let mut remove = None;
+                    for (i, channel) in app.event_channels_mut().iter_mut().enumerate() {
+                        ui.push_id(("EventChannel", i), |ui| {
+                            ui.separator();
+                            ui.horizontal(|ui| {
+                                egui::TextEdit::singleline(&mut channel.slot.name).desired_width(DROPDOWN_WIDTH).show(ui);
+                                if ui.small_button("x").clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                egui::TextEdit::singleline(&mut channel.pattern)
+                                    .hint_text("Pattern")
+                                    .desired_width(DROPDOWN_WIDTH - 10.0)
+                                    .show(ui);
+                                ui.checkbox(&mut channel.regex, "Re");
+                            });
+                            drag_value(ui, "Bucket", &mut channel.bucket, 5.0, 0.01..=60.0, 2, "s");
+                        });
+                    }
+                    if let Some(index) = remove {
+                        app.event_channels_mut().remove(index);
+                    }
+                    ui.separator();
+                    if ui.button("Add event channel").clicked() {
+                        app.add_event_channel();
+                    }
+                });
+        });
+    }
+
+    fn parse_stats_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(egui::RichText::new("Parse diagnostics").heading())
+                .id_source("ParseStatsPanel")
+                .show(ui, |ui| {
+                    let stats = app.parse_stats();
+                    egui::Grid::new("ParseStatsGrid").num_columns(2).show(ui, |ui| {
+                        for (label, value) in [("Lines", stats.lines), ("Parsed", stats.parsed), ("Ignored values", stats.dropped_tokens), ("Column mismatches", stats.mismatches)] {
+                            ui.label(label);
+                            ui.label(value.to_string());
+                            ui.end_row();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        let rejected = app.rejected_lines().len();
+                        if ui.add_enabled(rejected > 0, egui::Button::new(format!("Rejected lines ({})", rejected))).clicked() {
+                            self.rejected_open = true;
+                        }
+                        if ui.button("Reset").clicked() {
+                            app.reset_parse_stats();
+                        }
+                    });
+                });
+        });
+    }
+
+    fn parser_preview(&mut self, ui: &mut Ui, config: &FrameConfig, app: &SerialMonitorApp) {
+        ui.checkbox(&mut self.preview_custom, "Use sample lines");
+        let lines: Vec<&str> = match self.preview_custom {
+            true => {
+                egui::TextEdit::multiline(&mut self.preview_text)
+                    .hint_text("Paste sample lines")
+                    .desired_rows(4)
+                    .desired_width(f32::INFINITY)
+                    .code_editor()
+                    .show(ui);
+                self.preview_text.lines().collect()
+            },
+            false => {
+                let mut lines: Vec<&str> = app.console_lines().iter()
+                    .rev()
+                    .filter(|n| !n.sent)
+                    .take(PREVIEW_LINES)
+                    .map(|n| n.content.as_str())
+                    .collect();
+                lines.reverse();
+                lines
+            }
+        };
+        if lines.is_empty() {
+            ui.label(egui::RichText::new("No lines received yet").weak());
+            return;
+        }
+
+        let results = SerialParser::dry_run(config, app.separators(), &lines);
+        for (line, result) in lines.iter().zip(results) {
+            ui.label(egui::RichText::new(*line).monospace().small());
+            let text = match &result {
+                ParsePreview::Values(values) => values.iter()
+                    .map(|(name, value)| format!("{} = {}", name, value))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                ParsePreview::Pending(name, value) => format!("{} = {} (frame pending)", name, value),
+                ParsePreview::NoMatch => String::from("No match"),
+                ParsePreview::Error(e) => e.to_string()
+            };
+            let text = egui::RichText::new(format!("\u{2192} {}", text)).small();
+            match result {
+                ParsePreview::Values(_) => ui.label(text),
+                ParsePreview::Pending(..) => ui.label(text.weak()),
+                _ => ui.colored_label(ERROR_COLOR, text)
+            };
+        }
+    }
+
+    fn presets_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(egui::RichText::new("Send").heading())
+                .id_source("PresetsPanel")
+                .show(ui, |ui| {
+                    match self.preset_row(ui, app) {
+                        Some(RowButton::Preset(index)) => self.send_preset(app, index, false),
+                        Some(RowButton::Sequence(index)) => app.start_sequence(index),
+                        None => ()
+                    }
+                    if ui.button("Edit presets").clicked() {
+                        self.presets_open = !self.presets_open;
+                    }
+                });
+        });
+    }
+
+    fn disconnect_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(egui::RichText::new("On disconnect").heading())
+                .id_source("DisconnectPanel")
+                .show(ui, |ui| {
+                    let policy = app.disconnect_policy_mut();
+                    ui.checkbox(&mut policy.clear_samples, "Clear samples");
+                    ui.checkbox(&mut policy.clear_console, "Clear console");
+                    ui.checkbox(&mut policy.reset_parser, "Reset parser columns");
+                    ui.checkbox(&mut policy.reset_plot_ranges, "Reset plot ranges");
+                    ui.checkbox(&mut policy.reset_stats, "Reset statistics");
+                });
+        });
+    }
+
+    fn devices_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(egui::RichText::new("Devices").heading())
+                .id_source("DevicesPanel")
+                .show(ui, |ui| {
+                    let ports = app.available_devices();
+                    let mut remove = None;
+                    let mut toggle = None;
+                    let open = app.connected_devices();
+                    let connected: Vec<bool> = app.devices_mut().iter().map(|n| open.contains(&n.port)).collect();
+                    for (i, config) in app.devices_mut().iter_mut().enumerate() {
+                        ui.push_id(("Device", i), |ui| {
+                            ui.horizontal(|ui| {
+                                status_dot(ui, connected[i]);
+                                ui.add_enabled_ui(!connected[i], |ui| {
+                                    egui::ComboBox::new("Port", "")
+                                        .selected_text(&config.port)
+                                        .width(70.0)
+                                        .show_ui(ui, |ui| {
+                                            for port in &ports {
+                                                ui.selectable_value(&mut config.port, port.clone(), port);
+                                            }
+                                        });
+                                    egui::ComboBox::new("Baud", "")
+                                        .selected_text(config.baud_rate.to_string())
+                                        .width(70.0)
+                                        .show_ui(ui, |ui| {
+                                            for baud in &baud_options(&self.custom_bauds, config.baud_rate) {
+                                                ui.selectable_value(&mut config.baud_rate, *baud, baud.to_string());
+                                            }
+                                        });
+                                });
+                                let text = match connected[i] {
+                                    true => "Disconnect",
+                                    false => "Connect"
+                                };
+                                let enabled = connected[i] || config.port != ConnectionConfig::NO_PORT;
+                                if ui.add_enabled(enabled, egui::Button::new(text)).clicked() {
+                                    toggle = Some(i);
+                                }
+                                if ui.add_enabled(!connected[i], egui::Button::new("x")).clicked() {
+                                    remove = Some(i);
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Clock offset");
+                                ui.add(egui::DragValue::new(&mut config.clock_offset_ms).speed(1.0).suffix("ms"))
+                                    .on_hover_text("Added to the timestamps of this device to align it with the main connection");
+                            });
+                            ui.horizontal(|ui| {
+                                ui.add_enabled(!connected[i], egui::Checkbox::new(&mut config.timestamp_column, "First column is timestamp"));
+                                ui.add_enabled_ui(!connected[i] && config.timestamp_column, |ui| {
+                                    egui::ComboBox::new(("DeviceTimestampUnit", i), "")
+                                        .selected_text(config.timestamp_unit.to_string())
+                                        .width(40.0)
+                                        .show_ui(ui, |ui| {
+                                            for unit in TIMESTAMP_UNITS {
+                                                ui.selectable_value(&mut config.timestamp_unit, *unit, unit.to_string());
+                                            }
+                                        });
+                                });
+                            });
+                        });
+                    }
+                    if let Some(i) = toggle {
+                        let port = app.devices_mut()[i].port.clone();
+                        match connected[i] {
+                            true => app.disconnect_device(&port),
+                            false => if let Err(e) = app.connect_device(i) {
+                                self.set_notification(Notification::new(
+                                    &format!("Could not connect {}! ({})", port, e),
+                                    Duration::from_secs(5),
+                                    NotificationType::Error
+                                ), false);
+                            }
+                        }
+                    }
+                    if let Some(i) = remove {
+                        app.devices_mut().remove(i);
+                    }
+                    if ui.button("Add device").clicked() {
+                        let mut config = app.conn_config().clone();
+                        config.port = String::from(ConnectionConfig::NO_PORT);
+                        app.devices_mut().push(config);
+                    }
+                    match app.capture_time() {
+                        Some(t) => {
+                            ui.horizontal(|ui| {
+                                ui.label(format!("Capture clock {:.1}s", t));
+                                if ui.button("Restart capture").on_hover_text("Clear all data and restart the shared clock at 0").clicked() {
+                                    self.run_command(app, Command::RestartCapture);
+                                }
+                            });
+                        },
+                        None => {
+                            ui.label(egui::RichText::new("Devices connect once the main connection is open and share its clock").weak());
+                        }
+                    }
+                });
+        });
+    }
+
+    fn log_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(egui::RichText::new("Logging").heading())
+                .id_source("LogPanel")
+                .show(ui, |ui| {
+                    ui.set_enabled(!app.is_connected());
+                    let config = app.log_config_mut();
+                    ui.checkbox(&mut config.enabled, "Log to file on connect");
+                    ui.checkbox(&mut config.lock_config, "Lock configuration while logging");
+                    option_dropdown(ui, "Format", LOG_FORMATS, &mut config.format, 15.0);
+                    if config.format == LogFormat::Raw {
+                        ui.checkbox(&mut config.timestamps, "Prefix lines with timestamp");
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Folder");
+                        ui.add_space(19.0);
+                        let folder = match config.directory.chars().count() {
+                            0 => String::from("Working directory"),
+                            n if n > 20 => format!("\u{2026}{}", config.directory.chars().skip(n - 19).collect::<String>()),
+                            _ => config.directory.clone()
+                        };
+                        let resp = ui.add(egui::Button::new(folder).min_size(egui::Vec2::new(DROPDOWN_WIDTH - ui.style().spacing.item_spacing.x, 0.0)))
+                            .on_hover_text(&config.directory);
+                        if resp.clicked() {
+                            self.dialog = Some(PendingDialog::LogFolder);
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Name");
+                        ui.add_space(22.0);
+                        ui.add(egui::TextEdit::singleline(&mut config.pattern).desired_width(DROPDOWN_WIDTH - ui.style().spacing.item_spacing.x))
+                            .on_hover_text("{timestamp} (UTC, YYYYMMDD_HHMMSS) and {index} are replaced for each file");
+                    });
+                    ui.separator();
+                    option_dropdown(ui, "Rotate", LOG_ROTATIONS, &mut config.rotation, 14.0);
+                    match config.rotation {
+                        LogRotation::Duration => drag_value(ui, "Every", &mut config.rotate_minutes, 21.0, 1.0..=10080.0, 0, "min"),
+                        LogRotation::Size => drag_value(ui, "Every", &mut config.rotate_mb, 21.0, 1.0..=16384.0, 0, "MB"),
+                        LogRotation::Never => {}
+                    }
+                    if config.rotation != LogRotation::Never {
+                        drag_value(ui, "Keep last", &mut config.keep_files, -2.0, 0..=1000, 0, "")
+                    }
+                    ui.separator();
+                    let black_box = app.black_box_mut();
+                    ui.checkbox(&mut black_box.enabled, "Keep a black box for crash recovery")
+                        .on_hover_text("Keeps the last parsed rows on disk, independent of logging");
+                    if black_box.enabled {
+                        drag_value(ui, "Last", &mut black_box.seconds, 26.0, 1.0..=3600.0, 0, "s");
+                        drag_value(ui, "Max size", &mut black_box.max_mb, -3.0, 1.0..=1024.0, 0, "MB");
+                        drag_value(ui, "Sync every", &mut black_box.sync_seconds, -10.0, 0.1..=60.0, 1, "s");
+                    }
+                });
+        });
+    }
+
+    fn appearance_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(egui::RichText::new("Appearance").heading())
+                .id_source("AppearancePanel")
+                .show(ui, |ui| {
+                    let config = app.ui_config_mut();
+                    option_dropdown(ui, "Theme", THEMES, &mut config.theme, 21.0);
+                    drag_value(ui, "Line width", &mut config.line_width, 1.0, UiConfig::LINE_WIDTHS, 1, "");
+                    ui.horizontal(|ui| {
+                        let mut custom = config.plot_background.is_some();
+                        ui.checkbox(&mut custom, "Plot background");
+                        let background = ui.visuals().extreme_bg_color;
+                        if custom != config.plot_background.is_some() {
+                            config.plot_background = custom.then(|| [background.r(), background.g(), background.b()].map(|n| n as f32 / 255.0));
+                        }
+                        if let Some(color) = &mut config.plot_background {
+                            ui.color_edit_button_rgb(color);
+                        }
+                    });
+                });
+        });
+    }
+
+    fn apply_theme(&self, ctx: &egui::Context, frame: &eframe::Frame, theme: Theme) {
+        let dark = match theme {
+            Theme::Dark => true,
+            Theme::Light => false,
+            Theme::System => frame.info().system_theme != Some(eframe::Theme::Light)
+        };
+        if ctx.style().visuals.dark_mode != dark {
+            ctx.set_visuals(match dark {
+                true => egui::Visuals::dark(),
+                false => egui::Visuals::light()
+            });
+        }
+    }
+
+    fn input_panel(&mut self, ctx: &egui::Context, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        ui.add_space(5.0);
+        let frame = egui::Frame::window(&ctx.style())
+            .rounding(2.0);
+        frame.show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Serial Input");
+                if ui.small_button("Slots").on_hover_text("Reorder, remap and delete input slots").clicked() {
+                    self.run_command(app, Command::Slots);
+                }
+                let decimated = app.is_connected() && app.plot_config().decimation.is_some();
+                if app.bad_checksums() > 0 || decimated {
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if app.bad_checksums() > 0 {
+                            ui.colored_label(ERROR_COLOR, format!("{} bad lines", app.bad_checksums()))
+                                .on_hover_text("Lines that failed the checksum since connecting, their values are not plotted");
+                        }
+                        if decimated {
+                            ui.label(format!("Stored {:.1} Hz", app.stored_rate()))
+                                .on_hover_text("Highest rate of stored samples per channel after decimation");
+                        }
+                    });
+                }
+                ui.add_space(ui.available_width());
+            });
+            ui.separator();
+
+            if app.is_connected() && (app.has_input() || !app.event_channels_mut().is_empty()) {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    let columns = app.input_columns();
+                    let plot_ids: Vec<usize> = app.plots().iter()
+                        .filter(|n| n.is_graph())
+                        .map(|n| n.id)
+                        .collect();
+                    let ages: Vec<(Option<f64>, bool)> = app.channels().into_iter()
+                        .map(|(slot, values)| (app.sample_age(values), app.is_stale(slot, values)))
+                        .collect();
+                    let anomalies: Vec<usize> = (0..columns)
+                        .map(|i| app.anomalies(i).map_or(0, |n| n.count))
+                        .collect();
+                    let window = app.plot_config().window;
+                    let plots: Vec<&PlotData> = app.plots().iter().filter(|n| n.kind == PlotKind::Time).collect();
+                    let stats: Vec<Option<(f64, f64, f64, f64)>> = (0..columns)
+                        .map(|i| match plots.is_empty() || plots.iter().any(|n| n.shows(i) && !n.hidden.contains(&i)) {
+                            true => app.slot_stats(i).and_then(|n| Some((n.min()?, n.max()?, n.mean()?, n.rate()))),
+                            false => None
+                        })
+                        .collect();
+                    let sequences: Vec<String> = app.sequences_mut().iter().map(|n| n.name.clone()).collect();
+                    let alarms: HashMap<usize, String> = app.active_alarms().into_iter().map(|(i, n)| (i, n.to_owned())).collect();
+                    let mut acknowledge = None;
+                    let mut loss = app.loss_config_mut().clone();
+                    self.selected_slots.retain(|n| *n < columns);
+                    if !self.selected_slots.is_empty() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{} selected", self.selected_slots.len()));
+                            if ui.button("Edit").clicked() {
+                                self.bulk_edit.get_or_insert_with(BulkEdit::default);
+                            }
+                            if ui.button("Clear").clicked() {
+                                self.selected_slots.clear();
+                            }
+                        });
+                    }
+                    let selection_color = ui.visuals().selection.bg_fill.gamma_multiply(0.4);
+                    for (i, slot) in app.input_slots_mut().iter_mut().enumerate() {
+                        if i < columns {
+                            let background = ui.painter().add(egui::Shape::Noop);
+                            let row = ui.horizontal(|ui| {
+                                ui.color_edit_button_rgb(&mut slot.color);
+                                let name = egui::TextEdit::singleline(&mut slot.name).desired_width(100.0).show(ui);
+                                if self.slot_focus == Some(i) {
+                                    self.slot_focus = None;
+                                    name.response.request_focus();
+                                }
+                                ui.menu_button("\u{2699}", |ui| {
+                                    close_menu_on_escape(ui);
+                                    unit_settings(ui, slot);
+                                    ui.separator();
+                                    scale_settings(ui, slot);
+                                }).response.on_hover_text("Unit, precision, scale and offset");
+                                ui.separator();
+                                let (age, stale) = ages.get(i).copied().unwrap_or_default();
+                                let mut value = egui::RichText::new(slot.format(slot.apply(slot.value)));
+                                if stale {
+                                    value = value.weak();
+                                }
+                                let value = ui.label(value);
+                                if let Some(age) = age {
+                                    value.on_hover_text(format!("Last update {:.1}s ago", age));
+                                }
+                                if slot.time_shift_ms != 0.0 {
+                                    ui.label(egui::RichText::new(format!("{:+.0}ms", slot.time_shift_ms)).small().weak())
+                                        .on_hover_text("Time shift active");
+                                }
+                                if let Some(deadband) = &slot.deadband {
+                                    let threshold = match deadband.percent {
+                                        true => format!("{}%", deadband.threshold),
+                                        false => deadband.threshold.to_string()
+                                    };
+                                    ui.label(egui::RichText::new("filtered").small().weak())
+                                        .on_hover_text(format!("Deadband \u{b1}{}, keep alive {}s", threshold, deadband.keep_alive));
+                                }
+                                if loss.as_ref().is_some_and(|n| n.slot == i) {
+                                    ui.label(egui::RichText::new("counter").small().weak())
+                                        .on_hover_text("Sequence counter for packet loss");
+                                }
+                                if let Some(anomaly) = &slot.anomaly {
+                                    let count = anomalies.get(i).copied().unwrap_or_default();
+                                    let mut text = egui::RichText::new(format!("{} anomalies", count)).small();
+                                    text = match count {
+                                        0 => text.weak(),
+                                        _ => text.color(ERROR_COLOR)
+                                    };
+                                    ui.label(text)
+                                        .on_hover_text(format!("Values beyond {}\u{3c3} of the last {}s", anomaly.sigma, anomaly.window));
+                                }
+                                if let Some(msg) = alarms.get(&i) {
+                                    if ui.small_button("Ack").on_hover_text(msg).clicked() {
+                                        acknowledge = Some(i);
+                                    }
+                                }
+                                ui.add_space(10.0);
+                            });
+                            let row_resp = row.response.interact(egui::Sense::click());
+                            if row_resp.clicked() {
+                                self.select_slot(i, ui.input(|i| i.modifiers));
+                            }
+                            if alarms.contains_key(&i) {
+                                ui.painter().set(background, egui::Shape::rect_filled(row_resp.rect, 2.0, ERROR_COLOR.gamma_multiply(0.35)));
+                            } else if self.selected_slots.contains(&i) {
+                                ui.painter().set(background, egui::Shape::rect_filled(row_resp.rect, 2.0, selection_color));
+                            }
+                            row_resp.context_menu(|ui| {
+                                self.slot_menu(ui, i, slot, &plot_ids, &sequences, &mut loss);
+                            });
+                            if let Some(Some((min, max, mean, rate))) = stats.get(i).copied() {
+                                let (min, max) = (slot.apply(min), slot.apply(max));
+                                let p = slot.precision as usize;
+                                let text = format!("min {:.*}  max {:.*}  mean {:.*}  {:.1}/s", p, f64::min(min, max), p, f64::max(min, max), p, slot.apply(mean), rate);
+                                ui.label(egui::RichText::new(text).small().weak())
+                                    .on_hover_text(format!("Statistics over the last {}s", window));
+                            }
+                        }
+                    }
+                    if loss != *app.loss_config_mut() {
+                        *app.loss_config_mut() = loss;
+                    }
+                    if acknowledge.is_some() {
+                        app.acknowledge_alarm(acknowledge);
+                    }
+                    for channel in app.event_channels_mut() {
+                        ui.horizontal(|ui| {
+                            ui.color_edit_button_rgb(&mut channel.slot.color);
+                            egui::TextEdit::singleline(&mut channel.slot.name).desired_width(100.0).show(ui);
+                            ui.separator();
+                            ui.label(format!("{:.2}/s", channel.slot.value))
+                                .on_hover_text(format!("Event channel ({})", channel.pattern));
+                            ui.add_space(10.0);
+                        });
+                    }
+                    for derived in app.derived_slots_mut() {
+                        ui.horizontal(|ui| {
+                            ui.color_edit_button_rgb(&mut derived.slot.color);
+                            egui::TextEdit::singleline(&mut derived.slot.name).desired_width(100.0).show(ui);
+                            ui.separator();
+                            ui.label(format!("{:.2}", derived.slot.value))
+                                .on_hover_text(format!("Derived channel ({})", derived.expression));
+                            ui.add_space(10.0);
+                        });
+                    }
+                });
+            } else if let Some(elapsed) = app.connection_time() {
+                ui.label(format!("Waiting for data on {}\u{2026} ({:.1}s)", app.connected_port(), elapsed.as_secs_f32()));
+            } else {
+                ui.label("Not connected");
+            }
+            ui.separator();
+            self.derived_slots(ui, app);
+        });
+    }
+
+    fn derived_slots(&mut self, ui: &mut Ui, app: &mut SerialMonitorApp) {
+        let errors: Vec<Option<String>> = (0..app.derived_slots_mut().len())
+            .map(|i| app.derived_error(i).map(str::to_owned))
+            .collect();
+        let mut remove = None;
+        for (i, derived) in app.derived_slots_mut().iter_mut().enumerate() {
+            ui.push_id(("DerivedSlot", i), |ui| {
+                ui.horizontal(|ui| {
+                    ui.color_edit_button_rgb(&mut derived.slot.color);
+                    egui::TextEdit::singleline(&mut derived.slot.name).desired_width(100.0).show(ui);
+                    if ui.small_button("x").clicked() {
+                        remove = Some(i);
+                    }
+                });
+                egui::TextEdit::singleline(&mut derived.expression)
+                    .hint_text("e.g. slot1 * slot2")
+                    .code_editor()
+                    .desired_width(f32::INFINITY)
+                    .show(ui)
+                    .response
+                    .on_hover_text("Reference inputs by name or as slot1, slot2, \u{2026}\nSupports + - * / ( ) abs sqrt min max");
+                if let Some(Some(e)) = errors.get(i).filter(|_| !derived.expression.is_empty()) {
+                    ui.colored_label(ERROR_COLOR, egui::RichText::new(e).small());
+                }
+            });
+        }
+        if let Some(index) = remove {
+            app.derived_slots_mut().remove(index);
+        }
+        if ui.button("Add derived channel").clicked() {
+            app.add_derived_slot();
+        }
+    }
+
+    fn data_panel(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let collapsed = app.sidepanel_collapsed_mut();
+                let (chevron, hint) = match *collapsed {
+                    true => ("\u{bb}", "Show side panel"),
+                    false => ("\u{ab}", "Hide side panel")
+                };
+                if ui.button(chevron).on_hover_text(hint).clicked() {
+                    *collapsed = !*collapsed;
+                }
+                self.quick_connect(ui, app);
+                let lock_text = match self.locked {
+                    true => "\u{1f512}",
+                    false => "\u{1f513}"
+                };
+                let lock_hint = match self.locked {
+                    true => "Unlock configuration (stays locked while logging if enabled)",
+                    false => "Lock configuration"
+                };
+                if ui.selectable_label(self.locked, lock_text).on_hover_text(lock_hint).clicked() {
+                    self.run_command(app, Command::Lock);
+                }
+                let audio = app.audio_config_mut();
+                let audio_text = match audio.enabled {
+                    true => "\u{1f50a}",
+                    false => "\u{1f507}"
+                };
+                let audio_resp = ui.selectable_label(audio.enabled, audio_text)
+                    .on_hover_text("Audio feedback: tick per received frame, tone above 50 frames/s");
+                if audio_resp.clicked() {
+                    audio.enabled = !audio.enabled;
+                }
+                audio_resp.context_menu(|ui| {
+                    close_menu_on_escape(ui);
+                    ui.horizontal(|ui| {
+                        ui.label("Tick every");
+                        ui.add(egui::DragValue::new(&mut app.audio_config_mut().every).clamp_range(1..=1000).suffix(" frames"));
+                    });
+                });
+                ui.separator();
+                let locked = self.locked;
+                let resp = ui.add_enabled_ui(!locked, |ui| {
+                    if ui.button("Add Plot").clicked() {
+                        self.run_command(app, Command::AddPlot);
+                    }
+                    if ui.button("Add Watch").clicked() {
+                        self.run_command(app, Command::AddWatch);
+                    }
+                    if ui.button("Add Spectrum").clicked() {
+                        self.run_command(app, Command::AddSpectrum);
+                    }
+                    if ui.button("Add Table").clicked() {
+                        self.run_command(app, Command::AddTable);
+                    }
+                    let btn = egui::Button::new("Add Console");
+                    if ui.add_enabled(!app.has_console(), btn).clicked() {
+                        self.run_command(app, Command::AddConsole);
+                    }
+                }).response;
+                self.locked_hint(ui, resp.rect);
+                let btn_text = match app.is_paused() {
+                    true => "Continue",
+                    false => "Pause"
+                };
+                let btn = egui::Button::new(btn_text);
+                if ui.add_enabled(app.is_connected(), btn).clicked() {
+                    self.run_command(app, Command::Pause);
+                }
+                ui.add_enabled_ui(app.is_connected(), |ui| {
+                    let resp = ui.add(egui::TextEdit::singleline(&mut self.marker_text)
+                        .hint_text("Marker label")
+                        .desired_width(100.0));
+                    let enter = resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if ui.button("Mark").on_hover_text("Mark the current time on all plots (F2)").clicked() || enter {
+                        self.run_command(app, Command::AddMarker);
+                    }
+                });
+                let recording = app.is_recording();
+                let record_text = match recording {
+                    true => egui::RichText::new("\u{23fa} Stop").color(ERROR_COLOR),
+                    false => egui::RichText::new("\u{23fa} Record")
+                };
+                let record_state = self.command_state(app, Command::Record);
+                let record_hint = match record_state {
+                    Ok(_) => "Record all received lines with their timestamps to a session file (right click to replay one)",
+                    Err(_) => "Connect to record, right click to replay a recording"
+                };
+                let record_resp = ui.add(egui::SelectableLabel::new(recording, record_text))
+                    .on_hover_text(record_hint);
+                let mut open_recording = false;
+                record_resp.context_menu(|ui| {
+                    close_menu_on_escape(ui);
+                    option_dropdown(ui, "Replay speed", REPLAY_SPEEDS, app.replay_speed_mut(), 0.0);
+                    if ui.button("Open recording\u{2026}").clicked() {
+                        open_recording = true;
+                        ui.close_menu();
+                    }
+                });
+                if record_resp.clicked() && record_state.is_ok() {
+                    self.run_command(app, Command::Record);
+                }
+                if open_recording {
+                    self.run_command(app, Command::OpenRecording);
+                }
+                if ui.button("Clear Data").on_hover_text("Clear all samples, console lines and statistics").clicked() {
+                    self.run_command(app, Command::ClearData);
+                }
+                if ui.button("Sequences").clicked() {
+                    self.run_command(app, Command::Sequences);
+                }
+                let save_resp = ui.button("Save Config");
+                save_resp.context_menu(|ui| {
+                    close_menu_on_escape(ui);
+                    let backup = app.config_backup_mut();
+                    ui.checkbox(&mut backup.enabled, "Keep backups of the previous file");
+                    ui.add_enabled_ui(backup.enabled, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Keep last");
+                            ui.add(egui::DragValue::new(&mut backup.keep).clamp_range(1..=100));
+                        });
+                    });
+                    ui.separator();
+                    let autosave = app.autosave_mut();
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut autosave.enabled, "Autosave every");
+                        ui.add_enabled(autosave.enabled, egui::DragValue::new(&mut autosave.minutes).clamp_range(0.5..=240.0).suffix("min"));
+                    });
+                    ui.checkbox(&mut autosave.save_on_exit, "Save on exit");
+                });
+                if save_resp.clicked() {
+                    self.run_command(app, Command::SaveConfig);
+                }
+                let export_resp = ui.add_enabled(!app.raw_values().is_empty(), egui::Button::new("Export CSV"));
+                export_resp.context_menu(|ui| {
+                    close_menu_on_escape(ui);
+                    ui.checkbox(app.export_raw_mut(), "Export raw values (without scale and offset)");
+                    let keeps_lines = app.plot_config().raw_lines;
+                    ui.add_enabled(keeps_lines, egui::Checkbox::new(app.export_lines_mut(), "Include raw lines"))
+                        .on_disabled_hover_text("Enable \"Keep raw lines\" in the plot settings first");
+                });
+                if export_resp.clicked() {
+                    self.run_command(app, Command::ExportCsv);
+                }
+                let snapshot_resp = ui.add_enabled(!app.raw_values().is_empty() && self.snapshot.is_none(), egui::Button::new("Snapshot"))
+                    .on_hover_text("Save an image and the visible data of every plot into a new folder");
+                snapshot_resp.context_menu(|ui| {
+                    close_menu_on_escape(ui);
+                    let dir = app.snapshot_dir_mut();
+                    match dir.is_empty() {
+                        true => ui.weak("No folder chosen"),
+                        false => ui.weak(dir.as_str())
+                    };
+                    if ui.button("Change folder\u{2026}").clicked() {
+                        self.dialog = Some(PendingDialog::SnapshotFolder(false));
+                        ui.close_menu();
+                    }
+                });
+                if snapshot_resp.clicked() {
+                    self.run_command(app, Command::Snapshot);
+                }
+                let load_resp = ui.add_enabled(!self.locked, egui::Button::new("Load Config"));
+                let mut import = false;
+                load_resp.context_menu(|ui| {
+                    close_menu_on_escape(ui);
+                    if ui.button("Import settings\u{2026}").on_hover_text("Import channels and plot settings from a SerialPlot settings file").clicked() {
+                        import = true;
+                        ui.close_menu();
+                    }
+                });
+                if load_resp.clicked() {
+                    self.run_command(app, Command::LoadConfig);
+                }
+                if import {
+                    self.run_command(app, Command::ImportSettings);
+                }
+                ui.add_space(ui.available_width());
+            });
+            ui.separator();
+            
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                let frame = egui::Frame::none()
+                    .inner_margin(0.0)
+                    .outer_margin(0.0);
+                if self.maximized.is_some_and(|id| !app.plots().iter().any(|n| n.id == id && n.detached.is_none())) {
+                    self.maximized = None;
+                }
+                let mut i = 0;
+                while i < app.plots().len() {
+                    let id = app.plots()[i].id;
+                    let maximized = self.maximized.map(|n| n == id);
+                    if maximized == Some(false) || app.plots()[i].detached.is_some() {
+                        if self.focused_plot == Some(id) {
+                            self.focused_plot = None;
+                        }
+                        i += 1;
+                        continue;
+                    }
+                    let mut inc = 1;
+                    let mut panel = egui::TopBottomPanel::top(format!("PlotContainer_{}", app.plots()[i].id))
+                        .frame(frame)
+                        .default_height(app.plots()[i].height)
+                        .min_height(128.0)
+                        .resizable(maximized.is_none());
+                    if maximized.is_some() {
+                        panel = panel.exact_height(ui.available_height());
+                    }
+                    let res = panel
+                        .show_inside(ui, |ui| {
+                            inc = self.plot_contents(ctx, ui, i, app, maximized);
+                        });
+                    if self.palette.is_none() && ui.rect_contains_pointer(res.response.rect) {
+                        self.focused_plot = Some(id);
+                    }
+                    if inc != 0 && maximized.is_none() && i < app.plots().len() {
+                        app.plots_mut()[i].height = res.response.rect.height();
+                    }
+                        
+                    i += inc;
+                }
+            });
+        });
+    }
+    
+    fn plot_contents(&mut self, ctx: &egui::Context, ui: &mut Ui, i: usize, app: &mut SerialMonitorApp, maximized: Option<bool>) -> usize {
+        let id = app.plots()[i].id;
+        let mut inc = 1;
+        let plot = &app.plots()[i];
+        let (resp, hidden) = match plot.kind {
+            PlotKind::Console => (self.console(ui, plot, app), None),
+            PlotKind::Watch => (self.watch(ui, plot, app), None),
+            PlotKind::Spectrum => self.spectrum(ctx, ui, plot, app),
+            PlotKind::Xy => (self.xy_plot(ui, plot, app), None),
+            PlotKind::Table => (self.table(ui, plot, app), None),
+            PlotKind::Time => self.plot(ctx, ui, plot, app)
+        };
+        match resp {
+            PlotResponse::Reset => {
+                self.plot_ranges.remove(&id);
+                self.watch_ranges.retain(|key, _| key.0 != id);
+                self.spectra.retain(|key, _| key.0 != id);
+                self.cursors.remove(&id);
+                app.reset_plot(i);
+            },
+            PlotResponse::Remove => {
+                self.console_filters.remove(&id);
+                app.remove_plot(i);
+                inc = 0;
+            },
+            PlotResponse::MoveUp => app.move_plot(i, i.saturating_sub(1)),
+            PlotResponse::MoveDown => app.move_plot(i, i + 1),
+            PlotResponse::Maximize => {
+                self.maximized = match maximized {
+                    Some(_) => None,
+                    None => Some(app.plots()[i].id)
+                };
+            },
+            PlotResponse::Detach => {
+                let plot = &mut app.plots_mut()[i];
+                plot.detached = match plot.detached {
+                    Some(_) => None,
+                    None => Some(DetachedWindow::default())
+                };
+                if self.maximized == Some(id) {
+                    self.maximized = None;
+                }
+            },
+            PlotResponse::Rename(name) => app.plots_mut()[i].name = name,
+            PlotResponse::Axis(axis) => app.plots_mut()[i].axis = axis,
+            PlotResponse::Watch(watched, range) => {
+                app.plots_mut()[i].watched = watched;
+                app.plots_mut()[i].watch_range = range;
+            },
+            PlotResponse::Slots(slots) => app.plots_mut()[i].slots = slots,
+            PlotResponse::Shading(shading) => app.plots_mut()[i].shading = shading,
+            PlotResponse::FftSize(size) => app.plots_mut()[i].fft_size = size,
+            PlotResponse::Stacking(stacked, spacing) => {
+                self.plot_ranges.remove(&id);
+                app.plots_mut()[i].stacked = stacked;
+                app.plots_mut()[i].stack_spacing = spacing;
+            },
+            PlotResponse::Smoothing(smoothing) => app.plots_mut()[i].smoothing = smoothing,
+            PlotResponse::Kind(kind) => {
+                self.plot_ranges.remove(&id);
+                self.cursors.remove(&id);
+                app.plots_mut()[i].kind = kind;
+            },
+            PlotResponse::Xy(xy) => app.plots_mut()[i].xy = xy,
+            PlotResponse::Table(table) => app.plots_mut()[i].table = table,
+            PlotResponse::References(references, fit) => {
+                self.plot_ranges.remove(&id);
+                app.plots_mut()[i].references = references;
+                app.plots_mut()[i].fit_references = fit;
+            },
+            PlotResponse::Override(config) => {
+                self.plot_ranges.remove(&id);
+                self.sync_anchors.remove(&id);
+                app.plots_mut()[i].override_config = config;
+            },
+            PlotResponse::Promote(a, b) => if let Some(name) = app.add_difference_channel(a, b) {
+                self.set_notification(Notification::new(&format!("Added derived channel {}", name), Duration::from_secs(5), NotificationType::Info), false);
+            },
+            PlotResponse::Send(text) => if let Err(e) = app.send(&text) {
+                self.set_notification(Notification::new(&format!("Could not send ({})", e), Duration::from_secs(5), NotificationType::Error), false);
+            },
+            PlotResponse::Preset(index) => self.send_preset(app, index, false),
+            PlotResponse::Sequence(index) => app.start_sequence(index),
+            PlotResponse::None => if let Some(h) = hidden {
+                app.plots_mut()[i].hidden = h;
+            }
+        }
+        inc
+    }
+
+    fn detached_plots(&mut self, ctx: &egui::Context, app: &mut SerialMonitorApp) {
+        let detached: Vec<(usize, String, [f32; 2])> = app.plots().iter()
+            .filter_map(|n| Some((n.id, n.name.clone(), n.detached.as_ref()?.size)))
+            .collect();
+        for (id, name, size) in detached {
+            let builder = egui::ViewportBuilder::default()
+                .with_title(format!("{} - Serial Monitor", name))
+                .with_inner_size(size)
+                .with_min_inner_size([256.0, 160.0]);
+            ctx.show_viewport_immediate(egui::ViewportId::from_hash_of(("DetachedPlot", id)), builder, |ctx, class| {
+                if self.capture.as_ref().is_some_and(|n| n.plot_id == id) {
+                    self.capture_plot(ctx);
+                }
+                let mut open = true;
+                match class {
+                    egui::ViewportClass::Embedded => {
+                        egui::Window::new(&name)
+                            .id(Id::new("DetachedPlot").with(id))
+                            .default_size(size)
+                            .open(&mut open)
+                            .show(ctx, |ui| self.detached_plot(ctx, ui, id, app));
+                    },
+                    _ => {
+                        egui::CentralPanel::default().show(ctx, |ui| self.detached_plot(ctx, ui, id, app));
+                    }
+                }
+                let (close, rect) = ctx.input(|i| (i.viewport().close_requested(), i.viewport().inner_rect));
+                let Some(plot) = app.plots_mut().iter_mut().find(|n| n.id == id) else {
+                    return;
+                };
+                if close || !open {
+                    plot.detached = None;
+                } else if let (Some(window), Some(rect), false) = (&mut plot.detached, rect, class == egui::ViewportClass::Embedded) {
+                    window.size = rect.size().into();
+                }
+            });
+        }
+    }
+
+    fn detached_plot(&mut self, ctx: &egui::Context, ui: &mut Ui, id: usize, app: &mut SerialMonitorApp) {
+        let Some(i) = app.plots().iter().position(|n| n.id == id) else {
+            return;
+        };
+        self.plot_contents(ctx, ui, i, app, None);
+        if self.palette.is_none() && ui.ui_contains_pointer() {
+            self.focused_plot = Some(id);
+        }
+    }
+
+    fn notification(&mut self, ctx: &egui::Context) {
+        if let Some(notification) = &self.notification {
+            if notification.start.elapsed() > notification.duration {
+                self.notification = None;
+                return;
+            }
+            render_notification(ctx, notification, egui::Pos2::new(ctx.available_rect().center().x, 75.0), false);
+            ctx.request_repaint_after(notification.duration.saturating_sub(notification.start.elapsed()));
+        }
+    }
+
+    fn minor_notification(&mut self, ctx: &egui::Context) {
+        if let Some(notification) = &self.minor_notification {
+            if notification.start.elapsed() > notification.duration {
+                self.minor_notification = None;
+                return;
+            }
+            render_notification(ctx, notification, ctx.available_rect().right_top(), true);
+            ctx.request_repaint_after(notification.duration.saturating_sub(notification.start.elapsed()));
+        }
+    }
+
+    fn plot(&mut self, ctx: &egui::Context, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> (PlotResponse, Option<Vec<usize>>) {
+        ui.add_space(PLOT_MARGIN);
+    
+        let result = self.plot_header(ui, plot, app);
+        if result == PlotResponse::Remove {
+            return (result, None);
+        }
+
+        let print = self.is_printing(plot.id);
+        let line_width = plot_style(ui, print, app.ui_config());
+        let config = &plot.effective_config(app.plot_config());
+        let window = config.window;
+        let (x_axis, mut y_axis, label_axis) = (plot.axis.clone(), plot.axis.clone(), plot.axis.clone());
+        let input_slots = app.input_slots();
+        let channels = app.display_channels();
+        let zoom_enabled = app.zoom_enabled();
+        let plt_id = format!("Plot_{}", plot.id);
+        let empty = app.raw_values().is_empty();
+        let t_now = plot_engine::latest_time(&channels);
+        let (raw_lines, raw_slots, raw_config) = (app.raw_lines(), input_slots.to_vec(), config.clone());
+        let units: BTreeSet<&str> = channels.iter()
+            .enumerate()
+            .filter(|(i, (_, values))| !values.is_empty() && plot.shows(*i) && !plot.hidden.contains(i))
+            .map(|(_, (slot, _))| slot.unit.as_str())
+            .collect();
+        if let (true, Some(unit)) = (y_axis.unit.is_empty() && units.len() == 1, units.first()) {
+            y_axis.unit = unit.to_string();
+        }
+
+        if !plot_engine::has_range(&channels) {
+            self.plot_placeholder(ui, app);
+            ui.add_space(PLOT_MARGIN);
+            return (result, None);
+        }
+
+        let hidden = plot_engine::hidden_names(&plot.hidden, input_slots);
+        let mut legend = Legend::default().position(Corner::LeftTop);
+        if !empty {
+            legend = legend.hidden_items(hidden);
+        }
+
+        let cursor_color = match print {
+            true => Color32::BLACK,
+            false => ui.visuals().strong_text_color()
+        };
+        let (split, synced) = self.cyclic_split(app, plot.id, config, t_now);
+        let compare = self.compares.get(&plot.id)
+            .and_then(|n| compare_values(n, app, split, config));
+        let bands = plot.shading.as_ref()
+            .map(|n| shading_bands(n, &channels, split, config));
+        let draw_envelope = plot.smoothing.as_ref().is_some_and(|n| n.envelope);
+        let underlay_idx = (bands.is_some() || draw_envelope || !plot.references.is_empty()).then(|| (ui.painter().add(egui::Shape::Noop), ui.painter().add(egui::Shape::Noop)));
+        let mut envelopes: Vec<(Vec<[f64; 3]>, Color32)> = Vec::new();
+        let loss_hidden = app.loss().filter(|n| n.0.hide).map(|n| n.0.slot);
+        let offsets = match plot.stacked {
+            true => {
+                let memory = PlotMemory::load(ctx, Id::new(&plt_id));
+                let visible: Vec<usize> = channels.iter()
+                    .enumerate()
+                    .filter(|(i, (slot, values))| !values.is_empty() && plot.shows(*i) && loss_hidden != Some(*i)
+                        && !memory.as_ref().is_some_and(|mem| mem.hidden_items.contains(&slot.legend_name())))
+                    .map(|(i, _)| i)
+                    .collect();
+                plot_engine::stack_offsets(&visible, plot.stack_spacing)
+            },
+            false => HashMap::new()
+        };
+        let max_offset = offsets.values().copied().fold(0.0, f64::max);
+        let label_offsets: HashMap<String, f64> = offsets.iter()
+            .map(|(i, offset)| (channels[*i].0.legend_name(), *offset))
+            .collect();
+        let cursor_x: Vec<(usize, f64)> = self.cursors.get(&plot.id)
+            .map_or_else(Vec::new, |n| n.t.iter()
+                .enumerate()
+                .filter_map(|(k, t)| Some((k, plot_engine::display_x((*t)?, t_now, split, config)?)))
+                .collect());
+        let grabbed = self.cursors.get(&plot.id).and_then(|n| n.dragging).or_else(|| {
+            let pos = ctx.input(|i| i.pointer.hover_pos())?;
+            let transform = PlotMemory::load(ctx, Id::new(&plt_id))?.transform();
+            cursor_x.iter()
+                .find(|(_, x)| transform.frame().contains(pos) && (transform.position_from_point_x(*x) - pos.x).abs() <= CURSOR_GRAB_DISTANCE)
+                .map(|n| n.0)
+        }).filter(|_| zoom_enabled);
+        let bucket_width = PlotMemory::load(ctx, Id::new(&plt_id))
+            .map_or(window, |n| n.transform().bounds().width()) / f64::from(ui.available_width().max(1.0));
+
+        let plot_resp = egui_plot::Plot::new(&plt_id)
+            .id(Id::new(&plt_id))
+            .legend(legend)
+            .show_background(underlay_idx.is_none())
+            .height(ui.available_height() - (PLOT_MARGIN + ui.style().spacing.item_spacing.y))
+            .x_axis_formatter(move |grid_pt, _, _| format::format_time(grid_pt.value, x_axis.time_format, window, false))
+            .y_axis_formatter(move |grid_pt, _, _| format::format_value(grid_pt.value, y_axis.value_format, y_axis.digits, &y_axis.unit))
+            .label_formatter(move |name, point| {
+                let y = point.y - label_offsets.get(name).copied().unwrap_or_default();
+                let slot = raw_slots.iter().position(|n| n.legend_name() == name);
+                let value = match slot.map(|i| &raw_slots[i]) {
+                    Some(slot) if !slot.unit.is_empty() => format::format_value(y, label_axis.value_format, slot.precision as usize, &slot.unit),
+                    Some(slot) => format::format_value(y, label_axis.value_format, slot.precision as usize, &label_axis.unit),
+                    None => format::format_value(y, label_axis.value_format, label_axis.digits + 3, &label_axis.unit)
+                };
+                let label = format!("t = {}\ny = {}", format::format_time(point.x, label_axis.time_format, window, true), value);
+                let raw = raw_lines.as_ref()
+                    .zip(slot)
+                    .and_then(|(raw_lines, i)| {
+                        let slot = &raw_slots[i];
+                        let t = plot_engine::source_t(point.x, t_now, &raw_config) - slot.time_shift();
+                        raw_lines.lock().ok()?.find(i, t, |n| slot.apply(n), y).map(|n| n.line.clone())
+                    });
+                match raw {
+                    Some(raw) => format!("{}\n{}", label, raw),
+                    None => label
+                }
+            })
+            .y_axis_width(3)
+            .allow_scroll(false)
+            .allow_zoom(zoom_enabled)
+            .allow_boxed_zoom(zoom_enabled)
+            .allow_drag(zoom_enabled && grabbed.is_none())
+            .allow_double_click_reset(false)
+            .auto_bounds(egui::Vec2b::from([true, config.scale_mode != PlotScaleMode::Manual]))
+            .show(ui, |ui| {
+                let mut min = f64::MAX;
+                let mut max = f64::MIN;
+                let mut ranges = HashMap::new();
+
+                for (i, (slot, values)) in channels.iter().copied().enumerate() {
+                    if values.is_empty() || !plot.shows(i) || loss_hidden == Some(i) {
+                        continue;
+                    }
+                    let hidden = PlotMemory::load(ctx, Id::new(&plt_id))
+                        .is_some_and(|mem| mem.hidden_items.contains(&slot.legend_name()));
+                    
+                    let history = plot.smoothing.as_ref().map_or(0, |n| n.window.saturating_sub(1));
+                    let values = plot_engine::since_with_history(values, values[values.len() - 1][0] - config.window, history);
+                    let values = plot_engine::display_values(values, slot);
+                    let t_now = values[values.len() - 1][0];
+                    let offset = offsets.get(&i).copied();
+                    let prepare = |values: &[[f64; 2]]| {
+                        let mut filtered = plot_engine::decimate(plot_engine::window_values(values, t_now, split, config), bucket_width);
+                        if let Some(offset) = offset {
+                            filtered.iter_mut().for_each(|n| n[1] += offset);
+                        }
+                        filtered
+                    };
+                    let smoothed = plot.smoothing.as_ref().map(|n| (n, plot_engine::smooth(&values, n.window)));
+                    let filtered = match &smoothed {
+                        Some((_, smoothed)) => prepare(&smoothed.mean),
+                        None => prepare(&values)
+                    };
+                    let raw = smoothed.as_ref()
+                        .filter(|n| n.0.show_raw && !hidden)
+                        .map(|_| prepare(&values));
+                    let envelope = smoothed.as_ref()
+                        .filter(|n| n.0.envelope && !hidden)
+                        .map(|(_, n)| {
+                            let low = plot_engine::window_values(&n.low, t_now, split, config);
+                            let high = plot_engine::window_values(&n.high, t_now, split, config);
+                            let mut points = plot_engine::envelope(&low, &high, bucket_width);
+                            let offset = offset.unwrap_or_default();
+                            points.iter_mut().for_each(|n| {
+                                n[1] += offset;
+                                n[2] += offset;
+                            });
+                            points
+                        });
+
+                    if !hidden {
+                        let (mut local_min, mut local_max) = plot_engine::value_range(raw.as_ref().unwrap_or(&filtered));
+                        if let Some(envelope) = &envelope {
+                            let (low, high) = envelope.iter().fold((f64::MAX, f64::MIN), |(min, max), n| (f64::min(min, n[1]), f64::max(max, n[2])));
+                            local_min = f64::min(local_min, low);
+                            local_max = f64::max(local_max, high);
+                        }
+                        min = f64::min(min, local_min);
+                        max = f64::max(max, local_max);
+                        ranges.insert(i, [local_min, local_max]);
+                    }
+
+                    if let Some(envelope) = envelope {
+                        envelopes.push((envelope, slot_color(slot, print).gamma_multiply(ENVELOPE_ALPHA)));
+                    }
+                    if let Some(raw) = raw {
+                        let line = Line::new(PlotPoints::from(raw))
+                            .color(slot_color(slot, print).gamma_multiply(RAW_ALPHA))
+                            .width(line_width);
+                        ui.add(line);
+                    }
+    
+                    match &slot.color_map {
+                        Some(map) => {
+                            for (points, color) in plot_engine::color_segments(&filtered, map, offset.unwrap_or_default()) {
+                                let line = Line::new(PlotPoints::from(points))
+                                    .name(slot.legend_name())
+                                    .color(rgb_color(color))
+                                    .width(line_width);
+                                ui.add(line);
+                            }
+                        },
+                        None => {
+                            let line = Line::new(PlotPoints::from(filtered))
+                                .name(slot.legend_name())
+                                .color(slot_color(slot, print))
+                                .width(line_width);
+                            ui.add(line);
+                        }
+                    }
+
+                    if let Some(offset) = offset {
+                        let line = HLine::new(offset)
+                            .color(slot_color(slot, print).gamma_multiply(0.35))
+                            .style(LineStyle::dotted_dense())
+                            .width(1.0);
+                        ui.add(line);
+                    }
+
+                    let markers = app.anomalies(i)
+                        .filter(|n| i < input_slots.len() && !hidden && !n.markers.is_empty())
+                        .map(|n| plot_engine::window_values(&plot_engine::display_values(&n.markers, slot), t_now, split, config));
+                    if let Some(mut markers) = markers {
+                        markers.iter_mut().for_each(|n| n[1] += offset.unwrap_or_default());
+                        let points = Points::new(PlotPoints::from(markers))
+                            .color(ERROR_COLOR)
+                            .shape(MarkerShape::Circle)
+                            .radius(ANOMALY_RADIUS);
+                        ui.add(points);
+                    }
+    
+                    if config.mode == PlotMode::Cyclic {
+                        let line = VLine::new(t_now)
+                            .color(cursor_color)
+                            .width(1.5);
+                        ui.add(line);
+                    }
+                }
+
+                if let Some((_, loss)) = app.loss() {
+                    for x in loss.gaps.iter().filter_map(|t| plot_engine::display_x(*t, t_now, split, config)) {
+                        let line = VLine::new(x)
+                            .color(WARNING_COLOR.gamma_multiply(0.6))
+                            .style(LineStyle::dotted_dense())
+                            .width(1.0);
+                        ui.add(line);
+                    }
+                }
+
+                for x in app.markers().iter().filter_map(|n| plot_engine::display_x(n.t, t_now, split, config)) {
+                    let line = VLine::new(x)
+                        .color(MARKER_COLOR)
+                        .width(1.5);
+                    ui.add(line);
+                }
+
+                for (_, x) in &cursor_x {
+                    let line = VLine::new(*x)
+                        .color(cursor_color)
+                        .style(LineStyle::dashed_loose())
+                        .width(1.0);
+                    ui.add(line);
+                }
+
+                if let Some((t, start)) = self.marker {
+                    ui.ctx().request_repaint();
+                    if let Some(x) = plot_engine::display_x(t, t_now, split, config) {
+                        let alpha = 1.0 - (start.elapsed().as_secs_f32() / MARKER_DURATION.as_secs_f32()).clamp(0.0, 1.0);
+                        let line = VLine::new(x)
+                            .color(Color32::YELLOW.gamma_multiply(alpha))
+                            .style(LineStyle::dashed_dense())
+                            .width(1.5);
+                        ui.add(line);
+                    }
+                }
+
+                let mut exclude_compare = false;
+                if let Some(diff) = &compare {
+                    let (local_min, local_max) = plot_engine::value_range(&diff.values);
+                    match diff.autoscale {
+                        true => {
+                            min = f64::min(min, local_min);
+                            max = f64::max(max, local_max);
+                            ranges.insert(usize::MAX, [local_min, local_max]);
+                        },
+                        false => exclude_compare = true
+                    }
+                    let line = Line::new(PlotPoints::from(diff.values.clone()))
+                        .name(&diff.name)
+                        .color(cursor_color)
+                        .style(LineStyle::dashed_loose());
+                    ui.add(line);
+                }
+
+                let fit_references = plot.fit_references && !plot.references.is_empty();
+                if fit_references {
+                    let (local_min, local_max) = plot.references.iter()
+                        .map(RefLine::range)
+                        .fold((f64::MAX, f64::MIN), |(min, max), n| (f64::min(min, n.0), f64::max(max, n.1)));
+                    min = f64::min(min, local_min);
+                    max = f64::max(max, local_max);
+                    ranges.insert(usize::MAX - 1, [local_min, local_max]);
+                }
+
+                if !zoom_enabled {
+                    let bounds_x: RangeInclusive<f64> = ui.plot_bounds().range_x();
+                    match config.scale_mode {
+                        PlotScaleMode::Auto if (exclude_compare || fit_references) && min <= max => {
+                            let (min, max) = plot_engine::padded_range(min, max);
+                            ui.set_plot_bounds(PlotBounds::from_min_max(
+                                [*bounds_x.start(), min],
+                                [*bounds_x.end(), max]));
+                            ui.set_auto_bounds(egui::Vec2b::from([true, false]));
+                        },
+                        PlotScaleMode::Auto => {
+                            ui.set_auto_bounds(egui::Vec2b::from([true, true]));
+                        },
+                        PlotScaleMode::AutoMax => {
+                            let stored = self.plot_ranges.entry(plot.id).or_default();
+                            if let Some((min, max)) = plot_engine::track_max_range(stored, ranges) {
+                                ui.set_plot_bounds(PlotBounds::from_min_max(
+                                    [*bounds_x.start(), min],
+                                    [*bounds_x.end(), max]));
+                            }
+                            ui.set_auto_bounds(egui::Vec2b::from([true, false]));
+                        },
+                        PlotScaleMode::Manual => {
+                            let (min, max) = plot_engine::manual_range(config, max_offset);
+                            ui.set_plot_bounds(PlotBounds::from_min_max(
+                                [*bounds_x.start(), min],
+                                [*bounds_x.end(), max]));
+                            ui.set_auto_bounds(egui::Vec2b::from([true, false]));
+                        }
+                    }
+                }
+            });
+
+        if let Some((idx, envelope_idx)) = underlay_idx {
+            let rect = plot_resp.response.rect;
+            let mut shapes = vec![egui::Shape::rect_filled(rect, 2.0, ui.visuals().extreme_bg_color)];
+            shapes.extend(bands.unwrap_or_default().into_iter().map(|(x0, x1, color)| {
+                let x0 = plot_resp.transform.position_from_point_x(x0).max(rect.left());
+                let x1 = plot_resp.transform.position_from_point_x(x1).min(rect.right());
+                egui::Shape::rect_filled(egui::Rect::from_x_y_ranges(x0..=x1, rect.y_range()), 0.0, color)
+            }));
+            for reference in &plot.references {
+                shapes.extend(reference_shapes(ui, reference, &plot_resp.transform, rect));
+            }
+            shapes.push(egui::Shape::rect_stroke(rect, 2.0, ui.visuals().widgets.noninteractive.bg_stroke));
+            ui.painter().set(idx, egui::Shape::Vec(shapes));
+            let meshes = envelopes.iter()
+                .map(|(points, color)| egui::Shape::mesh(envelope_mesh(points, &plot_resp.transform, *color)))
+                .collect();
+            ui.painter().with_clip_rect(rect).set(envelope_idx, egui::Shape::Vec(meshes));
+        }
+        if let Some(synced) = synced {
+            let (text, color) = match synced {
+                true => ("Synced", INFO_COLOR),
+                false => ("Free running", WARNING_COLOR)
+            };
+            let rect = plot_resp.response.rect;
+            ui.painter().text(rect.right_top() + egui::vec2(-8.0, 6.0), Align2::RIGHT_TOP, text, egui::FontId::proportional(12.0), color);
+        }
+        let rect = plot_resp.response.rect;
+        let hover = plot_resp.response.hover_pos();
+        for marker in app.markers() {
+            let Some(x) = plot_engine::display_x(marker.t, t_now, split, config).map(|n| plot_resp.transform.position_from_point_x(n)) else {
+                continue;
+            };
+            if !rect.x_range().contains(x) {
+                continue;
+            }
+            ui.painter().text(egui::pos2(x + 3.0, rect.top() + 4.0), Align2::LEFT_TOP, &marker.label, egui::FontId::proportional(11.0), MARKER_COLOR);
+            if hover.is_some_and(|n| (n.x - x).abs() <= MARKER_HOVER_DISTANCE) {
+                egui::show_tooltip_at_pointer(ctx, Id::new(&plt_id).with("Marker"), |ui| {
+                    ui.label(format!("{} (t = {:.3}s)", marker.label, marker.t));
+                });
+            }
+        }
+        for (k, x) in &cursor_x {
+            let x = plot_resp.transform.position_from_point_x(*x);
+            if rect.x_range().contains(x) {
+                ui.painter().text(egui::pos2(x + 3.0, rect.bottom() - 4.0), Align2::LEFT_BOTTOM, CURSOR_NAMES[*k], egui::FontId::monospace(11.0), cursor_color);
+            }
+        }
+        self.plot_rect(ui, plot.id, plot_resp.response.rect);
+        if plot_resp.response.secondary_clicked() {
+            self.context_time = plot_resp.response.interact_pointer_pos()
+                .map(|pos| plot_resp.transform.value_from_position(pos).x)
+                .map(|x| plot_engine::source_t(x, t_now, config));
+        }
+        if zoom_enabled {
+            let pointer_t = plot_resp.response.interact_pointer_pos()
+                .map(|pos| plot_engine::source_t(plot_resp.transform.value_from_position(pos).x, t_now, config));
+            let response = &plot_resp.response;
+            if response.drag_started() && grabbed.is_some() {
+                self.cursors.entry(plot.id).or_default().dragging = grabbed;
+            }
+            if let Some(cursors) = self.cursors.get_mut(&plot.id) {
+                if let (Some(k), Some(t)) = (cursors.dragging, pointer_t) {
+                    cursors.t[k] = Some(t);
+                }
+                if response.drag_stopped() {
+                    cursors.dragging = None;
+                }
+            }
+            if let (true, Some(t)) = (response.clicked(), pointer_t) {
+                let k = ctx.input(|i| i.modifiers.shift) as usize;
+                self.cursors.entry(plot.id).or_default().t[k] = Some(t);
+            }
+        }
+        if let Some(cursors) = self.cursors.get(&plot.id).filter(|n| n.t.iter().any(Option::is_some)) {
+            let hidden = PlotMemory::load(ctx, Id::new(&plt_id)).map(|n| n.hidden_items).unwrap_or_default();
+            let visible: Vec<usize> = channels.iter()
+                .enumerate()
+                .filter(|(i, (slot, values))| !values.is_empty() && plot.shows(*i) && loss_hidden != Some(*i) && !hidden.contains(&slot.legend_name()))
+                .map(|(i, _)| i)
+                .collect();
+            let text = cursor_readout(cursors, &visible, app, &plot.axis);
+            let galley = ui.painter().layout_no_wrap(text, egui::FontId::monospace(11.0), ui.visuals().text_color());
+            let pos = rect.right_bottom() - galley.size() - egui::vec2(PLOT_MARGIN, PLOT_MARGIN);
+            let background = egui::Rect::from_min_size(pos, galley.size()).expand(4.0);
+            ui.painter().rect(background, 2.0, ui.visuals().extreme_bg_color.gamma_multiply(0.9), ui.visuals().widgets.noninteractive.bg_stroke);
+            ui.painter().galley(pos, galley, ui.visuals().text_color());
+        }
+        let hidden = PlotMemory::load(ctx, Id::new(&plt_id)).map(|n| n.hidden_items).unwrap_or_default();
+        let maps: Vec<(&str, &ColorMap)> = channels.iter()
+            .enumerate()
+            .filter(|(i, (slot, values))| !values.is_empty() && plot.shows(*i) && !hidden.contains(&slot.legend_name()))
+            .filter_map(|(_, (slot, _))| Some((slot.name.as_str(), slot.color_map.as_ref()?)))
+            .collect();
+        if !maps.is_empty() {
+            color_map_legend(ui, rect, &maps, &plot.axis);
+        }
+        let mut menu_result = PlotResponse::None;
+        plot_resp.response.context_menu(|ui| {
+            let t = self.context_time.unwrap_or_default();
+            let raw = app.raw_lines()
+                .and_then(|n| n.lock().ok()?.nearest(t).map(|n| (n.t, n.line.clone())));
+            ui.label(format!("t = {:.3}s", t));
+            if let Some((_, line)) = &raw {
+                ui.label(egui::RichText::new(line).monospace());
+            }
+            ui.separator();
+            if ui.add_enabled(app.has_console(), egui::Button::new("Show in console")).clicked() {
+                let (t, content) = match raw {
+                    Some((t, line)) => (t, Some(line)),
+                    None => (t, None)
+                };
+                self.console_focus = Some(ConsoleFocus {
+                    t,
+                    content,
+                    start: Instant::now(),
+                    scrolled: false
+                });
+                ui.close_menu();
+            }
+            if self.cursors.contains_key(&plot.id) && ui.button("Clear cursors").clicked() {
+                self.cursors.remove(&plot.id);
+                ui.close_menu();
+            }
+            ui.separator();
+            menu_result = self.plot_menu(ui, plot, app);
+        });
+        let result = match menu_result {
+            PlotResponse::None => result,
+            _ => menu_result
+        };
+
+        if let Some(diff) = &compare {
+            let text = format!("{}   now: {:.3}   rms: {:.3}   max: {:.3}", diff.name, diff.current, diff.rms, diff.max_abs);
+            let pos = plot_resp.response.rect.right_top() + egui::Vec2::new(-PLOT_MARGIN, PLOT_MARGIN);
+            ui.painter().text(pos, Align2::RIGHT_TOP, text, egui::FontId::monospace(12.0), ui.visuals().strong_text_color());
+        }
+
+        let hidden = PlotMemory::load(ctx, Id::new(&plt_id))
+            .map_or_else(Vec::new, |mem| plot_engine::hidden_slots(input_slots, |name| mem.hidden_items.contains(name)));
+
+        ui.add_space(PLOT_MARGIN);        
+        (result, match !empty {
+            true => Some(hidden),
+            false => None
+        })
+    }
+
+    fn cyclic_split(&mut self, app: &SerialMonitorApp, id: usize, config: &PlotConfig, t_now: f64) -> (f64, Option<bool>) {
+        let free = plot_engine::free_split(t_now, config.window);
+        let trigger = match config.mode {
+            PlotMode::Cyclic => config.sync_channel.and_then(|n| app.display_channels().get(n).copied()),
+            PlotMode::Continous => None
+        };
+        let Some((slot, values)) = trigger else {
+            self.sync_anchors.remove(&id);
+            return (free, None);
+        };
+        if let Some(anchor) = self.sync_anchors.get(&id).copied().filter(|n| *n <= t_now && t_now - n < config.window) {
+            return (anchor, Some(true));
+        }
+
+        let values = plot_engine::display_values(values, slot);
+        let edge = plot_engine::rising_edge(&values, t_now, config.window, config.sync_threshold);
+        match edge {
+            Some(edge) => self.sync_anchors.insert(id, edge),
+            None => self.sync_anchors.remove(&id)
+        };
+        match edge {
+            Some(edge) => (edge, Some(true)),
+            None => (free, Some(false))
+        }
+    }
+
+    fn spectrum(&mut self, ctx: &egui::Context, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> (PlotResponse, Option<Vec<usize>>) {
+        ui.add_space(PLOT_MARGIN);
+
+        let result = self.plot_header(ui, plot, app);
+        if result == PlotResponse::Remove {
+            return (result, None);
+        }
+
+        let print = self.is_printing(plot.id);
+        let line_width = plot_style(ui, print, app.ui_config());
+        let (y_axis, label_axis) = (plot.axis.clone(), plot.axis.clone());
+        let input_slots = app.input_slots();
+        let channels = app.display_channels();
+        let zoom_enabled = app.zoom_enabled();
+        let plt_id = format!("Plot_{}", plot.id);
+
+        if channels.iter().all(|n| n.1.is_empty()) {
+            self.plot_placeholder(ui, app);
+            ui.add_space(PLOT_MARGIN);
+            return (result, None);
+        }
+
+        let hidden = plot_engine::hidden_names(&plot.hidden, input_slots);
+        let legend = Legend::default()
+            .position(Corner::LeftTop)
+            .hidden_items(hidden);
+
+        let mut rates = (f64::MAX, f64::MIN);
+        let plot_resp = egui_plot::Plot::new(&plt_id)
+            .id(Id::new(&plt_id))
             .legend(legend)
             .height(ui.available_height() - (PLOT_MARGIN + ui.style().spacing.item_spacing.y))
-            .x_axis_formatter(|grid_pt, _, _| format!("{:.2}s", grid_pt.value))
-            .y_axis_formatter(|grid_pt, _, _| format!("{:.2}", grid_pt.value))
-            .label_formatter(|_, point| format!("t = {:.5}s\ny = {:.5}", point.x, point.y))
+            .x_axis_formatter(|grid_pt, _, _| format!("{} Hz", grid_pt.value))
+            .y_axis_formatter(move |grid_pt, _, _| format::format_value(grid_pt.value, y_axis.value_format, y_axis.digits, &y_axis.unit))
+            .label_formatter(move |_, point| format!("f = {:.2} Hz\ny = {}",
+                point.x,
+                format::format_value(point.y, label_axis.value_format, label_axis.digits + 3, &label_axis.unit)))
+            .y_axis_width(3)
+            .allow_scroll(false)
+            .allow_zoom(zoom_enabled)
+            .allow_boxed_zoom(zoom_enabled)
+            .allow_drag(zoom_enabled)
+            .allow_double_click_reset(false)
+            .show(ui, |ui| {
+                for (i, (slot, values)) in channels.iter().copied().enumerate() {
+                    if values.is_empty() || !plot.shows(i) {
+                        continue;
+                    }
+                    let values = plot_engine::display_values(values, slot);
+                    let spectrum = self.spectra.entry((plot.id, i)).or_default();
+                    if !spectrum.compute(&values, plot.fft_size) {
+                        continue;
+                    }
+                    rates = (f64::min(rates.0, spectrum.sample_rate), f64::max(rates.1, spectrum.sample_rate));
+                    let line = Line::new(PlotPoints::from(spectrum.points.clone()))
+                        .name(slot.legend_name())
+                        .color(slot_color(slot, print))
+                        .width(line_width);
+                    ui.add(line);
+                }
+                if !zoom_enabled {
+                    ui.set_auto_bounds(egui::Vec2b::from([true, true]));
+                }
+            });
+
+        let rect = plot_resp.response.rect;
+        let text = match rates {
+            (min, max) if min > max => String::from("Waiting for samples"),
+            (min, max) if max - min <= min * 0.01 => format!("Sample rate {:.1} Hz", min),
+            (min, max) => format!("Sample rate {:.1}-{:.1} Hz", min, max)
+        };
+        ui.painter().text(rect.right_top() + egui::vec2(-8.0, 6.0), Align2::RIGHT_TOP, text, egui::FontId::proportional(12.0), ui.visuals().weak_text_color());
+        self.plot_rect(ui, plot.id, rect);
+        let mut menu_result = PlotResponse::None;
+        plot_resp.response.context_menu(|ui| {
+            menu_result = self.plot_menu(ui, plot, app);
+        });
+        let result = match menu_result {
+            PlotResponse::None => result,
+            _ => menu_result
+        };
+
+        let hidden = PlotMemory::load(ctx, Id::new(&plt_id))
+            .map_or_else(Vec::new, |mem| plot_engine::hidden_slots(input_slots, |name| mem.hidden_items.contains(name)));
+
+        ui.add_space(PLOT_MARGIN);
+        (result, Some(hidden))
+    }
+
+    fn xy_plot(&mut self, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> PlotResponse {
+        ui.add_space(PLOT_MARGIN);
+
+        let result = self.plot_header(ui, plot, app);
+        if result == PlotResponse::Remove {
+            return result;
+        }
+
+        let print = self.is_printing(plot.id);
+        let line_width = plot_style(ui, print, app.ui_config());
+        let window = plot.effective_config(app.plot_config()).window;
+        let (x_axis, y_axis, label_axis) = (plot.axis.clone(), plot.axis.clone(), plot.axis.clone());
+        let channels = app.display_channels();
+        let zoom_enabled = app.zoom_enabled();
+        let plt_id = format!("Plot_{}", plot.id);
+
+        let (Some((x_slot, xs)), Some((y_slot, ys))) = (channels.get(plot.xy.x_slot).copied(), channels.get(plot.xy.y_slot).copied()) else {
+            self.plot_placeholder(ui, app);
+            ui.add_space(PLOT_MARGIN);
+            return result;
+        };
+        if xs.is_empty() || ys.is_empty() {
+            self.plot_placeholder(ui, app);
+            ui.add_space(PLOT_MARGIN);
+            return result;
+        }
+
+        let xs = plot_engine::display_values(xs, x_slot);
+        let ys = plot_engine::display_values(ys, y_slot);
+        let t_end = f64::max(xs[xs.len() - 1][0], ys[ys.len() - 1][0]);
+        let points = plot_engine::pair_nearest(plot_engine::since(&xs, t_end - window), plot_engine::since(&ys, t_end - window));
+        let name = format!("{} / {}", y_slot.name, x_slot.name);
+        let plot_resp = egui_plot::Plot::new(&plt_id)
+            .id(Id::new(&plt_id))
+            .height(ui.available_height() - (PLOT_MARGIN + ui.style().spacing.item_spacing.y))
+            .x_axis_formatter(move |grid_pt, _, _| format::format_value(grid_pt.value, x_axis.value_format, x_axis.digits, &x_axis.unit))
+            .y_axis_formatter(move |grid_pt, _, _| format::format_value(grid_pt.value, y_axis.value_format, y_axis.digits, &y_axis.unit))
+            .label_formatter(move |_, point| format!("x = {}\ny = {}",
+                format::format_value(point.x, label_axis.value_format, label_axis.digits + 3, &label_axis.unit),
+                format::format_value(point.y, label_axis.value_format, label_axis.digits + 3, &label_axis.unit)))
             .y_axis_width(3)
             .allow_scroll(false)
             .allow_zoom(zoom_enabled)
             .allow_boxed_zoom(zoom_enabled)
             .allow_drag(zoom_enabled)
             .allow_double_click_reset(false)
-            .auto_bounds(egui::Vec2b::from([true, config.scale_mode != PlotScaleMode::Manual]))
             .show(ui, |ui| {
-                let mut min = f64::MAX;
-                let mut max = f64::MIN;
+                let color = slot_color(y_slot, print);
+                match plot.xy.points {
+                    true => ui.points(Points::new(PlotPoints::from(points))
+                        .name(&name)
+                        .color(color)
+                        .radius(line_width + 0.5)),
+                    false => ui.line(Line::new(PlotPoints::from(points))
+                        .name(&name)
+                        .color(color)
+                        .width(line_width))
+                }
+                if !zoom_enabled {
+                    ui.set_auto_bounds(egui::Vec2b::from([true, true]));
+                }
+            });
 
-                for (slot, values) in zip(input_slots, input_values) {
-                    let hidden = PlotMemory::load(&ctx, Id::new(&plt_id))
-                        .map_or(false, |mem| mem.hidden_items.contains(&slot.name));
-                    
-                    let t_now = values.last().unwrap_or(&[0.0, 0.0])[0];
-                    let filtered: Vec<[f64; 2]> = match config.mode {
-                        PlotMode::Continous => values.iter()
-                            .filter(|n| t_now - n[0] <= config.window)
-                            .copied()
-                            .collect::<Vec<[f64; 2]>>(),
-                        PlotMode::Cyclic => {
-                            let sub = t_now % config.window;
-                            let split = t_now - sub;
-                            let start = split - (config.window - sub);
-                            let mut v: Vec<[f64; 2]> = Vec::with_capacity(values.len());
-                            v.extend(values.iter()
-                                .filter(|n| n[0] > split));
-                            v.extend(values.iter()
-                                .filter(|n| n[0] >= start && n[0] < split)
-                                .map(|n| [n[0] + config.window, n[1]]));
-                            v
+        self.plot_rect(ui, plot.id, plot_resp.response.rect);
+        let mut menu_result = PlotResponse::None;
+        plot_resp.response.context_menu(|ui| {
+            menu_result = self.plot_menu(ui, plot, app);
+        });
+
+        ui.add_space(PLOT_MARGIN);
+        match menu_result {
+            PlotResponse::None => result,
+            _ => menu_result
+        }
+    }
+
+    fn plot_placeholder(&self, ui: &mut Ui, app: &SerialMonitorApp) {
+        let size = egui::Vec2::new(ui.available_width(), ui.available_height() - (PLOT_MARGIN + ui.style().spacing.item_spacing.y));
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let visuals = ui.visuals();
+        ui.painter().rect(rect, 2.0, visuals.extreme_bg_color, visuals.widgets.noninteractive.bg_stroke);
+        let text = match app.connection_time() {
+            Some(elapsed) => format!("Waiting for data on {}\u{2026} ({:.1}s)", app.connected_port(), elapsed.as_secs_f32()),
+            None => String::from("Not connected")
+        };
+        ui.painter().text(rect.center(), Align2::CENTER_CENTER, text, egui::FontId::proportional(16.0), visuals.weak_text_color());
+    }
+
+    fn console(&mut self, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> PlotResponse {
+        ui.add_space(PLOT_MARGIN);
+    
+        let result = self.plot_header(ui, plot, app);
+        if result == PlotResponse::Remove {
+            return result;
+        }
+    
+        if self.console_focus.as_ref().is_some_and(|n| n.start.elapsed() > FLASH_DURATION) {
+            self.console_focus = None;
+        }
+        if let Some(focus) = &self.console_focus {
+            ui.ctx().request_repaint_after(FLASH_DURATION.saturating_sub(focus.start.elapsed()));
+        }
+        let lines = app.console_lines();
+        let shown = match app.paused_at() {
+            Some(t) => lines.partition_point(|n| n.t <= t),
+            None => lines.len()
+        };
+        let focus_index = self.console_focus.as_ref().and_then(|focus| {
+            lines.iter()
+                .enumerate()
+                .take(shown)
+                .min_by(|(_, l), (_, r)| {
+                    let unrelated = |n: &ConsoleLine| focus.content.as_ref().is_some_and(|c| !c.contains(n.content.as_str()));
+                    unrelated(l).cmp(&unrelated(r)).then((l.t - focus.t).abs().total_cmp(&(r.t - focus.t).abs()))
+                })
+                .map(|(i, _)| i)
+        });
+
+        let mut result = result;
+        match self.preset_row(ui, app) {
+            Some(RowButton::Preset(index)) => result = PlotResponse::Preset(index),
+            Some(RowButton::Sequence(index)) => result = PlotResponse::Sequence(index),
+            None => ()
+        }
+
+        let filter = self.console_filters.entry(plot.id).or_default();
+        filter.sync();
+        let visible: Vec<(usize, String)> = lines.iter()
+            .take(shown)
+            .enumerate()
+            .map(|(i, line)| {
+                let dir = match line.sent {
+                    true => "<",
+                    false => ">"
+                };
+                (i, format!("{} {} {}", line.prefix(), dir, line.content))
+            })
+            .filter(|(i, text)| focus_index == Some(*i) || filter.matches(text))
+            .collect();
+        if self.console_filter_row(ui, plot.id, &visible, shown) {
+            result = PlotResponse::Reset;
+        }
+
+        let plt_id = format!("Plot_{}", plot.id);    
+        let input_height = ui.spacing().interact_size.y + ui.style().spacing.item_spacing.y;
+        let jump = self.console_filters.get_mut(&plot.id).is_some_and(|n| std::mem::take(&mut n.jump));
+        let output = egui::ScrollArea::vertical()
+            .id_source(plt_id)
+            .max_height(ui.available_height() - (PLOT_MARGIN + input_height + ui.style().spacing.item_spacing.y))
+            .auto_shrink([false, false])
+            .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::VisibleWhenNeeded)
+            .stick_to_bottom(self.console_focus.is_none())
+            .show(ui, |ui| {
+                for (i, text) in &visible {
+                    let (i, line) = (*i, &lines[*i]);
+                    let mut text = egui::RichText::new(text);
+                    if focus_index == Some(i) {
+                        text = text.background_color(ui.visuals().selection.bg_fill);
+                    }
+                    let resp = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+                    if let (Some(focus), Some(index)) = (&mut self.console_focus, focus_index) {
+                        if index == i && !focus.scrolled {
+                            focus.scrolled = true;
+                            resp.scroll_to_me(Some(Align::Center));
+                        }
+                    }
+                    resp.context_menu(|ui| {
+                        if ui.button("Show on plot").clicked() {
+                            self.marker = Some((line.t, Instant::now()));
+                            ui.close_menu();
+                        }
+                    });
+                }
+                if jump {
+                    ui.scroll_to_cursor(Some(Align::BOTTOM));
+                }
+            });
+        if let Some(filter) = self.console_filters.get_mut(&plot.id) {
+            filter.scrolled_up = output.state.offset.y < output.content_size.y - output.inner_rect.height() - 1.0;
+        }
+
+        ui.horizontal(|ui| {
+            let connected = app.is_connected();
+            if ui.button("Presets").on_hover_text("Edit the send presets").clicked() {
+                self.presets_open = !self.presets_open;
+            }
+            let send = ui.add_enabled_ui(connected, |ui| {
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    let button = ui.button("Send");
+                    let input = egui::TextEdit::singleline(&mut self.send_text)
+                        .hint_text("Send to device")
+                        .desired_width(ui.available_width())
+                        .show(ui);
+                    let enter = input.response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if enter {
+                        input.response.request_focus();
+                    }
+                    button.clicked() || enter
+                }).inner
+            }).inner;
+            if send && connected {
+                result = PlotResponse::Send(std::mem::take(&mut self.send_text));
+            }
+        });
+
+        ui.add_space(PLOT_MARGIN);        
+        result
+    }
+
+    fn console_filter_row(&mut self, ui: &mut Ui, id: usize, visible: &[(usize, String)], total: usize) -> bool {
+        let Some(filter) = self.console_filters.get_mut(&id) else {
+            return false;
+        };
+        let mut clear = false;
+        ui.horizontal(|ui| {
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui.button("Clear").on_hover_text("Clear the console").clicked() {
+                    clear = true;
+                }
+                if ui.button("Copy").on_hover_text("Copy the visible lines").clicked() {
+                    let text = visible.iter().map(|(_, n)| n.as_str()).collect::<Vec<_>>().join("\n");
+                    ui.output_mut(|o| o.copied_text = text);
+                }
+                if filter.scrolled_up && ui.button("\u{2B07}").on_hover_text("Scroll to the newest line and follow it again").clicked() {
+                    filter.jump = true;
+                }
+                let count = match filter.is_active() {
+                    true => format!("{}/{}", visible.len(), total),
+                    false => total.to_string()
+                };
+                ui.label(count).on_hover_text("Matching/stored lines");
+                ui.toggle_value(&mut filter.case_sensitive, "Aa").on_hover_text("Case sensitive");
+                ui.toggle_value(&mut filter.regex, ".*").on_hover_text("Regular expression");
+                let mut edit = egui::TextEdit::singleline(&mut filter.text)
+                    .hint_text("Filter")
+                    .desired_width(ui.available_width());
+                if matches!(filter.matcher, ConsoleMatcher::Invalid) {
+                    edit = edit.text_color(ui.visuals().error_fg_color);
+                }
+                edit.show(ui);
+            });
+        });
+        clear
+    }
+
+    fn preset_row(&mut self, ui: &mut Ui, app: &SerialMonitorApp) -> Option<RowButton> {
+        if app.presets().is_empty() && !app.sequences().iter().any(|n| n.button) {
+            return None;
+        }
+        let running = app.sequence_progress().is_some();
+        let mut clicked = None;
+        ui.add_enabled_ui(app.is_connected(), |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (i, preset) in app.presets().iter().enumerate() {
+                    let text = match preset.confirm {
+                        true => format!("{} \u{26A0}", preset.label),
+                        false => preset.label.clone()
+                    };
+                    if ui.button(text).on_hover_text(&preset.payload).clicked() {
+                        clicked = Some(RowButton::Preset(i));
+                    }
+                }
+                for (i, sequence) in app.sequences().iter().enumerate().filter(|(_, n)| n.button) {
+                    let button = egui::Button::new(format!("\u{25B6} {}", sequence.name));
+                    let hover = format!("Run sequence ({} steps)", sequence.steps.len());
+                    if ui.add_enabled(!running, button).on_hover_text(hover).clicked() {
+                        clicked = Some(RowButton::Sequence(i));
+                    }
+                }
+            });
+        });
+        clicked
+    }
+
+    fn table(&mut self, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> PlotResponse {
+        ui.add_space(PLOT_MARGIN);
+
+        let mut result = self.plot_header(ui, plot, app);
+        if result == PlotResponse::Remove {
+            return result;
+        }
+
+        let channels = app.display_channels();
+        let shown: Vec<(&InputSlot, &[[f64; 2]])> = channels.into_iter()
+            .enumerate()
+            .filter(|(i, _)| plot.shows(*i))
+            .map(|(_, n)| n)
+            .collect();
+        let values: Vec<&[[f64; 2]]> = shown.iter().map(|n| n.1).collect();
+        let times = plot_engine::recent_times(&values, plot.table.rows);
+        let mut table = plot.table.clone();
+        let mut copy = false;
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut table.rows).clamp_range(1..=TableConfig::MAX_ROWS).prefix("Last ").suffix(" rows"));
+            ui.add(egui::DragValue::new(&mut table.precision).clamp_range(0..=TableConfig::MAX_PRECISION).suffix(" decimals"));
+            copy = ui.button("Copy").on_hover_text("Copy the visible rows as tab separated values").clicked();
+            let text = match app.is_paused() {
+                true => format!("{} rows (paused)", times.len()),
+                false => format!("{} rows", times.len())
+            };
+            ui.label(text);
+        });
+        if table != plot.table {
+            result = PlotResponse::Table(table);
+        }
+
+        let precision = plot.table.precision;
+        let cells = |t: f64| -> Vec<String> {
+            std::iter::once(format!("{:.6}", t))
+                .chain(shown.iter().map(|(slot, values)| match plot_engine::value_at(values, t) {
+                    Some(value) => format!("{:.*}", precision, slot.apply(value)),
+                    None => String::new()
+                }))
+                .collect()
+        };
+        let width = f32::max(TABLE_COLUMN_WIDTH, (ui.available_width() - PLOT_MARGIN) / (shown.len() + 1) as f32 - ui.spacing().item_spacing.x);
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        if shown.is_empty() {
+            ui.weak("Select slots in the header");
+            return result;
+        }
+        let heading = |ui: &mut Ui, text: egui::RichText| {
+            ui.add_sized([width, row_height], egui::Label::new(text).truncate(true));
+        };
+        egui::ScrollArea::horizontal().id_source(("TableScroll", plot.id)).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                heading(ui, egui::RichText::new("t (s)").monospace().strong());
+                for (slot, _) in &shown {
+                    heading(ui, egui::RichText::new(&slot.name).monospace().strong().color(slot_color(slot, false)));
+                }
+            });
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .id_source(("TableRows", plot.id))
+                .auto_shrink([false, false])
+                .max_height(ui.available_height() - PLOT_MARGIN)
+                .show_rows(ui, row_height, times.len(), |ui, range| {
+                    if copy {
+                        let names = std::iter::once("t").chain(shown.iter().map(|n| n.0.name.as_str()));
+                        let mut text = names.collect::<Vec<&str>>().join("\t");
+                        for &t in &times[range.clone()] {
+                            text.push('\n');
+                            text.push_str(&cells(t).join("\t"));
+                        }
+                        ui.output_mut(|o| o.copied_text = text);
+                    }
+                    for &t in &times[range] {
+                        ui.horizontal(|ui| {
+                            for cell in cells(t) {
+                                ui.add_sized([width, row_height], egui::Label::new(egui::RichText::new(cell).monospace()));
+                            }
+                        });
+                    }
+                });
+        });
+        result
+    }
+
+    fn watch(&mut self, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> PlotResponse {
+        ui.add_space(PLOT_MARGIN);
+
+        let result = self.plot_header(ui, plot, app);
+        if result == PlotResponse::Remove {
+            return result;
+        }
+
+        let print = self.is_printing(plot.id);
+        if print {
+            ui.style_mut().visuals = egui::Visuals::light();
+        }
+        let channels = app.display_channels();
+        let watched: Vec<(usize, &InputSlot, &[[f64; 2]])> = plot.watched.iter()
+            .filter_map(|i| channels.get(*i).map(|(slot, values)| (*i, *slot, *values)))
+            .collect();
+        let size = egui::Vec2::new(ui.available_width(), ui.available_height() - (PLOT_MARGIN + ui.style().spacing.item_spacing.y));
+        let (rect, resp) = ui.allocate_exact_size(size, egui::Sense::click());
+        self.plot_rect(ui, plot.id, rect);
+
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+        if watched.is_empty() {
+            painter.text(rect.center(), Align2::CENTER_CENTER, "Select channels from the context menu", egui::FontId::proportional(14.0), ui.visuals().weak_text_color());
+        }
+        let axis = &plot.axis;
+        let width = rect.width() / watched.len().max(1) as f32;
+        for (n, (i, slot, values)) in watched.into_iter().enumerate() {
+            let cell = egui::Rect::from_min_size(rect.min + egui::Vec2::new(width * n as f32, 0.0), egui::Vec2::new(width, rect.height()))
+                .shrink(PLOT_MARGIN * 2.0);
+            let value = match values.last() {
+                Some(last) => format::format_value(slot.apply(last[1]), axis.value_format, axis.digits, &axis.unit),
+                None => String::from("-")
+            };
+            let value_size = f32::min(cell.height() * 0.5, cell.width() / (value.chars().count().max(1) as f32 * 0.62));
+            let label_size = f32::max(value_size * 0.2, 12.0);
+            painter.text(cell.center_top(), Align2::CENTER_TOP, &slot.name, egui::FontId::proportional(label_size), slot_color(slot, print));
+            let value_color = match app.is_stale(slot, values) {
+                true => ui.visuals().weak_text_color(),
+                false => ui.visuals().strong_text_color()
+            };
+            painter.text(cell.center(), Align2::CENTER_CENTER, value, egui::FontId::monospace(value_size), value_color);
+
+            if plot.watch_range {
+                if let Some(range) = self.watch_range(plot.id, i, values) {
+                    let (a, b) = (slot.apply(range.min), slot.apply(range.max));
+                    let text = format!("min {}   max {}",
+                        format::format_value(f64::min(a, b), axis.value_format, axis.digits, &axis.unit),
+                        format::format_value(f64::max(a, b), axis.value_format, axis.digits, &axis.unit));
+                    painter.text(cell.center_bottom(), Align2::CENTER_BOTTOM, text, egui::FontId::monospace(label_size), ui.visuals().text_color());
+                }
+            }
+        }
+
+        let mut menu_result = PlotResponse::None;
+        resp.context_menu(|ui| {
+            menu_result = self.plot_menu(ui, plot, app);
+        });
+
+        ui.add_space(PLOT_MARGIN);
+        match menu_result {
+            PlotResponse::None => result,
+            _ => menu_result
+        }
+    }
+
+    fn watch_range(&mut self, plot_id: usize, channel: usize, values: &[[f64; 2]]) -> Option<&WatchRange> {
+        let range = match self.watch_ranges.entry((plot_id, channel)) {
+            Entry::Occupied(o) => o.into_mut(),
+            Entry::Vacant(v) => v.insert(WatchRange {
+                t: f64::MIN,
+                min: f64::MAX,
+                max: f64::MIN
+            })
+        };
+        if values.last().is_some_and(|n| n[0] < range.t) {
+            range.t = f64::MIN;
+        }
+        let start = values.partition_point(|n| n[0] <= range.t);
+        for n in &values[start..] {
+            range.min = f64::min(range.min, n[1]);
+            range.max = f64::max(range.max, n[1]);
+            range.t = n[0];
+        }
+        Some(&*range).filter(|n| n.min <= n.max)
+    }
+
+    fn plot_header(&mut self, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> PlotResponse {
+        let mut result = PlotResponse::None;
+        let input_slots: Vec<&InputSlot> = match plot.kind {
+            PlotKind::Console => Vec::new(),
+            _ => app.channels().into_iter().map(|n| n.0).collect()
+        };
+        let locked = self.locked;
+        let header = ui.horizontal(|ui| {
+            ui.set_enabled(!locked);
+            match &mut self.renaming {
+                Some((id, name)) if *id == plot.id => {
+                    let resp = ui.add(egui::TextEdit::singleline(name).font(egui::TextStyle::Heading).desired_width(200.0));
+                    if !resp.has_focus() && !resp.lost_focus() {
+                        resp.request_focus();
+                    }
+                    if resp.lost_focus() {
+                        if !ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                            result = PlotResponse::Rename(name.clone());
+                        }
+                        self.renaming = None;
+                    }
+                },
+                _ => {
+                    let resp = ui.heading(&plot.name).interact(egui::Sense::click());
+                    resp.context_menu(|ui| {
+                        result = self.plot_menu(ui, plot, app);
+                    });
+                }
+            }
+            if plot.is_graph() || plot.kind == PlotKind::Table {
+                ui.menu_button("Slots", |ui| {
+                    if let Some(slots) = slot_selection(ui, plot, &input_slots, app.input_columns()..app.input_slots().len()) {
+                        result = PlotResponse::Slots(slots);
+                    }
+                });
+            }
+            if matches!(plot.kind, PlotKind::Time | PlotKind::Xy) {
+                if let Some(response) = xy_menu(ui, plot, &input_slots) {
+                    result = response;
+                }
+            }
+            if matches!(plot.kind, PlotKind::Time | PlotKind::Xy) {
+                if let Some(config) = override_menu(ui, plot, app.plot_config()) {
+                    result = PlotResponse::Override(config);
+                }
+            }
+            if plot.kind == PlotKind::Time {
+                if let Some(response) = self.compare_menu(ui, plot, &input_slots) {
+                    result = response;
+                }
+                if let Some(smoothing) = smoothing_menu(ui, plot) {
+                    result = PlotResponse::Smoothing(smoothing);
+                }
+                if let Some((references, fit)) = references_menu(ui, plot) {
+                    result = PlotResponse::References(references, fit);
+                }
+            }
+            if plot.kind == PlotKind::Spectrum {
+                egui::ComboBox::new(format!("FftSize_{}", plot.id), "")
+                    .selected_text(format!("{} pt", plot.fft_size))
+                    .width(70.0)
+                    .show_ui(ui, |ui| {
+                        for size in PlotData::FFT_SIZES {
+                            if ui.selectable_label(plot.fft_size == *size, format!("{} pt", size)).clicked() {
+                                result = PlotResponse::FftSize(*size);
+                            }
+                        }
+                    }).response.on_hover_text("Number of recent samples per spectrum, uneven samples are resampled to the mean rate");
+            }
+            let reset_btn_text = match plot.kind {
+                PlotKind::Console => "Clear",
+                _ => "Reset"
+            };
+            let detach_text = match plot.detached.is_some() {
+                true => "Dock",
+                false => "Pop out"
+            };
+            if ui.button(detach_text).on_hover_text("Show the plot in its own window or move it back").clicked() {
+                result = PlotResponse::Detach;
+            }
+            if ui.button(reset_btn_text).clicked() {
+                result = PlotResponse::Reset;
+            }
+            if ui.button("Delete").clicked() {
+                result = PlotResponse::Remove;
+            }
+        }).response;
+        self.locked_hint(ui, header.rect);
+        if result == PlotResponse::None {
+            if let Some((_, command)) = self.plot_command.take_if(|n| n.0 == plot.id) {
+                result = command;
+            }
+        }
+        if result == PlotResponse::Remove {
+            self.compares.remove(&plot.id);
+            self.spectra.retain(|key, _| key.0 != plot.id);
+        }
+        result
+    }
+
+    fn plot_menu(&mut self, ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> PlotResponse {
+        let mut result = PlotResponse::None;
+        close_menu_on_escape(ui);
+        if ui.button("Rename").clicked() {
+            self.renaming = Some((plot.id, plot.name.clone()));
+            ui.close_menu();
+        }
+        if plot.kind == PlotKind::Watch {
+            ui.menu_button("Channels", |ui| {
+                let mut watched = plot.watched.clone();
+                let mut range = plot.watch_range;
+                for (i, (slot, _)) in app.channels().into_iter().enumerate() {
+                    let mut checked = watched.contains(&i);
+                    if ui.checkbox(&mut checked, &slot.name).changed() {
+                        match checked {
+                            true => watched.push(i),
+                            false => watched.retain(|n| *n != i)
+                        }
+                    }
+                }
+                ui.separator();
+                ui.checkbox(&mut range, "Show min/max");
+                if watched != plot.watched || range != plot.watch_range {
+                    result = PlotResponse::Watch(watched, range);
+                }
+            });
+        } else if plot.is_graph() || plot.kind == PlotKind::Table {
+            ui.menu_button("Channels", |ui| {
+                let channels: Vec<&InputSlot> = app.channels().into_iter().map(|n| n.0).collect();
+                if let Some(slots) = slot_selection(ui, plot, &channels, app.input_columns()..app.input_slots().len()) {
+                    result = PlotResponse::Slots(slots);
+                }
+            });
+        }
+        if !matches!(plot.kind, PlotKind::Console | PlotKind::Table) {
+            ui.menu_button("Axis", |ui| {
+                let mut axis = plot.axis.clone();
+                ui.label("Values");
+                for value_format in VALUE_FORMATS {
+                    ui.selectable_value(&mut axis.value_format, *value_format, value_format.to_string());
+                }
+                ui.horizontal(|ui| {
+                    ui.label("Digits");
+                    ui.add(egui::DragValue::new(&mut axis.digits).clamp_range(0..=9));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Unit");
+                    egui::TextEdit::singleline(&mut axis.unit).desired_width(60.0).show(ui);
+                });
+                if plot.kind == PlotKind::Time {
+                    ui.separator();
+                    ui.label("Time");
+                    for time_format in TIME_FORMATS {
+                        ui.selectable_value(&mut axis.time_format, *time_format, time_format.to_string());
+                    }
+                }
+                if axis != plot.axis {
+                    result = PlotResponse::Axis(axis);
+                }
+            });
+        }
+        if plot.kind == PlotKind::Time {
+            ui.menu_button("Stack", |ui| {
+                let (mut stacked, mut spacing) = (plot.stacked, plot.stack_spacing);
+                ui.checkbox(&mut stacked, "Stack channels")
+                    .on_hover_text("Offset every visible channel by the spacing, the readout still shows the true values");
+                ui.horizontal(|ui| {
+                    ui.label("Spacing");
+                    ui.add_enabled(stacked, egui::DragValue::new(&mut spacing).clamp_range(0.001..=f64::MAX).speed(0.1));
+                });
+                if stacked != plot.stacked || spacing != plot.stack_spacing {
+                    result = PlotResponse::Stacking(stacked, spacing);
+                }
+            });
+            ui.menu_button("Shading", |ui| {
+                if let Some(shading) = shading_menu(ui, plot, app) {
+                    result = PlotResponse::Shading(shading);
+                }
+            });
+        }
+        let exportable = matches!(plot.kind, PlotKind::Time | PlotKind::Xy | PlotKind::Table);
+        if exportable && ui.add_enabled(!app.raw_values().is_empty(), egui::Button::new("Export visible")).clicked() {
+            self.dialog = Some(PendingDialog::ExportVisible(plot.id));
+            ui.close_menu();
+        }
+        if !matches!(plot.kind, PlotKind::Console | PlotKind::Table) {
+            for (text, print) in [("Copy image", false), ("Copy image (print style)", true)] {
+                if ui.button(text).clicked() {
+                    self.capture = Some(PlotCapture {
+                        plot_id: plot.id,
+                        rect: None,
+                        visible: false,
+                        requested: false,
+                        print
+                    });
+                    ui.close_menu();
+                }
+            }
+        }
+        let maximize_text = match self.maximized == Some(plot.id) {
+            true => "Restore",
+            false => "Maximize"
+        };
+        if plot.detached.is_none() && ui.button(maximize_text).clicked() {
+            result = PlotResponse::Maximize;
+            ui.close_menu();
+        }
+        let index = app.plots().iter().position(|n| n.id == plot.id).unwrap_or_default();
+        if ui.add_enabled(index > 0, egui::Button::new("Move up")).clicked() {
+            result = PlotResponse::MoveUp;
+            ui.close_menu();
+        }
+        if ui.add_enabled(index + 1 < app.plots().len(), egui::Button::new("Move down")).clicked() {
+            result = PlotResponse::MoveDown;
+            ui.close_menu();
+        }
+        ui.separator();
+        if ui.button("Delete").clicked() {
+            result = PlotResponse::Remove;
+            ui.close_menu();
+        }
+        result
+    }
+
+    fn slot_menu(&mut self, ui: &mut Ui, index: usize, slot: &mut InputSlot, plot_ids: &[usize], sequences: &[String], loss: &mut Option<LossConfig>) {
+        close_menu_on_escape(ui);
+        if ui.button("Rename").clicked() {
+            self.slot_focus = Some(index);
+            ui.close_menu();
+        }
+        ui.menu_button("Color", |ui| {
+            let mut hsva = egui::ecolor::Hsva::from_rgb(slot.color);
+            if egui::color_picker::color_picker_hsva_2d(ui, &mut hsva, egui::color_picker::Alpha::Opaque) {
+                slot.color = hsva.to_rgb();
+            }
+        });
+        ui.menu_button("Print color", |ui| {
+            let mut custom = slot.print_color.is_some();
+            if ui.checkbox(&mut custom, "Override").changed() {
+                slot.print_color = custom.then(|| print_color(slot));
+            }
+            let mut hsva = egui::ecolor::Hsva::from_rgb(print_color(slot));
+            ui.add_enabled_ui(custom, |ui| {
+                if egui::color_picker::color_picker_hsva_2d(ui, &mut hsva, egui::color_picker::Alpha::Opaque) {
+                    slot.print_color = Some(hsva.to_rgb());
+                }
+            });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Time shift");
+            ui.add(egui::DragValue::new(&mut slot.time_shift_ms).speed(1.0).clamp_range(-60000.0..=60000.0).suffix("ms"));
+            if ui.add_enabled(slot.time_shift_ms != 0.0, egui::Button::new("Reset").small()).clicked() {
+                slot.time_shift_ms = 0.0;
+            }
+        });
+        ui.menu_button("Scale/offset\u{2026}", |ui| {
+            close_menu_on_escape(ui);
+            scale_settings(ui, slot);
+        });
+        if ui.button("Calibrate\u{2026}").clicked() {
+            let calibration = slot.calibration.clone();
+            self.calibration = Some(CalibrationDialog {
+                slot: index,
+                raw: calibration.as_ref().map(|n| n.raw.map(Some)).unwrap_or_default(),
+                reference: calibration.map(|n| n.reference).unwrap_or_default(),
+                average: false,
+                seconds: 2.0,
+                capturing: None
+            });
+            ui.close_menu();
+        }
+        ui.horizontal(|ui| {
+            let mut custom = slot.stale_after.is_some();
+            if ui.checkbox(&mut custom, "Stale after").changed() {
+                slot.stale_after = custom.then_some(5.0);
+            }
+            if let Some(stale_after) = &mut slot.stale_after {
+                ui.add(egui::DragValue::new(stale_after).speed(0.1).clamp_range(0.1..=3600.0).suffix("s"));
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut enabled = slot.deadband.is_some();
+            if ui.checkbox(&mut enabled, "Deadband").changed() {
+                slot.deadband = enabled.then(Deadband::default);
+            }
+            if let Some(deadband) = &mut slot.deadband {
+                let suffix = if deadband.percent { "%" } else { "" };
+                ui.add(egui::DragValue::new(&mut deadband.threshold).speed(0.001).clamp_range(0.0..=f64::MAX).suffix(suffix));
+                ui.checkbox(&mut deadband.percent, "%");
+            }
+        });
+        if let Some(deadband) = &mut slot.deadband {
+            ui.horizontal(|ui| {
+                ui.label("Keep alive");
+                ui.add(egui::DragValue::new(&mut deadband.keep_alive).speed(0.1).clamp_range(0.01..=3600.0).suffix("s"));
+            });
+        }
+        ui.horizontal(|ui| {
+            let mut enabled = slot.anomaly.is_some();
+            if ui.checkbox(&mut enabled, "Anomalies beyond").changed() {
+                slot.anomaly = enabled.then(Anomaly::default);
+            }
+            if let Some(anomaly) = &mut slot.anomaly {
+                ui.add(egui::DragValue::new(&mut anomaly.sigma).speed(0.1).clamp_range(0.5..=100.0).suffix("\u{3c3}"));
+            }
+        });
+        if let Some(anomaly) = &mut slot.anomaly {
+            ui.horizontal(|ui| {
+                ui.label("Rolling window");
+                ui.add(egui::DragValue::new(&mut anomaly.window).speed(0.1).clamp_range(0.1..=3600.0).suffix("s"));
+            });
+            ui.horizontal(|ui| {
+                let mut alarm = anomaly.burst.is_some();
+                if ui.checkbox(&mut alarm, "Alarm after").changed() {
+                    anomaly.burst = alarm.then_some(3);
+                }
+                if let Some(burst) = &mut anomaly.burst {
+                    ui.add(egui::DragValue::new(burst).clamp_range(1..=1000));
+                    ui.label("in window");
+                }
+            });
+            if anomaly.burst.is_some() {
+                let text = anomaly.sequence.as_deref().unwrap_or("None");
+                ui.menu_button(format!("Alarm sequence: {}", text), |ui| {
+                    if ui.selectable_label(anomaly.sequence.is_none(), "None").clicked() {
+                        anomaly.sequence = None;
+                        ui.close_menu();
+                    }
+                    for name in sequences {
+                        if ui.selectable_label(anomaly.sequence.as_ref() == Some(name), name).clicked() {
+                            anomaly.sequence = Some(name.clone());
+                            ui.close_menu();
                         }
-                    };
-
-                    if config.scale_mode == PlotScaleMode::AutoMax && !hidden {
-                        let (local_min, local_max) = filtered.iter()
-                            .fold((f64::MAX, f64::MIN), |(min, max), n| {
-                                (f64::min(min, n[1]), f64::max(max, n[1]))
-                            });
-                        min = f64::min(min, local_min);
-                        max = f64::max(max, local_max);
                     }
-    
-                    let line = Line::new(PlotPoints::from(filtered))
-                        .name(&slot.name)
-                        .color(Color32::from_rgb(
-                            linear_u8_from_linear_f32(slot.color[0]),
-                            linear_u8_from_linear_f32(slot.color[1]),
-                            linear_u8_from_linear_f32(slot.color[2])
-                        ));
-                    ui.add(line);
-    
-                    if config.mode == PlotMode::Cyclic {
-                        let line = VLine::new(t_now)
-                            .color(Color32::WHITE)
-                            .width(1.5);
-                        ui.add(line);
+                });
+            }
+        }
+        let current = slot.apply(slot.value);
+        let mut enabled = slot.alarm.is_some();
+        if ui.checkbox(&mut enabled, "Alarm").changed() {
+            slot.alarm = enabled.then(ThresholdAlarm::default);
+        }
+        if let Some(alarm) = &mut slot.alarm {
+            for (label, limit) in [("Below", &mut alarm.low), ("Above", &mut alarm.high)] {
+                ui.horizontal(|ui| {
+                    let mut enabled = limit.is_some();
+                    if ui.checkbox(&mut enabled, label).changed() {
+                        *limit = enabled.then_some(current);
+                    }
+                    if let Some(limit) = limit {
+                        ui.add(egui::DragValue::new(limit).speed(0.01));
                     }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("Hysteresis");
+                ui.add(egui::DragValue::new(&mut alarm.hysteresis).speed(0.01).clamp_range(0.0..=f64::MAX))
+                    .on_hover_text("The value has to return this far inside the limits before the alarm can trip again");
+            });
+            ui.checkbox(&mut alarm.beep, "Beep");
+        }
+        let mut enabled = slot.color_map.is_some();
+        if ui.checkbox(&mut enabled, "Color map").on_hover_text("Color the plot line by its value").changed() {
+            slot.color_map = enabled.then(ColorMap::default);
+        }
+        if let Some(map) = &mut slot.color_map {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut map.min).speed(0.1));
+                ui.color_edit_button_rgb(&mut map.low);
+                let mut mid = map.mid.is_some();
+                if ui.checkbox(&mut mid, "").on_hover_text("Middle color").changed() {
+                    map.mid = mid.then(|| map.color_at((map.min + map.max) / 2.0));
+                }
+                if let Some(mid) = &mut map.mid {
+                    ui.color_edit_button_rgb(mid);
+                }
+                ui.color_edit_button_rgb(&mut map.high);
+                ui.add(egui::DragValue::new(&mut map.max).speed(0.1));
+            });
+        }
+        let mut counter = loss.as_ref().is_some_and(|n| n.slot == index);
+        if ui.checkbox(&mut counter, "Sequence counter").on_hover_text("Count missed increments of this slot as packet loss").changed() {
+            *loss = counter.then(|| LossConfig::new(index));
+        }
+        if let Some(config) = loss.as_mut().filter(|n| n.slot == index) {
+            ui.horizontal(|ui| {
+                ui.label("Wraps at");
+                ui.add(egui::DragValue::new(&mut config.modulus).clamp_range(0..=u64::MAX))
+                    .on_hover_text("0 if the counter does not wrap around");
+            });
+            ui.checkbox(&mut config.hide, "Hide from plots");
+            ui.horizontal(|ui| {
+                let mut alarm = config.alarm_rate.is_some();
+                if ui.checkbox(&mut alarm, "Alarm above").changed() {
+                    config.alarm_rate = alarm.then_some(1.0);
+                }
+                if let Some(rate) = &mut config.alarm_rate {
+                    ui.add(egui::DragValue::new(rate).speed(0.1).clamp_range(0.01..=100.0).suffix("%"));
+                }
+            });
+        }
+        ui.separator();
+        if ui.button("Hide everywhere").clicked() {
+            if let Some(ctx) = &self.ctx {
+                for id in plot_ids {
+                    set_item_hidden(ctx, Id::new(format!("Plot_{}", id)), &slot.legend_name(), true);
                 }
+            }
+            ui.close_menu();
+        }
+        if ui.button("Copy value").clicked() {
+            ui.output_mut(|o| o.copied_text = slot.apply(slot.value).to_string());
+            ui.close_menu();
+        }
+    }
 
-                if !zoom_enabled {
-                    let bounds_x: RangeInclusive<f64> = ui.plot_bounds().range_x();
-                    match config.scale_mode {
-                        PlotScaleMode::Auto => {
-                            ui.set_auto_bounds(egui::Vec2b::from([true, true]));
-                        },
-                        PlotScaleMode::AutoMax => {
-                            let entry = match self.plot_ranges.entry(plot.id) {
-                                Entry::Occupied(o) => o.into_mut(),
-                                Entry::Vacant(v) => v.insert([min, max])
-                            };
-                            entry[0] = f64::min(entry[0], min);
-                            entry[1] = f64::max(entry[1], max);
-                            ui.set_plot_bounds(PlotBounds::from_min_max(
-                                [*bounds_x.start(), entry[0]], 
-                                [*bounds_x.end(), entry[1]]));
-                            ui.set_auto_bounds(egui::Vec2b::from([true, false]));
-                        },
-                        PlotScaleMode::Manual => {
-                            ui.set_plot_bounds(PlotBounds::from_min_max(
-                                [*bounds_x.start(), config.y_min], 
-                                [*bounds_x.end(), config.y_max]));
-                            ui.set_auto_bounds(egui::Vec2b::from([true, false]));
+    fn compare_menu(&mut self, ui: &mut Ui, plot: &PlotData, input_slots: &[&InputSlot]) -> Option<PlotResponse> {
+        let slot_name = |index: usize| match input_slots.get(index) {
+            Some(slot) => slot.name.clone(),
+            None => format!("Slot {}", index + 1)
+        };
+        let mut result = None;
+        ui.add_enabled_ui(input_slots.len() >= 2 || self.compares.contains_key(&plot.id), |ui| {
+            ui.menu_button("Compare", |ui| {
+                let active = self.compares.get(&plot.id).copied();
+                let mut compare = active.unwrap_or(Compare {
+                    a: 0,
+                    b: 1,
+                    autoscale: true
+                });
+                let mut changed = false;
+                for (label, index) in [("A", &mut compare.a), ("B", &mut compare.b)] {
+                    ui.menu_button(format!("{}: {}", label, slot_name(*index)), |ui| {
+                        for (i, slot) in input_slots.iter().enumerate() {
+                            if ui.selectable_value(index, i, &slot.name).clicked() {
+                                changed = true;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
+                if active.is_some() {
+                    changed |= ui.checkbox(&mut compare.autoscale, "Include in autoscale").changed();
+                }
+                if changed {
+                    self.compares.insert(plot.id, compare);
+                }
+                ui.separator();
+                let promote = ui.add_enabled(active.is_some() && compare.a != compare.b, egui::Button::new("Promote to derived channel"))
+                    .on_hover_text("Add the difference A \u{2212} B as a derived channel");
+                if promote.clicked() {
+                    result = Some(PlotResponse::Promote(compare.a, compare.b));
+                    ui.close_menu();
+                }
+                if ui.add_enabled(active.is_some(), egui::Button::new("Clear")).clicked() {
+                    self.compares.remove(&plot.id);
+                    ui.close_menu();
+                }
+            });
+        });
+        result
+    }
+}
+
+fn sequence_steps(ui: &mut Ui, steps: &mut Vec<SequenceStep>) {
+    let mut remove = None;
+    for (i, step) in steps.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}.", i + 1));
+            egui::ComboBox::new(("StepKind", i), "")
+                .selected_text(step.kind())
+                .width(80.0)
+                .show_ui(ui, |ui| {
+                    for kind in SequenceStep::KINDS {
+                        if ui.selectable_label(step.kind() == *kind, *kind).clicked() && step.kind() != *kind {
+                            *step = SequenceStep::from_kind(kind);
                         }
                     }
+                });
+            match step {
+                SequenceStep::Send(text) => {
+                    egui::TextEdit::singleline(text).desired_width(200.0).show(ui);
+                },
+                SequenceStep::Wait(ms) => {
+                    ui.add(egui::DragValue::new(ms).clamp_range(0..=600000).suffix("ms"));
+                },
+                SequenceStep::WaitFor { pattern, timeout } => {
+                    egui::TextEdit::singleline(pattern).desired_width(120.0).show(ui);
+                    ui.add(egui::DragValue::new(timeout).clamp_range(0..=600000).suffix("ms"));
+                }
+            }
+            if ui.small_button("x").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        steps.remove(index);
+    }
+    if ui.small_button("Add step").clicked() {
+        steps.push(SequenceStep::Send(String::new()));
+    }
+}
+
+fn unit_settings(ui: &mut Ui, slot: &mut InputSlot) {
+    egui::Grid::new("UnitSettings").num_columns(2).show(ui, |ui| {
+        ui.label("Unit");
+        ui.add(egui::TextEdit::singleline(&mut slot.unit).hint_text("e.g. V").desired_width(60.0));
+        ui.end_row();
+        ui.label("Precision");
+        ui.add(egui::DragValue::new(&mut slot.precision).clamp_range(0..=InputSlot::MAX_PRECISION).suffix(" decimals"));
+        ui.end_row();
+    });
+}
+
+fn scale_settings(ui: &mut Ui, slot: &mut InputSlot) {
+    let (scale, offset) = (slot.scale, slot.offset);
+    egui::Grid::new("ScaleSettings").num_columns(2).show(ui, |ui| {
+        ui.label("Scale");
+        ui.add(egui::DragValue::new(&mut slot.scale).speed(0.01).max_decimals(6));
+        ui.end_row();
+        ui.label("Offset");
+        ui.add(egui::DragValue::new(&mut slot.offset).speed(0.01).max_decimals(6));
+        ui.end_row();
+    });
+    ui.label(egui::RichText::new("Shown value = raw \u{d7} scale + offset").small().weak());
+    if slot.scale == 0.0 {
+        slot.scale = scale;
+    }
+    if slot.scale != scale || slot.offset != offset {
+        slot.calibration = None;
+    }
+    if ui.add_enabled(slot.is_scaled(), egui::Button::new("Reset")).clicked() {
+        slot.scale = 1.0;
+        slot.offset = 0.0;
+        slot.calibration = None;
+        ui.close_menu();
+    }
+}
+
+fn plot_style(ui: &mut Ui, print: bool, config: &UiConfig) -> f32 {
+    match print {
+        true => {
+            ui.style_mut().visuals = egui::Visuals::light();
+            PRINT_LINE_WIDTH
+        },
+        false => {
+            if let Some(color) = config.plot_background {
+                ui.style_mut().visuals.extreme_bg_color = rgb_color(color);
+            }
+            config.line_width
+        }
+    }
+}
+
+fn slot_color(slot: &InputSlot, print: bool) -> Color32 {
+    match print {
+        true => rgb_color(print_color(slot)),
+        false => rgb_color(slot.color)
+    }
+}
+
+fn rgb_color(color: [f32; 3]) -> Color32 {
+    Color32::from_rgb(
+        linear_u8_from_linear_f32(color[0]),
+        linear_u8_from_linear_f32(color[1]),
+        linear_u8_from_linear_f32(color[2]))
+}
+
+fn lighten(color: Color32, amount: f32) -> Color32 {
+    let mix = |n: u8| n + ((255 - n) as f32 * amount) as u8;
+    Color32::from_rgb(mix(color.r()), mix(color.g()), mix(color.b()))
+}
+
+fn print_color(slot: &InputSlot) -> [f32; 3] {
+    if let Some(color) = slot.print_color {
+        return color;
+    }
+    let mut hsva = egui::ecolor::Hsva::from_rgb(slot.color);
+    hsva.s = f32::max(hsva.s, PRINT_MIN_SATURATION);
+    hsva.v = f32::min(hsva.v, PRINT_MAX_VALUE);
+    hsva.to_rgb()
+}
+
+fn snapshot_columns(plot: &PlotData, app: &SerialMonitorApp) -> Option<Columns> {
+    let channels = app.display_channels();
+    let t_now = plot_engine::latest_time(&channels);
+    let (shown, start): (Vec<usize>, f64) = match plot.kind {
+        PlotKind::Time => (
+            (0..channels.len()).filter(|i| plot.shows(*i) && !plot.hidden.contains(i)).collect(),
+            t_now - plot.effective_config(app.plot_config()).window
+        ),
+        PlotKind::Xy => (
+            vec![plot.xy.x_slot, plot.xy.y_slot],
+            t_now - plot.effective_config(app.plot_config()).window
+        ),
+        PlotKind::Table => {
+            let shown: Vec<usize> = (0..channels.len()).filter(|i| plot.shows(*i)).collect();
+            let values: Vec<&[[f64; 2]]> = shown.iter().map(|i| channels[*i].1).collect();
+            let start = plot_engine::recent_times(&values, plot.table.rows).first().copied().unwrap_or(f64::MAX);
+            (shown, start)
+        },
+        _ => return None
+    };
+    let (names, values) = shown.into_iter()
+        .filter_map(|i| channels.get(i))
+        .map(|(slot, values)| (slot.legend_name(), plot_engine::since(&plot_engine::display_values(values, slot), start).to_vec()))
+        .unzip();
+    Some(Columns { names, values })
+}
+
+fn save_temp_image(image: &egui::ColorImage) -> Result<String, image::ImageError> {
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    let path = std::env::temp_dir().join(format!("serial_monitor_plot_{}.png", stamp));
+    image::save_buffer(&path, image.as_raw(), image.width() as u32, image.height() as u32, image::ColorType::Rgba8)?;
+    Ok(path.display().to_string())
+}
+
+fn close_menu_on_escape(ui: &mut Ui) {
+    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+        ui.close_menu();
+    }
+}
+
+fn shading_menu(ui: &mut Ui, plot: &PlotData, app: &SerialMonitorApp) -> Option<Option<Shading>> {
+    close_menu_on_escape(ui);
+    let channels = app.channels();
+    let mut shading = plot.shading.clone();
+    let name = match shading.as_ref().map(|n| channels.get(n.channel)) {
+        Some(Some((slot, _))) => slot.name.clone(),
+        Some(None) => String::from("Missing"),
+        None => String::from("None")
+    };
+    ui.menu_button(format!("Channel: {}", name), |ui| {
+        if ui.selectable_label(shading.is_none(), "None").clicked() {
+            shading = None;
+            ui.close_menu();
+        }
+        for (i, (slot, _)) in channels.iter().enumerate() {
+            if ui.selectable_label(shading.as_ref().is_some_and(|n| n.channel == i), &slot.name).clicked() {
+                shading.get_or_insert_with(|| Shading::new(i)).channel = i;
+                ui.close_menu();
+            }
+        }
+    });
+    if let Some(shading) = &mut shading {
+        ui.separator();
+        let mut remove = None;
+        for (i, entry) in shading.colors.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut entry.value).speed(0.1).max_decimals(2));
+                for color in SHADING_COLORS {
+                    let stroke = match entry.color == *color {
+                        true => ui.visuals().selection.stroke,
+                        false => egui::Stroke::NONE
+                    };
+                    let button = egui::Button::new("")
+                        .fill(rgb_color(*color))
+                        .stroke(stroke)
+                        .min_size(egui::vec2(14.0, 14.0));
+                    if ui.add(button).clicked() {
+                        entry.color = *color;
+                    }
+                }
+                if ui.small_button("x").clicked() {
+                    remove = Some(i);
                 }
             });
+        }
+        if let Some(i) = remove {
+            shading.colors.remove(i);
+        }
+        if ui.button("Add value").clicked() {
+            let value = shading.colors.iter().map(|n| n.value + 1.0).fold(0.0, f64::max);
+            let color = SHADING_COLORS[shading.colors.len() % SHADING_COLORS.len()];
+            shading.colors.push(ShadingColor { value, color });
+        }
+    }
+    match shading != plot.shading {
+        true => Some(shading),
+        false => None
+    }
+}
 
-        let hidden = PlotMemory::load(&ctx, Id::new(&plt_id))
-            .map_or_else(Vec::new, |mem| {
-                input_slots.iter()
-                    .filter(|slot| mem.hidden_items.contains(&slot.name))
-                    .map(|slot| slot.index)
-                    .collect()
+fn references_menu(ui: &mut Ui, plot: &PlotData) -> Option<(Vec<RefLine>, bool)> {
+    let (mut references, mut fit) = (plot.references.clone(), plot.fit_references);
+    ui.menu_button("Ref", |ui| {
+        close_menu_on_escape(ui);
+        let mut remove = None;
+        for (i, reference) in references.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut reference.value).speed(0.1).max_decimals(3));
+                ui.add(egui::TextEdit::singleline(&mut reference.label).hint_text("Label").desired_width(70.0));
+                for color in SHADING_COLORS {
+                    let stroke = match reference.color == *color {
+                        true => ui.visuals().selection.stroke,
+                        false => egui::Stroke::NONE
+                    };
+                    let button = egui::Button::new("")
+                        .fill(rgb_color(*color))
+                        .stroke(stroke)
+                        .min_size(egui::vec2(14.0, 14.0));
+                    if ui.add(button).clicked() {
+                        reference.color = *color;
+                    }
+                }
+                let mut band = reference.band.is_some();
+                if ui.checkbox(&mut band, "\u{b1}").on_hover_text("Shade a band around the value").changed() {
+                    reference.band = band.then_some(1.0);
+                }
+                if let Some(band) = &mut reference.band {
+                    ui.add(egui::DragValue::new(band).speed(0.1).clamp_range(0.001..=f64::MAX).max_decimals(3));
+                }
+                if ui.small_button("x").clicked() {
+                    remove = Some(i);
+                }
             });
+        }
+        if let Some(i) = remove {
+            references.remove(i);
+        }
+        if ui.button("Add line").clicked() {
+            let value = references.last().map_or(0.0, |n| n.value + 1.0);
+            let color = SHADING_COLORS[(references.len() + 1) % SHADING_COLORS.len()];
+            references.push(RefLine { value, label: String::new(), color, band: None });
+        }
+        ui.separator();
+        ui.checkbox(&mut fit, "Include in auto scale")
+            .on_hover_text("Keep the lines and bands in view in the Auto and AutoMax scale modes");
+    }).response.on_hover_text("Horizontal reference lines and bands");
+    (references != plot.references || fit != plot.fit_references).then_some((references, fit))
+}
 
-        ui.add_space(PLOT_MARGIN);        
-        (result, match !empty {
-            true => Some(hidden),
-            false => None
-        })
+fn reference_shapes(ui: &Ui, reference: &RefLine, transform: &egui_plot::PlotTransform, rect: egui::Rect) -> Vec<egui::Shape> {
+    let color = rgb_color(reference.color);
+    let mut shapes = Vec::new();
+    let (low, high) = reference.range();
+    if reference.band.is_some() {
+        let y0 = transform.position_from_point_y(high).clamp(rect.top(), rect.bottom());
+        let y1 = transform.position_from_point_y(low).clamp(rect.top(), rect.bottom());
+        if y1 > y0 {
+            shapes.push(egui::Shape::rect_filled(egui::Rect::from_x_y_ranges(rect.x_range(), y0..=y1), 0.0, color.gamma_multiply(SHADING_ALPHA)));
+        }
+    }
+    let y = transform.position_from_point_y(reference.value);
+    if rect.y_range().contains(y) {
+        shapes.push(egui::Shape::hline(rect.x_range(), y, egui::Stroke::new(1.5, color)));
+        if !reference.label.is_empty() {
+            let font = egui::FontId::proportional(12.0);
+            shapes.push(ui.fonts(|f| egui::Shape::text(f, egui::pos2(rect.left() + 6.0, y - 2.0), Align2::LEFT_BOTTOM, &reference.label, font, color)));
+        }
     }
+    shapes
+}
 
-    fn console(&mut self, _ctx: &egui::Context, ui: &mut Ui, plot: &PlotData, lines: &VecDeque<String>) -> PlotResponse {
-        ui.add_space(PLOT_MARGIN);
-    
-        let result = self.plot_header(ui, plot);
-        if result == PlotResponse::Remove {
-            return result;
+fn envelope_mesh(points: &[[f64; 3]], transform: &egui_plot::PlotTransform, color: Color32) -> egui::Mesh {
+    let mut mesh = egui::Mesh::default();
+    for (k, n) in points.iter().enumerate() {
+        mesh.colored_vertex(transform.position_from_point(&egui_plot::PlotPoint::new(n[0], n[1])), color);
+        mesh.colored_vertex(transform.position_from_point(&egui_plot::PlotPoint::new(n[0], n[2])), color);
+        if k > 0 {
+            let i = 2 * k as u32;
+            mesh.add_triangle(i - 2, i - 1, i);
+            mesh.add_triangle(i - 1, i, i + 1);
         }
-    
-        let plt_id = format!("Plot_{}", plot.id);    
-        egui::ScrollArea::vertical()
-            .id_source(plt_id)
-            .max_height(ui.available_height() - (PLOT_MARGIN + ui.style().spacing.item_spacing.y))
-            .auto_shrink([false, false])
-            .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::VisibleWhenNeeded)
-            .stick_to_bottom(true)
-            .show(ui, |ui| {
-                for line in lines.iter() {
-                    ui.label(line);
+    }
+    mesh
+}
+
+fn xy_menu(ui: &mut Ui, plot: &PlotData, input_slots: &[&InputSlot]) -> Option<PlotResponse> {
+    let mut result = None;
+    let mode = match plot.kind {
+        PlotKind::Xy => "X/Y",
+        _ => "Time"
+    };
+    egui::ComboBox::new(format!("PlotMode_{}", plot.id), "")
+        .selected_text(mode)
+        .width(50.0)
+        .show_ui(ui, |ui| {
+            for (kind, text) in [(PlotKind::Time, "Time"), (PlotKind::Xy, "X/Y")] {
+                if ui.selectable_label(plot.kind == kind, text).clicked() && plot.kind != kind {
+                    result = Some(PlotResponse::Kind(kind));
+                }
+            }
+        }).response.on_hover_text("Plot the channels over time, or one channel against another");
+    if plot.kind != PlotKind::Xy {
+        return result;
+    }
+    let mut xy = plot.xy.clone();
+    let slot_name = |index: usize| match input_slots.get(index) {
+        Some(slot) => slot.name.clone(),
+        None => format!("Slot {}", index + 1)
+    };
+    for (label, index) in [("X", &mut xy.x_slot), ("Y", &mut xy.y_slot)] {
+        egui::ComboBox::new(format!("PlotXy{}_{}", label, plot.id), label)
+            .selected_text(slot_name(*index))
+            .width(90.0)
+            .show_ui(ui, |ui| {
+                for (i, slot) in input_slots.iter().enumerate() {
+                    ui.selectable_value(index, i, &slot.name);
                 }
             });
-
-        ui.add_space(PLOT_MARGIN);        
-        result
     }
+    ui.checkbox(&mut xy.points, "Points");
+    if xy != plot.xy {
+        result = Some(PlotResponse::Xy(xy));
+    }
+    result
+}
 
-    fn plot_header(&self, ui: &mut Ui, plot: &PlotData) -> PlotResponse {
-        let mut result = PlotResponse::None;
+fn smoothing_menu(ui: &mut Ui, plot: &PlotData) -> Option<Option<Smoothing>> {
+    let mut smoothing = plot.smoothing.clone();
+    ui.menu_button("Smooth", |ui| {
+        close_menu_on_escape(ui);
+        let mut enabled = smoothing.is_some();
+        if ui.checkbox(&mut enabled, "Moving average").changed() {
+            smoothing = enabled.then(|| plot.smoothing.clone().unwrap_or_default());
+        }
+        let mut config = smoothing.clone().unwrap_or_default();
+        ui.add_enabled_ui(enabled, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Samples");
+                ui.add(egui::DragValue::new(&mut config.window).clamp_range(1..=Smoothing::MAX_WINDOW));
+            });
+            ui.checkbox(&mut config.show_raw, "Show raw values");
+            ui.checkbox(&mut config.envelope, "Min/max envelope")
+                .on_hover_text("Shade the spread of the raw values within each averaging window");
+        });
+        if enabled {
+            smoothing = Some(config);
+        }
+    }).response.on_hover_text("Average the plotted values over the last samples");
+    Some(smoothing).filter(|n| *n != plot.smoothing)
+}
+
+fn override_menu(ui: &mut Ui, plot: &PlotData, global: &PlotConfig) -> Option<Option<PlotConfig>> {
+    let mut override_config = plot.override_config.clone();
+    ui.menu_button("\u{2699}", |ui| {
+        close_menu_on_escape(ui);
+        let mut custom = override_config.is_some();
+        ui.radio_value(&mut custom, false, "Use global settings");
+        ui.radio_value(&mut custom, true, "Custom");
+        if custom != override_config.is_some() {
+            override_config = custom.then(|| plot.effective_config(global));
+        }
+        let Some(config) = &mut override_config else {
+            return;
+        };
+        ui.separator();
+        for mode in PLOT_MODES {
+            ui.selectable_value(&mut config.mode, *mode, mode.to_string());
+        }
         ui.horizontal(|ui| {
-            ui.heading(&plot.name);
-            let reset_btn_text = match plot.console {
-                true => "Clear",
-                false => "Reset"
-            };
-            if ui.button(reset_btn_text).clicked() {
-                result = PlotResponse::Reset;
+            ui.label("Window");
+            ui.add(egui::DragValue::new(&mut config.window).clamp_range(0.01..=global.history_secs).fixed_decimals(2).suffix("s"));
+        });
+        ui.separator();
+        for scale_mode in SCALE_MODES {
+            ui.selectable_value(&mut config.scale_mode, *scale_mode, scale_mode.to_string());
+        }
+        if config.scale_mode == PlotScaleMode::Manual {
+            let (min, max) = (config.y_min, config.y_max);
+            ui.horizontal(|ui| {
+                ui.label("Min");
+                ui.add(egui::DragValue::new(&mut config.y_min).clamp_range(f64::MIN..=max).fixed_decimals(2));
+                ui.label("Max");
+                ui.add(egui::DragValue::new(&mut config.y_max).clamp_range(min..=f64::MAX).fixed_decimals(2));
+            });
+        }
+    }).response.on_hover_text(match plot.override_config.is_some() {
+        true => "Mode, window and scale of this plot (custom)",
+        false => "Mode, window and scale of this plot (global settings)"
+    });
+    Some(override_config).filter(|n| *n != plot.override_config)
+}
+
+fn shading_bands(shading: &Shading, channels: &[(&InputSlot, &[[f64; 2]])], split: f64, config: &PlotConfig) -> Vec<(f64, f64, Color32)> {
+    let Some((slot, values)) = channels.get(shading.channel).copied().filter(|n| !n.1.is_empty()) else {
+        return Vec::new();
+    };
+    let values = plot_engine::display_values(values, slot);
+    let t_now = values[values.len() - 1][0];
+    let mut bands: Vec<(f64, f64, Color32)> = Vec::new();
+    for pair in plot_engine::window_values(&values, t_now, split, config).windows(2) {
+        let ([x0, value], [x1, _]) = (pair[0], pair[1]);
+        let Some(color) = shading.color(value).filter(|_| x1 > x0) else {
+            continue;
+        };
+        let color = rgb_color(color).gamma_multiply(SHADING_ALPHA);
+        match bands.last_mut() {
+            Some(last) if last.1 == x0 && last.2 == color => last.1 = x1,
+            _ => bands.push((x0, x1, color))
+        }
+    }
+    bands
+}
+
+fn slot_selection(ui: &mut Ui, plot: &PlotData, channels: &[&InputSlot], missing: std::ops::Range<usize>) -> Option<Option<Vec<usize>>> {
+    close_menu_on_escape(ui);
+    let mut result = None;
+    for (i, slot) in channels.iter().enumerate() {
+        let mut checked = plot.shows(i);
+        let text = match missing.contains(&i) {
+            true => format!("{} (no data)", slot.name),
+            false => slot.name.clone()
+        };
+        if ui.checkbox(&mut checked, text).changed() {
+            let mut slots = plot.slots.clone().unwrap_or_else(|| (0..channels.len()).collect());
+            match checked {
+                true => slots.push(i),
+                false => slots.retain(|n| *n != i)
             }
-            if ui.button("Delete").clicked() {
-                result = PlotResponse::Remove;
+            slots.sort_unstable();
+            result = Some(Some(slots));
+        }
+    }
+    ui.separator();
+    if ui.add_enabled(plot.slots.is_some(), egui::Button::new("Show all")).clicked() {
+        result = Some(None);
+        ui.close_menu();
+    }
+    result
+}
+
+fn set_item_hidden(ctx: &egui::Context, plot_id: Id, name: &str, hidden: bool) {
+    if let Some(mut mem) = PlotMemory::load(ctx, plot_id) {
+        match hidden {
+            true => mem.hidden_items.insert(name.to_owned()),
+            false => mem.hidden_items.remove(name)
+        };
+        mem.store(ctx, plot_id);
+    }
+}
+
+fn color_map_legend(ui: &egui::Ui, rect: egui::Rect, maps: &[(&str, &ColorMap)], axis: &PlotAxis) {
+    const STEPS: usize = 32;
+    const BAR_SIZE: egui::Vec2 = egui::vec2(100.0, 8.0);
+    let font = egui::FontId::monospace(11.0);
+    let color = ui.visuals().text_color();
+    let mut top = rect.top() + PLOT_MARGIN + 4.0;
+    for (name, map) in maps {
+        let format = |n: f64| format::format_value(n, axis.value_format, axis.digits, &axis.unit);
+        let text = format!("{}  {} \u{2192} {}", name, format(map.min), format(map.max));
+        let galley = ui.painter().layout_no_wrap(text, font.clone(), color);
+        let width = f32::max(galley.size().x, BAR_SIZE.x);
+        let min = egui::pos2(rect.right() - width - PLOT_MARGIN - 4.0, top);
+        let background = egui::Rect::from_min_size(min, egui::vec2(width, galley.size().y + BAR_SIZE.y + 2.0)).expand(4.0);
+        ui.painter().rect(background, 2.0, ui.visuals().extreme_bg_color.gamma_multiply(0.9), ui.visuals().widgets.noninteractive.bg_stroke);
+        ui.painter().galley(min, galley.clone(), color);
+
+        let bar = egui::Rect::from_min_size(min + egui::vec2(0.0, galley.size().y + 2.0), egui::vec2(width, BAR_SIZE.y));
+        let mut mesh = egui::Mesh::default();
+        for k in 0..=STEPS {
+            let t = k as f64 / STEPS as f64;
+            let color = rgb_color(map.color_at(map.min + (map.max - map.min) * t));
+            let x = bar.left() + bar.width() * t as f32;
+            mesh.colored_vertex(egui::pos2(x, bar.top()), color);
+            mesh.colored_vertex(egui::pos2(x, bar.bottom()), color);
+            if k > 0 {
+                let i = k as u32 * 2;
+                mesh.add_triangle(i - 2, i - 1, i);
+                mesh.add_triangle(i - 1, i, i + 1);
             }
-        });
-        result
+        }
+        ui.painter().add(egui::Shape::mesh(mesh));
+        top = background.bottom() + 8.0;
     }
 }
 
+fn cursor_readout(cursors: &Cursors, visible: &[usize], app: &SerialMonitorApp, axis: &PlotAxis) -> String {
+    let mut lines: Vec<String> = CURSOR_NAMES.iter()
+        .zip(cursors.t)
+        .filter_map(|(name, t)| Some(format!("{} t = {:.3}s", name, t?)))
+        .collect();
+    if let [Some(a), Some(b)] = cursors.t {
+        lines.push(format!("Δt = {:.3}s", b - a));
+    }
+    let format = |value: Option<f64>| value.map_or(String::from("-"), |n| format::format_value(n, axis.value_format, axis.digits + 3, &axis.unit));
+    let channels = app.channels();
+    let width = visible.iter().map(|i| channels[*i].0.name.chars().count()).max().unwrap_or_default();
+    for i in visible {
+        let values = cursors.t.map(|t| t.and_then(|t| app.interpolate(*i, t)));
+        let mut line = format!("{:width$}", channels[*i].0.name, width = width);
+        for (t, value) in cursors.t.iter().zip(values) {
+            if t.is_some() {
+                line.push_str(&format!("  {}", format(value)));
+            }
+        }
+        if let [Some(a), Some(b)] = values {
+            line.push_str(&format!("  Δ {}", format(Some(b - a))));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+fn compare_values(compare: &Compare, app: &SerialMonitorApp, split: f64, config: &PlotConfig) -> Option<CompareValues> {
+    let channels = app.display_channels();
+    let (slot_a, a) = *channels.get(compare.a)?;
+    let (slot_b, b) = *channels.get(compare.b)?;
+    let a = plot_engine::display_values(a, slot_a);
+    let b = plot_engine::display_values(b, slot_b);
+
+    let mut diff: Vec<[f64; 2]> = Vec::with_capacity(usize::min(a.len(), b.len()));
+    let mut j = 0;
+    for [t, value] in a.iter().copied() {
+        while j + 1 < b.len() && b[j + 1][0] <= t {
+            j += 1;
+        }
+        let Some(&[t0, v0]) = b.get(j) else {
+            break;
+        };
+        if t0 == t {
+            diff.push([t, value - v0]);
+        } else if let Some(&[t1, v1]) = b.get(j + 1).filter(|_| t0 < t) {
+            diff.push([t, value - (v0 + (v1 - v0) * (t - t0) / (t1 - t0))]);
+        }
+    }
+    let [t_now, current] = *diff.last()?;
+    let diff = plot_engine::window_values(&diff, t_now, split, config);
+
+    let rms = (diff.iter().map(|n| n[1] * n[1]).sum::<f64>() / diff.len() as f64).sqrt();
+    let max_abs = diff.iter().fold(0.0, |max, n| f64::max(max, n[1].abs()));
+    Some(CompareValues {
+        name: plot_engine::difference_name(slot_a, slot_b),
+        values: diff,
+        autoscale: compare.autoscale,
+        current,
+        rms,
+        max_abs
+    })
+}
+
+fn status_dot(ui: &mut egui::Ui, connected: bool) {
+    let (rect, _) = ui.allocate_exact_size(egui::Vec2::splat(STATUS_RADIUS * 2.5), egui::Sense::hover());
+    let col = match connected {
+        true => egui::Color32::DARK_GREEN,
+        false => egui::Color32::DARK_RED
+    };
+    ui.painter().circle_filled(rect.center(), STATUS_RADIUS, col);
+}
+
 fn option_dropdown<T: PartialEq + Clone + Display>(ui: &mut egui::Ui, label: &'static str, options: &[T], value: &mut T, spacing: f32) {
     ui.horizontal(|ui| {
         ui.label(label);
@@ -550,6 +5574,50 @@ fn option_dropdown<T: PartialEq + Clone + Display>(ui: &mut egui::Ui, label: &'s
     });
 }
 
+fn editable_dropdown<T: PartialEq + Copy + Display + FromStr>(ui: &mut egui::Ui, label: &'static str, options: &[T], value: &mut T, entry_text: &mut Option<(&'static str, String)>, spacing: f32, valid: impl Fn(T) -> bool) -> Option<T> {
+    let mut entered = None;
+    ui.horizontal(|ui| {
+        ui.label(label);
+        ui.add_space(spacing);
+        let mut text = match entry_text.as_ref() {
+            Some((field, text)) if *field == label => text.clone(),
+            _ => value.to_string()
+        };
+        let parsed = text.trim().parse::<T>().ok().filter(|n| valid(*n));
+        let arrow_width = ui.spacing().icon_width + ui.spacing().button_padding.x * 2.0;
+        let input = egui::TextEdit::singleline(&mut text)
+            .id(Id::new(label).with("Entry"))
+            .text_color_opt(parsed.is_none().then_some(ERROR_COLOR))
+            .desired_width(DROPDOWN_WIDTH - arrow_width - ui.spacing().item_spacing.x * 2.0)
+            .show(ui).response;
+        if input.has_focus() {
+            *entry_text = Some((label, text));
+        } else if input.lost_focus() {
+            if let Some(parsed) = text.trim().parse::<T>().ok().filter(|n| valid(*n)) {
+                *value = parsed;
+                entered = Some(parsed);
+            }
+            *entry_text = None;
+        }
+        egui::ComboBox::new(label, "")
+            .selected_text("")
+            .width(arrow_width)
+            .show_ui(ui, |ui| {
+                for option in options {
+                    ui.selectable_value(value, *option, option.to_string());
+                }
+            });
+    }).response.on_hover_text("Pick a value or type another one");
+    entered
+}
+
+fn baud_options(custom: &[u32], current: u32) -> Vec<u32> {
+    let mut bauds: Vec<u32> = BAUD_RATES.iter().chain(custom).copied().chain(Some(current).filter(|n| *n > 0)).collect();
+    bauds.sort_unstable();
+    bauds.dedup();
+    bauds
+}
+
 fn drag_value<T: Numeric>(ui: &mut egui::Ui, label: &'static str, value: &mut T, spacing: f32, range: RangeInclusive<T>, decimals: usize, suffix: &str) {
     ui.horizontal_top(|ui| {
         ui.label(label);
@@ -569,6 +5637,10 @@ fn render_notification(ctx: &egui::Context, notification: &Notification, pos: eg
         NotificationType::Warning => WARNING_COLOR,
         NotificationType::Error => ERROR_COLOR
     };
+    let color = match ctx.style().visuals.dark_mode {
+        true => color,
+        false => lighten(color, 0.35)
+    };
     let frame = egui::Frame::popup(&ctx.style()).fill(color);
     egui::Window::new(format!("Notification_{}", minor))
         .fixed_pos(pos)
@@ -0,0 +1,32 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn wide_line(columns: usize) -> String {
+    (0..columns).map(|n| n.to_string()).collect::<Vec<_>>().join(",")
+}
+
+#[test]
+fn wide_lines_are_rejected_once() {
+    let wide = wide_line(10_000);
+    let input = format!("{wide}\n1,2,3\n{wide}\n4,5,6\n");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_serial_monitor"))
+        .args(["--stdin", "--headless", "--output", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run serial_monitor");
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let rows: Vec<&str> = stdout.lines().collect();
+    assert_eq!(rows.len(), 3, "{stdout}");
+    assert_eq!(rows[0], "t,Slot 1,Slot 2,Slot 3");
+    assert!(rows[1].ends_with(",1,2,3") && rows[2].ends_with(",4,5,6"));
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let warnings: Vec<&str> = stderr.lines().filter(|n| n.contains("are ignored")).collect();
+    assert_eq!(warnings, ["Lines with 10000 values are ignored, the channel limit is 64"]);
+}
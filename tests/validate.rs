@@ -0,0 +1,77 @@
+use serde_json::Value;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+}
+
+fn validate(name: &str, json: bool) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_serial_monitor"));
+    command.arg("--validate").arg(fixture(name));
+    if json {
+        command.arg("--json");
+    }
+    command.output().expect("failed to run serial_monitor")
+}
+
+fn json_result(name: &str) -> (i32, Value) {
+    let output = validate(name, true);
+    let result = serde_json::from_slice(&output.stdout).expect("--json output is not valid json");
+    (output.status.code().unwrap_or(-1), result)
+}
+
+fn errors(result: &Value) -> Vec<&str> {
+    result["errors"].as_array()
+        .expect("errors is not an array")
+        .iter()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+#[test]
+fn valid_config() {
+    let (code, result) = json_result("valid.json");
+    assert_eq!(code, 0);
+    assert_eq!(result["valid"], true);
+    assert!(errors(&result).is_empty());
+    assert!(result["file"].as_str().is_some_and(|n| n.ends_with("valid.json")));
+
+    let output = validate("valid.json", false);
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains(": OK"));
+}
+
+#[test]
+fn invalid_values() {
+    let (code, result) = json_result("invalid_values.json");
+    assert_eq!(code, 1);
+    assert_eq!(result["valid"], false);
+    assert_eq!(errors(&result), [
+        "conn_config.baud_rate must be greater than 0",
+        "conn_config.data_bits must be 5-8 (found 9)",
+        "plot_config.window must be greater than 0 (found -1)"
+    ]);
+
+    let output = validate("invalid_values.json", false);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("3 error(s)"));
+}
+
+#[test]
+fn malformed_json() {
+    let (code, result) = json_result("malformed.json");
+    assert_eq!(code, 1);
+    assert_eq!(result["valid"], false);
+    let errors = errors(&result);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].starts_with("Could not parse config: Invalid JSON at line "), "{}", errors[0]);
+}
+
+#[test]
+fn missing_file() {
+    let (code, result) = json_result("does_not_exist.json");
+    assert_eq!(code, 1);
+    assert_eq!(result["valid"], false);
+    assert!(errors(&result)[0].starts_with("Could not parse config"));
+}